@@ -0,0 +1,52 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+
+// Time to show each frame for by default.
+pub const FRAME_DELAY: Duration = Duration::from_millis(100);
+
+// Shows frames one after another in a terminal, clearing the screen between them.
+pub struct Animation<W: Write> {
+    w: W,
+    delay: Duration,
+}
+
+impl Animation<io::Stdout> {
+    pub fn stdout() -> Self {
+        Animation::new(io::stdout(), FRAME_DELAY)
+    }
+}
+
+impl<W: Write> Animation<W> {
+    pub fn new(w: W, delay: Duration) -> Self {
+        Animation { w, delay }
+    }
+
+    // Clear the screen, draw `title` above `body`, and wait for the frame delay.
+    pub fn frame(&mut self, title: &str, body: &dyn fmt::Display) -> io::Result<()> {
+        write!(self.w, "\x1b[2J\x1b[H")?;
+        writeln!(self.w, "{title}")?;
+        write!(self.w, "{body}")?;
+        self.w.flush()?;
+        std::thread::sleep(self.delay);
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let mut animation = Animation::new(Vec::new(), Duration::ZERO);
+        animation.frame("== 1 ==", &"#.\n").unwrap();
+        animation.frame("== 2 ==", &".#\n").unwrap();
+        let out = String::from_utf8(animation.into_inner()).unwrap();
+        assert_eq!(out, "\x1b[2J\x1b[H== 1 ==\n#.\n\x1b[2J\x1b[H== 2 ==\n.#\n");
+    }
+}
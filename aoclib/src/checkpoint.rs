@@ -0,0 +1,237 @@
+// Saving a long search's progress to a file every so often, so that it can pick up where it left
+// off after being interrupted. A checkpoint is a text file with a record on each line: a key, then
+// fields separated by spaces, like:
+//
+//     checkpoint day16a
+//     input 9418263744710231530
+//     best 24 1651 DD -
+//     state 1700 20 560 BB DD,JJ
+//
+// The first line says what kind of search it's for, so one search doesn't try to resume another's
+// checkpoint. The file is written to a temporary file that's then renamed over it, so being
+// interrupted while saving leaves the previous checkpoint intact.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    kind: String,
+    records: Vec<(String, Vec<String>)>,
+}
+
+// A record from a checkpoint, with helpers for parsing its fields.
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    key: &'a str,
+    fields: &'a [String],
+}
+
+impl<'a> Record<'a> {
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn str(&self, i: usize) -> Result<&'a str, String> {
+        self.fields.get(i).map(String::as_str)
+            .ok_or_else(|| format!("{} record has no field {}", self.key, i + 1))
+    }
+
+    pub fn field<T: FromStr>(&self, i: usize) -> Result<T, String>
+    where
+        T::Err: fmt::Display,
+    {
+        let s = self.str(i)?;
+        s.parse().map_err(|e| format!("{} record, field {}: {e}: {s:?}", self.key, i + 1))
+    }
+}
+
+impl Checkpoint {
+    pub fn new(kind: &str) -> Self {
+        Checkpoint { kind: kind.to_string(), records: Vec::new() }
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    // Fields can't contain whitespace, since it separates them.
+    pub fn push<T: fmt::Display>(&mut self, key: &str, fields: impl IntoIterator<Item = T>) {
+        let fields: Vec<String> = fields.into_iter().map(|f| f.to_string()).collect();
+        debug_assert!(fields.iter().all(|f| !f.is_empty() && !f.contains(char::is_whitespace)));
+        self.records.push((key.to_string(), fields));
+    }
+
+    pub fn records<'a>(&'a self, key: &'a str) -> impl Iterator<Item = Record<'a>> + 'a {
+        self.records.iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(key, fields)| Record { key, fields })
+    }
+
+    // The first record with `key`, which should be there.
+    pub fn record<'a>(&'a self, key: &'a str) -> Result<Record<'a>, String> {
+        self.records(key).next().ok_or_else(|| format!("checkpoint has no {key} record"))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut lines = s.lines();
+        let kind = lines.next()
+            .and_then(|l| l.strip_prefix("checkpoint "))
+            .ok_or("not a checkpoint: the first line should be `checkpoint KIND`")?;
+        let mut checkpoint = Checkpoint::new(kind.trim());
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+            checkpoint.push(key, fields);
+        }
+        Ok(checkpoint)
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "checkpoint {}", self.kind)?;
+        for (key, fields) in &self.records {
+            write!(f, "{key}")?;
+            for field in fields {
+                write!(f, " {field}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// A hash of a search's input, for checking that a checkpoint is for the same one. It's FNV-1a,
+// rather than std's hasher, since the std one can change between releases.
+pub fn fingerprint(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+// Checking the clock on every tick would slow down tight search loops, so only look every so
+// often.
+const TICKS_PER_CHECK: u32 = 1024;
+
+// Decides when a search should save a checkpoint, and reads and writes the file.
+pub struct Checkpointer {
+    path: PathBuf,
+    interval: Duration,
+    ticks: u32,
+    last: Instant,
+}
+
+impl Checkpointer {
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Checkpointer { path: path.into(), interval, ticks: 0, last: Instant::now() }
+    }
+
+    // The saved checkpoint, if there is one. It's an error if it's for a different kind of search.
+    pub fn load(&self, kind: &str) -> Result<Option<Checkpoint>, String> {
+        let err = |e: String| format!("checkpoint {}: {e}", self.path.display());
+        let s = match fs::read_to_string(&self.path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(err(e.to_string())),
+        };
+        let checkpoint = Checkpoint::parse(&s).map_err(err)?;
+        if checkpoint.kind() != kind {
+            return Err(err(format!("it's for {}, not {kind}", checkpoint.kind())));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    // Whether it's time to save again. Call it once per state.
+    pub fn due(&mut self) -> bool {
+        self.ticks += 1;
+        if self.ticks < TICKS_PER_CHECK {
+            return false;
+        }
+        self.ticks = 0;
+        self.due_now()
+    }
+
+    // Like due, but always check the clock, for searches that only get a chance to save every so
+    // often, such as between rounds.
+    pub fn due_now(&self) -> bool {
+        self.last.elapsed() >= self.interval
+    }
+
+    pub fn save(&mut self, checkpoint: &Checkpoint) -> io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, checkpoint.to_string())?;
+        fs::rename(&tmp, &self.path)?;
+        self.last = Instant::now();
+        Ok(())
+    }
+
+    // Remove the checkpoint once the search is done, so the next run starts afresh.
+    pub fn finish(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let mut checkpoint = Checkpoint::new("day16a");
+        checkpoint.push("best", [1651]);
+        checkpoint.push("state", ["1700", "BB"]);
+        checkpoint.push("state", ["1600", "CC"]);
+        let s = checkpoint.to_string();
+        assert_eq!(s, "checkpoint day16a\nbest 1651\nstate 1700 BB\nstate 1600 CC\n");
+
+        let parsed = Checkpoint::parse(&s).unwrap();
+        assert_eq!(parsed, checkpoint);
+        assert_eq!(parsed.record("best").unwrap().field::<u32>(0), Ok(1651));
+        let states: Vec<&str> = parsed.records("state").map(|r| r.str(1).unwrap()).collect();
+        assert_eq!(states, ["BB", "CC"]);
+
+        let best = parsed.record("best").unwrap();
+        assert_eq!(best.str(1), Err("best record has no field 2".to_string()));
+        assert_eq!(parsed.record("state").unwrap().field::<u32>(1).unwrap_err(),
+            "state record, field 2: invalid digit found in string: \"BB\"");
+        assert!(parsed.record("nstates").is_err());
+        assert!(Checkpoint::parse("best 1651\n").is_err());
+    }
+
+    #[test]
+    fn test_checkpointer() {
+        let path = std::env::temp_dir().join(format!("aoc-checkpoint-test-{}", std::process::id()));
+        let mut checkpointer = Checkpointer::new(&path, Duration::ZERO);
+        assert_eq!(checkpointer.load("day19"), Ok(None));
+        assert!((0..TICKS_PER_CHECK).any(|_| checkpointer.due()));
+        assert!(checkpointer.due_now());
+
+        let mut checkpoint = Checkpoint::new("day19");
+        checkpoint.push("best", [9]);
+        checkpointer.save(&checkpoint).unwrap();
+        assert_eq!(checkpointer.load("day19"), Ok(Some(checkpoint)));
+        assert!(checkpointer.load("day24").unwrap_err().ends_with("it's for day19, not day24"));
+
+        checkpointer.finish().unwrap();
+        assert_eq!(checkpointer.load("day19"), Ok(None));
+        checkpointer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(""), 0xcbf29ce484222325);
+        assert_eq!(fingerprint("a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(fingerprint("ab"), fingerprint("ba"));
+    }
+}
@@ -0,0 +1,285 @@
+// Writing a simulation's frames to files instead of a terminal, to look at later or put on a web
+// page: either an SVG for each frame, or one animated GIF of all of them.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::image::{GifEncoder, Image, Rgb};
+use crate::render::{Color, Glyph, Scene, Sink};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Svg,
+    Gif,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(ExportFormat::Svg),
+            "gif" => Ok(ExportFormat::Gif),
+            _ => Err(format!("unknown export format: {s}")),
+        }
+    }
+}
+
+// The background, then the color of glyphs without one, then each Color.
+const PALETTE: [Rgb; 10] = [
+    [16, 24, 32],
+    [200, 200, 200],
+    [220, 60, 50],
+    [60, 200, 90],
+    [230, 200, 60],
+    [60, 110, 230],
+    [200, 80, 200],
+    [60, 200, 220],
+    [255, 255, 255],
+    [110, 110, 110],
+];
+
+// The palette index for a glyph. Empty-looking cells are left as background.
+fn palette_index(glyph: Glyph) -> u8 {
+    match (glyph.ch, glyph.color) {
+        (' ' | '.', _) => 0,
+        (_, None) => 1,
+        (_, Some(color)) => match color {
+            Color::Red => 2,
+            Color::Green => 3,
+            Color::Yellow => 4,
+            Color::Blue => 5,
+            Color::Magenta => 6,
+            Color::Cyan => 7,
+            Color::White => 8,
+            Color::Gray => 9,
+        },
+    }
+}
+
+fn hex(rgb: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// Pixels per cell in SVGs, and where in the cell the text's baseline is.
+const CELL_WIDTH: i64 = 10;
+const CELL_HEIGHT: i64 = 16;
+const BASELINE: i64 = 12;
+
+// Draw the whole scene, with a text element per non-empty cell.
+pub fn write_svg(mut w: impl Write, title: &str, scene: &dyn Scene) -> io::Result<()> {
+    let (xs, ys) = scene.extent();
+    let (width, height) = ((xs.end - xs.start) * CELL_WIDTH, (ys.end - ys.start) * CELL_HEIGHT);
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}">"#)?;
+    writeln!(w, "<title>{}</title>", escape(title))?;
+    writeln!(w, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(PALETTE[0]))?;
+    writeln!(w, r#"<g font-family="monospace" font-size="14" text-anchor="middle">"#)?;
+    for y in ys.clone() {
+        for x in xs.clone() {
+            let glyph = scene.glyph(x, y);
+            let color = palette_index(glyph);
+            if color == 0 {
+                continue;
+            }
+            let (px, py) = ((x - xs.start) * CELL_WIDTH + CELL_WIDTH / 2, (y - ys.start) * CELL_HEIGHT + BASELINE);
+            let ch = escape(glyph.ch.encode_utf8(&mut [0; 4]));
+            writeln!(w, r#"<text x="{px}" y="{py}" fill="{}">{ch}</text>"#, hex(PALETTE[color as usize]))?;
+        }
+    }
+    writeln!(w, "</g>")?;
+    writeln!(w, "</svg>")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// A frame kept for the GIF, as runs of palette indexes over its extent, row by row. Most of a
+// frame is usually background, so runs keep long simulations from taking much memory.
+struct Frame {
+    xs: Range<i64>,
+    ys: Range<i64>,
+    runs: Vec<(u8, usize)>,
+}
+
+impl Frame {
+    fn new(scene: &dyn Scene) -> Self {
+        let (xs, ys) = scene.extent();
+        let mut runs: Vec<(u8, usize)> = Vec::new();
+        for y in ys.clone() {
+            for x in xs.clone() {
+                let color = palette_index(scene.glyph(x, y));
+                match runs.last_mut() {
+                    Some((c, n)) if *c == color => *n += 1,
+                    _ => runs.push((color, 1)),
+                }
+            }
+        }
+        Frame { xs, ys, runs }
+    }
+
+    // Draw the frame onto an image covering `xs` and `ys` at `scale` pixels per cell.
+    fn draw(&self, image: &mut Image, xs: &Range<i64>, ys: &Range<i64>, scale: usize) {
+        let width = (self.xs.end - self.xs.start).max(0) as usize;
+        let cells = self.runs.iter().flat_map(|&(color, n)| std::iter::repeat_n(color, n));
+        for (i, color) in cells.enumerate().filter(|&(_, color)| color != 0) {
+            let x = self.xs.start + (i % width) as i64 - xs.start;
+            let y = self.ys.start + (i / width) as i64 - ys.start;
+            image.fill_cell(x as usize, y as usize, scale, color);
+        }
+    }
+}
+
+// Hundredths of a second to show the last frame of a GIF for, so it's clear where it loops.
+const GIF_FINAL_DELAY: u16 = 300;
+
+// Writes frames to a directory: frame-000001.svg and so on, or frames.gif once finish() is called.
+// GIF frames all have to be the same size, so they're kept until then, and each covers the
+// extents of all of them. The directory is created with the first frame.
+pub struct Exporter {
+    dir: PathBuf,
+    format: ExportFormat,
+    delay: Duration,
+    nframes: usize,
+    frames: Vec<Frame>,
+}
+
+impl Exporter {
+    // `delay` is how long to show each frame of a GIF for.
+    pub fn new(dir: impl Into<PathBuf>, format: ExportFormat, delay: Duration) -> Self {
+        Exporter { dir: dir.into(), format, delay, nframes: 0, frames: Vec::new() }
+    }
+
+    fn write_gif(&self) -> io::Result<()> {
+        let cover = |a: &Range<i64>, b: &Range<i64>| a.start.min(b.start)..a.end.max(b.end);
+        let Some(first) = self.frames.first() else {
+            return Ok(());
+        };
+        let (mut xs, mut ys) = (first.xs.clone(), first.ys.clone());
+        for frame in &self.frames {
+            (xs, ys) = (cover(&xs, &frame.xs), cover(&ys, &frame.ys));
+        }
+        let (cols, rows) = ((xs.end - xs.start) as usize, (ys.end - ys.start) as usize);
+        // Make small scenes big enough to see.
+        let scale = (512 / cols.max(rows).max(1)).clamp(1, 16);
+        let delay = (self.delay.as_millis() / 10).clamp(2, u16::MAX as u128) as u16;
+
+        let f = fs::File::create(self.dir.join("frames.gif"))?;
+        let mut gif = GifEncoder::new(BufWriter::new(f), cols * scale, rows * scale, &PALETTE)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let mut image = Image::new(cols * scale, rows * scale);
+            frame.draw(&mut image, &xs, &ys, scale);
+            gif.add_frame(&image, if i + 1 == self.frames.len() { GIF_FINAL_DELAY } else { delay })?;
+        }
+        gif.finish()?;
+        Ok(())
+    }
+}
+
+impl Sink for Exporter {
+    fn frame(&mut self, title: fmt::Arguments, scene: &dyn Scene) -> io::Result<()> {
+        if self.nframes == 0 {
+            fs::create_dir_all(&self.dir)?;
+        }
+        self.nframes += 1;
+        match self.format {
+            ExportFormat::Svg => {
+                let path = self.dir.join(format!("frame-{:06}.svg", self.nframes));
+                let mut w = BufWriter::new(fs::File::create(path)?);
+                write_svg(&mut w, &title.to_string(), scene)?;
+                w.flush()
+            },
+            ExportFormat::Gif => {
+                self.frames.push(Frame::new(scene));
+                Ok(())
+            },
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Svg => Ok(()),
+            ExportFormat::Gif => self.write_gif(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A row of cells from 0 to n, with a red < at the end.
+    struct Row(i64);
+
+    impl Scene for Row {
+        fn extent(&self) -> (Range<i64>, Range<i64>) {
+            (0..self.0 + 1, 0..1)
+        }
+
+        fn glyph(&self, x: i64, _y: i64) -> Glyph {
+            if x == self.0 { Glyph::colored('<', Color::Red) } else { '.'.into() }
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aoc-export-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn test_write_svg() {
+        let mut svg = Vec::new();
+        write_svg(&mut svg, "a & b", &Row(2)).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="16">"#));
+        assert!(svg.contains("<title>a &amp; b</title>"));
+        assert!(svg.contains(r##"<text x="25" y="12" fill="#dc3c32">&lt;</text>"##));
+        assert_eq!(svg.matches("<text").count(), 1);
+    }
+
+    #[test]
+    fn test_export_svg() {
+        let dir = temp_dir("svg");
+        let mut exporter = Exporter::new(&dir, ExportFormat::Svg, Duration::ZERO);
+        for i in 0..3 {
+            exporter.frame(format_args!("Step {i}"), &Row(i)).unwrap();
+        }
+        exporter.finish().unwrap();
+        let svg = fs::read_to_string(dir.join("frame-000003.svg")).unwrap();
+        assert!(svg.contains("<title>Step 2</title>"));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_gif() {
+        let dir = temp_dir("gif");
+        let mut exporter = Exporter::new(&dir, ExportFormat::Gif, Duration::from_millis(100));
+        for i in 0..3 {
+            exporter.frame(format_args!("Step {i}"), &Row(i)).unwrap();
+        }
+        assert!(!dir.join("frames.gif").exists());
+        exporter.finish().unwrap();
+        let gif = fs::read(dir.join("frames.gif")).unwrap();
+        // Every frame covers the biggest extent, 3x1 cells, scaled up to 16 pixels per cell.
+        assert_eq!(&gif[..10], b"GIF89a\x30\x00\x10\x00");
+        assert_eq!(gif.windows(3).filter(|w| w == &[0x21, 0xf9, 4]).count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut image = Image::new(3, 1);
+        Frame::new(&Row(1)).draw(&mut image, &(-1..2), &(0..1), 1);
+        assert_eq!(image.pixels(), &[0, 0, 2]);
+    }
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!("gif".parse(), Ok(ExportFormat::Gif));
+        assert_eq!("svg".parse(), Ok(ExportFormat::Svg));
+        assert!("png".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+// Integer types that can be used as coordinates.
+pub trait Coord: Copy + Ord + Add<Output = Self> + Sub<Output = Self> {
+    // Move by d, or None if the result doesn't fit, eg is negative for usize.
+    fn offset(self, d: i64) -> Option<Self>;
+
+    fn abs_diff(self, o: Self) -> u64;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(
+            impl Coord for $t {
+                fn offset(self, d: i64) -> Option<Self> {
+                    <$t>::try_from(i64::try_from(self).ok()?.checked_add(d)?).ok()
+                }
+
+                fn abs_diff(self, o: Self) -> u64 {
+                    <$t>::abs_diff(self, o) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_coord!(i32, i64, usize);
+
+// A point on a plane. Where it matters, y grows downward, like the rows of a puzzle input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T: Coord> Point2<T> {
+    pub fn offset(self, dx: i64, dy: i64) -> Option<Self> {
+        Some(Point2::new(self.x.offset(dx)?, self.y.offset(dy)?))
+    }
+
+    pub fn step(self, dir: Dir) -> Option<Self> {
+        let (dx, dy) = dir.delta();
+        self.offset(dx, dy)
+    }
+
+    pub fn manhattan(self, o: Self) -> u64 {
+        self.x.abs_diff(o.x) + self.y.abs_diff(o.y)
+    }
+
+    pub fn chebyshev(self, o: Self) -> u64 {
+        self.x.abs_diff(o.x).max(self.y.abs_diff(o.y))
+    }
+
+    // The orthogonal neighbors, clockwise from up, leaving out any that don't fit in T.
+    pub fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Dir::ALL.into_iter().filter_map(move |dir| self.step(dir))
+    }
+
+    // The orthogonal and diagonal neighbors, clockwise from up.
+    pub fn neighbors8(self) -> impl Iterator<Item = Self> {
+        const OFFSETS: [(i64, i64); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+        OFFSETS.into_iter().filter_map(move |(dx, dy)| self.offset(dx, dy))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point2<T> {
+    type Output = Self;
+
+    fn add(self, o: Self) -> Self {
+        Point2::new(self.x + o.x, self.y + o.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point2<T> {
+    type Output = Self;
+
+    fn sub(self, o: Self) -> Self {
+        Point2::new(self.x - o.x, self.y - o.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coord> Point3<T> {
+    pub fn offset(self, dx: i64, dy: i64, dz: i64) -> Option<Self> {
+        Some(Point3::new(self.x.offset(dx)?, self.y.offset(dy)?, self.z.offset(dz)?))
+    }
+
+    pub fn manhattan(self, o: Self) -> u64 {
+        self.x.abs_diff(o.x) + self.y.abs_diff(o.y) + self.z.abs_diff(o.z)
+    }
+
+    pub fn chebyshev(self, o: Self) -> u64 {
+        self.x.abs_diff(o.x).max(self.y.abs_diff(o.y)).max(self.z.abs_diff(o.z))
+    }
+
+    // The neighbors sharing a face.
+    pub fn neighbors6(self) -> impl Iterator<Item = Self> {
+        const OFFSETS: [(i64, i64, i64); 6] = [(-1, 0, 0), (1, 0, 0), (0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1)];
+        OFFSETS.into_iter().filter_map(move |(dx, dy, dz)| self.offset(dx, dy, dz))
+    }
+
+    // The neighbors sharing a face, edge or corner.
+    pub fn neighbors26(self) -> impl Iterator<Item = Self> {
+        (0..27)
+            .filter(|&i| i != 13)
+            .filter_map(move |i| self.offset(i % 3 - 1, i / 3 % 3 - 1, i / 9 - 1))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, o: Self) -> Self {
+        Point3::new(self.x + o.x, self.y + o.y, self.z + o.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, o: Self) -> Self {
+        Point3::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+// A direction on the plane, in clockwise order starting from up, so it can index arrays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    Up, Right, Down, Left,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    // The change in x and y for a step, with y growing downward.
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Dir::Up => (0, -1),
+            Dir::Right => (1, 0),
+            Dir::Down => (0, 1),
+            Dir::Left => (-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Dir {
+        Dir::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Dir {
+        Dir::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Dir {
+        Dir::ALL[(self as usize + 2) % 4]
+    }
+
+    // How many degrees to turn clockwise to face `to`: 0, 90, 180 or 270.
+    pub fn clockwise_angle(self, to: Dir) -> u16 {
+        ((to as u16 + 4 - self as u16) % 4) * 90
+    }
+}
+
+// Parses up|right|down|left, or U|R|D|L.
+impl FromStr for Dir {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" | "U" => Ok(Dir::Up),
+            "right" | "R" => Ok(Dir::Right),
+            "down" | "D" => Ok(Dir::Down),
+            "left" | "L" => Ok(Dir::Left),
+            _ => Err(format!("unexpected dir: {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_point2() {
+        let p = Point2::new(3i64, -2);
+        assert_eq!(p + Point2::new(1, 1), Point2::new(4, -1));
+        assert_eq!(p - Point2::new(1, 1), Point2::new(2, -3));
+        assert_eq!(p.manhattan(Point2::new(0, 0)), 5);
+        assert_eq!(p.chebyshev(Point2::new(0, 0)), 3);
+        assert_eq!(p.step(Dir::Up), Some(Point2::new(3, -3)));
+        assert_eq!(p.to_string(), "3,-2");
+        assert_eq!(p.neighbors8().count(), 8);
+
+        let corner = Point2::new(0usize, 0);
+        assert_eq!(corner.neighbors4().collect::<Vec<_>>(), [Point2::new(1, 0), Point2::new(0, 1)]);
+        assert_eq!(corner.neighbors8().count(), 3);
+        assert_eq!(corner.step(Dir::Left), None);
+    }
+
+    #[test]
+    fn test_point3() {
+        let p = Point3::new(1i32, 2, 3);
+        assert_eq!(p + Point3::new(1, 1, 1), Point3::new(2, 3, 4));
+        assert_eq!(p.manhattan(Point3::new(0, 0, 0)), 6);
+        assert_eq!(p.chebyshev(Point3::new(0, 0, 0)), 3);
+        assert_eq!(p.neighbors6().count(), 6);
+        assert!(p.neighbors6().all(|n| n.manhattan(p) == 1));
+        let neighbors: Vec<_> = p.neighbors26().collect();
+        assert_eq!(neighbors.len(), 26);
+        assert!(neighbors.iter().all(|&n| n.chebyshev(p) == 1));
+        assert_eq!(Point3::new(0usize, 0, 0).neighbors26().count(), 7);
+    }
+
+    #[test]
+    fn test_dir() {
+        assert_eq!(Dir::Up.turn_right(), Dir::Right);
+        assert_eq!(Dir::Up.turn_left(), Dir::Left);
+        assert_eq!(Dir::Left.opposite(), Dir::Right);
+        assert_eq!(Dir::Up.clockwise_angle(Dir::Left), 270);
+        assert_eq!(Dir::Left.clockwise_angle(Dir::Up), 90);
+        assert_eq!(Dir::Down.clockwise_angle(Dir::Down), 0);
+        assert_eq!("down".parse(), Ok(Dir::Down));
+        assert_eq!("L".parse(), Ok(Dir::Left));
+        assert!("north".parse::<Dir>().is_err());
+    }
+}
@@ -0,0 +1,391 @@
+// Shortest paths over graphs given by a neighbor function, so callers don't need to build an
+// adjacency list first. Nodes are kept in a Vec and referred to by index in the priority queues, so
+// they only need to be hashable, not ordered.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Add;
+
+// Edge weights. Default is taken to be zero.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+// The fewest steps from any of the starts to every reachable node.
+pub fn bfs<N, I>(starts: impl IntoIterator<Item = N>, mut neighbors: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut dist: HashMap<N, usize> = HashMap::new();
+    let mut queue: VecDeque<N> = VecDeque::new();
+    for start in starts {
+        if dist.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        let d = dist[&node] + 1;
+        for next in neighbors(&node) {
+            if !dist.contains_key(&next) {
+                dist.insert(next.clone(), d);
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+// The lowest cost from any of the starts to every reachable node. `neighbors` gives each neighbor
+// along with the cost of the edge to it.
+pub fn dijkstra<N, C, I>(starts: impl IntoIterator<Item = N>, mut neighbors: impl FnMut(&N) -> I) -> HashMap<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = Nodes::new();
+    let mut frontier: BinaryHeap<Reverse<(C, usize)>> = BinaryHeap::new();
+    for start in starts {
+        let (i, _) = nodes.relax(start, C::default(), None);
+        frontier.push(Reverse((C::default(), i)));
+    }
+    while let Some(Reverse((cost, i))) = frontier.pop() {
+        // Stale entries are left in the heap instead of being updated.
+        if nodes.closed[i] {
+            continue;
+        }
+        nodes.closed[i] = true;
+        for (next, step) in neighbors(&nodes.nodes[i]) {
+            if let (j, true) = nodes.relax(next, cost + step, Some(i)) {
+                frontier.push(Reverse((cost + step, j)));
+            }
+        }
+    }
+    nodes.into_costs()
+}
+
+// The cheapest path from start to a goal, along with its cost, guided by a heuristic that must
+// never overestimate the remaining cost. With a heuristic of zero this is Dijkstra's algorithm.
+pub fn astar<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(C, Vec<N>)>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = Nodes::new();
+    let mut frontier: BinaryHeap<Reverse<(C, usize)>> = BinaryHeap::new();
+    frontier.push(Reverse((heuristic(&start), 0)));
+    nodes.relax(start, C::default(), None);
+    while let Some(Reverse((_, i))) = frontier.pop() {
+        if nodes.closed[i] {
+            continue;
+        }
+        nodes.closed[i] = true;
+        let cost = nodes.best[i].0;
+        if is_goal(&nodes.nodes[i]) {
+            return Some((cost, nodes.path_to(i)));
+        }
+        for (next, step) in neighbors(&nodes.nodes[i]) {
+            let estimate = heuristic(&next);
+            if let (j, true) = nodes.relax(next, cost + step, Some(i)) {
+                frontier.push(Reverse((cost + step + estimate, j)));
+            }
+        }
+    }
+    None
+}
+
+// The nodes seen so far by a search, with their best known cost and the node it was reached from.
+struct Nodes<N, C> {
+    nodes: Vec<N>,
+    index_for: HashMap<N, usize>,
+    best: Vec<(C, Option<usize>)>,
+    closed: Vec<bool>,
+}
+
+impl<N: Clone + Eq + Hash, C: Cost> Nodes<N, C> {
+    fn new() -> Self {
+        Nodes { nodes: Vec::new(), index_for: HashMap::new(), best: Vec::new(), closed: Vec::new() }
+    }
+
+    // Record reaching `node` for `cost`, returning its index and whether it's an improvement.
+    fn relax(&mut self, node: N, cost: C, from: Option<usize>) -> (usize, bool) {
+        match self.index_for.get(&node) {
+            Some(&i) if self.closed[i] || self.best[i].0 <= cost => (i, false),
+            Some(&i) => {
+                self.best[i] = (cost, from);
+                (i, true)
+            },
+            None => {
+                let i = self.nodes.len();
+                self.index_for.insert(node.clone(), i);
+                self.nodes.push(node);
+                self.best.push((cost, from));
+                self.closed.push(false);
+                (i, true)
+            },
+        }
+    }
+
+    fn path_to(&self, mut i: usize) -> Vec<N> {
+        let mut path = vec![self.nodes[i].clone()];
+        while let Some(prev) = self.best[i].1 {
+            path.push(self.nodes[prev].clone());
+            i = prev;
+        }
+        path.reverse();
+        path
+    }
+
+    fn into_costs(self) -> HashMap<N, C> {
+        self.nodes.into_iter().zip(self.best).map(|(n, (c, _))| (n, c)).collect()
+    }
+}
+
+// Shortest distances between every pair of nodes 0..n.
+#[derive(Clone, Debug)]
+pub struct AllPairs<C> {
+    n: usize,
+    dist: Vec<Option<C>>,
+}
+
+impl<C: Copy> AllPairs<C> {
+    // None if dst can't be reached from src.
+    pub fn get(&self, src: usize, dst: usize) -> Option<C> {
+        self.dist[src * self.n + dst]
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+}
+
+// Floyd-Warshall, given the weight of the edge from src to dst, if there is one. Every node is
+// distance zero from itself.
+pub fn all_pairs<C: Cost>(n: usize, mut weight: impl FnMut(usize, usize) -> Option<C>) -> AllPairs<C> {
+    let mut dist: Vec<Option<C>> = (0..n * n)
+        .map(|i| if i / n == i % n { Some(C::default()) } else { weight(i / n, i % n) })
+        .collect();
+    for mid in 0..n {
+        for src in 0..n {
+            let Some(a) = dist[src * n + mid] else { continue };
+            for dst in 0..n {
+                let Some(b) = dist[mid * n + dst] else { continue };
+                let d = &mut dist[src * n + dst];
+                if d.is_none_or(|d| a + b < d) {
+                    *d = Some(a + b);
+                }
+            }
+        }
+    }
+    AllPairs { n, dist }
+}
+
+// A weighted, directed graph on nodes 0..n, stored as an adjacency matrix. Suits small graphs that
+// get close to complete, like day16's tunnels once the rooms without valves are bypassed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenseGraph<C> {
+    n: usize,
+    weights: Vec<Option<C>>,
+}
+
+impl<C: Cost> DenseGraph<C> {
+    pub fn new(n: usize) -> Self {
+        DenseGraph { n, weights: vec![None; n * n] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    // The weight of the edge from src to dst, if there is one.
+    pub fn get(&self, src: usize, dst: usize) -> Option<C> {
+        self.weights[src * self.n + dst]
+    }
+
+    // Add an edge, replacing any that's already there.
+    pub fn add_edge(&mut self, src: usize, dst: usize, weight: C) {
+        self.weights[src * self.n + dst] = Some(weight);
+    }
+
+    pub fn remove_edge(&mut self, src: usize, dst: usize) {
+        self.weights[src * self.n + dst] = None;
+    }
+
+    // The weights of the edges from src, indexed by dst.
+    pub fn row(&self, src: usize) -> &[Option<C>] {
+        &self.weights[src * self.n..(src + 1) * self.n]
+    }
+
+    // The nodes that src has an edge to, with their weights.
+    pub fn edges(&self, src: usize) -> impl Iterator<Item = (usize, C)> + '_ {
+        self.row(src).iter().enumerate().filter_map(|(dst, w)| w.map(|w| (dst, w)))
+    }
+
+    pub fn all_pairs(&self) -> AllPairs<C> {
+        all_pairs(self.n, |src, dst| self.get(src, dst))
+    }
+
+    // Remove the edges to and from `node`, first joining its neighbors with edges weighing as much
+    // as the path through it, unless they already have a lighter one.
+    pub fn bypass(&mut self, node: usize) {
+        for src in (0..self.n).filter(|&src| src != node) {
+            let Some(a) = self.get(src, node) else { continue };
+            for dst in (0..self.n).filter(|&dst| dst != node && dst != src) {
+                let Some(b) = self.get(node, dst) else { continue };
+                if self.get(src, dst).is_none_or(|w| a + b < w) {
+                    self.add_edge(src, dst, a + b);
+                }
+            }
+        }
+        for other in 0..self.n {
+            self.remove_edge(node, other);
+            self.remove_edge(other, node);
+        }
+    }
+
+    // Bypass every node that `keep` rejects, leaving edges between the rest that weigh as much as
+    // the shortest paths between them through the removed nodes. Node numbers don't change.
+    pub fn compact(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        for node in 0..self.n {
+            if !keep(node) {
+                self.bypass(node);
+            }
+        }
+    }
+}
+
+// The weight matrix, with - for missing edges.
+impl<C: Cost + fmt::Display> fmt::Display for DenseGraph<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for src in 0..self.n {
+            for w in self.row(src) {
+                match w {
+                    Some(w) => write!(f, "{w:>2}")?,
+                    None => write!(f, "{:>2}", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A weighted, directed graph: 0 -1-> 1 -1-> 2, 0 -5-> 2, 2 -1-> 3, and 4 is unreachable.
+    fn edges(n: &u8) -> Vec<(u8, u32)> {
+        match n {
+            0 => vec![(1, 1), (2, 5)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            4 => vec![(0, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let dist = bfs([0], |&n| edges(&n).into_iter().map(|(m, _)| m));
+        assert_eq!(dist, HashMap::from([(0, 0), (1, 1), (2, 1), (3, 2)]));
+        let dist = bfs([1, 4], |&n| edges(&n).into_iter().map(|(m, _)| m));
+        assert_eq!((dist[&0], dist[&3]), (1, 2));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let cost = dijkstra([0], edges);
+        assert_eq!(cost, HashMap::from([(0, 0), (1, 1), (2, 2), (3, 3)]));
+    }
+
+    #[test]
+    fn test_astar() {
+        assert_eq!(astar(0, edges, |_| 0, |&n| n == 3), Some((3, vec![0, 1, 2, 3])));
+        assert_eq!(astar(0, edges, |_| 0, |&n| n == 4), None);
+
+        // On an open grid, Manhattan distance is an exact heuristic.
+        let goal = (3i32, 2i32);
+        let grid_neighbors = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y))
+                .map(|p| (p, 1))
+        };
+        let h = |&(x, y): &(i32, i32)| x.abs_diff(goal.0) + y.abs_diff(goal.1);
+        let (cost, path) = astar((0, 0), grid_neighbors, h, |&p| p == goal).unwrap();
+        assert_eq!((cost, path.len()), (5, 6));
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let dist = all_pairs(5, |src, dst| {
+            edges(&(src as u8)).into_iter().find(|&(m, _)| m as usize == dst).map(|(_, w)| w)
+        });
+        assert_eq!(dist.len(), 5);
+        assert_eq!(dist.get(0, 2), Some(2));
+        assert_eq!(dist.get(4, 3), Some(4));
+        assert_eq!(dist.get(3, 3), Some(0));
+        assert_eq!(dist.get(3, 0), None);
+    }
+
+    fn dense_edges() -> DenseGraph<u32> {
+        let mut graph = DenseGraph::new(5);
+        for src in 0..5 {
+            for (dst, w) in edges(&src) {
+                graph.add_edge(src as usize, dst as usize, w);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_dense_graph() {
+        let mut graph = dense_edges();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.get(0, 2), Some(5));
+        assert_eq!(graph.edges(0).collect::<Vec<_>>(), [(1, 1), (2, 5)]);
+        assert_eq!(graph.row(3), [None; 5]);
+        assert_eq!(graph.all_pairs().get(4, 3), Some(4));
+        graph.remove_edge(0, 2);
+        assert_eq!(graph.edges(0).collect::<Vec<_>>(), [(1, 1)]);
+        assert_eq!(graph.to_string(), " - 1 - - -\n - - 1 - -\n - - - 1 -\n - - - - -\n 1 - - - -\n");
+    }
+
+    #[test]
+    fn test_dense_graph_compact() {
+        let mut graph = dense_edges();
+        // 0 -> 1 -> 2 is lighter than 0 -> 2, and 4 -> 0 -> 1 becomes 4 -> 1.
+        graph.compact(|node| node != 1 && node != 0);
+        assert_eq!(graph.get(4, 2), Some(3));
+        assert_eq!(graph.get(0, 1), None);
+        assert_eq!(graph.edges(4).collect::<Vec<_>>(), [(2, 3)]);
+        assert_eq!(graph.edges(2).collect::<Vec<_>>(), [(3, 1)]);
+
+        let mut graph = dense_edges();
+        graph.bypass(1);
+        assert_eq!(graph.get(0, 2), Some(2));
+        // Distances between the remaining nodes don't change.
+        let (before, after) = (dense_edges().all_pairs(), graph.all_pairs());
+        for (src, dst) in [(0, 2), (0, 3), (4, 3), (2, 0)] {
+            assert_eq!(before.get(src, dst), after.get(src, dst));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::ops::{Index, IndexMut, Range};
+
+// A rectangular grid of cells, stored in row-major order. Cells are addressed by x, the column,
+// and y, the row. Coordinates start at 0,0 in the top left unless the grid is moved with
+// `with_origin`, for puzzles whose coordinates start far from zero.
+//
+// `get` and `get_mut` return None for coordinates outside the grid, while indexing with (x, y)
+// panics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+    x0: usize,
+    y0: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid { data: vec![fill; width * height], width, height, x0: 0, y0: 0 }
+    }
+}
+
+impl<T> Grid<T> {
+    // Make a grid from its rows, which must all be the same length.
+    pub fn from_rows(rows: impl IntoIterator<Item = Vec<T>>) -> Result<Self, String> {
+        let mut data = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for row in rows {
+            let len = row.len();
+            if len != *width.get_or_insert(len) {
+                return Err(format!("row {} has {} cells, expected {}", height, len, width.unwrap()));
+            }
+            data.extend(row);
+            height += 1;
+        }
+        Ok(Grid { data, width: width.unwrap_or(0), height, x0: 0, y0: 0 })
+    }
+
+    // Move the grid so its top-left cell is at x0,y0.
+    pub fn with_origin(self, x0: usize, y0: usize) -> Self {
+        Grid { x0, y0, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn xs(&self) -> Range<usize> {
+        self.x0..self.x0 + self.width
+    }
+
+    pub fn ys(&self) -> Range<usize> {
+        self.y0..self.y0 + self.height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.xs().contains(&x) && self.ys().contains(&y)
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        self.contains(x, y).then(|| (y - self.y0) * self.width + (x - self.x0))
+    }
+
+    // The coordinates of the i'th cell in row-major order.
+    pub fn position(&self, i: usize) -> (usize, usize) {
+        (self.x0 + i % self.width, self.y0 + i / self.width)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.data[i])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|i| &mut self.data[i])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, v: T) {
+        self[(x, y)] = v;
+    }
+
+    // The row at y, from left to right.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(self.ys().contains(&y), "row {y} is outside the grid's {:?}", self.ys());
+        let start = (y - self.y0) * self.width;
+        &self.data[start..start + self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        // chunks panics for a chunk size of zero.
+        self.data.chunks(self.width.max(1))
+    }
+
+    // The column at x, from top to bottom.
+    pub fn col(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(self.xs().contains(&x), "column {x} is outside the grid's {:?}", self.xs());
+        self.data.iter().skip(x - self.x0).step_by(self.width)
+    }
+
+    // Every cell in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    // Every cell in row-major order, along with its coordinates.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.data.iter().enumerate().map(|(i, v)| (self.position(i), v))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        match self.index_of(x, y) {
+            Some(i) => &self.data[i],
+            None => panic!("{x},{y} is outside the grid's {:?} x {:?}", self.xs(), self.ys()),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        match self.index_of(x, y) {
+            Some(i) => &mut self.data[i],
+            None => panic!("{x},{y} is outside the grid's {:?} x {:?}", self.xs(), self.ys()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let mut grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], 3);
+        assert_eq!(grid.get(0, 1), Some(&4));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        grid.set(1, 1, 50);
+        assert_eq!(grid.row(1), [4, 50, 6]);
+        assert_eq!(grid.col(1).copied().collect::<Vec<_>>(), [2, 50]);
+        assert_eq!(grid.col(2).rev().copied().collect::<Vec<_>>(), [6, 3]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.cells().find(|&(_, &v)| v == 50), Some(((1, 1), &50)));
+
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+        let empty = Grid::<u8>::from_rows(vec![]).unwrap();
+        assert_eq!((empty.width(), empty.height(), empty.rows().count()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_origin() {
+        let mut grid = Grid::new(4, 2, '.').with_origin(498, 10);
+        assert_eq!(grid.xs(), 498..502);
+        assert_eq!(grid.ys(), 10..12);
+        grid[(500, 11)] = '#';
+        assert_eq!(grid.row(11), ['.', '.', '#', '.']);
+        assert_eq!(grid.get(497, 10), None);
+        assert_eq!(grid.get(0, 0), None);
+        assert_eq!(grid.position(6), (500, 11));
+        assert!(grid.contains(501, 10) && !grid.contains(502, 10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_outside() {
+        let grid = Grid::new(2, 2, 0).with_origin(1, 1);
+        _ = grid[(0, 0)];
+    }
+}
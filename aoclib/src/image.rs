@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+pub type Rgb = [u8; 3];
+
+// An image whose pixels are indexes into a palette.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image { width, height, pixels: vec![0; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: u8) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    // Fill the `scale`x`scale` square of pixels for the given cell, like when each tile of a grid
+    // is drawn as a square.
+    pub fn fill_cell(&mut self, x: usize, y: usize, scale: usize, color: u8) {
+        for py in (y * scale)..((y + 1) * scale).min(self.height) {
+            for px in (x * scale)..((x + 1) * scale).min(self.width) {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+// Writes an animated GIF, one frame at a time. All frames share the global palette and have the
+// same size. finish() must be called to write the trailer.
+pub struct GifEncoder<W: Write> {
+    w: W,
+    width: u16,
+    height: u16,
+    // Number of bits needed for a palette index.
+    color_bits: u8,
+}
+
+impl<W: Write> GifEncoder<W> {
+    pub fn new(mut w: W, width: usize, height: usize, palette: &[Rgb]) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_string());
+        let width = u16::try_from(width).map_err(|_| invalid("image too wide for a GIF"))?;
+        let height = u16::try_from(height).map_err(|_| invalid("image too tall for a GIF"))?;
+        if palette.is_empty() || palette.len() > 256 {
+            return Err(invalid("palette should have between 1 and 256 colors"));
+        }
+        let color_bits = (palette.len().next_power_of_two().trailing_zeros() as u8).max(1);
+
+        w.write_all(b"GIF89a")?;
+        w.write_all(&width.to_le_bytes())?;
+        w.write_all(&height.to_le_bytes())?;
+        // Global color table present, 8 bits of color resolution, and the table's size.
+        w.write_all(&[0xf0 | (color_bits - 1), 0, 0])?;
+        for i in 0..(1 << color_bits) {
+            w.write_all(palette.get(i).unwrap_or(&[0, 0, 0]))?;
+        }
+        // NETSCAPE2.0 application extension, so viewers loop forever.
+        w.write_all(&[0x21, 0xff, 11])?;
+        w.write_all(b"NETSCAPE2.0")?;
+        w.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(GifEncoder { w, width, height, color_bits })
+    }
+
+    // Add a frame that's shown for `delay` hundredths of a second.
+    pub fn add_frame(&mut self, image: &Image, delay: u16) -> io::Result<()> {
+        if (image.width(), image.height()) != (self.width as usize, self.height as usize) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame size doesn't match"));
+        }
+        // Graphic control extension, for the delay.
+        self.w.write_all(&[0x21, 0xf9, 4, 0])?;
+        self.w.write_all(&delay.to_le_bytes())?;
+        self.w.write_all(&[0, 0])?;
+        // Image descriptor covering the whole canvas, with no local color table.
+        self.w.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.w.write_all(&self.width.to_le_bytes())?;
+        self.w.write_all(&self.height.to_le_bytes())?;
+        self.w.write_all(&[0])?;
+
+        let min_code_size = self.color_bits.max(2);
+        self.w.write_all(&[min_code_size])?;
+        let data = lzw_encode(image.pixels(), min_code_size);
+        for block in data.chunks(255) {
+            self.w.write_all(&[block.len() as u8])?;
+            self.w.write_all(block)?;
+        }
+        self.w.write_all(&[0])
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.w.write_all(&[0x3b])?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+}
+
+// Packs variable-width codes into bytes, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    nbits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.acc |= (code as u32) << self.nbits;
+        self.nbits += width;
+        while self.nbits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.nbits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.nbits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+// The largest code GIF allows.
+const MAX_CODE: u16 = 4095;
+
+// Compress palette indexes with the variable-width LZW variant GIF uses.
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter { bytes: Vec::new(), acc: 0, nbits: 0 };
+    let mut width = min_code_size as u32 + 1;
+    let mut next_code = end + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+
+    out.write(clear, width);
+    let mut pixels = pixels.iter();
+    let Some(&first) = pixels.next() else {
+        out.write(end, width);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &p in pixels {
+        if let Some(&code) = table.get(&(prefix, p)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, width);
+        if next_code > MAX_CODE {
+            out.write(clear, width);
+            table.clear();
+            width = min_code_size as u32 + 1;
+            next_code = end + 1;
+        } else {
+            table.insert((prefix, p), next_code);
+            // The decoder widens its codes once the code it'll assign next doesn't fit.
+            if next_code == (1 << width) && width < 12 {
+                width += 1;
+            }
+            next_code += 1;
+        }
+        prefix = p as u16;
+    }
+    out.write(prefix, width);
+    out.write(end, width);
+    out.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Decode GIF LZW data, to check the encoder against.
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let mut width = min_code_size as u32 + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            table.clear();
+            table.extend((0..clear).map(|i| vec![i as u8]));
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+        let mut out = Vec::new();
+        let mut prev: Option<Vec<u8>> = None;
+        let (mut acc, mut nbits, mut bytes) = (0u32, 0u32, data.iter());
+        loop {
+            while nbits < width {
+                acc |= (*bytes.next().expect("ran out of data") as u32) << nbits;
+                nbits += 8;
+            }
+            let code = (acc & ((1 << width) - 1)) as u16;
+            acc >>= width;
+            nbits -= width;
+            if code == clear {
+                reset(&mut table);
+                width = min_code_size as u32 + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code as usize), &prev) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("unknown code {code}"),
+            };
+            out.extend_from_slice(&entry);
+            if let Some(prev) = prev {
+                if table.len() <= MAX_CODE as usize {
+                    table.push([prev, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == (1 << width) && width < 12 {
+                width += 1;
+            }
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        let mut state: u64 = 1;
+        let mut noise = Vec::new();
+        for _ in 0..50_000 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            noise.push((state >> 60) as u8);
+        }
+        let inputs: [&[u8]; 4] = [&[], &[3], &[1; 10_000], &noise];
+        for input in inputs {
+            assert_eq!(lzw_decode(&lzw_encode(input, 4), 4), input);
+        }
+        let two_colors: Vec<u8> = noise.iter().map(|n| n & 1).collect();
+        assert_eq!(lzw_decode(&lzw_encode(&two_colors, 2), 2), two_colors);
+    }
+
+    #[test]
+    fn test_gif_layout() {
+        let mut image = Image::new(3, 2);
+        image.fill_cell(1, 0, 2, 1);
+        assert_eq!(image.pixels(), &[0, 0, 1, 0, 0, 1]);
+
+        let mut gif = GifEncoder::new(Vec::new(), 3, 2, &[[0, 0, 0], [255, 255, 255]]).unwrap();
+        gif.add_frame(&image, 10).unwrap();
+        assert!(gif.add_frame(&Image::new(2, 2), 10).is_err());
+        let bytes = gif.finish().unwrap();
+        assert_eq!(&bytes[..13], b"GIF89a\x03\x00\x02\x00\xf0\x00\x00");
+        assert_eq!(&bytes[13..19], &[0, 0, 0, 255, 255, 255]);
+        assert_eq!(bytes.last(), Some(&0x3b));
+    }
+}
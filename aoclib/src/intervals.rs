@@ -0,0 +1,250 @@
+use std::cmp;
+use std::ops::{Add, Range, Sub};
+
+// A set of values stored as sorted, half-open ranges. Overlapping and adjacent ranges are merged
+// when they're inserted, so the ranges are always disjoint and there's a gap between each.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisjointIntervals<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Copy + Ord> DisjointIntervals<T> {
+    pub fn new() -> Self {
+        DisjointIntervals { ranges: Vec::new() }
+    }
+
+    // The number of separate ranges, not the number of values covered.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn insert(&mut self, r: Range<T>) {
+        if r.is_empty() {
+            return;
+        }
+        // The ranges from lo to hi overlap or touch r.
+        let lo = self.ranges.partition_point(|x| x.end < r.start);
+        let hi = self.ranges.partition_point(|x| x.start <= r.end);
+        let mut merged = r;
+        if lo < hi {
+            merged.start = cmp::min(merged.start, self.ranges[lo].start);
+            merged.end = cmp::max(merged.end, self.ranges[hi - 1].end);
+        }
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    pub fn subtract(&mut self, r: Range<T>) {
+        if r.is_empty() {
+            return;
+        }
+        // The ranges from lo to hi overlap r, and only the first and last can stick out past it.
+        let lo = self.ranges.partition_point(|x| x.end <= r.start);
+        let hi = self.ranges.partition_point(|x| x.start < r.end);
+        if lo == hi {
+            return;
+        }
+        let (first, last) = (self.ranges[lo].start, self.ranges[hi - 1].end);
+        let left = (first < r.start).then_some(first..r.start);
+        let right = (r.end < last).then_some(r.end..last);
+        self.ranges.splice(lo..hi, left.into_iter().chain(right));
+    }
+
+    pub fn contains(&self, v: T) -> bool {
+        let i = self.ranges.partition_point(|x| x.end <= v);
+        self.ranges.get(i).is_some_and(|x| x.start <= v)
+    }
+
+    // Whether every value in r is in the set.
+    pub fn covers(&self, r: &Range<T>) -> bool {
+        if r.is_empty() {
+            return true;
+        }
+        let i = self.ranges.partition_point(|x| x.end <= r.start);
+        self.ranges.get(i).is_some_and(|x| x.start <= r.start && r.end <= x.end)
+    }
+
+    // Whether any value in r is in the set.
+    pub fn overlaps(&self, r: &Range<T>) -> bool {
+        if r.is_empty() {
+            return false;
+        }
+        let i = self.ranges.partition_point(|x| x.end <= r.start);
+        self.ranges.get(i).is_some_and(|x| x.start < r.end)
+    }
+
+    // The parts of `within` that aren't in the set, in order.
+    pub fn gaps(&self, within: Range<T>) -> Vec<Range<T>> {
+        let mut gaps = Vec::new();
+        let mut cur = within.start;
+        let i = self.ranges.partition_point(|x| x.end <= cur);
+        for r in &self.ranges[i..] {
+            if r.start >= within.end {
+                break;
+            }
+            if cur < r.start {
+                gaps.push(cur..r.start);
+            }
+            cur = r.end;
+        }
+        if cur < within.end {
+            gaps.push(cur..within.end);
+        }
+        gaps
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Range<T>> + '_ {
+        self.ranges.iter().cloned()
+    }
+}
+
+impl<T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>> DisjointIntervals<T> {
+    // The number of values in the set.
+    pub fn covered_len(&self) -> T {
+        self.ranges.iter().fold(T::default(), |sum, r| sum + (r.end - r.start))
+    }
+}
+
+// An inclusive range of values, like a puzzle's "2-4" or a sensor's reach along a row, with set
+// operations on a pair of them. It's empty if the end is before the start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Copy + Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Interval { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+
+    pub fn contains_point(&self, v: T) -> bool {
+        self.start <= v && v <= self.end
+    }
+
+    // Whether every value in other is in this one.
+    pub fn covers(&self, other: &Interval<T>) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    // The values in both, if there are any.
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let i = Interval::new(cmp::max(self.start, other.start), cmp::min(self.end, other.end));
+        (!i.is_empty()).then_some(i)
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>> Interval<T> {
+    // The number of values in the interval.
+    pub fn len(&self) -> T {
+        if self.is_empty() { T::from(0) } else { self.end - self.start + T::from(1) }
+    }
+
+    // The values in either, if they overlap or touch so that they make one interval.
+    pub fn union(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        if self.is_empty() || other.is_empty() {
+            return Some(if self.is_empty() { *other } else { *self });
+        }
+        let (first, second) = if self.start <= other.start { (self, other) } else { (other, self) };
+        (second.start <= first.end + T::from(1))
+            .then(|| Interval::new(first.start, cmp::max(first.end, second.end)))
+    }
+
+    // The same values as a half-open range, the way DisjointIntervals keeps them.
+    pub fn half_open(&self) -> Range<T> {
+        self.start..self.end + T::from(1)
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for DisjointIntervals<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = DisjointIntervals::new();
+        for r in iter {
+            set.insert(r);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ranges(set: &DisjointIntervals<i64>) -> Vec<Range<i64>> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set: DisjointIntervals<i64> = [5..8, 0..2, 12..15].into_iter().collect();
+        assert_eq!(ranges(&set), [0..2, 5..8, 12..15]);
+        set.insert(2..3);
+        assert_eq!(ranges(&set), [0..3, 5..8, 12..15]);
+        set.insert(6..13);
+        assert_eq!(ranges(&set), [0..3, 5..15]);
+        set.insert(4..4);
+        set.insert(-10..20);
+        assert_eq!(ranges(&set), vec![-10..20]);
+        assert_eq!(set.covered_len(), 30);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let mut set: DisjointIntervals<i64> = [0..10, 20..30].into_iter().collect();
+        set.subtract(3..5);
+        assert_eq!(ranges(&set), [0..3, 5..10, 20..30]);
+        set.subtract(8..25);
+        assert_eq!(ranges(&set), [0..3, 5..8, 25..30]);
+        set.subtract(10..20);
+        set.subtract(-5..0);
+        assert_eq!(ranges(&set), [0..3, 5..8, 25..30]);
+        set.subtract(-5..100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_queries() {
+        let set: DisjointIntervals<i64> = [0..3, 5..8].into_iter().collect();
+        assert!(set.contains(0) && set.contains(7));
+        assert!(!set.contains(3) && !set.contains(-1) && !set.contains(8));
+        assert!(set.covers(&(5..8)) && set.covers(&(1..2)) && set.covers(&(9..9)));
+        assert!(!set.covers(&(2..6)));
+        assert!(set.overlaps(&(2..6)) && set.overlaps(&(7..20)));
+        assert!(!set.overlaps(&(3..5)) && !set.overlaps(&(1..1)));
+        assert_eq!(set.gaps(-2..10), [-2..0, 3..5, 8..10]);
+        assert_eq!(set.gaps(1..7), vec![3..5]);
+        assert_eq!(set.gaps(5..8), []);
+        assert_eq!(DisjointIntervals::new().gaps(1..4), vec![1..4]);
+    }
+
+    #[test]
+    fn test_interval() {
+        let i = Interval::<i64>::new;
+        assert_eq!((i(2, 4).len(), i(7, 7).len(), i(3, 2).len()), (3, 1, 0));
+        assert_eq!(Interval::new(3u64, 2).len(), 0);
+        assert!(i(2, 4).contains_point(2) && i(2, 4).contains_point(4));
+        assert!(!i(2, 4).contains_point(1) && !i(2, 4).contains_point(5));
+        assert!(i(2, 8).covers(&i(3, 7)) && i(2, 8).covers(&i(2, 8)) && i(2, 8).covers(&i(9, 0)));
+        assert!(!i(3, 7).covers(&i(2, 8)));
+
+        assert_eq!(i(2, 8).intersection(&i(3, 7)), Some(i(3, 7)));
+        assert_eq!(i(5, 7).intersection(&i(7, 9)), Some(i(7, 7)));
+        assert_eq!(i(2, 4).intersection(&i(6, 8)), None);
+        assert_eq!(i(2, 3).intersection(&i(4, 5)), None);
+
+        assert_eq!(i(5, 7).union(&i(2, 5)), Some(i(2, 7)));
+        assert_eq!(i(2, 3).union(&i(4, 5)), Some(i(2, 5)));
+        assert_eq!(i(2, 8).union(&i(3, 7)), Some(i(2, 8)));
+        assert_eq!(i(2, 3).union(&i(5, 6)), None);
+        assert_eq!(i(2, 3).union(&i(1, 0)), Some(i(2, 3)));
+
+        assert_eq!(i(-1, 2).half_open(), -1..3);
+    }
+}
@@ -0,0 +1,358 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+// Iterates over the blank-line-separated paragraphs of a reader. Each paragraph keeps the line
+// ending of its last line, but not the blank line after it. CRLF line endings are turned into LF,
+// and runs of blank lines count as a single separator, with any at the start or end ignored, so
+// there are never empty paragraphs.
+pub struct Paragraphs<R> {
+    r: R,
+    nlines: usize,
+    first_line: usize,
+}
+
+impl<R: BufRead> Paragraphs<R> {
+    pub fn new(r: R) -> Self {
+        Self { r, nlines: 0, first_line: 0 }
+    }
+
+    // The 1-based number of the first line of the last paragraph returned, for errors.
+    pub fn line_no(&self) -> usize {
+        self.first_line
+    }
+}
+
+impl<R: BufRead> Iterator for Paragraphs<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = String::new();
+        loop {
+            let len = buf.len();
+            match self.r.read_line(&mut buf) {
+                Ok(0) if !buf.is_empty() => return Some(Ok(buf)),
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.nlines += 1;
+                    if buf.ends_with("\r\n") {
+                        buf.truncate(buf.len() - 2);
+                        buf.push('\n');
+                    }
+                    if &buf[len..] == "\n" {
+                        buf.truncate(len);
+                        if !buf.is_empty() {
+                            return Some(Ok(buf));
+                        }
+                    } else if len == 0 {
+                        self.first_line = self.nlines;
+                    }
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// Groups an iterator's items into Vecs of a fixed length. If the items run out partway through a
+// chunk, the partial chunk is returned as an error, and iteration ends.
+pub struct Chunks<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I: Iterator> Chunks<I> {
+    pub fn new(iter: I, len: usize) -> Self {
+        assert!(len > 0, "chunk length must be greater than 0");
+        Self { iter, len }
+    }
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Result<Vec<I::Item>, ShortChunk<I::Item>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = (&mut self.iter).take(self.len).collect();
+        match chunk.len() {
+            0 => None,
+            n if n == self.len => Some(Ok(chunk)),
+            _ => Some(Err(ShortChunk { items: chunk, want: self.len })),
+        }
+    }
+}
+
+// The last, incomplete chunk from Chunks.
+#[derive(Debug, PartialEq)]
+pub struct ShortChunk<T> {
+    pub items: Vec<T>,
+    pub want: usize,
+}
+
+impl<T> fmt::Display for ShortChunk<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "last chunk has {} items, want {}", self.items.len(), self.want)
+    }
+}
+
+impl<T: fmt::Debug> Error for ShortChunk<T> {}
+
+// Keeps the n greatest values pushed to it, in a min-heap so the smallest kept value is the one
+// to compare against and evict. Iterates in ascending order.
+#[derive(Debug, Clone)]
+pub struct TopN<T> {
+    n: usize,
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> TopN<T> {
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "n must be greater than 0");
+        Self { n, heap: BinaryHeap::with_capacity(n + 1) }
+    }
+
+    pub fn push(&mut self, val: T) {
+        if self.heap.len() < self.n {
+            self.heap.push(Reverse(val));
+        } else if let Some(mut min) = self.heap.peek_mut() {
+            if val > min.0 {
+                *min = Reverse(val);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    // The smallest kept value.
+    pub fn min(&self) -> Option<&T> {
+        self.heap.peek().map(|r| &r.0)
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // Sorting Reverses ascending puts their values in descending order.
+        let mut vec: Vec<T> = self.heap.into_sorted_vec().into_iter().map(|r| r.0).collect();
+        vec.reverse();
+        vec
+    }
+}
+
+impl<T: Ord> Extend<T> for TopN<T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<T: Ord> IntoIterator for TopN<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_sorted_vec().into_iter()
+    }
+}
+
+// A value ranked by its key, and then by the order it was pushed in, earliest highest.
+#[derive(Debug, Clone)]
+struct Keyed<K, T> {
+    key: K,
+    seq: Reverse<usize>,
+    val: T,
+}
+
+impl<K: Ord, T> PartialEq for Keyed<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.key, self.seq) == (&other.key, other.seq)
+    }
+}
+
+impl<K: Ord, T> Eq for Keyed<K, T> {}
+
+impl<K: Ord, T> PartialOrd for Keyed<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for Keyed<K, T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (&self.key, self.seq).cmp(&(&other.key, other.seq))
+    }
+}
+
+// Like TopN, but ranks values by a key given at construction, which is computed once for each
+// value pushed. Of values with equal keys, the ones pushed first are kept. Iterates in ascending
+// order of key, with equal keys latest first, so that reversed it's from the greatest to the least
+// with equal keys in the order they were pushed.
+#[derive(Debug, Clone)]
+pub struct TopByKey<T, K, F> {
+    top: TopN<Keyed<K, T>>,
+    key: F,
+    npushed: usize,
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> TopByKey<T, K, F> {
+    pub fn new(n: usize, key: F) -> Self {
+        Self { top: TopN::new(n), key, npushed: 0 }
+    }
+
+    pub fn push(&mut self, val: T) {
+        let key = (self.key)(&val);
+        self.top.push(Keyed { key, seq: Reverse(self.npushed), val });
+        self.npushed += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.top.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.top.is_empty()
+    }
+
+    // The kept value with the smallest key.
+    pub fn min(&self) -> Option<&T> {
+        self.top.min().map(|k| &k.val)
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.top.into_sorted_vec().into_iter().map(|k| k.val).collect()
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Extend<T> for TopByKey<T, K, F> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> IntoIterator for TopByKey<T, K, F> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_sorted_vec().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn paragraphs(s: &str) -> Vec<String> {
+        Paragraphs::new(s.as_bytes()).collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        assert_eq!(paragraphs("a\nb\n\nc\n"), ["a\nb\n", "c\n"]);
+        assert_eq!(paragraphs("a\n\nb"), ["a\n", "b"]);
+        assert_eq!(paragraphs(""), Vec::<String>::new());
+        assert_eq!(paragraphs("a\r\nb\r\n\r\nc\r\n"), ["a\nb\n", "c\n"]);
+        assert_eq!(paragraphs("\n\na\n\n\n\nb\n\n\n"), ["a\n", "b\n"]);
+        assert_eq!(paragraphs("\r\n\n"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_paragraphs_line_no() {
+        let mut paragraphs = Paragraphs::new("\na\nb\n\n\nc\r\n".as_bytes());
+        assert_eq!(paragraphs.line_no(), 0);
+        paragraphs.next();
+        assert_eq!(paragraphs.line_no(), 2);
+        paragraphs.next();
+        assert_eq!(paragraphs.line_no(), 6);
+        assert!(paragraphs.next().is_none());
+    }
+
+    #[test]
+    fn test_paragraphs_error() {
+        let mut paragraphs = Paragraphs::new(&[b'a', 0xff, b'\n'][..]);
+        assert!(paragraphs.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_chunks() {
+        let chunks: Vec<_> = Chunks::new(1..=6, 3).collect();
+        assert_eq!(chunks, [Ok(vec![1, 2, 3]), Ok(vec![4, 5, 6])]);
+        assert_eq!(Chunks::new(0..0, 3).next(), None);
+    }
+
+    #[test]
+    fn test_chunks_short() {
+        let mut chunks = Chunks::new(1..=4, 3);
+        assert_eq!(chunks.next(), Some(Ok(vec![1, 2, 3])));
+        let err = chunks.next().unwrap().unwrap_err();
+        assert_eq!(err, ShortChunk { items: vec![4], want: 3 });
+        assert_eq!(err.to_string(), "last chunk has 1 items, want 3");
+        assert_eq!(chunks.next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chunks_zero_len() {
+        Chunks::new(1..3, 0);
+    }
+
+    #[test]
+    fn test_topn() {
+        let mut topn = TopN::new(3);
+        topn.extend([3, 5, 8, 2, 9, 12, 3]);
+        assert_eq!(topn.len(), 3);
+        assert_eq!(topn.min(), Some(&8));
+        assert_eq!(topn.into_iter().collect::<Vec<_>>(), [8, 9, 12]);
+    }
+
+    #[test]
+    fn test_topn_fewer_than_n() {
+        let mut topn = TopN::new(3);
+        assert!(topn.is_empty());
+        assert_eq!(topn.min(), None);
+        topn.extend([2, 1]);
+        assert_eq!(topn.into_sorted_vec(), [1, 2]);
+    }
+
+    #[test]
+    fn test_topn_ties() {
+        let mut topn = TopN::new(2);
+        topn.extend([(5, 'a'), (7, 'b'), (5, 'c'), (7, 'd')]);
+        assert_eq!(topn.into_sorted_vec(), [(7, 'b'), (7, 'd')]);
+    }
+
+    #[test]
+    fn test_top_by_key() {
+        let mut top = TopByKey::new(3, |s: &&str| s.len());
+        top.extend(["ccc", "a", "dddd", "bb", "eeeee"]);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top.min(), Some(&"ccc"));
+        assert_eq!(top.into_iter().collect::<Vec<_>>(), ["ccc", "dddd", "eeeee"]);
+
+        let mut top = TopByKey::new(2, |s: &&str| s.len());
+        assert!(top.is_empty());
+        top.push("x");
+        assert_eq!(top.into_sorted_vec(), ["x"]);
+    }
+
+    #[test]
+    fn test_top_by_key_ties() {
+        // The key is computed once per value, and ties keep the earliest.
+        let calls = std::cell::Cell::new(0);
+        let mut top = TopByKey::new(2, |&(n, _): &(u32, char)| {
+            calls.set(calls.get() + 1);
+            n
+        });
+        top.extend([(5, 'a'), (7, 'b'), (5, 'c'), (7, 'd'), (7, 'e')]);
+        assert_eq!(calls.get(), 5);
+        assert_eq!(top.clone().into_sorted_vec(), [(7, 'd'), (7, 'b')]);
+        assert_eq!(top.into_iter().rev().collect::<Vec<_>>(), [(7, 'b'), (7, 'd')]);
+    }
+}
@@ -0,0 +1,19 @@
+// Utilities shared by the day solutions.
+
+pub mod animate;
+pub mod checkpoint;
+pub mod export;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod image;
+pub mod intervals;
+pub mod iter;
+pub mod log;
+pub mod memstats;
+pub mod parallel;
+pub mod progress;
+pub mod render;
+pub mod rng;
+pub mod scan;
+pub mod simd;
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// Debug output for the solutions, toggled at runtime with RUST_LOG, eg:
+//
+//     RUST_LOG=debug day16b
+//     RUST_LOG=warn,day22=trace day22 part2
+//
+// RUST_LOG is a comma-separated list of `level` or `target=level` directives, where a target
+// matches a module path, like advent_of_code_2022::day22, or just its last part, like day22. The
+// most specific matching directive wins. Messages go to stderr.
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("unknown log level: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Filter {
+    // The most verbose level to show for modules no directive targets. None shows nothing.
+    default: Option<Level>,
+    directives: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    // Unknown levels are ignored rather than rejected, since there's nowhere good to report them.
+    pub fn parse(s: &str) -> Filter {
+        let mut filter = Filter::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let (target, level) = match directive.split_once('=') {
+                Some((target, level)) => (Some(target), level),
+                None => (None, directive),
+            };
+            let level = match level {
+                "off" => None,
+                level => match level.parse() {
+                    Ok(level) => Some(level),
+                    Err(_) => continue,
+                },
+            };
+            match target {
+                Some(target) => filter.directives.push((target.to_string(), level)),
+                None => filter.default = level,
+            }
+        }
+        filter
+    }
+
+    pub fn enabled(&self, level: Level, module: &str) -> bool {
+        let matches = |target: &str| {
+            module == target
+                || module.strip_prefix(target).is_some_and(|rest| rest.starts_with("::"))
+                || module.rsplit("::").next() == Some(target)
+        };
+        let max = self.directives.iter()
+            .filter(|(target, _)| matches(target))
+            .max_by_key(|(target, _)| target.len())
+            .map_or(self.default, |&(_, level)| level);
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+fn filter() -> &'static Filter {
+    static FILTER: OnceLock<Filter> = OnceLock::new();
+    FILTER.get_or_init(|| Filter::parse(&std::env::var("RUST_LOG").unwrap_or_default()))
+}
+
+pub fn enabled(level: Level, module: &str) -> bool {
+    filter().enabled(level, module)
+}
+
+pub fn write(level: Level, module: &str, args: fmt::Arguments) {
+    eprintln!("[{level} {module}] {args}");
+}
+
+// Each call site caches whether it's enabled, so disabled logging in a hot loop costs an atomic
+// load rather than a walk over the filter.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        static ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        if *ENABLED.get_or_init(|| $crate::log::enabled($level, module_path!())) {
+            $crate::log::write($level, module_path!(), format_args!($($arg)+));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter = Filter::parse("");
+        assert!(!filter.enabled(Level::Error, "aoc::day16b"));
+
+        let filter = Filter::parse("debug");
+        assert!(filter.enabled(Level::Debug, "aoc::day16b"));
+        assert!(!filter.enabled(Level::Trace, "aoc::day16b"));
+
+        let filter = Filter::parse("warn, day22=trace, aoc::day1=off, bogus=loud");
+        assert!(filter.enabled(Level::Trace, "aoc::day22"));
+        assert!(filter.enabled(Level::Warn, "aoc::day16b"));
+        assert!(!filter.enabled(Level::Info, "aoc::day16b"));
+        assert!(!filter.enabled(Level::Error, "aoc::day1"));
+        assert!(!filter.enabled(Level::Error, "aoc::day1::inner"));
+        assert!(filter.enabled(Level::Warn, "aoc::day10"));
+        assert_eq!(filter.directives.len(), 2);
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::mem;
+
+// Approximate memory use of a state-space search: how big its arena of states and its queue got,
+// and roughly how many bytes they took up at their peak. Searches sample their sizes as they go,
+// since the peaks are gone by the time they finish.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemStats {
+    // The most states kept in an arena, such as a Vec of every state created so far.
+    pub arena_len: usize,
+    // The most states waiting to be expanded at once, in a queue or on the stack.
+    pub peak_queue: usize,
+    // The most bytes the arena and queue held at once, by the search's own estimate.
+    pub peak_bytes: usize,
+}
+
+impl MemStats {
+    pub fn sample(&mut self, arena_len: usize, queue_len: usize, bytes: usize) {
+        self.arena_len = self.arena_len.max(arena_len);
+        self.peak_queue = self.peak_queue.max(queue_len);
+        self.peak_bytes = self.peak_bytes.max(bytes);
+    }
+
+    // Combine the stats of searches that ran one after another, so only the biggest one's peaks
+    // matter.
+    pub fn merge(&mut self, other: MemStats) {
+        self.sample(other.arena_len, other.peak_queue, other.peak_bytes);
+    }
+}
+
+// The bytes taken up by `n` values of type T, not counting anything they point to.
+pub fn bytes_of<T>(n: usize) -> usize {
+    n.saturating_mul(mem::size_of::<T>())
+}
+
+// Roughly the bytes a HashSet has allocated: a slot for each value it has room for, plus a control
+// byte per slot.
+pub fn hash_set_bytes<T>(set: &HashSet<T>) -> usize {
+    set.capacity().saturating_mul(mem::size_of::<T>() + 1)
+}
+
+impl fmt::Display for MemStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arena_len={} peak_queue={} peak_bytes={}", self.arena_len, self.peak_queue, self.peak_bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let mut stats = MemStats::default();
+        stats.sample(1, 5, 100);
+        stats.sample(3, 2, 80);
+        assert_eq!(stats, MemStats { arena_len: 3, peak_queue: 5, peak_bytes: 100 });
+
+        let mut other = MemStats::default();
+        other.sample(2, 7, 60);
+        stats.merge(other);
+        assert_eq!(stats, MemStats { arena_len: 3, peak_queue: 7, peak_bytes: 100 });
+        assert_eq!(stats.to_string(), "arena_len=3 peak_queue=7 peak_bytes=100");
+        assert_eq!(bytes_of::<u64>(3), 24);
+        assert_eq!(hash_set_bytes(&HashSet::<u64>::new()), 0);
+        assert!(hash_set_bytes(&HashSet::<u64>::from([1, 2, 3])) >= 27);
+    }
+}
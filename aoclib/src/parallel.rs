@@ -0,0 +1,316 @@
+// A branch-and-bound search spread over threads. Each thread has its own queue of nodes to
+// expand, highest bound first, and pushes the children it makes onto it. When a thread's queue
+// runs dry it steals from the others, so the work spreads out from the roots. The best score so
+// far is shared through an atomic, so every thread prunes with it as soon as it improves.
+//
+// For work that splits up evenly without any searching, map_chunks just hands each thread a part
+// of a slice.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering::{Relaxed, SeqCst}};
+use std::sync::Mutex;
+use std::thread;
+
+pub trait Search: Sync {
+    type Node: Clone + Send;
+
+    // The most any node in the subtree under `node` could score. Nodes with the highest bound are
+    // expanded first, and ones that can't beat the best so far are pruned.
+    fn bound(&self, node: &Self::Node) -> i64;
+
+    // The node's score, if it counts as a solution.
+    fn score(&self, node: &Self::Node) -> Option<i64>;
+
+    // Push the nodes reachable from `node` onto `children`.
+    fn expand(&self, node: &Self::Node, children: &mut Vec<Self::Node>);
+
+    // For searches where different paths lead to equivalent nodes: a key for the node, and its
+    // rank among the nodes with that key. A node is dropped if one with the same key and at least
+    // its rank has already been queued.
+    fn key(&self, _node: &Self::Node) -> Option<(u64, i64)> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct Outcome<N> {
+    // The best score and the node that got it, if any node scored.
+    pub best: Option<(i64, N)>,
+    // Nodes taken off the queues and expanded.
+    pub nstates: usize,
+    // Nodes queued, including the roots.
+    pub npushed: usize,
+    // The most nodes queued at once, over all the threads.
+    pub peak_queue: usize,
+}
+
+// A queued node, ordered by its bound.
+struct Entry<N> {
+    bound: i64,
+    node: N,
+}
+
+impl<N> PartialEq for Entry<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+
+impl<N> Eq for Entry<N> {}
+
+impl<N> PartialOrd for Entry<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Entry<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound.cmp(&other.bound)
+    }
+}
+
+struct Shared<'a, S: Search> {
+    search: &'a S,
+    queues: Vec<Mutex<BinaryHeap<Entry<S::Node>>>>,
+    // The best rank queued for each key, sharded by key like the queues are by thread.
+    seen: Vec<Mutex<HashMap<u64, i64>>>,
+    best_score: AtomicI64,
+    best: Mutex<Option<(i64, S::Node)>>,
+    // Nodes that are queued or being expanded. The search is over once it drops to 0, since only
+    // nodes being expanded can queue more.
+    pending: AtomicUsize,
+    queued: AtomicUsize,
+    peak_queue: AtomicUsize,
+    nstates: AtomicUsize,
+    npushed: AtomicUsize,
+}
+
+impl<S: Search> Shared<'_, S> {
+    fn push(&self, queue: usize, node: S::Node) {
+        let bound = self.search.bound(&node);
+        if bound <= self.best_score.load(Relaxed) {
+            return;
+        }
+        if let Some((key, rank)) = self.search.key(&node) {
+            let mut seen = self.seen[key as usize % self.seen.len()].lock().unwrap();
+            match seen.get(&key) {
+                Some(&seen_rank) if seen_rank >= rank => return,
+                _ => seen.insert(key, rank),
+            };
+        }
+        self.pending.fetch_add(1, SeqCst);
+        self.npushed.fetch_add(1, Relaxed);
+        let queued = self.queued.fetch_add(1, Relaxed) + 1;
+        self.peak_queue.fetch_max(queued, Relaxed);
+        self.queues[queue].lock().unwrap().push(Entry { bound, node });
+    }
+
+    // Take the best node from this thread's queue, or else steal one from another thread's.
+    fn pop(&self, queue: usize) -> Option<Entry<S::Node>> {
+        let n = self.queues.len();
+        let entry = (0..n).find_map(|i| self.queues[(queue + i) % n].lock().unwrap().pop())?;
+        self.queued.fetch_sub(1, Relaxed);
+        Some(entry)
+    }
+
+    fn offer(&self, score: i64, node: &S::Node) {
+        if score <= self.best_score.fetch_max(score, SeqCst) {
+            return;
+        }
+        // Another thread might have found something even better in the meantime.
+        let mut best = self.best.lock().unwrap();
+        if best.as_ref().is_none_or(|&(s, _)| score > s) {
+            *best = Some((score, node.clone()));
+        }
+    }
+
+    fn work(&self, queue: usize) {
+        let mut children = Vec::new();
+        loop {
+            let Some(Entry { bound, node }) = self.pop(queue) else {
+                if self.pending.load(SeqCst) == 0 {
+                    return;
+                }
+                thread::yield_now();
+                continue;
+            };
+            // The best score may have improved since the node was queued.
+            if bound > self.best_score.load(Relaxed) {
+                self.nstates.fetch_add(1, Relaxed);
+                if let Some(score) = self.search.score(&node) {
+                    self.offer(score, &node);
+                }
+                self.search.expand(&node, &mut children);
+                for child in children.drain(..) {
+                    self.push(queue, child);
+                }
+            }
+            self.pending.fetch_sub(1, SeqCst);
+        }
+    }
+}
+
+// Search from `roots` with `threads` threads, returning the best-scoring node.
+pub fn run<S: Search>(search: &S, roots: impl IntoIterator<Item = S::Node>, threads: usize) -> Outcome<S::Node> {
+    let threads = threads.max(1);
+    let shared = Shared {
+        search,
+        queues: (0..threads).map(|_| Mutex::new(BinaryHeap::new())).collect(),
+        seen: (0..threads).map(|_| Mutex::new(HashMap::new())).collect(),
+        best_score: AtomicI64::new(i64::MIN),
+        best: Mutex::new(None),
+        pending: AtomicUsize::new(0),
+        queued: AtomicUsize::new(0),
+        peak_queue: AtomicUsize::new(0),
+        nstates: AtomicUsize::new(0),
+        npushed: AtomicUsize::new(0),
+    };
+    // Deal the roots out, so each thread starts with some work.
+    for (i, root) in roots.into_iter().enumerate() {
+        shared.push(i % threads, root);
+    }
+    thread::scope(|scope| {
+        for queue in 0..threads {
+            let shared = &shared;
+            scope.spawn(move || shared.work(queue));
+        }
+    });
+    Outcome {
+        best: shared.best.into_inner().unwrap(),
+        nstates: shared.nstates.into_inner(),
+        npushed: shared.npushed.into_inner(),
+        peak_queue: shared.peak_queue.into_inner(),
+    }
+}
+
+// Call `f` on about one chunk of `items` per thread, in parallel, with the index of the chunk's
+// first item. Every chunk but the last is a multiple of `align` items long, for work on groups of
+// items that mustn't be split up. The results are in the order of the chunks.
+pub fn map_chunks<T, R, F>(items: &[T], align: usize, threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &[T]) -> R + Sync,
+{
+    let align = align.max(1);
+    let len = items.len().div_ceil(threads.max(1)).div_ceil(align).max(1) * align;
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items.chunks(len)
+            .enumerate()
+            .map(|(i, chunk)| scope.spawn(move || f(i * len, chunk)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+// The number of threads to use when none is given: one per core.
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Pick one item from each of several groups to maximize the total, with items scoring
+    // differently depending on their group. The bound assumes the rest of the groups give their
+    // best item.
+    struct Knapsack {
+        groups: Vec<Vec<i64>>,
+    }
+
+    impl Search for Knapsack {
+        // The items picked so far.
+        type Node = Vec<usize>;
+
+        fn bound(&self, node: &Vec<usize>) -> i64 {
+            let picked: i64 = node.iter().enumerate().map(|(g, &i)| self.groups[g][i]).sum();
+            let rest: i64 = self.groups[node.len()..].iter().map(|g| g.iter().max().unwrap()).sum();
+            picked + rest
+        }
+
+        fn score(&self, node: &Vec<usize>) -> Option<i64> {
+            (node.len() == self.groups.len()).then(|| self.bound(node))
+        }
+
+        fn expand(&self, node: &Vec<usize>, children: &mut Vec<Vec<usize>>) {
+            if let Some(group) = self.groups.get(node.len()) {
+                for i in 0..group.len() {
+                    children.push([node.as_slice(), &[i]].concat());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let groups = (0..12).map(|g| (0..4).map(|i| (g * 7 + i * 13) % 10).collect()).collect();
+        let knapsack = Knapsack { groups };
+        let want: i64 = knapsack.groups.iter().map(|g| g.iter().max().unwrap()).sum();
+        for threads in [1, 2, 4] {
+            let outcome = run(&knapsack, [Vec::new()], threads);
+            let (score, picked) = outcome.best.unwrap();
+            assert_eq!(score, want, "threads={threads}");
+            assert_eq!(knapsack.score(&picked), Some(want));
+            assert!(outcome.nstates > 0 && outcome.npushed >= outcome.nstates);
+            assert!(outcome.peak_queue > 0);
+        }
+        assert!(run(&knapsack, [], 2).best.is_none());
+    }
+
+    // Counting down from a number, where nodes with the same value are the same, and the ones
+    // with fewer steps taken rank higher.
+    struct Countdown;
+
+    impl Search for Countdown {
+        // The value and the steps taken.
+        type Node = (u64, i64);
+
+        fn bound(&self, &(_, steps): &(u64, i64)) -> i64 {
+            -steps
+        }
+
+        fn score(&self, &(n, steps): &(u64, i64)) -> Option<i64> {
+            (n == 0).then_some(-steps)
+        }
+
+        fn expand(&self, &(n, steps): &(u64, i64), children: &mut Vec<(u64, i64)>) {
+            for d in [1, 2] {
+                if n >= d {
+                    children.push((n - d, steps + 1));
+                }
+            }
+        }
+
+        fn key(&self, &(n, steps): &(u64, i64)) -> Option<(u64, i64)> {
+            Some((n, -steps))
+        }
+    }
+
+    #[test]
+    fn test_map_chunks() {
+        let items: Vec<usize> = (0..100).collect();
+        for (threads, align) in [(1, 1), (3, 1), (4, 3), (8, 7), (200, 3)] {
+            let chunks = map_chunks(&items, align, threads, |start, chunk| (start, chunk.to_vec()));
+            assert!(chunks.len() <= threads, "threads={threads} align={align}");
+            for (start, chunk) in &chunks[..chunks.len() - 1] {
+                assert_eq!(chunk.len() % align, 0, "threads={threads} align={align}");
+                assert_eq!(chunk[0], *start);
+            }
+            let all: Vec<usize> = chunks.into_iter().flat_map(|(_, chunk)| chunk).collect();
+            assert_eq!(all, items);
+        }
+        assert!(map_chunks(&[] as &[u8], 3, 4, |_, chunk| chunk.len()).is_empty());
+    }
+
+    #[test]
+    fn test_run_keys() {
+        let outcome = run(&Countdown, [(40, 0)], 3);
+        assert_eq!(outcome.best, Some((-20, (0, 20))));
+        // Without the keys there'd be a node for every sequence of steps.
+        assert!(outcome.npushed < 200, "{}", outcome.npushed);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Progress reports are off unless a binary turns them on, eg for --progress.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const INTERVAL: Duration = Duration::from_millis(250);
+
+// Checking the clock on every tick would slow down tight search loops, so only look every so
+// often.
+const TICKS_PER_CHECK: u32 = 1024;
+
+pub fn enable(on: bool) {
+    ENABLED.store(on, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// Periodically overwrites a line on stderr with how many states a search has explored and its best
+// result so far, like:
+//
+//     day16: nstates=1048576 best=1651
+//
+// The line is finished when the Progress is dropped.
+pub struct Progress {
+    label: &'static str,
+    what: &'static str,
+    enabled: bool,
+    ticks: u32,
+    // Only read the clock when enabled, since some targets, like wasm32, don't have one.
+    last: Option<Instant>,
+    printed: bool,
+}
+
+impl Progress {
+    // `what` names the value passed to tick along with the number of states.
+    pub fn new(label: &'static str, what: &'static str) -> Progress {
+        let enabled = enabled();
+        Progress {
+            label,
+            what,
+            enabled,
+            ticks: 0,
+            last: enabled.then(Instant::now),
+            printed: false,
+        }
+    }
+
+    pub fn tick(&mut self, nstates: usize, best: impl fmt::Display) {
+        if !self.enabled {
+            return;
+        }
+        self.ticks += 1;
+        if self.ticks < TICKS_PER_CHECK {
+            return;
+        }
+        self.ticks = 0;
+        if self.last.is_none_or(|last| last.elapsed() >= INTERVAL) {
+            self.print(nstates, best);
+        }
+    }
+
+    // Print the current state regardless of when it was last printed, eg when a search finishes.
+    pub fn print(&mut self, nstates: usize, best: impl fmt::Display) {
+        if !self.enabled {
+            return;
+        }
+        let mut stderr = io::stderr().lock();
+        _ = write!(stderr, "\r{}: nstates={nstates} {}={best}", self.label, self.what);
+        _ = stderr.flush();
+        self.last = Some(Instant::now());
+        self.printed = true;
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.printed {
+            eprintln!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_disabled() {
+        let mut progress = Progress::new("test", "best");
+        for i in 0..10 * TICKS_PER_CHECK as usize {
+            progress.tick(i, i);
+        }
+        progress.print(1, 2);
+        assert!(!progress.printed);
+    }
+}
@@ -0,0 +1,296 @@
+// Drawing simulation state in a terminal. A day describes what's at each cell by implementing
+// Scene, and sends scenes to a Sink as the simulation runs, which either draws them or, with
+// NoRender, ignores them, so the solvers don't need to know whether --render was given. See
+// export.rs for writing them to files instead.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::animate::Animation;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Red, Green, Yellow, Blue, Magenta, Cyan, White, Gray,
+}
+
+impl Color {
+    fn ansi(self) -> &'static str {
+        match self {
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Blue => "\x1b[34m",
+            Color::Magenta => "\x1b[35m",
+            Color::Cyan => "\x1b[36m",
+            Color::White => "\x1b[97m",
+            Color::Gray => "\x1b[90m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+// What to draw in a cell. The color is only used if the renderer has colors turned on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+impl Glyph {
+    pub fn colored(ch: char, color: Color) -> Self {
+        Glyph { ch, color: Some(color) }
+    }
+}
+
+impl From<char> for Glyph {
+    fn from(ch: char) -> Self {
+        Glyph { ch, color: None }
+    }
+}
+
+// Something that can be drawn as a grid of glyphs. y grows downward.
+pub trait Scene {
+    // The columns and rows that have something to draw.
+    fn extent(&self) -> (Range<i64>, Range<i64>);
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph;
+
+    // A cell to keep in view when the scene doesn't fit in the viewport, eg the falling rock.
+    fn focus(&self) -> Option<(i64, i64)> {
+        None
+    }
+}
+
+// The most columns and rows of a scene to draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub cols: usize,
+    pub rows: usize,
+}
+
+impl Viewport {
+    pub const UNLIMITED: Viewport = Viewport { cols: usize::MAX, rows: usize::MAX };
+
+    // The size of the terminal, less a line for the title, if the shell exports COLUMNS and
+    // LINES. Otherwise scenes are drawn in full.
+    pub fn terminal() -> Self {
+        let var = |name| std::env::var(name).ok().and_then(|v| v.parse::<usize>().ok());
+        match (var("COLUMNS"), var("LINES")) {
+            (Some(cols), Some(rows)) => Viewport { cols, rows: rows.saturating_sub(1).max(1) },
+            _ => Viewport::UNLIMITED,
+        }
+    }
+
+    // The part of a scene to draw.
+    pub fn window(&self, scene: &dyn Scene) -> (Range<i64>, Range<i64>) {
+        let (xs, ys) = scene.extent();
+        let focus = scene.focus();
+        (clip(xs, self.cols, focus.map(|f| f.0)), clip(ys, self.rows, focus.map(|f| f.1)))
+    }
+}
+
+// Shrink `extent` to at most `len` cells, centered on `focus` if there is one, or else keeping the
+// start.
+fn clip(extent: Range<i64>, len: usize, focus: Option<i64>) -> Range<i64> {
+    let len = i64::try_from(len).unwrap_or(i64::MAX);
+    if extent.end.saturating_sub(extent.start) <= len {
+        return extent;
+    }
+    let start = focus.map_or(extent.start, |f| f - len / 2).clamp(extent.start, extent.end - len);
+    start..start + len
+}
+
+// Draw the part of `scene` in the viewport, one line per row.
+pub fn draw(scene: &dyn Scene, viewport: Viewport, color: bool) -> String {
+    let (xs, ys) = viewport.window(scene);
+    let mut s = String::new();
+    for y in ys {
+        let mut current = None;
+        for x in xs.clone() {
+            let glyph = scene.glyph(x, y);
+            let want = glyph.color.filter(|_| color);
+            if want != current {
+                s.push_str(want.map_or(RESET, Color::ansi));
+                current = want;
+            }
+            s.push(glyph.ch);
+        }
+        if current.is_some() {
+            s.push_str(RESET);
+        }
+        s.push('\n');
+    }
+    s
+}
+
+// Where a simulation sends its state as it changes.
+pub trait Sink {
+    fn frame(&mut self, title: fmt::Arguments, scene: &dyn Scene) -> io::Result<()>;
+
+    // Called once the simulation is done, for sinks that can only write their output at the end.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Ignores every frame.
+pub struct NoRender;
+
+impl Sink for NoRender {
+    fn frame(&mut self, _: fmt::Arguments, _: &dyn Scene) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Animates frames in a terminal.
+pub struct Renderer<W: Write> {
+    animation: Animation<W>,
+    viewport: Viewport,
+    color: bool,
+}
+
+impl Renderer<io::Stdout> {
+    pub fn stdout(delay: Duration) -> Self {
+        Renderer::new(Animation::new(io::stdout(), delay))
+    }
+}
+
+impl<W: Write> Renderer<W> {
+    pub fn new(animation: Animation<W>) -> Self {
+        Renderer { animation, viewport: Viewport::terminal(), color: false }
+    }
+
+    pub fn with_viewport(self, viewport: Viewport) -> Self {
+        Renderer { viewport, ..self }
+    }
+
+    pub fn with_color(self, color: bool) -> Self {
+        Renderer { color, ..self }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.animation.into_inner()
+    }
+}
+
+impl<W: Write> Sink for Renderer<W> {
+    fn frame(&mut self, title: fmt::Arguments, scene: &dyn Scene) -> io::Result<()> {
+        let body = draw(scene, self.viewport, self.color);
+        self.animation.frame(&title.to_string(), &body)
+    }
+}
+
+// Draws frames like Renderer, but instead of waiting for a delay after each one, waits for Enter to
+// be pressed, so a simulation can be compared against a worked example a step at a time. Entering
+// q, or the end of the keys, stops drawing and lets the simulation run to the end.
+pub struct Stepper<R: BufRead, W: Write> {
+    keys: R,
+    renderer: Renderer<W>,
+    quit: bool,
+}
+
+impl Stepper<Box<dyn BufRead>, io::Stdout> {
+    // Read keys from the terminal, since stdin might be the puzzle input.
+    pub fn stdout() -> Self {
+        let keys: Box<dyn BufRead> = match File::open("/dev/tty") {
+            Ok(tty) => Box::new(BufReader::new(tty)),
+            Err(_) => Box::new(BufReader::new(io::stdin())),
+        };
+        Stepper::new(keys, Renderer::new(Animation::new(io::stdout(), Duration::ZERO)))
+    }
+}
+
+impl<R: BufRead, W: Write> Stepper<R, W> {
+    pub fn new(keys: R, renderer: Renderer<W>) -> Self {
+        Stepper { keys, renderer, quit: false }
+    }
+
+    pub fn with_color(self, color: bool) -> Self {
+        Stepper { renderer: self.renderer.with_color(color), ..self }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.renderer.into_inner()
+    }
+}
+
+impl<R: BufRead, W: Write> Sink for Stepper<R, W> {
+    fn frame(&mut self, title: fmt::Arguments, scene: &dyn Scene) -> io::Result<()> {
+        if self.quit {
+            return Ok(());
+        }
+        self.renderer.frame(format_args!("{title}  [Enter: next, q: run to the end]"), scene)?;
+        let mut line = String::new();
+        if self.keys.read_line(&mut line)? == 0 || line.trim() == "q" {
+            self.quit = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A diagonal line of #'s on a 5x5 board, with the focus on one of them.
+    struct Diagonal(i64);
+
+    impl Scene for Diagonal {
+        fn extent(&self) -> (Range<i64>, Range<i64>) {
+            (0..5, 0..5)
+        }
+
+        fn glyph(&self, x: i64, y: i64) -> Glyph {
+            match (x == y, x == self.0) {
+                (true, true) => Glyph::colored('@', Color::Red),
+                (true, false) => '#'.into(),
+                _ => '.'.into(),
+            }
+        }
+
+        fn focus(&self) -> Option<(i64, i64)> {
+            Some((self.0, self.0))
+        }
+    }
+
+    #[test]
+    fn test_draw() {
+        let scene = Diagonal(1);
+        assert_eq!(draw(&scene, Viewport::UNLIMITED, false), "#....\n.@...\n..#..\n...#.\n....#\n");
+        assert_eq!(draw(&scene, Viewport { cols: 3, rows: 1 }, true), ".\x1b[31m@\x1b[0m.\n");
+        // The window stays inside the extent rather than centering on the focus.
+        assert_eq!(draw(&Diagonal(4), Viewport { cols: 2, rows: 2 }, false), "#.\n.@\n");
+        assert_eq!(draw(&Diagonal(0), Viewport { cols: 2, rows: 2 }, false), "@.\n.#\n");
+    }
+
+    #[test]
+    fn test_sinks() {
+        let scene = Diagonal(1);
+        NoRender.frame(format_args!("== 1 =="), &scene).unwrap();
+
+        let animation = Animation::new(Vec::new(), Duration::ZERO);
+        let mut renderer = Renderer::new(animation).with_viewport(Viewport { cols: 2, rows: 2 });
+        renderer.frame(format_args!("== {} ==", 1), &scene).unwrap();
+        let out = String::from_utf8(renderer.into_inner()).unwrap();
+        assert_eq!(out, "\x1b[2J\x1b[H== 1 ==\n#.\n.@\n");
+    }
+
+    #[test]
+    fn test_stepper() {
+        let renderer = Renderer::new(Animation::new(Vec::new(), Duration::ZERO))
+            .with_viewport(Viewport { cols: 2, rows: 1 });
+        let mut stepper = Stepper::new("\nq\n".as_bytes(), renderer).with_color(true);
+        for i in 1..=4 {
+            stepper.frame(format_args!("{i}"), &Diagonal(1)).unwrap();
+        }
+        // Nothing is drawn after q.
+        let out = String::from_utf8(stepper.into_inner()).unwrap();
+        let prompt = "  [Enter: next, q: run to the end]";
+        let row = ".\x1b[31m@\x1b[0m\n";
+        assert_eq!(out, format!("\x1b[2J\x1b[H1{prompt}\n{row}\x1b[2J\x1b[H2{prompt}\n{row}"));
+    }
+}
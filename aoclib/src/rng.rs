@@ -0,0 +1,52 @@
+use std::ops::Range;
+
+// A small deterministic random number generator for property tests and generated inputs, so
+// failures and benchmarks are repeatable.
+// It's a 64-bit LCG, so the low bits are poor and the helpers use the high ones.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    // A number in 0..n.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() >> 32) as usize % n
+    }
+
+    pub fn range(&mut self, r: Range<i64>) -> i64 {
+        r.start + (self.next_u64() >> 1) as i64 % (r.end - r.start)
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    // Damage a string by deleting, duplicating or replacing one of its characters, or truncating
+    // it, for checking that parsers reject bad input without panicking.
+    pub fn mutate(&mut self, s: &str, alphabet: &str) -> String {
+        let mut chars: Vec<char> = s.chars().collect();
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        if chars.is_empty() {
+            return self.choose(&alphabet).to_string();
+        }
+        let i = self.below(chars.len());
+        match self.below(4) {
+            0 => _ = chars.remove(i),
+            1 => chars.insert(i, chars[i]),
+            2 => chars[i] = *self.choose(&alphabet),
+            _ => chars.truncate(i),
+        }
+        chars.into_iter().collect()
+    }
+}
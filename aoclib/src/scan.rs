@@ -0,0 +1,140 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+// A cursor over a line of puzzle input, for hand-written parsers. Errors are strings that start
+// with the column where scanning failed, so callers only need to add the line number.
+pub struct Scanner<'a> {
+    s: &'a str,
+    i: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self { s, i: 0 }
+    }
+
+    // The byte offset of the next char.
+    pub fn pos(&self) -> usize {
+        self.i
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.s[self.i..]
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.i += c.len_utf8();
+        Some(c)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.i >= self.s.len()
+    }
+
+    // Skip whatever's left, eg to stop after an error.
+    pub fn skip_rest(&mut self) {
+        self.i = self.s.len();
+    }
+
+    // Make an error message pointing at the current column.
+    pub fn error(&self, msg: impl Display) -> String {
+        format!("col {}: {msg}", self.i + 1)
+    }
+
+    pub fn take_while<P>(&mut self, predicate: P) -> &'a str
+    where
+        P: Fn(char) -> bool,
+    {
+        let start = self.i;
+        while let Some(true) = self.peek().map(&predicate) {
+            self.next_char();
+        }
+        &self.s[start..self.i]
+    }
+
+    // Consume `s` if it's next, returning whether it was.
+    pub fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.i += s.len();
+        }
+        found
+    }
+
+    pub fn expect(&mut self, expect: &str) -> Result<&'a str, String> {
+        let start = self.i;
+        if self.eat(expect) {
+            Ok(&self.s[start..self.i])
+        } else {
+            Err(self.error(format_args!("expected {expect:?}, got {:?}", self.rest())))
+        }
+    }
+
+    // Parse an integer, with a leading minus sign if it's negative.
+    pub fn int<T>(&mut self) -> Result<T, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let start = self.i;
+        self.eat("-");
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        let result = match digits {
+            "" => Err(format!("expected an integer, got {:?}", &self.s[start..])),
+            _ => self.s[start..self.i].parse::<T>().map_err(|e| format!("{e}: {:?}", &self.s[start..self.i])),
+        };
+        result.map_err(|e| {
+            self.i = start;
+            self.error(e)
+        })
+    }
+
+    // Check that there's nothing left.
+    pub fn end(&self) -> Result<(), String> {
+        match self.rest() {
+            "" => Ok(()),
+            rest => Err(self.error(format_args!("unexpected trailing {rest:?}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let mut s = Scanner::new("x=-12, name=AB; ñ");
+        assert_eq!(s.expect("x="), Ok("x="));
+        assert_eq!(s.int::<i64>(), Ok(-12));
+        assert!(!s.eat(";"));
+        assert!(s.eat(", name="));
+        assert_eq!(s.take_while(|c| c.is_ascii_uppercase()), "AB");
+        assert_eq!(s.peek(), Some(';'));
+        assert_eq!(s.expect(";"), Ok(";"));
+        assert_eq!(s.next_char(), Some(' '));
+        assert_eq!(s.next_char(), Some('ñ'));
+        assert!(s.is_done() && s.end().is_ok());
+        assert_eq!(s.next_char(), None);
+    }
+
+    #[test]
+    fn test_scanner_errors() {
+        let mut s = Scanner::new("a=1 b=-2");
+        assert_eq!(s.expect("b="), Err(r#"col 1: expected "b=", got "a=1 b=-2""#.to_string()));
+        s.expect("a=").unwrap();
+        assert_eq!(s.int::<u8>(), Ok(1));
+        assert_eq!(s.end(), Err(r#"col 4: unexpected trailing " b=-2""#.to_string()));
+        s.expect(" b=").unwrap();
+        // Failed ints don't consume anything.
+        assert!(s.int::<u8>().unwrap_err().starts_with("col 7: "));
+        assert_eq!(s.int::<i8>(), Ok(-2));
+        assert_eq!(Scanner::new("-").int::<i32>(), Err(r#"col 1: expected an integer, got "-""#.to_string()));
+        assert!(Scanner::new("300").int::<u8>().is_err());
+    }
+}
@@ -0,0 +1,393 @@
+// Vectorized versions of a few hot loops, with scalar fallbacks. The vector code is only built with
+// the `simd` feature on x86_64, and is only used if the CPU turns out to support it when the
+// program runs. Either way the results are the same, which the tests check by comparing the two.
+
+use std::ops::Range;
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub fn enabled() -> bool {
+    is_x86_feature_detected!("sse2")
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+pub fn enabled() -> bool {
+    false
+}
+
+// Whether no byte appears twice.
+pub fn all_distinct(bytes: &[u8]) -> bool {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if bytes.len() <= 16 && enabled() {
+        // Safe since the CPU supports SSE2.
+        return unsafe { sse2::all_distinct(bytes) };
+    }
+    scalar::all_distinct(bytes)
+}
+
+// The longest window find_distinct_window checks with vectors. Longer ones are checked one at a
+// time.
+pub const MAX_VECTOR_WINDOW: usize = 16;
+
+// The end of the first `len` bytes in a row that are all different, or None if there aren't any.
+// `len` should be positive.
+pub fn find_distinct_window(bytes: &[u8], len: usize) -> Option<usize> {
+    assert!(len > 0, "windows should be at least one byte long");
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if len <= MAX_VECTOR_WINDOW && enabled() {
+        // Safe since the CPU supports SSE2.
+        return unsafe { sse2::find_distinct_window(bytes, len) };
+    }
+    scalar::find_distinct_window(bytes, len, 0)
+}
+
+// Look at the rows of a grid of heights, stored a row at a time with `width` cells to a row, in
+// the order given by `rows`, and set visible[i] to 1 for each cell that's taller than every cell
+// before it in its column. Heights must be less than 127.
+pub fn mark_visible(heights: &[u8], width: usize, rows: impl Iterator<Item = usize>, visible: &mut [u8]) {
+    assert_eq!(heights.len(), visible.len());
+    // The tallest height so far in each column, plus one, so that 0 can mean there's nothing yet.
+    let mut tallest = vec![0; width];
+    for y in rows {
+        let row = &heights[y * width..(y + 1) * width];
+        let vis = &mut visible[y * width..(y + 1) * width];
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        if enabled() {
+            // Safe since the CPU supports SSE2.
+            unsafe { sse2::mark_visible_row(row, &mut tallest, vis) };
+            continue;
+        }
+        scalar::mark_visible_row(row, &mut tallest, vis, 0);
+    }
+}
+
+// Manhattan-distance circles, kept as separate arrays of x, y and radius so they can be loaded a few
+// at a time. Everything is stored as an i32, which is enough for day15's coordinates, and faster.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Diamonds {
+    xs: Vec<i32>,
+    ys: Vec<i32>,
+    radii: Vec<i32>,
+}
+
+// Coordinates and radii are kept within this, so the ends of a span can't overflow.
+const DIAMOND_LIMIT: i64 = 1 << 29;
+
+impl Diamonds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, x: i64, y: i64, radius: i64) -> Result<(), String> {
+        let fits = |v: i64| v.abs() < DIAMOND_LIMIT;
+        if !(fits(x) && fits(y) && fits(radius)) || radius < 0 {
+            return Err(format!("diamond at {x},{y} with radius {radius} is too big"));
+        }
+        self.xs.push(x as i32);
+        self.ys.push(y as i32);
+        self.radii.push(radius as i32);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+}
+
+// Append the columns each diamond covers in `row`, clipped to `clip`, skipping the ones that don't
+// cover any of it. The spans are in the same order as the diamonds.
+pub fn row_spans(diamonds: &Diamonds, row: i32, clip: Range<i32>, out: &mut Vec<Range<i32>>) {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if enabled() {
+        // Safe since the CPU supports SSE2.
+        unsafe { sse2::row_spans(diamonds, row, clip, out) };
+        return;
+    }
+    scalar::row_spans(diamonds, row, clip, out, 0);
+}
+
+mod scalar {
+    use super::*;
+
+    pub fn all_distinct(bytes: &[u8]) -> bool {
+        let mut seen = [false; 256];
+        bytes.iter().all(|&b| !std::mem::replace(&mut seen[b as usize], true))
+    }
+
+    // Like super::find_distinct_window, for the windows starting at `start` or later.
+    pub fn find_distinct_window(bytes: &[u8], len: usize, start: usize) -> Option<usize> {
+        bytes.get(start..)?.windows(len).position(all_distinct).map(|i| start + i + len)
+    }
+
+    // Like super::mark_visible, for one row, starting at column `start`.
+    pub fn mark_visible_row(row: &[u8], tallest: &mut [u8], visible: &mut [u8], start: usize) {
+        for x in start..row.len() {
+            let h = row[x] + 1;
+            if h > tallest[x] {
+                tallest[x] = h;
+                visible[x] = 1;
+            }
+        }
+    }
+
+    // Like super::row_spans, starting with the diamond at `start`.
+    pub fn row_spans(d: &Diamonds, row: i32, clip: Range<i32>, out: &mut Vec<Range<i32>>, start: usize) {
+        for i in start..d.len() {
+            let half = d.radii[i] - (d.ys[i] - row).abs();
+            let lo = (d.xs[i] - half).max(clip.start);
+            let hi = (d.xs[i] + half + 1).min(clip.end);
+            if lo < hi {
+                out.push(lo..hi);
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    use super::*;
+
+    // For at most 16 bytes. Compares the bytes against themselves shifted along by k, for each k,
+    // so each pair is compared once, in 15 compares instead of 120.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn all_distinct(bytes: &[u8]) -> bool {
+        let n = bytes.len();
+        debug_assert!(n <= 16);
+        // Room to load 16 bytes starting at any of the first 16.
+        let mut buf = [0u8; 32];
+        buf[..n].copy_from_slice(bytes);
+        let v = _mm_loadu_si128(buf.as_ptr() as *const __m128i);
+        for k in 1..n {
+            let shifted = _mm_loadu_si128(buf.as_ptr().add(k) as *const __m128i);
+            let eq = _mm_movemask_epi8(_mm_cmpeq_epi8(v, shifted)) as u32;
+            // Only the first n - k lanes hold pairs of bytes from the slice.
+            if eq & ((1 << (n - k)) - 1) != 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    // 16 windows at a time, one to a lane, by the byte they end at. Each byte is compared with the
+    // ones up to len - 1 before it, giving where the closest repeat of it starts, and a window
+    // has a byte twice if any repeat that ends in it starts in it too. Repeats can't end before
+    // they start, so that's if the latest start of a repeat ending at or before its last byte is
+    // in it, which is a running max along the lanes.
+    //
+    // Positions are kept as bytes, relative to 16 before the first window's last byte, with 0 for
+    // no repeat. Anything further back is before every window in the block anyway.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn find_distinct_window(bytes: &[u8], len: usize) -> Option<usize> {
+        debug_assert!(0 < len && len <= MAX_VECTOR_WINDOW);
+        // The windows ending in the first 15 bytes, which don't have 15 bytes before them to load.
+        let head = bytes.len().min(15);
+        if let Some(end) = scalar::find_distinct_window(&bytes[..head], len, 0) {
+            return Some(end);
+        }
+        // The latest start of a repeat in the first 15 bytes, relative to 16 before byte 15.
+        let mut latest = (1..head)
+            .filter_map(|q| (1..len.min(q + 1)).find(|&d| bytes[q - d] == bytes[q]).map(|d| q - d + 1))
+            .max()
+            .unwrap_or(0) as u8;
+
+        let lanes = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        // A window is bad if a repeat starts at or after its first byte.
+        let first_bytes = _mm_add_epi8(lanes, _mm_set1_epi8(16 - len as i8));
+        let load = |i: usize| _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i);
+        let mut i = 15;
+        while i + 16 <= bytes.len() {
+            let v = load(i);
+            let mut starts = _mm_setzero_si128();
+            for d in 1..len {
+                let repeat = _mm_cmpeq_epi8(v, load(i - d));
+                let start = _mm_add_epi8(lanes, _mm_set1_epi8(16 - d as i8));
+                starts = _mm_max_epu8(starts, _mm_and_si128(repeat, start));
+            }
+            starts = _mm_max_epu8(starts, _mm_slli_si128::<1>(starts));
+            starts = _mm_max_epu8(starts, _mm_slli_si128::<2>(starts));
+            starts = _mm_max_epu8(starts, _mm_slli_si128::<4>(starts));
+            starts = _mm_max_epu8(starts, _mm_slli_si128::<8>(starts));
+            starts = _mm_max_epu8(starts, _mm_set1_epi8(latest as i8));
+            let bad = _mm_movemask_epi8(_mm_cmpgt_epi8(starts, first_bytes)) as u32;
+            if bad != 0xffff {
+                return Some(i + (!bad).trailing_zeros() as usize + 1);
+            }
+            latest = ((_mm_extract_epi16::<7>(starts) >> 8) as u8).saturating_sub(16);
+            i += 16;
+        }
+        scalar::find_distinct_window(bytes, len, (i + 1).saturating_sub(len))
+    }
+
+    // 16 columns at a time, then the rest one at a time.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn mark_visible_row(row: &[u8], tallest: &mut [u8], visible: &mut [u8]) {
+        let ones = _mm_set1_epi8(1);
+        let mut x = 0;
+        while x + 16 <= row.len() {
+            let h = _mm_add_epi8(_mm_loadu_si128(row.as_ptr().add(x) as *const __m128i), ones);
+            let t = _mm_loadu_si128(tallest.as_ptr().add(x) as *const __m128i);
+            let v = _mm_loadu_si128(visible.as_ptr().add(x) as *const __m128i);
+            // Heights are under 128, so comparing them as signed bytes works.
+            let taller = _mm_cmpgt_epi8(h, t);
+            let v = _mm_or_si128(v, _mm_and_si128(taller, ones));
+            _mm_storeu_si128(visible.as_mut_ptr().add(x) as *mut __m128i, v);
+            _mm_storeu_si128(tallest.as_mut_ptr().add(x) as *mut __m128i, _mm_max_epu8(h, t));
+            x += 16;
+        }
+        scalar::mark_visible_row(row, tallest, visible, x);
+    }
+
+    // SSE2 has no min or max for 32-bit lanes, so they're made from a compare and masks.
+    #[target_feature(enable = "sse2")]
+    unsafe fn max_epi32(a: __m128i, b: __m128i) -> __m128i {
+        let a_gt = _mm_cmpgt_epi32(a, b);
+        _mm_or_si128(_mm_and_si128(a_gt, a), _mm_andnot_si128(a_gt, b))
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn min_epi32(a: __m128i, b: __m128i) -> __m128i {
+        let a_gt = _mm_cmpgt_epi32(a, b);
+        _mm_or_si128(_mm_and_si128(a_gt, b), _mm_andnot_si128(a_gt, a))
+    }
+
+    // 4 diamonds at a time, then the rest one at a time.
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn row_spans(d: &Diamonds, row: i32, clip: Range<i32>, out: &mut Vec<Range<i32>>) {
+        let rows = _mm_set1_epi32(row);
+        let (clip_lo, clip_hi) = (_mm_set1_epi32(clip.start), _mm_set1_epi32(clip.end));
+        let ones = _mm_set1_epi32(1);
+        let load = |v: &[i32], i: usize| _mm_loadu_si128(v.as_ptr().add(i) as *const __m128i);
+        let mut i = 0;
+        while i + 4 <= d.len() {
+            let (xs, ys, radii) = (load(&d.xs, i), load(&d.ys, i), load(&d.radii, i));
+            let dy = _mm_sub_epi32(ys, rows);
+            let sign = _mm_srai_epi32(dy, 31);
+            let abs_dy = _mm_sub_epi32(_mm_xor_si128(dy, sign), sign);
+            let half = _mm_sub_epi32(radii, abs_dy);
+            let lo = max_epi32(_mm_sub_epi32(xs, half), clip_lo);
+            let hi = min_epi32(_mm_add_epi32(_mm_add_epi32(xs, half), ones), clip_hi);
+            // A diamond that doesn't reach the row has a negative half-width, so hi <= lo.
+            let keep = _mm_movemask_ps(_mm_castsi128_ps(_mm_cmpgt_epi32(hi, lo)));
+            let (mut los, mut his) = ([0i32; 4], [0i32; 4]);
+            _mm_storeu_si128(los.as_mut_ptr() as *mut __m128i, lo);
+            _mm_storeu_si128(his.as_mut_ptr() as *mut __m128i, hi);
+            for lane in 0..4 {
+                if keep & (1 << lane) != 0 {
+                    out.push(los[lane]..his[lane]);
+                }
+            }
+            i += 4;
+        }
+        scalar::row_spans(d, row, clip, out, i);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_all_distinct() {
+        assert!(all_distinct(b""));
+        assert!(all_distinct(b"abcd"));
+        assert!(!all_distinct(b"abca"));
+        assert!(all_distinct(b"abcdefghijklmnop"));
+        assert!(!all_distinct(b"abcdefghijklmnoa"));
+        assert!(!all_distinct(b"abcdefghijklmnopa"));
+    }
+
+    // These compare against the scalar versions, which is only a real test with the simd feature.
+    #[test]
+    fn test_all_distinct_random() {
+        let mut rng = Rng::new(6);
+        for _ in 0..10_000 {
+            let bytes: Vec<u8> = (0..rng.below(20)).map(|_| b'a' + rng.below(26) as u8).collect();
+            assert_eq!(all_distinct(&bytes), scalar::all_distinct(&bytes), "{bytes:?}");
+        }
+    }
+
+    #[test]
+    fn test_find_distinct_window() {
+        assert_eq!(find_distinct_window(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), Some(7));
+        assert_eq!(find_distinct_window(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(find_distinct_window(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab", 2), Some(38));
+        assert_eq!(find_distinct_window(b"abc", 4), None);
+        assert_eq!(find_distinct_window(b"", 1), None);
+    }
+
+    #[test]
+    fn test_find_distinct_window_random() {
+        let mut rng = Rng::new(6);
+        for _ in 0..5000 {
+            let letters = 1 + rng.below(26);
+            let bytes: Vec<u8> = (0..rng.below(200)).map(|_| b'a' + rng.below(letters) as u8).collect();
+            let len = 1 + rng.below(20);
+            assert_eq!(find_distinct_window(&bytes, len), scalar::find_distinct_window(&bytes, len, 0), "{bytes:?} {len}");
+        }
+    }
+
+    #[test]
+    fn test_mark_visible() {
+        // From day8's example, looking down from the top.
+        let heights = [3, 0, 3, 7, 3, 2, 5, 5, 1, 2, 6, 5, 3, 3, 2];
+        let mut visible = [0; 15];
+        mark_visible(&heights, 5, 0..3, &mut visible);
+        assert_eq!(visible, [1, 1, 1, 1, 1, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_mark_visible_random() {
+        let mut rng = Rng::new(8);
+        for _ in 0..200 {
+            let (width, height) = (1 + rng.below(40), 1 + rng.below(10));
+            let heights: Vec<u8> = (0..width * height).map(|_| rng.below(10) as u8).collect();
+            let (mut got, mut want) = (vec![0; heights.len()], vec![0; heights.len()]);
+            mark_visible(&heights, width, (0..height).rev(), &mut got);
+            let mut tallest = vec![0; width];
+            for y in (0..height).rev() {
+                let row = y * width..(y + 1) * width;
+                scalar::mark_visible_row(&heights[row.clone()], &mut tallest, &mut want[row], 0);
+            }
+            assert_eq!(got, want);
+        }
+    }
+
+    #[test]
+    fn test_row_spans() {
+        let mut diamonds = Diamonds::new();
+        diamonds.push(8, 7, 9).unwrap();
+        diamonds.push(2, 18, 7).unwrap();
+        diamonds.push(-20, 10, 5).unwrap();
+        let mut spans = Vec::new();
+        row_spans(&diamonds, 10, -100..100, &mut spans);
+        assert_eq!(spans, [2..15, -25..-14]);
+        spans.clear();
+        row_spans(&diamonds, 10, 0..20, &mut spans);
+        assert_eq!(spans, [Range { start: 2, end: 15 }]);
+        assert!(diamonds.push(0, 0, 1 << 30).is_err());
+        assert!(diamonds.push(0, 0, -1).is_err());
+    }
+
+    #[test]
+    fn test_row_spans_random() {
+        let mut rng = Rng::new(15);
+        for _ in 0..500 {
+            let mut diamonds = Diamonds::new();
+            for _ in 0..rng.below(12) {
+                diamonds.push(rng.range(-50..50), rng.range(-50..50), rng.range(0..30)).unwrap();
+            }
+            let row = rng.range(-60..60) as i32;
+            let clip = rng.range(-60..0) as i32..rng.range(0..60) as i32;
+            let (mut got, mut want) = (Vec::new(), Vec::new());
+            row_spans(&diamonds, row, clip.clone(), &mut got);
+            scalar::row_spans(&diamonds, row, clip, &mut want, 0);
+            assert_eq!(got, want);
+        }
+    }
+}
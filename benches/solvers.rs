@@ -0,0 +1,52 @@
+// Time every solver on the inputs in input/, to compare rewrites of the slower searches. Run a
+// subset by giving day numbers, eg `cargo bench -- 16 19`.
+//
+// Each part is run once to warm up, then repeatedly until it's used up its time budget, and the
+// fastest and median runs are reported.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use advent_of_code_2022::fetch::{input_path, INPUT_DIR};
+use advent_of_code_2022::error::AocError;
+use advent_of_code_2022::runner::SOLVERS;
+
+const BUDGET: Duration = Duration::from_secs(2);
+const MAX_RUNS: usize = 100;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // cargo passes --bench, and maybe other flags meant for libtest, which we ignore.
+    let days: Vec<u32> = std::env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with('-'))
+        .map(|a| a.parse())
+        .collect::<Result<_, _>>()?;
+    println!("{:>3} {:>4} {:>5} {:>12} {:>12}", "day", "part", "runs", "min", "median");
+    for solver in SOLVERS {
+        let day = solver.day();
+        if !days.is_empty() && !days.contains(&day) {
+            continue;
+        }
+        let Ok(input) = std::fs::read_to_string(input_path(INPUT_DIR, day)) else {
+            println!("{day:>3} skipped, no input");
+            continue;
+        };
+        for part in 1..=2 {
+            match solver.solve(part, &input) {
+                Err(AocError::NoSolver { .. }) => continue,
+                result => result?,
+            };
+            let mut times = Vec::new();
+            let start = Instant::now();
+            while times.len() < MAX_RUNS && start.elapsed() < BUDGET {
+                let t = Instant::now();
+                std::hint::black_box(solver.solve(part, &input)?);
+                times.push(t.elapsed());
+            }
+            times.sort();
+            let (min, median) = (times[0], times[times.len() / 2]);
+            println!("{day:>3} {part:>4} {:>5} {:>12?} {:>12?}", times.len(), min, median);
+        }
+    }
+    Ok(())
+}
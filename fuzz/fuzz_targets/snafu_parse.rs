@@ -0,0 +1,32 @@
+#![no_main]
+
+// Run with `cargo +nightly fuzz run snafu_parse` from the repo root.
+
+use advent_of_code_2022::day25::{add_digits, format_digits, from_snafu, parse_digits, part1, to_snafu};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // part1 should only ever fail cleanly, however mangled its input.
+    let _ = part1(data);
+
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(digits) = parse_digits(s) else {
+        assert!(from_snafu(s).is_err());
+        return;
+    };
+    // Formatting drops leading zeros but keeps the value.
+    let formatted = format_digits(&digits);
+    let mut reparsed = parse_digits(&formatted).unwrap();
+    assert_eq!(format_digits(&reparsed), formatted);
+    add_digits(&mut reparsed, &digits);
+    let mut doubled = digits.clone();
+    add_digits(&mut doubled, &digits);
+    assert_eq!(format_digits(&reparsed), format_digits(&doubled));
+
+    // Up to 27 digits always fit in an i64.
+    if digits.len() <= 27 {
+        assert_eq!(to_snafu(from_snafu(s).unwrap()), formatted);
+    }
+});
@@ -0,0 +1,160 @@
+// Known-correct answers, read from a file in a small subset of TOML, with a table for each day and
+// a key for each part:
+//
+//     [day10]
+//     part1 = 14160
+//     part2 = """
+//     ###....##.
+//     """
+//
+// Values are integers, "strings", or """strings""" spanning lines, for answers like day10 part2's.
+// Strings have no escapes, since no answer needs them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::AocError;
+
+// Where `aoc` looks for answers, relative to the working directory.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers(HashMap<(u32, u32), String>);
+
+// How an answer compares to the recorded one.
+#[derive(Debug, PartialEq)]
+pub enum Verdict<'a> {
+    Right,
+    Wrong { want: &'a str },
+    Unknown,
+}
+
+impl Answers {
+    pub fn read(path: impl AsRef<Path>) -> Result<Answers, AocError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| AocError::BadArgs(format!("read {}: {e}", path.display())))?;
+        Answers::parse(&contents).map_err(|e| AocError::Parse(format!("{}: {e}", path.display())))
+    }
+
+    pub fn parse(s: &str) -> Result<Answers, String> {
+        let mut answers = HashMap::new();
+        let mut day = None;
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+        while let Some((lineno, line)) = lines.next() {
+            let err = |msg: String| format!("line {lineno}: {msg}");
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let n = table.trim().strip_prefix("day").and_then(|n| n.parse().ok());
+                day = Some(n.ok_or_else(|| err(format!("table should be [dayN]: {line}")))?);
+                continue;
+            }
+            let day = day.ok_or_else(|| err("answer before any [dayN] table".to_string()))?;
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| err(format!("want part = answer: {line}")))?;
+            let part: u32 = key.trim().strip_prefix("part").and_then(|n| n.parse().ok())
+                .ok_or_else(|| err(format!("key should be partN: {}", key.trim())))?;
+            let value = value.trim();
+            let answer = if let Some(rest) = value.strip_prefix("\"\"\"") {
+                // The newline right after the opening quotes isn't part of the string.
+                let mut answer = rest.to_string();
+                let mut first = true;
+                loop {
+                    if let Some(i) = answer.find("\"\"\"") {
+                        if !answer[i + 3..].trim().is_empty() {
+                            return Err(err("text after closing \"\"\"".to_string()));
+                        }
+                        answer.truncate(i);
+                        break;
+                    }
+                    let (_, next) = lines.next().ok_or_else(|| err("unterminated \"\"\"".to_string()))?;
+                    if !first || !rest.is_empty() {
+                        answer.push('\n');
+                    }
+                    answer.push_str(next);
+                    first = false;
+                }
+                answer
+            } else if let Some(rest) = value.strip_prefix('"') {
+                rest.strip_suffix('"').filter(|s| !s.contains('"'))
+                    .ok_or_else(|| err(format!("bad string: {value}")))?
+                    .to_string()
+            } else if value.parse::<i64>().is_ok() {
+                value.to_string()
+            } else {
+                return Err(err(format!("answer should be an integer or string: {value}")));
+            };
+            if answers.insert((day, part), answer).is_some() {
+                return Err(err(format!("day{day} part{part} given twice")));
+            }
+        }
+        Ok(Answers(answers))
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+
+    // Trailing whitespace is ignored, so multi-line answers can end with a newline or not.
+    pub fn check(&self, day: u32, part: u32, answer: &str) -> Verdict<'_> {
+        match self.get(day, part) {
+            Some(want) if want.trim_end() == answer.trim_end() => Verdict::Right,
+            Some(want) => Verdict::Wrong { want: want.trim_end() },
+            None => Verdict::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("\
+# comment
+[day1]
+part1 = 71471
+part2 = \"-12\"
+
+[ day10 ]
+part2 = \"\"\"
+#.
+.#
+\"\"\"
+[day25]
+part1 = \"\"\"2=-01\"\"\"
+").unwrap();
+        assert_eq!(answers.get(1, 1), Some("71471"));
+        assert_eq!(answers.get(1, 2), Some("-12"));
+        assert_eq!(answers.get(10, 2), Some("#.\n.#\n"));
+        assert_eq!(answers.get(25, 1), Some("2=-01"));
+        assert_eq!(answers.get(10, 1), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s| Answers::parse(s).unwrap_err();
+        assert_eq!(err("part1 = 1"), "line 1: answer before any [dayN] table");
+        assert_eq!(err("[1]"), "line 1: table should be [dayN]: [1]");
+        assert_eq!(err("[day1]\npart1 1"), "line 2: want part = answer: part1 1");
+        assert_eq!(err("[day1]\nanswer = 1"), "line 2: key should be partN: answer");
+        assert_eq!(err("[day1]\npart1 = one"), "line 2: answer should be an integer or string: one");
+        assert_eq!(err("[day1]\npart1 = \"one"), "line 2: bad string: \"one");
+        assert_eq!(err("[day1]\npart1 = \"\"\"\none\n"), "line 2: unterminated \"\"\"");
+        assert_eq!(err("[day1]\npart1 = 1\n[day1]\npart1 = 2"), "line 4: day1 part1 given twice");
+    }
+
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse("[day10]\npart1 = 13140\npart2 = \"\"\"\n#.\n.#\n\"\"\"").unwrap();
+        assert_eq!(answers.check(10, 1, "13140"), Verdict::Right);
+        assert_eq!(answers.check(10, 1, "13141"), Verdict::Wrong { want: "13140" });
+        assert_eq!(answers.check(10, 2, "#.\n.#"), Verdict::Right);
+        assert_eq!(answers.check(11, 1, "1"), Verdict::Unknown);
+    }
+}
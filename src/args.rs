@@ -0,0 +1,470 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::slice;
+use std::str::FromStr;
+use std::time::Duration;
+
+use aoclib::animate::FRAME_DELAY;
+use aoclib::checkpoint::Checkpointer;
+use aoclib::export::{ExportFormat, Exporter};
+use aoclib::parallel;
+use aoclib::render::{NoRender, Renderer, Sink, Stepper};
+
+use crate::error::AocError;
+
+// A binary's command line, and what they all handle the same way: -h|--help prints the usage, and
+// errors are reported with the binary's name. Errors in the arguments, which are
+// AocError::BadArgs, also get a reminder of the usage and exit with status 2 instead of 1.
+pub struct Cli {
+    // The first lines of the usage, starting with the binary's name.
+    pub synopsis: &'static str,
+    // Sections of the usage shared with other binaries, like INPUT_HELP, which go before `options`.
+    pub common: &'static [&'static str],
+    // The binary's own commands and options.
+    pub options: &'static str,
+}
+
+pub const INPUT_HELP: &str = "\
+--input FILE
+    Read the puzzle input from FILE instead of stdin. - means stdin.
+";
+
+pub const RENDER_HELP: &str = "\
+--render
+    Show the simulation as it runs.
+
+--delay MS
+    Milliseconds to show each frame for with --render. Defaults to 100.
+
+--step
+    Like --render, but wait for Enter after each frame instead of the delay.
+    Entering q runs to the end without showing any more.
+
+--color
+    With --render or --step, color the scene, if stdout is a terminal.
+
+--export-frames DIR
+    Instead of showing the frames, write them to files in DIR:
+    frame-000001.svg and so on, or with --export-format gif, one animated
+    frames.gif, shown for --delay per frame.
+
+--export-format svg|gif
+    What to write with --export-frames. Defaults to svg.
+";
+
+pub const SEARCH_HELP: &str = "\
+--progress
+    Show how many states the search has expanded on stderr as it goes.
+
+--checkpoint FILE
+    Save the search's progress to FILE every so often, and resume from it if
+    it's there. It's removed once the search finishes.
+
+--checkpoint-secs N
+    Seconds between saves with --checkpoint. Defaults to 60.
+
+--threads N
+    Spread the search over N threads, or one per core if N is 0. Doesn't go
+    with --checkpoint.
+";
+
+impl Cli {
+    pub fn usage(&self) -> String {
+        let mut usage = format!("{}\n\n-h|--help\n    Show this help.\n", self.synopsis);
+        for section in self.common.iter().chain([&self.options]).filter(|s| !s.is_empty()) {
+            usage.push('\n');
+            usage.push_str(section);
+        }
+        usage
+    }
+
+    fn name(&self) -> &str {
+        self.synopsis.split_whitespace().next().unwrap_or_default()
+    }
+
+    // Call `main` with the command line's arguments, unless they ask for help, and report any error
+    // it returns.
+    pub fn run(&self, main: impl FnOnce(&mut Vec<&str>) -> Result<(), Box<dyn Error>>) -> ExitCode {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+        if args.iter().any(|&a| a == "-h" || a == "--help") {
+            print!("{}", self.usage());
+            return ExitCode::SUCCESS;
+        }
+        match main(&mut args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => ExitCode::from(self.report(e.as_ref(), &mut io::stderr().lock())),
+        }
+    }
+
+    // Write an error to `w`, returning the exit status.
+    fn report(&self, e: &(dyn Error + 'static), w: &mut impl Write) -> u8 {
+        let name = self.name();
+        // There's nowhere left to report a failure to write to stderr.
+        let _ = writeln!(w, "{name}: {e}");
+        match e.downcast_ref::<AocError>() {
+            Some(AocError::BadArgs(_)) => {
+                let _ = writeln!(w, "usage: {}\nRun `{name} --help` for the options.", self.synopsis);
+                2
+            },
+            _ => 1,
+        }
+    }
+}
+
+// The error for arguments left over that a binary doesn't understand: the first one that looks
+// like an option, or else the commands it expected. `commands` is empty for binaries without any.
+pub fn unexpected(args: &[&str], commands: &str) -> AocError {
+    let msg = match args.iter().find(|a| a.starts_with('-')) {
+        Some(arg) => format!("unknown option: {arg}"),
+        None if commands.is_empty() => format!("unexpected arguments: {}", args.join(" ")),
+        None if args.is_empty() => format!("must specify {commands}"),
+        None => format!("must specify {commands}, not {}", args.join(" ")),
+    };
+    AocError::BadArgs(msg)
+}
+
+// Parse an option's value, saying which option it was for if it's bad.
+pub fn parse_value<T: FromStr>(flag: &str, value: &str) -> Result<T, AocError>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|e| AocError::BadArgs(format!("{flag} {value:?}: {e}")))
+}
+
+// Steps through the options after a command, for the binaries whose commands have their own, so
+// they all report missing and bad values the same way.
+pub struct Flags<'a, 'b> {
+    args: slice::Iter<'b, &'a str>,
+    flag: &'a str,
+}
+
+impl<'a, 'b> Flags<'a, 'b> {
+    pub fn new(args: &'b [&'a str]) -> Self {
+        Flags { args: args.iter(), flag: "" }
+    }
+
+    // The value of the last option returned by next().
+    pub fn value(&mut self) -> Result<&'a str, AocError> {
+        self.args.next().copied().ok_or_else(|| AocError::BadArgs(format!("{} needs a value", self.flag)))
+    }
+
+    pub fn parse<T: FromStr>(&mut self) -> Result<T, AocError>
+    where
+        T::Err: Display,
+    {
+        parse_value(self.flag, self.value()?)
+    }
+
+    // The error for an option the command doesn't have.
+    pub fn unknown(&self) -> AocError {
+        AocError::BadArgs(format!("unknown option: {}", self.flag))
+    }
+}
+
+impl<'a> Iterator for Flags<'a, '_> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.flag = self.args.next()?;
+        Some(self.flag)
+    }
+}
+
+// Where to read the puzzle input from: stdin, or a file given with `--input FILE`.
+#[derive(Debug, Default, PartialEq)]
+pub enum Input {
+    #[default]
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    // `-` means stdin, like most command line tools.
+    pub fn from_arg(arg: &str) -> Input {
+        match arg {
+            "-" => Input::Stdin,
+            path => Input::File(PathBuf::from(path)),
+        }
+    }
+
+    // Remove `--input FILE` from anywhere in args, so it can be given to any subcommand without
+    // each binary having to parse it. If it's given more than once the last one wins.
+    pub fn take(args: &mut Vec<&str>) -> Result<Input, AocError> {
+        Ok(take_value(args, "--input")?.map_or(Input::Stdin, Input::from_arg))
+    }
+
+    pub fn reader(&self) -> Result<Box<dyn BufRead>, String> {
+        match self {
+            Input::Stdin => Ok(Box::new(io::stdin().lock())),
+            Input::File(path) => {
+                let f = File::open(path).map_err(|e| format!("open {}: {e}", path.display()))?;
+                Ok(Box::new(BufReader::new(f)))
+            },
+        }
+    }
+
+    pub fn lines(&self) -> Result<io::Lines<Box<dyn BufRead>>, String> {
+        Ok(self.reader()?.lines())
+    }
+
+    // Every line of the input, for the solvers that take an iterator of lines. A line that can't
+    // be read, eg because it isn't UTF-8, is an AocError::Io instead of a panic.
+    pub fn read_lines(&self) -> Result<Vec<String>, AocError> {
+        Ok(self.lines()?.collect::<io::Result<_>>()?)
+    }
+
+    pub fn read_to_string(&self) -> Result<String, String> {
+        io::read_to_string(self.reader()?).map_err(|e| format!("read input: {e}"))
+    }
+}
+
+// Remove every occurrence of a flag that takes no value from args, returning whether it was there.
+pub fn take_flag(args: &mut Vec<&str>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|&a| a != flag);
+    args.len() != len
+}
+
+// Remove `FLAG VALUE` from anywhere in args, returning the value. If it's given more than once the
+// last one wins.
+pub fn take_value<'a>(args: &mut Vec<&'a str>, flag: &str) -> Result<Option<&'a str>, AocError> {
+    let mut value = None;
+    while let Some(i) = args.iter().position(|&a| a == flag) {
+        value = Some(*args.get(i + 1).ok_or_else(|| AocError::BadArgs(format!("{flag} needs a value")))?);
+        args.drain(i..i + 2);
+    }
+    Ok(value)
+}
+
+// Save a checkpoint every minute by default.
+const CHECKPOINT_SECS: u64 = 60;
+
+// Remove `--checkpoint FILE` and `--checkpoint-secs N` from anywhere in args, for the long searches
+// that can save their progress and resume it after being interrupted.
+pub fn take_checkpointer(args: &mut Vec<&str>) -> Result<Option<Checkpointer>, AocError> {
+    let path = take_value(args, "--checkpoint")?;
+    let secs = match take_value(args, "--checkpoint-secs")? {
+        Some(secs) => parse_value("--checkpoint-secs", secs)?,
+        None => CHECKPOINT_SECS,
+    };
+    Ok(path.map(|path| Checkpointer::new(path, Duration::from_secs(secs))))
+}
+
+// Remove `--threads N` from anywhere in args, for the days that can spread their work over
+// threads. 0 means one per core.
+pub fn take_threads(args: &mut Vec<&str>) -> Result<Option<usize>, AocError> {
+    match take_value(args, "--threads")? {
+        Some(n) => match parse_value("--threads", n)? {
+            0 => Ok(Some(parallel::default_threads())),
+            n => Ok(Some(n)),
+        },
+        None => Ok(None),
+    }
+}
+
+// How to run a long search, from `--checkpoint FILE`, `--checkpoint-secs N` and `--threads N`.
+pub enum SearchMode {
+    Serial,
+    Checkpointed(Checkpointer),
+    Parallel(usize),
+}
+
+pub fn take_search_mode(args: &mut Vec<&str>) -> Result<SearchMode, AocError> {
+    match (take_checkpointer(args)?, take_threads(args)?) {
+        (Some(_), Some(_)) => Err(AocError::BadArgs("--checkpoint doesn't work with --threads".into())),
+        (Some(checkpointer), None) => Ok(SearchMode::Checkpointed(checkpointer)),
+        (None, Some(threads)) => Ok(SearchMode::Parallel(threads)),
+        (None, None) => Ok(SearchMode::Serial),
+    }
+}
+
+// `--render`, `--delay MS`, `--step`, `--color`, `--export-frames DIR` and `--export-format
+// svg|gif`, for the days that can draw their simulation as it runs.
+#[derive(Debug, PartialEq)]
+pub struct RenderOpts {
+    pub render: bool,
+    pub delay: Duration,
+    // Wait for Enter after each frame instead of the delay. Implies render.
+    pub step: bool,
+    // Colorize the cells, if stdout is a terminal.
+    pub color: bool,
+    // Write the frames to files in this directory instead of drawing them.
+    pub export_frames: Option<PathBuf>,
+    pub export_format: ExportFormat,
+}
+
+impl RenderOpts {
+    // Remove the render options from anywhere in args, like Input::take.
+    pub fn take(args: &mut Vec<&str>) -> Result<RenderOpts, AocError> {
+        let render = take_flag(args, "--render");
+        let delay = match take_value(args, "--delay")? {
+            Some(ms) => Duration::from_millis(parse_value("--delay", ms)?),
+            None => FRAME_DELAY,
+        };
+        let step = take_flag(args, "--step");
+        let color = take_flag(args, "--color");
+        let export_frames = take_value(args, "--export-frames")?.map(PathBuf::from);
+        let export_format = match take_value(args, "--export-format")? {
+            Some(format) => parse_value("--export-format", format)?,
+            None => ExportFormat::default(),
+        };
+        Ok(RenderOpts { render: render || step, delay, step, color, export_frames, export_format })
+    }
+
+    // Where the simulation should send its frames. Call finish() on it once the simulation's
+    // done, since a GIF is only written then.
+    pub fn sink(&self) -> Box<dyn Sink> {
+        if let Some(dir) = &self.export_frames {
+            return Box::new(Exporter::new(dir, self.export_format, self.delay));
+        }
+        // Escape codes would only be noise in a file or pipe.
+        let color = self.color && io::stdout().is_terminal();
+        match (self.render, self.step) {
+            (_, true) => Box::new(Stepper::stdout().with_color(color)),
+            (true, false) => Box::new(Renderer::stdout(self.delay).with_color(color)),
+            (false, false) => Box::new(NoRender),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_take() {
+        let mut args = vec!["part1", "--input", "input/1.dat", "--stats"];
+        assert_eq!(Input::take(&mut args).unwrap(), Input::File("input/1.dat".into()));
+        assert_eq!(args, ["part1", "--stats"]);
+
+        let mut args = vec!["--input", "a", "part2", "--input", "-"];
+        assert_eq!(Input::take(&mut args).unwrap(), Input::Stdin);
+        assert_eq!(args, ["part2"]);
+
+        let mut args = vec!["part1"];
+        assert_eq!(Input::take(&mut args).unwrap(), Input::Stdin);
+        assert!(Input::take(&mut vec!["part1", "--input"]).is_err());
+    }
+
+    const CLI: Cli = Cli { synopsis: "day0 <opts> part1|part2", common: &[INPUT_HELP], options: "--fast\n    Go fast.\n" };
+
+    #[test]
+    fn test_usage() {
+        let usage = CLI.usage();
+        assert!(usage.starts_with("day0 <opts> part1|part2\n\n-h|--help\n    Show this help.\n\n--input FILE\n"));
+        assert!(usage.ends_with("stdin.\n\n--fast\n    Go fast.\n"));
+        let bare = Cli { synopsis: "day0 part1", common: &[], options: "" };
+        assert_eq!(bare.usage(), "day0 part1\n\n-h|--help\n    Show this help.\n");
+    }
+
+    #[test]
+    fn test_report() {
+        let report = |e: Box<dyn Error>| {
+            let mut w = Vec::new();
+            let status = CLI.report(e.as_ref(), &mut w);
+            (status, String::from_utf8(w).unwrap())
+        };
+        let (status, msg) = report(unexpected(&["--slow"], "part1|part2").into());
+        assert_eq!(status, 2);
+        assert_eq!(msg, "day0: unknown option: --slow\nusage: day0 <opts> part1|part2\nRun `day0 --help` for the options.\n");
+        assert_eq!(report(AocError::Parse("line 3: bad".into()).into()), (1, "day0: line 3: bad\n".into()));
+        assert_eq!(report("no route".into()).0, 1);
+    }
+
+    #[test]
+    fn test_unexpected() {
+        assert_eq!(unexpected(&[], "part1|part2").to_string(), "must specify part1|part2");
+        assert_eq!(unexpected(&["part3"], "part1|part2").to_string(), "must specify part1|part2, not part3");
+        assert_eq!(unexpected(&["part1", "--x"], "part1|part2").to_string(), "unknown option: --x");
+        assert_eq!(unexpected(&["part1"], "").to_string(), "unexpected arguments: part1");
+    }
+
+    #[test]
+    fn test_flags() {
+        let args = ["--size", "10", "--fast", "--seed"];
+        let mut flags = Flags::new(&args);
+        assert_eq!(flags.next(), Some("--size"));
+        assert_eq!(flags.parse::<u32>().unwrap(), 10);
+        assert_eq!(flags.next(), Some("--fast"));
+        assert_eq!(flags.unknown().to_string(), "unknown option: --fast");
+        assert_eq!(flags.next(), Some("--seed"));
+        assert!(matches!(flags.value(), Err(AocError::BadArgs(msg)) if msg == "--seed needs a value"));
+        assert_eq!(flags.next(), None);
+        let mut flags = Flags::new(&["--size", "big"]);
+        flags.next();
+        assert_eq!(flags.parse::<u32>().unwrap_err().to_string(), "--size \"big\": invalid digit found in string");
+    }
+
+    #[test]
+    fn test_take_flag() {
+        let mut args = vec!["--progress", "part1", "--progress"];
+        assert!(take_flag(&mut args, "--progress"));
+        assert_eq!(args, ["part1"]);
+        assert!(!take_flag(&mut args, "--progress"));
+    }
+
+    #[test]
+    fn test_render_opts() {
+        let mut args = vec!["part1", "--delay", "5", "--render"];
+        let opts = RenderOpts::take(&mut args).unwrap();
+        let want = RenderOpts { render: true, delay: Duration::from_millis(5), step: false, color: false, export_frames: None, export_format: ExportFormat::Svg };
+        assert_eq!(opts, want);
+        assert_eq!(args, ["part1"]);
+        let opts = RenderOpts::take(&mut args).unwrap();
+        assert_eq!(opts, RenderOpts { render: false, delay: FRAME_DELAY, step: false, color: false, export_frames: None, export_format: ExportFormat::Svg });
+        let opts = RenderOpts::take(&mut vec!["--step", "--color"]).unwrap();
+        assert_eq!(opts, RenderOpts { render: true, delay: FRAME_DELAY, step: true, color: true, export_frames: None, export_format: ExportFormat::Svg });
+        let opts = RenderOpts::take(&mut vec!["--export-frames", "out", "--export-format", "gif"]).unwrap();
+        assert_eq!((opts.export_frames, opts.export_format), (Some("out".into()), ExportFormat::Gif));
+        assert!(RenderOpts::take(&mut vec!["--export-format", "png"]).is_err());
+        assert!(RenderOpts::take(&mut vec!["--delay", "soon"]).is_err());
+        assert!(take_value(&mut vec!["--delay"], "--delay").is_err());
+    }
+
+    #[test]
+    fn test_take_checkpointer() {
+        let mut args = vec!["part1", "--checkpoint", "day19.ckpt", "--checkpoint-secs", "5"];
+        assert!(take_checkpointer(&mut args).unwrap().is_some());
+        assert_eq!(args, ["part1"]);
+        assert!(take_checkpointer(&mut args).unwrap().is_none());
+        assert!(take_checkpointer(&mut vec!["--checkpoint", "f", "--checkpoint-secs", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_take_search_mode() {
+        assert!(matches!(take_search_mode(&mut vec![]), Ok(SearchMode::Serial)));
+        assert!(matches!(take_search_mode(&mut vec!["--threads", "2"]), Ok(SearchMode::Parallel(2))));
+        assert!(matches!(take_search_mode(&mut vec!["--checkpoint", "f"]), Ok(SearchMode::Checkpointed(_))));
+        assert!(take_search_mode(&mut vec!["--checkpoint", "f", "--threads", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_take_threads() {
+        let mut args = vec!["--threads", "4", "part2"];
+        assert_eq!(take_threads(&mut args).unwrap(), Some(4));
+        assert_eq!(args, ["part2"]);
+        assert_eq!(take_threads(&mut args).unwrap(), None);
+        assert!(take_threads(&mut vec!["--threads", "0"]).unwrap().unwrap() >= 1);
+        assert!(take_threads(&mut vec!["--threads", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_read() {
+        let path = std::env::temp_dir().join(format!("aoc-args-test-{}", std::process::id()));
+        std::fs::write(&path, "a\nb\n").unwrap();
+        let input = Input::File(path.clone());
+        assert_eq!(input.read_to_string().unwrap(), "a\nb\n");
+        let lines: Vec<String> = input.lines().unwrap().map(Result::unwrap).collect();
+        assert_eq!(lines, ["a", "b"]);
+        assert_eq!(input.read_lines().unwrap(), ["a", "b"]);
+        std::fs::write(&path, b"a\n\xff\n").unwrap();
+        assert!(matches!(input.read_lines(), Err(AocError::Io(_))));
+        std::fs::remove_file(&path).unwrap();
+        assert!(input.reader().is_err());
+    }
+}
@@ -0,0 +1,382 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use aoclib::progress;
+use advent_of_code_2022::answers::{Answers, Verdict, ANSWERS_FILE};
+use advent_of_code_2022::args::{unexpected, Cli, Flags, Input};
+use advent_of_code_2022::fetch::{cached_input, download, input_path, session, INPUT_DIR};
+use advent_of_code_2022::error::AocError;
+use advent_of_code_2022::gen::{default_size, generate};
+use advent_of_code_2022::runner::{solve_timed, Solution, Timing, SOLVERS};
+
+const CLI: Cli = Cli {
+    synopsis: "\
+aoc run DAY part1|part2 [--input FILE] [--format text|json] [--time]
+        [--progress] [--check]
+aoc all [--jobs N] [--check]
+aoc fetch DAY
+aoc gen DAY [--size N] [--seed N]",
+    common: &[],
+    options: "\
+run
+    Solve a puzzle. Without --input, the input is read from input/DAY.dat,
+    which is downloaded first if it doesn't exist yet.
+
+all
+    Solve every part of every day, using the cached inputs, and print a table
+    of the answers and how long each took. Exits with an error if any solver
+    fails. Parts are solved in parallel and printed as they finish.
+
+--jobs N
+    For all, how many parts to solve at once. Defaults to the number of CPUs.
+    With 1, rows are printed in order.
+
+fetch
+    Download a day's input to input/DAY.dat, replacing any cached copy.
+
+gen
+    Write a random input for DAY to stdout, for benchmarking the solvers on
+    inputs bigger than the official ones. There are generators for days 1, 3,
+    5, 6, 9, 16, 23 and 24.
+
+--size N
+    For gen, how big an input to make: the number of elves for day 1, groups
+    of rucksacks for day 3, moves for days 5 and 9, characters for day 6 (at
+    least 14), valves for day 16 (at most 256), and the width of the grove for
+    day 23 and of the valley for day 24.
+    Defaults to the size of the official input.
+
+--seed N
+    For gen, the seed for the random number generator. The same seed always
+    gives the same input. Defaults to 1.
+
+--format text|json
+    Print just the answer (the default), or a line of JSON with the day, part,
+    answer, milliseconds taken to solve it, and the number of states searched
+    for days that count them (otherwise null).
+
+--input FILE
+    Read the puzzle input from FILE, or from stdin if FILE is -.
+
+--check
+    Compare answers against the known-correct ones in answers.toml and exit
+    with an error if any differ. Parts with no recorded answer aren't checked.
+
+--progress
+    For days with long searches, like 16, 19 and 24, show how many states
+    have been explored and the best result so far on stderr as they go.
+
+--time
+    Print how long solving took to stderr, split into the phases the solver
+    marks, like parsing, with the rest counted as solving. The JSON format
+    always includes the timings.
+
+Downloading needs the session cookie from a logged-in adventofcode.com
+browser session, taken from $AOC_SESSION or else the first line of
+~/.config/aoc/session.
+",
+};
+
+#[derive(Default, Debug, PartialEq)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format: {s}")),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Opts<'a> {
+    input: Option<&'a str>,
+    format: Format,
+    time: bool,
+    progress: bool,
+    check: bool,
+}
+
+impl<'a> Opts<'a> {
+    fn parse(args: &[&'a str]) -> Result<Self, AocError> {
+        let mut opts = Opts::default();
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next() {
+            match flag {
+                "--input" => opts.input = Some(flags.value()?),
+                "--format" => opts.format = flags.parse()?,
+                "--time" => opts.time = true,
+                "--progress" => opts.progress = true,
+                "--check" => opts.check = true,
+                _ => return Err(flags.unknown()),
+            }
+        }
+        Ok(opts)
+    }
+
+    fn read_input(&self, day: u32) -> Result<String, Box<dyn Error>> {
+        match self.input {
+            Some(arg) => Ok(Input::from_arg(arg).read_to_string()?),
+            None => cached_input(INPUT_DIR, day),
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<u32, AocError> {
+    s.parse().map_err(|_| AocError::BadArgs(format!("bad day: {s}")))
+}
+
+fn parse_part(s: &str) -> Result<u32, AocError> {
+    match s {
+        "part1" => Ok(1),
+        "part2" => Ok(2),
+        _ => Err(AocError::BadArgs(format!("must specify part1|part2, not {s}"))),
+    }
+}
+
+// How a solver run went, with errors flattened to strings so they can be sent between threads.
+enum Outcome {
+    Solved(Solution, Timing),
+    NoSolver(String),
+    Failed(String),
+}
+
+impl Outcome {
+    fn new(result: Result<(Solution, Timing), AocError>) -> Outcome {
+        match result {
+            Ok((solution, timing)) => Outcome::Solved(solution, timing),
+            Err(e @ AocError::NoSolver { .. }) => Outcome::NoSolver(e.to_string()),
+            Err(e) => Outcome::Failed(e.to_string()),
+        }
+    }
+}
+
+// Options for `aoc all`.
+#[derive(Debug, PartialEq)]
+struct AllOpts {
+    njobs: usize,
+    check: bool,
+}
+
+impl AllOpts {
+    fn parse(args: &[&str]) -> Result<Self, AocError> {
+        let mut opts = AllOpts {
+            njobs: thread::available_parallelism().map_or(1, |n| n.get()),
+            check: false,
+        };
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next() {
+            match flag {
+                "--jobs" => opts.njobs = flags.parse()?,
+                "--check" => opts.check = true,
+                _ => return Err(flags.unknown()),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+// Options for `aoc gen`.
+#[derive(Debug, PartialEq)]
+struct GenOpts {
+    size: Option<usize>,
+    seed: u64,
+}
+
+impl GenOpts {
+    fn parse(args: &[&str]) -> Result<Self, AocError> {
+        let mut opts = GenOpts { size: None, seed: 1 };
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next() {
+            match flag {
+                "--size" => opts.size = Some(flags.parse()?),
+                "--seed" => opts.seed = flags.parse()?,
+                _ => return Err(flags.unknown()),
+            }
+        }
+        Ok(opts)
+    }
+}
+
+// Solve everything on `njobs` threads and write a row for each part as it finishes, returning how
+// many solvers failed. Inputs are all read first, so a missing one is only downloaded once. With
+// `answers`, wrong answers count as failures.
+fn all(mut w: impl Write, njobs: usize, answers: Option<&Answers>) -> Result<usize, Box<dyn Error>> {
+    let inputs: Vec<Result<String, String>> = SOLVERS.iter()
+        .map(|solver| cached_input(INPUT_DIR, solver.day()).map_err(|e| format!("read input: {e}")))
+        .collect();
+    let jobs: Vec<(usize, u32)> = (0..SOLVERS.len())
+        .flat_map(|i| [(i, 1), (i, 2)])
+        .collect();
+    let next_job = AtomicUsize::new(0);
+
+    writeln!(w, "{:>3} {:>4} {:>12}  answer", "day", "part", "time")?;
+    let start = Instant::now();
+    let mut nfailed = 0;
+    let mut total = Duration::ZERO;
+    thread::scope(|scope| -> io::Result<()> {
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..njobs.max(1) {
+            let tx = tx.clone();
+            let (jobs, inputs, next_job) = (&jobs, &inputs, &next_job);
+            scope.spawn(move || {
+                while let Some(&(i, part)) = jobs.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+                    let day = SOLVERS[i].day();
+                    let outcome = match &inputs[i] {
+                        Ok(input) => Outcome::new(solve_timed(day, part, input)),
+                        Err(e) => Outcome::Failed(e.clone()),
+                    };
+                    if tx.send((day, part, outcome)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+        for (day, part, mut outcome) in rx {
+            if let (Outcome::Solved(solution, _), Some(answers)) = (&outcome, answers) {
+                if let Verdict::Wrong { want } = answers.check(day, part, &solution.answer) {
+                    outcome = Outcome::Failed(format!("got {:?}, want {want:?}", solution.answer));
+                }
+            }
+            match &outcome {
+                Outcome::Solved(_, timing) => total += timing.total,
+                Outcome::NoSolver(_) => (),
+                Outcome::Failed(_) => nfailed += 1,
+            }
+            write_row(&mut w, day, part, &outcome)?;
+        }
+        Ok(())
+    })?;
+    writeln!(w, "{:>8} {:>12?}", "total", total)?;
+    writeln!(w, "{:>8} {:>12?}", "wall", start.elapsed())?;
+    Ok(nfailed)
+}
+
+// Answers spanning several lines, like day10 part2's, continue under the answer column.
+fn write_row(mut w: impl Write, day: u32, part: u32, outcome: &Outcome) -> io::Result<()> {
+    let (time, answer) = match outcome {
+        Outcome::Solved(solution, timing) => (format!("{:?}", timing.total), solution.answer.clone()),
+        Outcome::NoSolver(msg) => ("-".to_string(), msg.clone()),
+        Outcome::Failed(msg) => ("-".to_string(), format!("error: {msg}")),
+    };
+    let mut lines = answer.lines();
+    writeln!(w, "{day:>3} {part:>4} {time:>12}  {}", lines.next().unwrap_or(""))?;
+    for line in lines {
+        writeln!(w, "{:23}{line}", "")?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    CLI.run(run)
+}
+
+fn run(args: &mut Vec<&str>) -> Result<(), Box<dyn Error>> {
+    match args[..] {
+        ["run", day, part, ref opts @ ..] => {
+            let day = parse_day(day)?;
+            let part = parse_part(part)?;
+            let opts = Opts::parse(opts)?;
+            progress::enable(opts.progress);
+            let input = opts.read_input(day)?;
+            let (solution, timing) = solve_timed(day, part, &input)?;
+            match opts.format {
+                Format::Text => println!("{}", solution.answer),
+                Format::Json => println!("{}", solution.json(day, part, &timing)),
+            }
+            if opts.time {
+                eprintln!("{timing}");
+            }
+            if opts.check {
+                match Answers::read(ANSWERS_FILE)?.check(day, part, &solution.answer) {
+                    Verdict::Right => (),
+                    Verdict::Wrong { want } => return Err(format!("wrong answer, want {want:?}").into()),
+                    Verdict::Unknown => eprintln!("no answer recorded for day{day} part{part}"),
+                }
+            }
+        },
+        ["all", ref opts @ ..] => {
+            let opts = AllOpts::parse(opts)?;
+            let answers = if opts.check { Some(Answers::read(ANSWERS_FILE)?) } else { None };
+            let nfailed = all(io::stdout().lock(), opts.njobs, answers.as_ref())?;
+            if nfailed > 0 {
+                return Err(format!("{nfailed} solvers failed").into());
+            }
+        },
+        ["fetch", day] => {
+            let day = parse_day(day)?;
+            let input = download(day, &session()?)?;
+            std::fs::create_dir_all(INPUT_DIR)?;
+            let path = input_path(INPUT_DIR, day);
+            std::fs::write(&path, input)?;
+            eprintln!("wrote {}", path.display());
+        },
+        ["gen", day, ref opts @ ..] => {
+            let day = parse_day(day)?;
+            let opts = GenOpts::parse(opts)?;
+            let size = opts.size.or(default_size(day)).unwrap_or(0);
+            let mut w = io::BufWriter::new(io::stdout().lock());
+            generate(day, size, opts.seed, &mut w)?;
+            w.flush()?;
+        },
+        _ => return Err(unexpected(args, "run|all|fetch|gen").into()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_row() {
+        let row = |result: Result<(Solution, Timing), AocError>| {
+            let mut buf = Vec::new();
+            write_row(&mut buf, 10, 2, &Outcome::new(result)).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        let timing = Timing { total: Duration::from_millis(5), phases: vec![] };
+        assert_eq!(
+            row(Ok((Solution::new("#.\n.#\n"), timing))),
+            " 10    2          5ms  #.\n                       .#\n",
+        );
+        assert_eq!(row(Err("bad".into())), " 10    2            -  error: bad\n");
+        assert_eq!(
+            row(Err(AocError::NoSolver { day: 10, part: 2 })),
+            " 10    2            -  day10 part2 has no solver\n",
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_day("22").unwrap(), 22);
+        assert!(parse_day("x").is_err());
+        assert_eq!(parse_part("part2").unwrap(), 2);
+        assert!(parse_part("part3").is_err());
+        assert_eq!(Opts::parse(&["--input", "x.dat"]).unwrap().input, Some("x.dat"));
+        assert!(Opts::parse(&["--input"]).is_err());
+        assert_eq!(Opts::parse(&["--format", "json"]).unwrap().format, Format::Json);
+        assert!(Opts::parse(&["--format", "xml"]).is_err());
+        assert!(Opts::parse(&["--check"]).unwrap().check);
+        assert_eq!(AllOpts::parse(&["--check", "--jobs", "2"]).unwrap(), AllOpts { njobs: 2, check: true });
+        assert!(AllOpts::parse(&["--jobs"]).is_err());
+        assert!(AllOpts::parse(&["--input", "x"]).is_err());
+        assert_eq!(GenOpts::parse(&["--seed", "3", "--size", "10"]).unwrap(), GenOpts { size: Some(10), seed: 3 });
+        assert_eq!(GenOpts::parse(&[]).unwrap(), GenOpts { size: None, seed: 1 });
+        assert!(GenOpts::parse(&["--size", "big"]).is_err());
+    }
+}
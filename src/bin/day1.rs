@@ -1,148 +1,89 @@
 use std::io;
-use std::fmt::Debug;
-use std::collections::VecDeque;
-
-#[derive(Clone,Debug,PartialEq)]
-struct Elf {
-    i: i32,
-    calories: i32,
-}
-
-struct ElfReader<T>
-where
-    T: Iterator<Item=io::Result<String>>,
-{
-    lines: T,
-    elf: Option<Elf>,
-}
-
-impl<T> ElfReader<T> 
-where
-    T: Iterator<Item=io::Result<String>>,
-{
-    pub fn new(lines: T) -> ElfReader<T> {
-        ElfReader {
-            lines,
-            elf: Some(Elf { i: 0, calories: 0 }),
-        }
-    }
-}
-
-impl<T> Iterator for ElfReader<T>
-where
-    T: Iterator<Item=io::Result<String>>,
-{
-    type Item = Elf;
-    fn next(&mut self) -> Option<Self::Item> {
-        for line in self.lines.by_ref() {
-            match line {
-                Err(msg) => {
-                    eprintln!("read stdin: {}", msg);
-                },
-                Ok(line) if line.as_str() == "" => {
-                    let elf = self.elf.clone();
-                    self.elf = self.elf.as_ref().map(|prev| Elf {
-                        i: prev.i + 1,
-                        calories: 0,
-                    });
-                    return elf;
-                },
-                Ok(line) => {
-                    let calories = line.parse::<i32>().map_err(|err| {
-                        eprintln!("bad line,err={},line={}", err, line);
-                        err
-                    });
-                    if let Ok(cals) = calories {
-                        self.elf.as_mut().unwrap().calories += cals;
-                    }
-                }
-            }
+use std::process::ExitCode;
+
+use advent_of_code_2022::args::{take_flag, unexpected, Cli, Flags, Input, INPUT_HELP};
+use advent_of_code_2022::day1::{
+    part1, part1_strict, part2, part2_strict, report, report_strict, top_elves, top_elves_strict, write_report,
+    ReportFormat,
+};
+use advent_of_code_2022::error::AocError;
+
+const CLI: Cli = Cli {
+    synopsis: "day1 <opts> part1|part2|top [--n N]|report [--format F]",
+    common: &[INPUT_HELP],
+    options: "\
+--strict
+    Fail on the first line that isn't a number of calories, saying which line
+    it is, instead of reporting it on stderr and skipping it.
+
+top [--n N]
+    Print the total calories carried by the N elves carrying the most, then
+    their 0-based indexes, from the most calories to the least. Defaults to 3.
+
+report [--format text|csv|json]
+    Print every elf's 0-based index, number of items and total calories, from
+    the most calories to the least. Defaults to text.
+",
+};
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let strict = take_flag(args, "--strict");
+        match args[..] {
+            ["part1"] if strict => println!("{}", part1_strict(input.reader()?)?),
+            ["part1"] => println!("{}", part1(input.reader()?)),
+            ["part2"] if strict => println!("{}", part2_strict(input.reader()?)?),
+            ["part2"] => println!("{}", part2(input.reader()?)),
+            ["top", ref opts @ ..] => {
+                let n = parse_top(opts)?;
+                let (total, elves) = if strict {
+                    top_elves_strict(input.reader()?, n)?
+                } else {
+                    top_elves(input.reader()?, n)
+                };
+                let elves: Vec<String> = elves.iter().map(|i| i.to_string()).collect();
+                println!("{total}");
+                println!("{}", elves.join(" "));
+            },
+            ["report", ref opts @ ..] => {
+                let format = parse_report(opts)?;
+                let elves = if strict { report_strict(input.reader()?)? } else { report(input.reader()?) };
+                write_report(io::stdout().lock(), format, &elves)?;
+            },
+            _ => return Err(unexpected(args, "part1|part2|top|report").into()),
         }
-        self.elf.take()
-    }
+        Ok(())
+    })
 }
 
-#[derive(Debug)]
-struct TopN<T> {
-    n: usize,
-    vec: VecDeque<T>,
-}
-
-impl<T> TopN<T>
-where
-    T: Debug,
-{
-    pub fn new(n: usize) -> Self {
-        if n == 0 {
-            panic!("n must be greater than 0");
-        }
-        TopN { n, vec: VecDeque::new() }
-    }
-
-    fn add<U, F>(&mut self, val: T, key_func: F)
-    where
-        U: PartialOrd + Debug,
-        F: Fn(&T) -> &U,
-    {
-        let val_key = key_func(&val);
-        let mut insert_index = self.vec.len();
-        for (i, el) in self.vec.iter().enumerate() {
-            if val_key < key_func(el) {
-                insert_index = i;
-                break;
-            }
-        }
-        self.vec.insert(insert_index, val);
-        while self.vec.len() > self.n {
-            self.vec.pop_front();
+// The number of elves for `top`.
+fn parse_top(args: &[&str]) -> Result<usize, AocError> {
+    let mut n = 3;
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--n" => n = flags.parse()?,
+            _ => return Err(flags.unknown()),
         }
     }
-
-    fn iter(&self) -> std::collections::vec_deque::Iter<'_, T> {
-        self.vec.iter()
-    }
-}
-
-fn calories_for_top_elf() {
-    let reader = ElfReader::new(io::stdin().lines());
-    let max = reader.max_by_key(|e| e.calories).unwrap();
-    println!("max: {:?}", max);
-}
-
-fn calories_for_top_3_elves() {
-    let reader = ElfReader::new(io::stdin().lines());
-    let mut topn = TopN::new(3);
-    for elf in reader {
-        topn.add(elf, |e| &e.calories);
+    if n == 0 {
+        return Err(AocError::BadArgs("--n should be positive".into()));
     }
-    let sum: i32 = topn.iter().map(|e| e.calories).sum();
-    dbg!(&topn);
-    println!("{}", sum);
+    Ok(n)
 }
 
-const HELP: &str = "\
-day1 <opts> part1|part2
-
--h|--help
-    Show help
-";
-
-pub fn main() -> Result<(), String> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    if args.iter().any(|&a| a == "-h" || a == "--help") {
-        print!("{}", HELP);
-        return Ok(());
-    }
-    match args[..] {
-        ["part1"] => calories_for_top_elf(),
-        ["part2"] => calories_for_top_3_elves(),
-        _ => {
-            eprint!("{}", HELP);
-            return Err("Must give part1|part2".to_owned());
+// The output format for `report`.
+fn parse_report(args: &[&str]) -> Result<ReportFormat, AocError> {
+    let mut format = ReportFormat::default();
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--format" => format = flags.parse()?,
+            _ => return Err(flags.unknown()),
         }
-    };
-    Ok(())
+    }
+    Ok(format)
 }
 
 #[cfg(test)]
@@ -150,27 +91,19 @@ mod test {
     use super::*;
 
     #[test]
-    fn basic() {
-        let input = vec!["23", "1", "", "1", "2"];
-        let lines = input.iter().map(|v| io::Result::Ok(String::from(*v)));
-        let mut reader = ElfReader::new(lines);
-        assert_eq!(reader.next(), Some(Elf { i: 0, calories: 24 }));
-        assert_eq!(reader.next(), Some(Elf { i: 1, calories: 3 }));
-
+    fn test_parse_top() {
+        assert_eq!(parse_top(&[]).unwrap(), 3);
+        assert_eq!(parse_top(&["--n", "10"]).unwrap(), 10);
+        assert!(parse_top(&["--n", "0"]).is_err());
+        assert!(parse_top(&["--n"]).is_err());
+        assert!(parse_top(&["-n", "2"]).is_err());
     }
 
     #[test]
-    fn topn() {
-        let input = vec![3, 5, 8, 2, 9, 12, 3];
-        let mut topn = TopN::new(3);
-        for v in input {
-            topn.add(v, |v| v);
-        }
-        dbg!(&topn);
-        let mut iter = topn.iter();
-        assert_eq!(iter.next(), Some(&8));
-        assert_eq!(iter.next(), Some(&9));
-        assert_eq!(iter.next(), Some(&12));
-        assert_eq!(iter.next(), None);
+    fn test_parse_report() {
+        assert_eq!(parse_report(&[]).unwrap(), ReportFormat::Text);
+        assert_eq!(parse_report(&["--format", "csv"]).unwrap(), ReportFormat::Csv);
+        assert!(parse_report(&["--format", "xml"]).is_err());
+        assert!(parse_report(&["--n", "2"]).is_err());
     }
 }
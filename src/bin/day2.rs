@@ -1,171 +1,173 @@
-use std::str::FromStr;
+use std::fs;
 use std::io;
-
-#[derive(Clone)]
-pub enum Move {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl FromStr for Move {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" | "X" => Ok(Move::Rock),
-            "B" | "Y" => Ok(Move::Paper),
-            "C" | "Z" => Ok(Move::Scissors),
-            bad => Err(format!("bad move: {}", bad)),
+use std::process::ExitCode;
+
+use advent_of_code_2022::args::{parse_value, take_flag, take_value, unexpected, Cli, Flags, Input, INPUT_HELP};
+use advent_of_code_2022::day2::{
+    part1, part1_strict, part2, part2_strict, part3, part3_strict, report, report_strict, tournament, tournament_strict,
+    write_report, write_standings, Format, Strategy, ReportFormat, Rules, LIZARD_SPOCK, ROCK_PAPER_SCISSORS,
+};
+use advent_of_code_2022::error::AocError;
+
+const CLI: Cli = Cli {
+    synopsis: "day2 <opts> part1|part2|part3|report part1|part2 [--format F]|tournament [--strategy S]...",
+    common: &[INPUT_HELP],
+    options: "\
+--rules standard|lizard-spock|FILE
+    The game to score the strategy guide with. A rules file has a line for each
+    shape, with its name, the score for playing it and its codes in the guide,
+    like `rock 1 A X`, and lines saying which shapes beat which, like
+    `rock beats scissors lizard`. Defaults to standard rock paper scissors.
+
+--guide auto|letters|names|csv
+    How the strategy guide is written: with the codes from the rules, like
+    `A Y`, with shape and outcome names separated by whitespace, like
+    `Rock Paper` or `Rock win`, or with names separated by commas. Defaults to
+    auto, which accepts any of them, line by line.
+
+part3
+    Work out the most we could score against the opponent's moves, ignoring
+    the second column, and compare it with the part1 and part2 scores. Then
+    print each round with our best response, like report does.
+
+--strict
+    Fail on the first round that can't be read, saying which line it's on,
+    instead of reporting it on stderr and scoring it as 0.
+
+report part1|part2 [--format text|json]
+    Print each round, read like part1 or part2 does: the line it's on, both
+    shapes, how it went for us, its score and the score so far. Defaults to
+    text.
+
+tournament [--strategy S]...
+    Play each strategy against the opponent's moves and print how they did,
+    from the highest score to the lowest. A strategy is one of:
+        always-SHAPE      always play the shape, like always-rock
+        cycle:SHAPE,...   play the shapes in turn, like cycle:rock,paper
+        mirror            play whatever the opponent does
+        part1-guide       read the second column like part1
+        part2-guide       read the second column like part2
+        best              our best response to each move, like part3
+    Defaults to always playing each shape, and then the rest that don't need
+    any shapes. Rounds a strategy can't read score 0, and are counted as
+    skipped unless --strict is given.
+",
+};
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let rules = take_value(args, "--rules")?.map_or(Ok(Rules::default()), read_rules)?;
+        let format = take_value(args, "--guide")?.map_or(Ok(Format::default()), |f| parse_value("--guide", f))?;
+        let strict = take_flag(args, "--strict");
+        let lines: Vec<String> = input.lines()?.collect::<io::Result<_>>()?;
+        let lines = lines.iter();
+        match args[..] {
+            ["part1"] if strict => println!("{}", part1_strict(&rules, format, lines)?),
+            ["part1"] => println!("{}", part1(&rules, format, lines)),
+            ["part2"] if strict => println!("{}", part2_strict(&rules, format, lines)?),
+            ["part2"] => println!("{}", part2(&rules, format, lines)),
+            ["part3"] => {
+                let analysis = if strict { part3_strict(&rules, format, lines)? } else { part3(&rules, format, lines) };
+                println!("best {}", analysis.best);
+                println!("part1 {}", analysis.part1);
+                println!("part2 {}", analysis.part2);
+                println!();
+                write_report(io::stdout().lock(), &rules, ReportFormat::Text, &analysis.rounds)?;
+            },
+            ["report", part @ ("part1" | "part2"), ref opts @ ..] => {
+                let report_format = parse_report(opts)?;
+                let part = if part == "part1" { 1 } else { 2 };
+                let rounds = if strict {
+                    report_strict(&rules, format, lines, part)?
+                } else {
+                    report(&rules, format, lines, part)
+                };
+                write_report(io::stdout().lock(), &rules, report_format, &rounds)?;
+            },
+            ["tournament", ref opts @ ..] => {
+                let strategies = parse_tournament(&rules, opts)?;
+                let standings = if strict {
+                    tournament_strict(&rules, format, lines, &strategies)?
+                } else {
+                    tournament(&rules, format, lines, &strategies)
+                };
+                write_standings(io::stdout().lock(), &standings)?;
+            },
+            _ => return Err(unexpected(args, "part1|part2|part3|report|tournament").into()),
         }
-    }
+        Ok(())
+    })
 }
 
-impl Move {
-    pub fn from_intent(them: &Self, intent: &Intent) -> Self {
-        use Move::*;
-        use Intent::*;
-        match (them, intent) {
-            (them, Draw) => them.clone(),
-            (Rock, Win) => Paper,
-            (Rock, Lose) => Scissors,
-            (Paper, Win) => Scissors,
-            (Paper, Lose) => Rock,
-            (Scissors, Win) => Rock,
-            (Scissors, Lose) => Paper,
-        }
-    }
-}
-
-pub enum Intent {
-    Win,
-    Draw,
-    Lose,
-}
-
-impl FromStr for Intent {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use Intent::*;
-        match s {
-            "X" => Ok(Lose),
-            "Y" => Ok(Draw),
-            "Z" => Ok(Win),
-            s => Err(format!("bad intent: {}", s)),
-        }
-    }
-}
-
-// score = shape_score + win_score
-// shape_score:
-//     Rock -> 1
-//     Paper -> 2
-//     Scissors -> 3
-//
-// win_score:
-//     win -> 6
-//     draw -> 3
-//     lose -> 0
-fn our_score(them: Move, us: Move) -> u32 {
-    use Move::{Rock,Paper,Scissors};
-    let win_score = match (&them, &us) {
-        (Rock, Rock) => 3,
-        (Rock, Paper) => 6,
-        (Rock, Scissors) => 0,
-        (Paper, Rock) => 0,
-        (Paper, Paper) => 3,
-        (Paper, Scissors) => 6,
-        (Scissors, Rock) => 6,
-        (Scissors, Paper) => 0,
-        (Scissors, Scissors) => 3,
-    };
-    let shape_score = match &us {
-        Rock => 1,
-        Paper => 2,
-        Scissors => 3,
+// A builtin game's rules, or else the ones in a file.
+fn read_rules(arg: &str) -> Result<Rules, AocError> {
+    let table = match arg {
+        "standard" => ROCK_PAPER_SCISSORS.to_string(),
+        "lizard-spock" => LIZARD_SPOCK.to_string(),
+        path => fs::read_to_string(path).map_err(|e| AocError::BadArgs(format!("--rules {path}: {e}")))?,
     };
-    win_score + shape_score
+    Rules::parse(&table).map_err(|e| AocError::BadArgs(format!("--rules {arg}: {e}")))
 }
 
-pub fn sum_line_scores<T, F>(lines: T, move_converter: F) -> u32
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-    F: Fn(&str) -> Result<(Move, Move), String>,
-{
-    lines.map(|line| {
-        match move_converter(line.as_ref()) {
-            Ok((them, us)) => our_score(them, us),
-            Err(e) => {
-                eprintln!("{}", e);
-                0
-            }
+// The output format for `report`.
+fn parse_report(args: &[&str]) -> Result<ReportFormat, AocError> {
+    let mut format = ReportFormat::default();
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--format" => format = flags.parse()?,
+            _ => return Err(flags.unknown()),
         }
-    }).sum()
-}
-
-fn line_to_moves_part1(line: &str) -> Result<(Move, Move), String> {
-    if let [them, us] = line.split(' ').collect::<Vec<_>>()[..] {
-        let them = Move::from_str(them)?;
-        let us = Move::from_str(us)?;
-        Ok((them, us))
-    } else {
-        Err(format!("bad line: {}", line))
     }
+    Ok(format)
 }
 
-fn line_to_moves_part2(line: &str) -> Result<(Move, Move), String> {
-    if let [them, intent] = line.split(' ').collect::<Vec<_>>()[..] {
-        let them = Move::from_str(them)?;
-        let intent = Intent::from_str(intent)?;
-        let us = Move::from_intent(&them, &intent);
-        Ok((them, us))
-    } else {
-        Err(format!("bad line: {}", line))
+// The strategies for `tournament`.
+fn parse_tournament(rules: &Rules, args: &[&str]) -> Result<Vec<Strategy>, AocError> {
+    let mut strategies = Vec::new();
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--strategy" => {
+                let s = flags.value()?;
+                strategies.push(Strategy::parse(rules, s).map_err(|e| AocError::BadArgs(format!("--strategy {s:?}: {e}")))?);
+            },
+            _ => return Err(flags.unknown()),
+        }
     }
-}
-
-const HELP: &str = "\
-day2 <opts> part1|part2
-
--h|--help
-    Show help
-";
-
-fn main() -> Result<(), String> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    if args.contains(&"-h") || args.contains(&"--help") {
-        print!("{}", HELP);
-        return Ok(());
+    if strategies.is_empty() {
+        strategies = Strategy::builtin(rules);
     }
-    let stdin = io::stdin().lines().map(|line| line.unwrap());
-    match args[..] {
-        ["part1"] => println!("{}", sum_line_scores(stdin, line_to_moves_part1)),
-        ["part2"] => println!("{}", sum_line_scores(stdin, line_to_moves_part2)),
-        _ => {
-            eprint!("{}", HELP);
-            return Err("No part specified".to_owned());
-        },
-    };
-    Ok(())
+    Ok(strategies)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use advent_of_code_2022::day2::Move;
 
     #[test]
-    fn part1() {
-        let lines = vec!["A Y", "B X", "C Z"];
-        let score = sum_line_scores(lines.iter(), line_to_moves_part1);
-        assert_eq!(score, 15);
+    fn test_parse_report() {
+        assert_eq!(parse_report(&[]).unwrap(), ReportFormat::Text);
+        assert_eq!(parse_report(&["--format", "json"]).unwrap(), ReportFormat::Json);
+        assert!(parse_report(&["--format", "csv"]).is_err());
     }
 
     #[test]
-    fn part2() {
-        let lines = vec!["A Y", "B X", "C Z"];
-        let score = sum_line_scores(lines.iter(), line_to_moves_part2);
-        assert_eq!(score, 12);
+    fn test_parse_tournament() {
+        let rules = Rules::default();
+        assert_eq!(parse_tournament(&rules, &[]).unwrap(), Strategy::builtin(&rules));
+        let strategies = parse_tournament(&rules, &["--strategy", "mirror", "--strategy", "always-rock"]).unwrap();
+        assert_eq!(strategies, [Strategy::Mirror, Strategy::Always(Move(0))]);
+        assert!(parse_tournament(&rules, &["--strategy", "always-spock"]).is_err());
+        assert!(parse_tournament(&rules, &["--strategy"]).is_err());
+    }
 
+    #[test]
+    fn test_read_rules() {
+        assert_eq!(read_rules("standard").unwrap(), Rules::default());
+        assert_eq!(read_rules("lizard-spock").unwrap().shapes.len(), 5);
+        assert!(matches!(read_rules("/nonexistent/rules"), Err(AocError::BadArgs(_))));
     }
 }
@@ -1,197 +1,281 @@
-use std::collections::{HashSet, HashMap};
 use std::error::Error;
-use std::fmt;
-use std::io::BufRead;
-use std::ops::Range;
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct Point {
-    x: i64,
-    y: i64,
-}
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Dir {
-    N, NE, E, SE, S, SW, W, NW,
+use aoclib::render::Sink;
+use advent_of_code_2022::args::{unexpected, Cli, Flags, Input, RenderOpts, INPUT_HELP};
+use advent_of_code_2022::day23::{self, Backend, Elves, Point, Rules};
+use advent_of_code_2022::error::AocError;
+
+const CLI: Cli = Cli {
+    synopsis: "day23 <opts> part1|part2",
+    common: &[INPUT_HELP],
+    options: "\
+--backend sparse|dense
+    How to store the elves: in a hash set (the default), or as rows of bits.
+
+--rounds N
+    Number of rounds to play for part1. Defaults to 10.
+
+--render
+    Show the board after every round.
+
+--delay MS
+    Milliseconds to show each round for with --render. Defaults to 100.
+
+--step
+    Like --render, but wait for Enter after each round instead of the delay.
+    Entering q runs to the end without showing any more.
+
+--color
+    With --render or --step, color the elves green, if stdout is a terminal.
+
+--export-frames DIR
+    Instead of showing the board after every round, write it to a file in DIR:
+    frame-000001.svg and so on, or with --export-format gif, one animated
+    frames.gif, shown for --delay per round.
+
+--export-format svg|gif
+    What to write with --export-frames. Defaults to svg.
+
+--order DIRS
+    Order that elves consider directions in for the first round, as a string
+    of distinct cardinal directions. Defaults to NSWE.
+
+--directions N
+    Number of directions, from the front of the order, that elves consider
+    each round. Defaults to the length of the order.
+
+--alone-radius R
+    Elves stay put when there are no other elves within R tiles. Defaults to 1.
+
+--stats csv|json
+    Write a timeline of the rounds to stderr, with the number of elves that
+    moved, and the area and number of empty tiles of the bounding box at the end
+    of each round.
+
+--gif FILE
+    Write an animated GIF of the elves spreading out to FILE.
+
+--every N
+    Only draw every Nth round in the GIF, plus the first and last. Defaults to 1.
+",
+};
+
+struct Opts {
+    backend: Backend,
+    rounds: usize,
+    stats: Option<StatsFormat>,
+    rules: Rules,
+    gif: Option<String>,
+    every: usize,
 }
 
-impl Point {
-    fn new(x: i64, y: i64) -> Self {
-        Self { x, y }
+impl Default for Opts {
+    fn default() -> Self {
+        Opts {
+            backend: Backend::Sparse,
+            rounds: day23::PART1_ROUNDS,
+            stats: None,
+            rules: Rules::default(),
+            gif: None,
+            every: 1,
+        }
     }
+}
 
-    fn neighbor(&self, dir: Dir) -> Self {
-        use Dir::*;
-        match dir {
-            N => Point::new(self.x, self.y + 1),
-            NE => Point::new(self.x + 1, self.y + 1),
-            E => Point::new(self.x + 1, self.y),
-            SE => Point::new(self.x + 1, self.y - 1),
-            S => Point::new(self.x, self.y - 1),
-            SW => Point::new(self.x - 1, self.y - 1),
-            W => Point::new(self.x - 1, self.y),
-            NW => Point::new(self.x - 1, self.y + 1),
+impl Opts {
+    fn parse(args: &[&str]) -> Result<Self, AocError> {
+        let mut opts = Opts::default();
+        let mut ndirs = None;
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next() {
+            match flag {
+                "--backend" => opts.backend = flags.parse()?,
+                "--rounds" => opts.rounds = flags.parse()?,
+                "--stats" => opts.stats = Some(flags.parse()?),
+                "--order" => {
+                    let order = flags.value()?;
+                    opts.rules.order = Rules::parse_order(order).map_err(|e| AocError::BadArgs(format!("--order: {e}")))?;
+                },
+                "--directions" => ndirs = Some(flags.parse()?),
+                "--alone-radius" => opts.rules.alone_radius = flags.parse()?,
+                "--gif" => opts.gif = Some(flags.value()?.to_string()),
+                "--every" => opts.every = flags.parse()?,
+                _ => return Err(flags.unknown()),
+            }
+        }
+        opts.rules.ndirs = ndirs.unwrap_or(opts.rules.order.len());
+        if opts.every == 0 {
+            return Err(AocError::BadArgs("--every should be positive".into()));
         }
+        Ok(opts)
     }
 }
 
-struct Board {
-    elves: HashSet<Point>,
-    round: usize,
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let mut sink = RenderOpts::take(args)?.sink();
+        let answer = match args[..] {
+            ["part1", ref opts @ ..] => part1(input.reader()?, &Opts::parse(opts)?, sink.as_mut())?,
+            ["part2", ref opts @ ..] => part2(input.reader()?, &Opts::parse(opts)?, sink.as_mut())?,
+            _ => return Err(unexpected(args, "part1|part2").into()),
+        };
+        println!("{answer}");
+        Ok(())
+    })
 }
 
-impl Board {
-    fn read(r: impl BufRead) -> Result<Board, Box<dyn Error>> {
-        let mut elves: HashSet<Point> = HashSet::new();
-        for (y, line) in r.lines().enumerate() {
-            let line = line?;
-            for (x, c) in line.chars().enumerate() {
-                match c {
-                    '.' => (),
-                    // Reverse y so north can be y+1.
-                    '#' => {
-                        elves.insert(Point::new(x as i64, -(y as i64)));
-                    },
-                    c => return Err(format!("unexpected board char: {}", c).into()),
-                };
-            }
-        }
-        Ok(Board { elves, round: 0 })
-    }
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StatsFormat {
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for StatsFormat {
+    type Err = String;
 
-    fn is_alone(&self, elf: Point) -> bool {
-        use Dir::*;
-        [N, NE, E, SE, S, SW, W, NW]
-            .iter()
-            .all(|&d| !self.elves.contains(&elf.neighbor(d)))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(StatsFormat::Csv),
+            "json" => Ok(StatsFormat::Json),
+            _ => Err(format!("unexpected stats format: {s}")),
+        }
     }
+}
 
-    // Return the number of elves that moved.
-    fn play_round(&mut self) -> u64 {
-        use Dir::*;
-        let mut count_for: HashMap<Point, i64> = HashMap::new();
-        let mut proposed: HashMap<Point, Point> = HashMap::new();
-        let mut nmoved = 0;
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RoundStats {
+    round: usize,
+    elves_moved: usize,
+    bounding_box_area: usize,
+    empty_tiles: usize,
+}
 
-        let dir_order = [N, S, W, E];
-        let dir_order = dir_order.iter().cycle().skip(self.round % 4).take(4);
+impl RoundStats {
+    fn new(board: &dyn Elves, elves_moved: usize) -> Self {
+        RoundStats {
+            round: board.round(),
+            elves_moved,
+            bounding_box_area: board.bounding_box_area(),
+            empty_tiles: board.empty_count(),
+        }
+    }
+}
 
-        for elf in self.elves.iter() {
-            if self.is_alone(*elf) {
-                continue;
+fn write_stats(mut w: impl Write, format: StatsFormat, timeline: &[RoundStats]) -> io::Result<()> {
+    match format {
+        StatsFormat::Csv => {
+            writeln!(w, "round,elves_moved,bounding_box_area,empty_tiles")?;
+            for s in timeline {
+                writeln!(w, "{},{},{},{}", s.round, s.elves_moved, s.bounding_box_area, s.empty_tiles)?;
             }
-            for dir in dir_order.clone() {
-                let dirs = match dir {
-                    N => [N, NE, NW],
-                    S => [S, SE, SW],
-                    W => [W, NW, SW],
-                    E => [E, NE, SE],
-                    _ => panic!("unexpected dir"),
-                };
-                if dirs.iter().all(|&d| !self.elves.contains(&elf.neighbor(d))) {
-                    let dst = elf.neighbor(*dir);
-                    count_for.entry(dst).and_modify(|v| *v += 1).or_insert(1);
-                    proposed.insert(*elf, dst);
-                    break;
-                }
+        },
+        StatsFormat::Json => {
+            writeln!(w, "[")?;
+            for (i, s) in timeline.iter().enumerate() {
+                let sep = if i + 1 < timeline.len() { "," } else { "" };
+                writeln!(
+                    w,
+                    "  {{\"round\": {}, \"elves_moved\": {}, \"bounding_box_area\": {}, \"empty_tiles\": {}}}{sep}",
+                    s.round, s.elves_moved, s.bounding_box_area, s.empty_tiles,
+                )?;
             }
-        }
+            writeln!(w, "]")?;
+        },
+    }
+    Ok(())
+}
 
-        for (elf, dst) in proposed.iter() {
-            if count_for[dst] == 1 {
-                self.elves.remove(elf);
-                self.elves.insert(*dst);
-                nmoved += 1;
-            }
-        }
+// Shows or records the rounds as requested by `opts`.
+struct Recorder<'a> {
+    opts: &'a Opts,
+    sink: &'a mut dyn Sink,
+    // The first error from drawing the board, reported once the simulation is done.
+    render_err: Option<io::Error>,
+    timeline: Vec<RoundStats>,
+    // Elf positions for each frame of the GIF.
+    frames: Vec<Vec<Point>>,
+    last_frame_round: Option<usize>,
+}
 
-        self.round += 1;
-        nmoved
+impl<'a> Recorder<'a> {
+    fn new(opts: &'a Opts, sink: &'a mut dyn Sink, board: &dyn Elves) -> Self {
+        let mut recorder = Recorder {
+            opts,
+            sink,
+            render_err: None,
+            timeline: Vec::new(),
+            frames: Vec::new(),
+            last_frame_round: None,
+        };
+        recorder.capture_frame(board);
+        recorder
     }
 
-    fn open_spot_count(&self) -> u64 {
-        let (x_range, y_range) = self.ranges();
-        let mut count: u64 = 0;
-        for x in x_range {
-            for y in y_range.clone() {
-                if !self.elves.contains(&Point::new(x, y)) {
-                    count += 1;
-                }
-            }
+    fn capture_frame(&mut self, board: &dyn Elves) {
+        if self.opts.gif.is_some() && self.last_frame_round != Some(board.round()) {
+            self.frames.push(board.elves().collect());
+            self.last_frame_round = Some(board.round());
         }
-        count
     }
 
-    fn ranges(&self) -> (Range<i64>, Range<i64>) {
-        let mut min_x = i64::MAX;
-        let mut max_x = i64::MIN;
-        let mut min_y = i64::MAX;
-        let mut max_y = i64::MIN;
-        for elf in self.elves.iter() {
-            min_x = min_x.min(elf.x);
-            min_y = min_y.min(elf.y);
-            max_x = max_x.max(elf.x);
-            max_y = max_y.max(elf.y);
+    fn after_round(&mut self, board: &dyn Elves, nmoved: usize) {
+        if self.render_err.is_none() {
+            let title = format_args!("== End of Round {} ({nmoved} moved) ==", board.round());
+            self.render_err = self.sink.frame(title, board).err();
+        }
+        if self.opts.stats.is_some() {
+            self.timeline.push(RoundStats::new(board, nmoved));
+        }
+        if board.round().is_multiple_of(self.opts.every) {
+            self.capture_frame(board);
         }
-        (min_x..max_x + 1, min_y..max_y + 1)
     }
-}
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (x_range, y_range) = self.ranges();
-        for y in ((y_range.start - 2)..(y_range.end + 2)).rev() {
-            for x in (x_range.start - 3)..(x_range.end + 3) {
-                let c = match self.elves.contains(&Point::new(x, y)) {
-                    true => '#',
-                    false => '.',
-                };
-                write!(f, "{c}")?;
-            }
-            writeln!(f)?;
+    fn finish(mut self, board: &dyn Elves) -> Result<(), Box<dyn Error>> {
+        if let Some(e) = self.render_err {
+            return Err(e.into());
+        }
+        self.sink.finish()?;
+        if let Some(format) = self.opts.stats {
+            write_stats(io::stderr().lock(), format, &self.timeline)?;
+        }
+        // Always end on the final board.
+        self.capture_frame(board);
+        if let Some(path) = &self.opts.gif {
+            let w = io::BufWriter::new(std::fs::File::create(path)?);
+            day23::write_gif(w, &self.frames)?;
         }
         Ok(())
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    match args[..] {
-        ["part1"] => println!("{}", part1(std::io::stdin().lock())?),
-        ["part2"] => println!("{}", part2(std::io::stdin().lock())?),
-        _ => return Err("must specify part1|part2".into()),
-    }
-    Ok(())
-}
-
-fn part1(r: impl BufRead) -> Result<u64, Box<dyn Error>> {
-    let mut board = Board::read(r)?;
-    //println!("== Initial State==");
-    //println!("{board}\n");
-    for _ in 0..10 {
-        board.play_round();
-        //println!("== End of Round {} ==", board.round);
-        //println!("{board}");
-    }
-    Ok(board.open_spot_count())
+fn part1(r: impl BufRead, opts: &Opts, sink: &mut dyn Sink) -> Result<usize, Box<dyn Error>> {
+    let mut board = opts.backend.read(r, &opts.rules)?;
+    let mut recorder = Recorder::new(opts, sink, board.as_ref());
+    let empty = day23::part1(board.as_mut(), opts.rounds, |board, nmoved| {
+        recorder.after_round(board, nmoved)
+    });
+    recorder.finish(board.as_ref())?;
+    Ok(empty)
 }
 
-fn part2(r: impl BufRead) -> Result<usize, Box<dyn Error>> {
-    let mut board = Board::read(r)?;
-    let max_rounds = 1_000_000;
-    for _ in 0..max_rounds {
-        let nmoved = board.play_round();
-        if nmoved == 0 {
-            return Ok(board.round);
-        }
-    }
-    Err(format!("Elves still moving after round {}", max_rounds).into())
+fn part2(r: impl BufRead, opts: &Opts, sink: &mut dyn Sink) -> Result<usize, Box<dyn Error>> {
+    let mut board = opts.backend.read(r, &opts.rules)?;
+    let mut recorder = Recorder::new(opts, sink, board.as_ref());
+    let rounds = day23::part2(board.as_mut(), |board, nmoved| {
+        recorder.after_round(board, nmoved)
+    })?;
+    recorder.finish(board.as_ref())?;
+    Ok(rounds)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use aoclib::render::NoRender;
 
     const EXAMPLE: &str = "\
 ....#..
@@ -204,11 +288,60 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 110);
+        assert_eq!(part1(EXAMPLE.as_bytes(), &Opts::default(), &mut NoRender).unwrap(), 110);
+        assert_eq!(part1(EXAMPLE.as_bytes(), &Opts { backend: Backend::Dense, ..Opts::default() }, &mut NoRender).unwrap(), 110);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 20);
+        assert_eq!(part2(EXAMPLE.as_bytes(), &Opts::default(), &mut NoRender).unwrap(), 20);
+        assert_eq!(part2(EXAMPLE.as_bytes(), &Opts { backend: Backend::Dense, ..Opts::default() }, &mut NoRender).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_part1_rounds() {
+        let opts = Opts { rounds: 5, ..Opts::default() };
+        let dense_opts = Opts { rounds: 5, backend: Backend::Dense, ..Opts::default() };
+        // From the puzzle description, at the end of round 5 the board is 11x11 with 22 elves.
+        assert_eq!(part1(EXAMPLE.as_bytes(), &opts, &mut NoRender).unwrap(), 11 * 11 - 22);
+        assert_eq!(part1(EXAMPLE.as_bytes(), &dense_opts, &mut NoRender).unwrap(), 11 * 11 - 22);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut board = Backend::Dense.read(EXAMPLE.as_bytes(), &Rules::default()).unwrap();
+        let timeline: Vec<RoundStats> = (0..10)
+            .map(|_| {
+                let nmoved = board.step();
+                RoundStats::new(board.as_ref(), nmoved)
+            })
+            .collect();
+        let last = timeline.last().unwrap();
+        assert_eq!((last.round, last.bounding_box_area, last.empty_tiles), (10, 12 * 11, 110));
+
+        let mut csv = Vec::new();
+        write_stats(&mut csv, StatsFormat::Csv, &timeline[..1]).unwrap();
+        let first = timeline[0];
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!("round,elves_moved,bounding_box_area,empty_tiles\n1,{},{},{}\n",
+                first.elves_moved, first.bounding_box_area, first.empty_tiles),
+        );
+
+        let mut json = Vec::new();
+        write_stats(&mut json, StatsFormat::Json, &timeline[..2]).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json.lines().count(), 4);
+        assert!(json.lines().nth(1).unwrap().ends_with("},"));
+        assert!(json.lines().nth(2).unwrap().starts_with("  {\"round\": 2,"));
+    }
+
+    #[test]
+    fn test_opts_parse() {
+        let opts = Opts::parse(&["--order", "WE"]).unwrap();
+        assert_eq!(opts.rules.ndirs, 2);
+        assert!(Opts::parse(&["--order", "WE", "--directions", "3"]).unwrap().rules.validate().is_err());
+        assert!(Opts::parse(&["--rounds"]).is_err());
+        assert!(Opts::parse(&["--every", "0"]).is_err());
     }
 }
@@ -1,275 +1,207 @@
-use core::cmp::Reverse;
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
 use std::error::Error;
-use std::io;
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Dir {
-    Up, Right, Down, Left
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum Tile {
-    Wall,
-    Open,
-    Blizzard,
-}
-
-#[derive(Clone, Copy, Debug)]
-struct Blizzard {
-    start: Point,
-    dir: Dir,
-}
-
-impl Blizzard {
-    fn new(start: Point, dir: Dir) -> Self {
-        Self { start, dir }
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    fn new(x: usize, y: usize) -> Self {
-        Point { x, y }
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-struct State {
-    lower_bound: usize,
-    player: Point,
-    round: usize,
-}
-
-impl State {
-    fn new(lower_bound: usize, player: Point, round: usize) -> Self {
-        Self { lower_bound, player, round }
-    }
-}
-
-impl PartialOrd for State {
-    fn partial_cmp(&self, o: &Self) -> Option<Ordering> {
-        Some(self.lower_bound.cmp(&o.lower_bound))
-    }
-}
-
-impl Ord for State {
-    fn cmp(&self, o: &Self) -> Ordering {
-        self.lower_bound.cmp(&o.lower_bound)
-    }
-}
-
-struct Board {
-    width: usize,  // including walls
-    height: usize,  // including walls
-    row_blizzards: Vec<Vec<Blizzard>>,
-    col_blizzards: Vec<Vec<Blizzard>>,
-    start_pos: Point,
-    end_pos: Point,
-}
-
-enum Action {
-    Move(Dir),
-    Wait
-}
-
-impl Board {
-    fn read(s: &str) -> Self {
-        let height = s.lines().count();
-        let width = s.lines().next().unwrap().len();
-        let mut col_blizzards: Vec<Vec<Blizzard>> = Vec::new();
-        col_blizzards.resize(width, Vec::default());
-        let mut row_blizzards: Vec<Vec<Blizzard>> = Vec::new();
-        row_blizzards.resize(height, Vec::default());
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                match c {
-                    '#' | '.' => (),
-                    '^' => col_blizzards[x].push(Blizzard::new(Point::new(x, y), Dir::Up)),
-                    '>' => row_blizzards[y].push(Blizzard::new(Point::new(x, y), Dir::Right)),
-                    'v' => col_blizzards[x].push(Blizzard::new(Point::new(x, y), Dir::Down)),
-                    '<' => row_blizzards[y].push(Blizzard::new(Point::new(x, y), Dir::Left)),
-                    _ => panic!("unexpected tile: {}", c),
-                };
-            }
-        }
-        let start_pos: Point = s.lines().next().unwrap()
-            .chars()
-            .position(|c| c == '.')
-            .map(|x| Point::new(x, 0))
-            .expect("first row should have one Open tile");
-        let end_pos: Point = s.lines().last().unwrap()
-            .chars()
-            .position(|c| c == '.')
-            .map(|x| Point::new(x, height - 1))
-            .expect("last row should have one Open tile");
-        Board { width, height, row_blizzards, col_blizzards, start_pos, end_pos }
-    }
-
-    fn blizzard_position(&self, b: Blizzard, round: usize) -> Point {
-        // #>....#
-        use Dir::*;
-        let open_width = self.width - 2;
-        let open_height = self.height - 2;
-        let round = round as isize;
-        // We need to remove and add the walls back in when calculating blizzard positions.
-        match b.dir {
-            Up => Point::new(b.start.x, _mod(b.start.y - 1, -round, open_height) + 1),
-            Right => Point::new(_mod(b.start.x - 1, round, open_width) + 1, b.start.y),
-            Down => Point::new(b.start.x, _mod(b.start.y - 1, round, open_height) + 1),
-            Left => Point::new(_mod(b.start.x - 1, -round, open_width) + 1, b.start.y),
-        }
-    }
-
-    fn get(&self, p: Point, round: usize) -> Tile {
-        if p == self.start_pos || p == self.end_pos {
-            return Tile::Open;
-        }
-
-        let is_blizzard = self.row_blizzards[p.y].iter()
-            .chain(self.col_blizzards[p.x].iter())
-            .any(|&b| self.blizzard_position(b, round) == p);
-        if is_blizzard {
-            return Tile::Blizzard;
-        }
-
-        let is_wall = p.x == 0
-            || p.x == self.width - 1
-            || p.y == 0
-            || p.y == self.height - 1;
-        if is_wall {
-            return Tile::Wall;
-        }
-
-        Tile::Open
-    }
-
-    fn move_player(&self, p: Point, dir: Dir) -> Option<Point> {
-        match dir {
-            Dir::Up if p.y == 0 => None,
-            Dir::Up => Some(Point::new(p.x, p.y - 1)),
-            Dir::Right if p.x == self.width - 1 => None,
-            Dir::Right => Some(Point::new(p.x + 1, p.y)),
-            Dir::Down if p.y == self.height - 1 => None,
-            Dir::Down => Some(Point::new(p.x, p.y + 1)),
-            Dir::Left if p.x == 0 => None,
-            Dir::Left => Some(Point::new(p.x - 1, p.y)),
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+
+use aoclib::checkpoint::Checkpointer;
+use aoclib::parallel;
+use aoclib::progress;
+use aoclib::render::Sink;
+use advent_of_code_2022::args::{parse_value, take_checkpointer, unexpected, Cli, Flags, Input, RenderOpts, INPUT_HELP};
+use advent_of_code_2022::day24::{trips, travel, travel_checkpointed, write_moves, Board, Frame, Point, Strategy};
+use advent_of_code_2022::error::AocError;
+
+const CLI: Cli = Cli {
+    synopsis: "day24 <opts> part1|part2",
+    common: &[INPUT_HELP],
+    options: "\
+--render
+    Replay the route, showing the valley each minute. Tiles with more than one
+    blizzard show how many there are, and E marks the expedition.
+
+--delay MS
+    Milliseconds to show each frame for with --render. Defaults to 100.
+
+--step
+    Like --render, but wait for Enter after each frame instead of the delay.
+    Entering q runs to the end without showing any more.
+
+--color
+    With --render or --step, color the expedition green and the blizzards
+    blue, if stdout is a terminal.
+
+--export-frames DIR
+    Instead of showing the route, write the valley each minute to a file in
+    DIR: frame-000001.svg and so on, or with --export-format gif, one animated
+    frames.gif, shown for --delay per minute.
+
+--export-format svg|gif
+    What to write with --export-frames. Defaults to svg.
+
+--moves
+    Print what to do each minute before the answer.
+
+--progress
+    Show how many states the search has expanded on stderr as it goes.
+
+--checkpoint FILE
+    Save the search's progress to FILE every so often, and resume from it if
+    it's there. It's removed once the route is found. With --search bfs, a leg
+    picks up where it left off; otherwise, it starts over.
+
+--checkpoint-secs N
+    Seconds between saves with --checkpoint. Defaults to 60.
+
+--search bfs|zero|manhattan
+    How to look for the quickest route: breadth-first, one minute at a time, or
+    A* with a heuristic of zero or the Manhattan distance to the goal. The
+    answer is the same either way. Defaults to bfs.
+
+--threads N
+    Search with A* and the Manhattan heuristic on N threads instead, or one per
+    core if N is 0. Doesn't go with --search.
+
+--stats
+    Print how many states the search expanded and queued to stderr.
+
+--trips N
+    For part2, cross the valley N times, alternating between the start and the
+    end. Defaults to 3.
+
+--waypoints X,Y;X,Y;...
+    For part2, visit these positions in order instead. Coordinates are 0-based
+    and include the walls.
+",
+};
+
+struct Opts {
+    trips: usize,
+    waypoints: Option<Vec<Point>>,
+    moves: bool,
+    strategy: Strategy,
+    stats: bool,
+    progress: bool,
+}
+
+impl Default for Opts {
+    fn default() -> Self {
+        Opts {
+            trips: 3,
+            waypoints: None,
+            moves: false,
+            strategy: Strategy::default(),
+            stats: false,
+            progress: false,
         }
     }
 }
 
-fn _mod(start: usize, change: isize, modulus: usize) -> usize {
-    let modulus = modulus as isize;
-    let mut rem = (start as isize + change) % modulus;
-    if rem < 0 {
-        rem += modulus;
-    }
-    rem as usize
-}
-
-fn find_min_actions(board: &Board, start: Point, end: Point, initial_round: usize) -> usize {
-    use Dir::*;
-    use Action::*;
-
-    let mut best: usize = usize::MAX;
-
-    let mut queue: BinaryHeap<Reverse<State>> = BinaryHeap::new();
-    let initial_state = State::new(
-        lower_bound(start, end, 0),
-        start,
-        initial_round);
-    queue.push(Reverse(initial_state));
-
-    let mut seen: HashSet<State> = HashSet::new();
-
-    while let Some(Reverse(state)) = queue.pop() {
-        if state.player == end {
-            if state.round < best {
-                best = state.round;
+impl Opts {
+    fn parse(args: &[&str]) -> Result<Self, AocError> {
+        let mut opts = Opts::default();
+        let mut threads = None;
+        let mut flags = Flags::new(args);
+        while let Some(flag) = flags.next() {
+            match flag {
+                "--trips" => opts.trips = flags.parse()?,
+                "--waypoints" => opts.waypoints = Some(parse_waypoints(flags.value()?)?),
+                "--moves" => opts.moves = true,
+                "--search" => opts.strategy = flags.parse()?,
+                "--threads" => threads = Some(flags.parse()?),
+                "--stats" => opts.stats = true,
+                "--progress" => opts.progress = true,
+                _ => return Err(flags.unknown()),
             }
-            continue;
         }
-
-        let round = state.round + 1;
-        let branches = [Move(Up), Move(Right), Move(Down), Move(Left), Wait]
-            .iter()
-            .filter_map(|a| match a {
-                Move(d) => board.move_player(state.player, *d),
-                Wait => Some(state.player),
-            })
-            .filter_map(|p| {
-                if board.get(p, round) != Tile::Open {
-                    return None;
-                }
-                let lb = lower_bound(p, end, round);
-                if lb >= best {
-                    return None;
-                }
-
-                let child_state = State::new(lb, p, round);
-                if seen.contains(&child_state) {
-                    return None;
-                } else {
-                    seen.insert(child_state);
-                }
-
-                Some(child_state)
-            });
-        for b in branches {
-            queue.push(Reverse(b));
+        if let Some(threads) = threads {
+            if !matches!(opts.strategy, Strategy::Bfs) {
+                return Err(AocError::BadArgs("--threads has its own search, so it doesn't work with --search".into()));
+            }
+            opts.strategy = match threads {
+                0 => Strategy::Parallel(parallel::default_threads()),
+                n => Strategy::Parallel(n),
+            };
         }
+        Ok(opts)
     }
-    best - initial_round
-}
-
-fn lower_bound(a: Point, b: Point, round: usize) -> usize {
-    manhattan_dist(a, b) + round
-}
 
-fn manhattan_dist(a: Point, b: Point) -> usize {
-    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    match args[..] {
-        ["part1"] => {
-            let input = io::read_to_string(io::stdin())?;
-            println!("{}", part1(&input));
-        },
-        ["part2"] => {
-            let input = io::read_to_string(io::stdin())?;
-            println!("{}", part2(&input));
-        },
-        _ => return Err("must specify part1|part2".into()),
+    fn waypoints(&self, board: &Board) -> Vec<Point> {
+        match &self.waypoints {
+            Some(waypoints) => waypoints.clone(),
+            None => trips(board, self.trips),
+        }
     }
-    Ok(())
-}
-
-fn part1(board_str: &str) -> usize {
-    let board = Board::read(board_str);
-    find_min_actions(&board, board.start_pos, board.end_pos, 0)
 }
 
-fn part2(board_str: &str) -> usize {
-    let board = Board::read(board_str);
-    let phase1 = find_min_actions(&board, board.start_pos, board.end_pos, 0);
-    let phase2 = find_min_actions(&board, board.end_pos, board.start_pos, phase1);
-    let phase3 = find_min_actions(&board, board.start_pos, board.end_pos, phase1 + phase2);
-    phase1 + phase2 + phase3
+fn parse_waypoints(s: &str) -> Result<Vec<Point>, AocError> {
+    s.split(';')
+        .map(|p| {
+            let (x, y) = p.split_once(',')
+                .ok_or_else(|| AocError::BadArgs(format!("--waypoints should be X,Y;X,Y;..., not {s:?}")))?;
+            Ok(Point::new(parse_value("--waypoints", x.trim())?, parse_value("--waypoints", y.trim())?))
+        })
+        .collect()
+}
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let mut sink = RenderOpts::take(args)?.sink();
+        let mut checkpointer = take_checkpointer(args)?;
+        let answer = match args[..] {
+            ["part1", ref opts @ ..] => {
+                part1(input.reader()?, &Opts::parse(opts)?, checkpointer.as_mut(), sink.as_mut())?
+            },
+            ["part2", ref opts @ ..] => {
+                part2(input.reader()?, &Opts::parse(opts)?, checkpointer.as_mut(), sink.as_mut())?
+            },
+            _ => return Err(unexpected(args, "part1|part2").into()),
+        };
+        println!("{answer}");
+        Ok(())
+    })
+}
+
+// Find the route through the waypoints and return the number of rounds it takes, after showing it
+// if requested.
+fn run(
+    board: &Board,
+    waypoints: &[Point],
+    opts: &Opts,
+    checkpointer: Option<&mut Checkpointer>,
+    sink: &mut dyn Sink,
+) -> Result<usize, Box<dyn Error>> {
+    progress::enable(opts.progress);
+    let route = match checkpointer {
+        Some(c) => travel_checkpointed(board, waypoints, &opts.strategy, c)?,
+        None => travel(board, waypoints, &opts.strategy)?,
+    };
+    if opts.stats {
+        eprintln!("{}", route.stats);
+    }
+    if opts.moves {
+        write_moves(io::stdout().lock(), &route.legs)?;
+    }
+    for (round, expedition) in route.positions().into_iter().enumerate() {
+        sink.frame(format_args!("== Minute {round} =="), &Frame { board, round, expedition: Some(expedition) })?;
+    }
+    sink.finish()?;
+    Ok(route.rounds())
+}
+
+fn part1(r: impl BufRead, opts: &Opts, checkpointer: Option<&mut Checkpointer>, sink: &mut dyn Sink) -> Result<usize, Box<dyn Error>> {
+    let board = Board::read_from(r)?;
+    run(&board, &trips(&board, 1), opts, checkpointer, sink)
+}
+
+fn part2(r: impl BufRead, opts: &Opts, checkpointer: Option<&mut Checkpointer>, sink: &mut dyn Sink) -> Result<usize, Box<dyn Error>> {
+    let board = Board::read_from(r)?;
+    run(&board, &opts.waypoints(&board), opts, checkpointer, sink)
 }
 
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use aoclib::render::NoRender;
 
     const EXAMPLE: &str = "\
 #.######
@@ -280,32 +212,34 @@ mod test {
 ######.#";
 
     #[test]
-    fn test_blizzard_position_horizontal() {
-        let board = Board::read(EXAMPLE);
-        let start = Point::new(6, 1);
-        let blizzard = Blizzard::new(start, Dir::Right);
-        assert_eq!(board.blizzard_position(blizzard, 0), start);
-        assert_eq!(board.blizzard_position(blizzard, 1), Point::new(1, 1));
-        assert_eq!(board.blizzard_position(blizzard, 2), Point::new(2, 1));
-    }
-
-    #[test]
-    fn test_blizzard_position_vertical() {
-        let board = Board::read(EXAMPLE);
-        let start = Point::new(4, 1);
-        let blizzard = Blizzard::new(start, Dir::Up);
-        assert_eq!(board.blizzard_position(blizzard, 0), start);
-        assert_eq!(board.blizzard_position(blizzard, 1), Point::new(4, 4));
-        assert_eq!(board.blizzard_position(blizzard, 2), Point::new(4, 3));
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes(), &Opts::default(), None, &mut NoRender).unwrap(), 18);
     }
 
     #[test]
-    fn test_part1() {
-        assert_eq!(part1(EXAMPLE), 18);
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes(), &Opts::default(), None, &mut NoRender).unwrap(), 54);
+        let astar = Opts::parse(&["--search", "manhattan"]).unwrap();
+        assert_eq!(part2(EXAMPLE.as_bytes(), &astar, None, &mut NoRender).unwrap(), 54);
+        assert!(Opts::parse(&["--search", "dfs"]).is_err());
+        let parallel = Opts::parse(&["--threads", "3"]).unwrap();
+        assert!(matches!(parallel.strategy, Strategy::Parallel(3)));
+        assert_eq!(part2(EXAMPLE.as_bytes(), &parallel, None, &mut NoRender).unwrap(), 54);
+        assert!(Opts::parse(&["--search", "zero", "--threads", "3"]).is_err());
     }
 
     #[test]
-    fn test_part2() {
-        assert_eq!(part2(EXAMPLE), 54);
+    fn test_trips() {
+        let opts = |trips| Opts { trips, ..Opts::default() };
+        assert_eq!(part2(EXAMPLE.as_bytes(), &opts(0), None, &mut NoRender).unwrap(), 0);
+        assert_eq!(part2(EXAMPLE.as_bytes(), &opts(1), None, &mut NoRender).unwrap(), 18);
+        assert_eq!(part2(EXAMPLE.as_bytes(), &opts(2), None, &mut NoRender).unwrap(), 18 + 23);
+        assert!(part2(EXAMPLE.as_bytes(), &opts(5), None, &mut NoRender).unwrap() > 54);
+
+        let waypoints = Opts::parse(&["--waypoints", "1,0;6,5;1,0;6,5"]).unwrap();
+        assert_eq!(part2(EXAMPLE.as_bytes(), &waypoints, None, &mut NoRender).unwrap(), 54);
+        let outside = Opts::parse(&["--waypoints", "1,0;0,3"]).unwrap();
+        assert!(part2(EXAMPLE.as_bytes(), &outside, None, &mut NoRender).is_err());
+        assert!(Opts::parse(&["--waypoints", "1"]).is_err());
     }
 }
@@ -1,78 +1,74 @@
-use std::io::BufRead;
 use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
 
-fn from_snafu_digit(c: char) -> i64 {
-    match c {
-        '2' => 2,
-        '1' => 1,
-        '0' => 0,
-        '-' => -1,
-        '=' => -2,
-        _ => panic!("unexpected char: {c}"),
-    }
-}
+use advent_of_code_2022::args::{parse_value, unexpected, Cli, Input, INPUT_HELP};
+use advent_of_code_2022::day25::{convert, part1, Base};
+use advent_of_code_2022::error::AocError;
 
-fn to_snafu_digit(digit: i64) -> char {
-    match digit {
-        2 => '2',
-        1 => '1',
-        0 => '0',
-        -1 => '-',
-        -2 => '=',
-        _ => panic!("unexpected digit: {digit}"),
-    }
-}
+const CLI: Cli = Cli {
+    synopsis: "day25 <opts> part1|convert [VALUE]",
+    common: &[INPUT_HELP],
+    options: "\
+convert [--from decimal|snafu] [VALUE]
+    Convert VALUE between decimal and SNAFU, or each line of stdin if no value
+    is given. Values that look like decimal integers are converted to SNAFU and
+    anything else is read as SNAFU, unless --from says which base they're in.
+",
+};
 
-fn from_snafu(s: &str) -> i64 {
-    let place_values = (0..).map(|i| 5i64.pow(i));
-    let digit_values = s.chars().rev().map(from_snafu_digit);
-    place_values.zip(digit_values).map(|(pv, dv)| pv * dv).sum()
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        match args[..] {
+            ["part1"] => println!("{}", part1(input.reader()?)?),
+            ["convert", ref args @ ..] => {
+                let opts = ConvertOpts::parse(args)?;
+                match opts.value {
+                    Some(value) => println!("{}", convert(value, opts.from)?),
+                    None => convert_lines(input.reader()?, io::stdout().lock(), opts.from)?,
+                }
+            },
+            _ => return Err(unexpected(args, "part1|convert").into()),
+        }
+        Ok(())
+    })
 }
 
-fn to_snafu(n: i64) -> String {
-    let mut snafu: String = String::new();
-    let mut n = n;
-
-    let mut place_value = 1;
-    while place_value * 2 < n {
-        place_value *= 5;
-    }
+#[derive(Debug, Default, PartialEq)]
+struct ConvertOpts<'a> {
+    from: Option<Base>,
+    value: Option<&'a str>,
+}
 
-    while place_value > 0 {
-        //let orig_n = n;
-        let mut digit = 0;
-        if n > 0 {
-            // `place_value / 2` is the max value representable by subsequent digits.
-            while n > place_value / 2 {
-                n -= place_value;
-                digit += 1;
-            }
-        } else {
-            while n < -place_value / 2 {
-                n += place_value;
-                digit -= 1;
+impl<'a> ConvertOpts<'a> {
+    fn parse(args: &[&'a str]) -> Result<Self, AocError> {
+        let mut opts = ConvertOpts::default();
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--from" => {
+                    let from = args.next().ok_or_else(|| AocError::BadArgs("--from needs a value".into()))?;
+                    opts.from = Some(parse_value("--from", from)?);
+                },
+                // Anything else is the value, which can start with a '-' or '='.
+                _ if opts.value.is_none() => opts.value = Some(arg),
+                _ => return Err(AocError::BadArgs(format!("unexpected argument: {arg}"))),
             }
         }
-        //println!("orig_n={orig_n} n={n} pv={place_value} digit={digit}");
-        snafu.push(to_snafu_digit(digit));
-        place_value /= 5;
+        Ok(opts)
     }
-    snafu
-}
-
-fn part1(r: impl BufRead) -> Result<String, Box<dyn Error>> {
-    let sum = r.lines()
-        .map(|line| Ok::<i64, Box<dyn Error>>(from_snafu(&line?)))
-        .sum::<Result<i64, _>>()?;
-    Ok(to_snafu(sum))
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    match args[..] {
-        ["part1"] => println!("{}", part1(std::io::stdin().lock())?),
-        _ => return Err("must specify part1|part2".into()),
+// Convert each non-blank line, stopping at the first one that can't be converted.
+fn convert_lines(r: impl BufRead, mut w: impl Write, from: Option<Base>) -> Result<(), Box<dyn Error>> {
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let converted = convert(&line, from).map_err(|e| format!("line {}: {e}", i + 1))?;
+        writeln!(w, "{converted}")?;
     }
     Ok(())
 }
@@ -81,62 +77,28 @@ fn main() -> Result<(), Box<dyn Error>> {
 mod test {
     use super::*;
 
-    const EXAMPLE: &str = "\
-1=-0-2
-12111
-2=0=
-21
-2=01
-111
-20012
-112
-1=-1=
-1-12
-12
-1=
-122";
-
     #[test]
-    fn test_part1() {
-        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), "2=-1=0");
-    }
+    fn test_convert_lines() {
+        let mut out = Vec::new();
+        convert_lines("2022\n\n1=11-2\n-3\r\n".as_bytes(), &mut out, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1=11-2\n2022\n-2\n");
 
-    #[test]
-    fn test_from_snafu() {
-        assert_eq!(from_snafu("1"), 1);
-        assert_eq!(from_snafu("2"), 2);
-        assert_eq!(from_snafu("1="), 3);
-        assert_eq!(from_snafu("1-"), 4);
-        assert_eq!(from_snafu("10"), 5);
-        assert_eq!(from_snafu("11"), 6);
-        assert_eq!(from_snafu("12"), 7);
-        assert_eq!(from_snafu("2="), 8);
-        assert_eq!(from_snafu("2-"), 9);
-        assert_eq!(from_snafu("20"), 10);
-        assert_eq!(from_snafu("1=0"), 15);
-        assert_eq!(from_snafu("1-0"), 20);
-        assert_eq!(from_snafu("1=11-2"), 2022);
-        assert_eq!(from_snafu("1-0---0"), 12345);
-        assert_eq!(from_snafu("1121-1110-1=0"), 314159265);
+        let mut out = Vec::new();
+        convert_lines("12\n".as_bytes(), &mut out, Some(Base::Snafu)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "7\n");
+
+        let err = convert_lines("1\n1x\n".as_bytes(), Vec::new(), None).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: line 1, column 2: unexpected character 'x'");
     }
 
     #[test]
-    fn test_to_snafu() {
-        assert_eq!(to_snafu(1), "1");
-        assert_eq!(to_snafu(2), "2");
-        assert_eq!(to_snafu(3), "1=");
-        assert_eq!(to_snafu(4), "1-");
-        assert_eq!(to_snafu(5), "10");
-        assert_eq!(to_snafu(6), "11");
-        assert_eq!(to_snafu(7), "12");
-        assert_eq!(to_snafu(8), "2=");
-        assert_eq!(to_snafu(9), "2-");
-        assert_eq!(to_snafu(10), "20");
-        assert_eq!(to_snafu(13), "1==");
-        assert_eq!(to_snafu(15), "1=0");
-        assert_eq!(to_snafu(20), "1-0");
-        assert_eq!(to_snafu(2022), "1=11-2");
-        assert_eq!(to_snafu(12345), "1-0---0");
-        assert_eq!(to_snafu(314159265), "1121-1110-1=0");
+    fn test_convert_opts() {
+        assert_eq!(ConvertOpts::parse(&[]).unwrap(), ConvertOpts::default());
+        let opts = ConvertOpts::parse(&["-2", "--from", "decimal"]).unwrap();
+        assert_eq!(opts, ConvertOpts { from: Some(Base::Decimal), value: Some("-2") });
+        let opts = ConvertOpts::parse(&["--from", "snafu", "--"]).unwrap();
+        assert_eq!(opts, ConvertOpts { from: Some(Base::Snafu), value: Some("--") });
+        assert!(ConvertOpts::parse(&["1", "2"]).is_err());
+        assert!(ConvertOpts::parse(&["--from", "hex"]).is_err());
     }
 }
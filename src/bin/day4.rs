@@ -1,124 +1,46 @@
 use std::io;
-use std::str::FromStr;
-
-struct Range {
-    start: i32,
-    end: i32,
-}
-
-impl Range {
-    pub fn new(start: i32, end: i32) -> Self {
-        Range { start, end }
-    }
-
-    pub fn contains(&self, o: &Self) -> bool {
-        o.start >= self.start && o.end <= self.end
-    }
-
-    pub fn either_contains_other(a: &Self, b: &Self) -> bool {
-        a.contains(b) || b.contains(a)
-    }
-
-    pub fn overlaps(&self, o: &Self) -> bool {
-        !(o.start > self.end || o.end < self.start)
-    }
-}
-
-impl FromStr for Range {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split('-').collect::<Vec<&str>>()[..] {
-            [start, end] => {
-                let start = start.parse::<i32>().map_err(|e| format!("parse range: {}", e))?;
-                let end = end.parse::<i32>().map_err(|e| format!("parse range: {}", e))?;
-                Ok(Range::new(start, end))
+use std::process::ExitCode;
+
+use advent_of_code_2022::args::{unexpected, Cli, Input, INPUT_HELP};
+use advent_of_code_2022::day4::{coverage, part1, part2, part3, report, write_coverage, write_report};
+
+const CLI: Cli = Cli {
+    synopsis: "day4 <opts> part1|part2|part3|report part1|part2|coverage",
+    common: &[INPUT_HELP],
+    options: "\
+part3
+    Count the sections that both elves in a pair are assigned, summed over all
+    the pairs.
+
+report part1|part2
+    Print the line, places on it and assignments of each pair of elves that
+    part1 or part2 counts: the ones where one contains the other, or that
+    overlap at all.
+
+coverage
+    Merge every assignment and print how many sections anyone is assigned,
+    the longest run of unassigned sections between them, and the section
+    assigned to the most elves.
+
+A line can have more than two assignments, for a bigger team. Every pair of
+elves in the team is then checked, and counted, on its own.
+",
+};
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        match args[..] {
+            ["part1"] => println!("{}", part1(input.read_lines()?.iter())?),
+            ["part2"] => println!("{}", part2(input.read_lines()?.iter())?),
+            ["part3"] => println!("{}", part3(input.read_lines()?.iter())?),
+            ["report", part @ ("part1" | "part2")] => {
+                let pairs = report(input.read_lines()?.iter(), if part == "part1" { 1 } else { 2 })?;
+                write_report(io::stdout().lock(), &pairs)?;
             },
-            _ => Err("parse range: unexpected number of fields".to_owned()),
+            ["coverage"] => write_coverage(io::stdout().lock(), &coverage(input.read_lines()?.iter())?)?,
+            _ => return Err(unexpected(args, "part1|part2|part3|report part1|part2|coverage").into()),
         }
-    }
-}
-
-fn line_to_ranges(line: &str) -> Result<(Range, Range), String> {
-    let ranges: Vec<&str> = line.split(',').collect();
-    if let [a, b] = ranges[..] {
-        let a = Range::from_str(a).unwrap();
-        let b = Range::from_str(b).unwrap();
-        Ok((a, b))
-    } else {
-        Err("unexpected number of ranges on line".to_owned())
-    }
-}
-
-fn part1<T>(lines: T) -> u32
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-{
-    lines.map(|l| {
-        let (a, b) = line_to_ranges(l.as_ref()).unwrap();
-        if Range::either_contains_other(&a, &b) { 1 } else { 0 }
-    }).sum()
-}
-
-fn part2<T>(lines: T) -> u32
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-{
-    lines.map(|l| {
-        let (a, b) = line_to_ranges(l.as_ref()).unwrap();
-        if a.overlaps(&b) { 1 } else { 0 }
-    }).sum()
-}
-
-const HELP: &str = "\
-day4 <opts> part1|part2
-
--h|--help
-    show help
-";
-
-fn main() -> Result<(), String> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    if args.iter().any(|&v| v == "-h" || v == "--help") {
-        print!("{}", HELP);
-        return Ok(());
-    }
-    match args[..] {
-        ["part1"] => println!("{}", part1(io::stdin().lines().map(|l| l.unwrap()))),
-        ["part2"] => println!("{}", part2(io::stdin().lines().map(|l| l.unwrap()))),
-        _ => {
-            eprint!("{}", HELP);
-            return Err("Must give part1|part2".to_owned())
-        }
-    };
-    Ok(())
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    fn lines() -> Vec<String> {
-        let input = "\
-2-4,6-8
-2-3,4-5
-5-7,7-9
-2-8,3-7
-6-6,4-6
-2-6,4-8";
-        input.lines().map(&str::to_string).collect()
-    }
-
-    #[test]
-    fn test_part1() {
-        let sum = part1(lines().iter());
-        assert_eq!(sum, 2);
-    }
-
-    #[test]
-    fn test_part2() {
-        let sum = part2(lines().iter());
-        assert_eq!(sum, 4);
-    }
+        Ok(())
+    })
 }
@@ -1,181 +1,98 @@
 use std::io;
-
-const MAX_STACKS: usize = 9;
-
-type Stacks = [Vec<char>; MAX_STACKS];
-
-struct Move {
-    n: usize,
-    src: usize,
-    dst: usize,
-}
-
-fn part1<T>(mut lines: T) -> String
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-{
-    let mut stacks = parse_stacks(&mut lines);
-    parse_moves(&mut lines, &mut stacks, move_lifo);
-    stacks.iter().map(|s| s.last().unwrap_or(&' ')).collect()
-}
-
-fn part2<T>(mut lines: T) -> String
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-{
-    let mut stacks = parse_stacks(&mut lines);
-    parse_moves(&mut lines, &mut stacks, move_fifo);
-    stacks.iter().map(|s| s.last().unwrap_or(&' ')).collect()
-}
-
-fn parse_stacks<T>(lines: T) -> Stacks
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-{
-    let mut stacks: Stacks = core::array::from_fn(|_| Vec::new());
-    for line in lines.take_while(|l| l.as_ref().trim().starts_with('[')) {
-        for (i, c) in line.as_ref().chars().enumerate() {
-            if !c.is_ascii_alphabetic() {
-                continue;
-            }
-            stacks[i / 4].insert(0, c);
+use std::process::ExitCode;
+
+use advent_of_code_2022::args::{
+    parse_value, take_value, unexpected, Cli, Flags, Input, RenderOpts, INPUT_HELP, RENDER_HELP,
+};
+use advent_of_code_2022::day5::{
+    parse_crane, replay_to, simulate, tops, write_stacks, CrateMover9000, CrateMover9001, ReportFormat,
+};
+use advent_of_code_2022::error::AocError;
+
+const CLI: Cli = Cli {
+    synopsis: "day5 <opts> part1|part2|run|stacks [--format F]",
+    common: &[INPUT_HELP, RENDER_HELP],
+    options: "\
+--crane 9000|9001|limit-K
+    The crane `run` and `stacks` move the crates with: a CrateMover 9000 moves
+    them one at a time, like part1, a 9001 moves them all at once, like part2,
+    and limit-K lifts up to K at a time. Defaults to 9000.
+
+--every N
+    With --render, only show the stacks after every Nth move, and once they're
+    done. Defaults to 1.
+
+--replay-to N
+    Stop after the Nth move instead of making them all. If N is negative, make
+    all the moves and then undo the last -N of them, so --render shows them
+    being taken back.
+
+run
+    Print the crates on top of the stacks after the crane given by --crane
+    has made all the moves.
+
+stacks [--format text|json]
+    Like run, but print every crate in each stack, from the bottom up, with
+    the stack's label. Defaults to text.
+
+The stacks in the diagram can be labelled with letters or any other words
+instead of numbers, like ` A   B   C`, as long as no two are the same. The
+moves then use the labels, like `move 2 from A to C`.
+",
+};
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let crane = parse_crane(take_value(args, "--crane")?.unwrap_or("9000"))
+            .map_err(|e| AocError::BadArgs(format!("--crane: {e}")))?;
+        let every = take_value(args, "--every")?.map_or(Ok(1), |n| parse_value("--every", n))?;
+        let to: Option<i64> = take_value(args, "--replay-to")?.map(|n| parse_value("--replay-to", n)).transpose()?;
+        let render_opts = RenderOpts::take(args)?;
+        let mut sink = render_opts.sink();
+        let lines = input.read_lines()?;
+        let mut replay = |crane| match to {
+            Some(to) => replay_to(lines.iter(), crane, to, every, sink.as_mut()),
+            None => simulate(lines.iter(), crane, every, sink.as_mut()),
+        };
+        match args[..] {
+            ["part1"] => println!("{}", tops(replay(&CrateMover9000)?.stacks())),
+            ["part2"] => println!("{}", tops(replay(&CrateMover9001)?.stacks())),
+            ["run"] => println!("{}", tops(replay(crane.as_ref())?.stacks())),
+            ["stacks", ref opts @ ..] => {
+                let format = parse_stacks(opts)?;
+                let replay = replay(crane.as_ref())?;
+                write_stacks(io::stdout().lock(), format, replay.labels(), replay.stacks())?;
+            },
+            _ => return Err(unexpected(args, "part1|part2|run|stacks").into()),
         }
-    }
-    stacks
+        sink.finish()?;
+        Ok(())
+    })
 }
 
-fn parse_moves<T, F>(lines: T, stacks: &mut Stacks, mut move_fn: F)
-where
-    T: Iterator,
-    T::Item: AsRef<str>,
-    F: FnMut(&mut Stacks, Move),
-{
-    for line in lines.skip_while(|l| !l.as_ref().starts_with("move")) {
-        let line = line.as_ref();
-        let fields = line.split(' ').collect::<Vec<&str>>();
-        if let [_, n, _, src, _, dst] = fields[..] {
-            let n = n.parse::<usize>().unwrap();
-            let src = src.parse::<usize>().unwrap() - 1;
-            let dst = dst.parse::<usize>().unwrap() - 1;
-            move_fn(stacks, Move { n, src, dst });
-        } else {
-            panic!("unexpected line: {}", line);
+// The output format for `stacks`.
+fn parse_stacks(args: &[&str]) -> Result<ReportFormat, AocError> {
+    let mut format = ReportFormat::default();
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--format" => format = flags.parse()?,
+            _ => return Err(flags.unknown()),
         }
     }
-}
-
-fn move_lifo(stacks: &mut Stacks, mv: Move) {
-    for _ in 0..mv.n {
-        let item = stacks[mv.src].pop().unwrap();
-        stacks[mv.dst].push(item);
-    }
-}
-
-fn move_fifo(stacks: &mut Stacks, mv: Move) {
-    let mut scratch: Vec<char> = Vec::new();
-    for _ in 0..mv.n {
-        let item = stacks[mv.src].pop().unwrap();
-        scratch.push(item);
-    }
-    while let Some(item) = scratch.pop() {
-        stacks[mv.dst].push(item);
-    }
-}
-
-const HELP: &str = "\
-day5 <opts> part1|part2
-
--h|--help
-    show help
-";
-
-fn main() -> Result<(), String> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    if args.iter().any(|&a| a == "-h" || a == "--help") {
-        print!("{}", HELP);
-        return Ok(());
-    }
-    match args[..] {
-        ["part1"] => println!("{}", part1(io::stdin().lines().map(|l| l.unwrap()))),
-        ["part2"] => println!("{}", part2(io::stdin().lines().map(|l| l.unwrap()))),
-        _ => return Err("Must specify part1|part2".to_owned()),
-    };
-    Ok(())
+    Ok(format)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fmt::Debug;
-
-    fn lines() -> Vec<String> {
-        // `input` starts with an extra empty line which we remove below.
-        let input = "
-    [D]
-[N] [C]
-[Z] [M] [P]
- 1   2   3
-
-move 1 from 2 to 1
-move 3 from 1 to 3
-move 2 from 2 to 1
-move 1 from 1 to 2";
-        input.trim_start_matches('\n').lines().map(|l| l.to_owned()).collect()
-    }
-
-    fn assert_slices_eq<T>(a: &[T], b: &[T])
-    where
-        T: Eq + Debug,
-    {
-        let (mut it_a, mut it_b) = (a.iter(), b.iter());
-        loop {
-            let (i, j) = (it_a.next(), it_b.next());
-            if let (None, None) = (i, j) {
-                return;
-            }
-            if i != j {
-                dbg!(a);
-                dbg!(b);
-                if let (Some(i), Some(j)) = (i, j) {
-                    assert_eq!(i, j);
-                } else {
-                    assert_eq!(i, j);
-                }
-            }
-        }
-    }
 
     #[test]
     fn test_parse_stacks() {
-        let stacks = parse_stacks(lines().iter());
-        assert_slices_eq(&stacks[0], &vec!['Z', 'N']);
-        assert_slices_eq(&stacks[1], &vec!['M', 'C', 'D']);
-        assert_slices_eq(&stacks[2], &vec!['P']);
-    }
-
-    #[test]
-    fn test_parse_moves_lifo() {
-        let lines = lines();
-        let mut lines = lines.iter();
-        let mut stacks = parse_stacks(&mut lines);
-        parse_moves(&mut lines, &mut stacks, move_lifo);
-        assert_slices_eq(&stacks[0], &vec!['C']);
-        assert_slices_eq(&stacks[1], &vec!['M']);
-        assert_slices_eq(&stacks[2], &vec!['P', 'D', 'N', 'Z']);
-    }
-
-    #[test]
-    fn test_parse_moves_fifo() {
-        let lines = lines();
-        let mut lines = lines.iter();
-        let mut stacks = parse_stacks(&mut lines);
-        parse_moves(&mut lines, &mut stacks, move_fifo);
-        assert_slices_eq(&stacks[0], &vec!['M']);
-        assert_slices_eq(&stacks[1], &vec!['C']);
-        assert_slices_eq(&stacks[2], &vec!['P', 'Z', 'N', 'D']);
+        assert_eq!(parse_stacks(&[]).unwrap(), ReportFormat::Text);
+        assert_eq!(parse_stacks(&["--format", "json"]).unwrap(), ReportFormat::Json);
+        assert!(parse_stacks(&["--format", "csv"]).is_err());
+        assert!(parse_stacks(&["--crane"]).is_err());
     }
 }
@@ -1,71 +1,99 @@
-use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
 
-fn find_packet_marker(s: &str) -> Option<usize> {
-    const WIN_LEN: usize = 4;
-    for (i, win) in s.as_bytes().windows(WIN_LEN).enumerate() {
-        // Just test every pair.
-        if win[0] != win[1] 
-            && win[0] != win[2]
-            && win[0] != win[3]
-            && win[1] != win[2]
-            && win[1] != win[3]
-            && win[2] != win[3]
-        {
-            return Some(i + WIN_LEN);
-        }
-    }
-    None
-}
+use advent_of_code_2022::args::{unexpected, Cli, Flags, Input, INPUT_HELP};
+use advent_of_code_2022::day6::{
+    find_marker_in, markers_in, write_marker_stats, MarkerStats, MESSAGE_MARKER_LEN, PACKET_MARKER_LEN,
+};
+use advent_of_code_2022::error::AocError;
 
-fn find_message_marker(s: &str) -> Option<usize> {
-    const WIN_LEN: usize = 14;
-    let mut window: VecDeque<char> = VecDeque::new();
-    let mut freq: HashMap<char, u32> = HashMap::new();
+const CLI: Cli = Cli {
+    synopsis: "day6 <opts> part1|part2|marker [--len N]|markers [--len N] [--summary]",
+    common: &[INPUT_HELP],
+    options: "\
+The datastream is read a piece at a time, up to the first newline if there is
+one, so it can be bigger than memory.
 
-    for (i, c) in s.chars().enumerate() {
-        window.push_back(c);
-        *freq.entry(c).or_insert(0) += 1;
+marker [--len N]
+    Print where the first marker of N different characters in a row ends.
+    part1 looks for 4 and part2 for 14. Defaults to 4.
 
-        while window.len() > WIN_LEN {
-            let c = window.pop_front().unwrap();
-            let count = freq.get_mut(&c).unwrap();
-            *count -= 1;
-            if *count == 0 {
-                freq.remove(&c);
-            }
+markers [--len N] [--summary]
+    Print where every marker ends, not just the first, one to a line, and then
+    how many there are and the gaps between them. Markers can overlap, so a
+    run of more than N different characters has a marker ending at each of
+    the characters after the Nth. With --summary, only print the counts.
+",
+};
+
+fn main() -> ExitCode {
+    CLI.run(|args| {
+        let input = Input::take(args)?;
+        let first = |len| -> Result<(), Box<dyn std::error::Error>> {
+            println!("{}", find_marker_in(input.reader()?, len)?.ok_or("no marker")?);
+            Ok(())
+        };
+        match args[..] {
+            ["part1"] => first(PACKET_MARKER_LEN)?,
+            ["part2"] => first(MESSAGE_MARKER_LEN)?,
+            ["marker", ref opts @ ..] => first(parse_marker(opts)?)?,
+            ["markers", ref opts @ ..] => {
+                let (len, summary) = parse_markers(opts)?;
+                let mut w = BufWriter::new(io::stdout().lock());
+                let mut stats = MarkerStats::default();
+                let mut result = Ok(());
+                markers_in(input.reader()?, len, |at| {
+                    stats.add(at);
+                    if !summary {
+                        result = writeln!(w, "{at}");
+                    }
+                    result.is_ok()
+                })?;
+                result?;
+                if !summary {
+                    writeln!(w)?;
+                }
+                write_marker_stats(&mut w, &stats)?;
+                w.flush()?;
+            },
+            _ => return Err(unexpected(args, "part1|part2|marker|markers").into()),
         }
+        Ok(())
+    })
+}
 
-        if freq.len() == WIN_LEN {
-            return Some(i + 1);
+// The marker length for `marker`.
+fn parse_marker(args: &[&str]) -> Result<usize, AocError> {
+    let mut len = PACKET_MARKER_LEN;
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--len" => len = flags.parse()?,
+            _ => return Err(flags.unknown()),
         }
     }
-    None
+    check_len(len)
 }
 
-const HELP: &str = "\
-day6 <opts> part1|part2
-
--h|--help
-    show help
-";
-
-fn main() -> Result<(), String> {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let args: Vec<&str> = args.iter().map(String::as_str).collect();
-    if args.iter().any(|&s| s == "-h" || s == "--help") {
-        print!("{}", HELP);
-        return Ok(());
+// The marker length for `markers`, and whether to only print the summary.
+fn parse_markers(args: &[&str]) -> Result<(usize, bool), AocError> {
+    let (mut len, mut summary) = (PACKET_MARKER_LEN, false);
+    let mut flags = Flags::new(args);
+    while let Some(flag) = flags.next() {
+        match flag {
+            "--len" => len = flags.parse()?,
+            "--summary" => summary = true,
+            _ => return Err(flags.unknown()),
+        }
     }
+    Ok((check_len(len)?, summary))
+}
 
-    let mut line = String::new();
-    std::io::stdin().read_line(&mut line).unwrap();
-
-    match args[..] {
-        ["part1"] => println!("{}", find_packet_marker(&line).unwrap()),
-        ["part2"] => println!("{}", find_message_marker(&line).unwrap()),
-        _ => return Err("Must give part1|part2".to_owned()),
+fn check_len(len: usize) -> Result<usize, AocError> {
+    if len == 0 {
+        return Err(AocError::BadArgs("--len should be positive".into()));
     }
-    Ok(())
+    Ok(len)
 }
 
 #[cfg(test)]
@@ -73,20 +101,19 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_find_packet_marker() {
-        assert_eq!(find_packet_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(7));
-        assert_eq!(find_packet_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(5));
-        assert_eq!(find_packet_marker("nppdvjthqldpwncqszvftbrmjlhg"), Some(6));
-        assert_eq!(find_packet_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Some(10));
-        assert_eq!(find_packet_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Some(11));
+    fn test_parse_marker() {
+        assert_eq!(parse_marker(&[]).unwrap(), 4);
+        assert_eq!(parse_marker(&["--len", "14"]).unwrap(), 14);
+        assert!(parse_marker(&["--len", "0"]).is_err());
+        assert!(parse_marker(&["--len", "x"]).is_err());
+        assert!(parse_marker(&["--every", "2"]).is_err());
     }
 
     #[test]
-    fn test_find_message_marker() {
-        assert_eq!(find_message_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(19));
-        assert_eq!(find_message_marker("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(23));
-        assert_eq!(find_message_marker("nppdvjthqldpwncqszvftbrmjlhg"), Some(23));
-        assert_eq!(find_message_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Some(29));
-        assert_eq!(find_message_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Some(26));
+    fn test_parse_markers() {
+        assert_eq!(parse_markers(&[]).unwrap(), (4, false));
+        assert_eq!(parse_markers(&["--summary", "--len", "14"]).unwrap(), (14, true));
+        assert!(parse_markers(&["--len", "0", "--summary"]).is_err());
+        assert!(parse_markers(&["--format", "json"]).is_err());
     }
 }
@@ -0,0 +1,236 @@
+use std::cmp::Reverse;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use std::str::FromStr;
+
+use aoclib::iter::{Paragraphs, TopByKey};
+
+use crate::error::AocError;
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct Elf {
+    // 0-based, in the order they're listed.
+    pub i: i32,
+    // The number of food items carried.
+    pub items: usize,
+    pub calories: i32,
+}
+
+// Parse an elf's paragraph, with an item's calories on each line. `line_no` is the number of the
+// paragraph's first line, for errors. Unless `strict`, malformed lines are reported on stderr and
+// skipped.
+pub fn parse_group(i: i32, group: &str, line_no: usize, strict: bool) -> Result<Elf, AocError> {
+    let mut elf = Elf { i, items: 0, calories: 0 };
+    for (line_no, line) in (line_no..).zip(group.lines()) {
+        match line.parse::<i32>() {
+            Ok(cals) => {
+                elf.items += 1;
+                elf.calories += cals;
+            },
+            Err(err) if strict => return Err(AocError::Parse(format!("line {line_no}: {err}: {line:?}"))),
+            Err(err) => eprintln!("bad line,err={},line={}", err, line),
+        }
+    }
+    Ok(elf)
+}
+
+// The elves in blank-line-separated paragraphs.
+fn read_elves<T: BufRead>(r: T, strict: bool) -> impl Iterator<Item=Result<Elf, AocError>> {
+    let mut paragraphs = Paragraphs::new(r);
+    let mut i = 0;
+    iter::from_fn(move || {
+        let elf = match paragraphs.next()? {
+            Ok(group) => parse_group(i, &group, paragraphs.line_no(), strict),
+            Err(err) => Err(err.into()),
+        };
+        i += 1;
+        Some(elf)
+    })
+}
+
+// The elves, with errors reported on stderr and skipped.
+fn lenient_elves<T: BufRead>(r: T) -> impl Iterator<Item=Elf> {
+    read_elves(r, false).filter_map(|elf| elf.map_err(|err| eprintln!("read input: {err}")).ok())
+}
+
+// The most calories carried by one elf.
+pub fn part1<T: BufRead>(r: T) -> i32 {
+    top_elves(r, 1).0
+}
+
+// Like part1, but fail on the first malformed line instead of skipping it.
+pub fn part1_strict<T: BufRead>(r: T) -> Result<i32, AocError> {
+    Ok(top_elves_strict(r, 1)?.0)
+}
+
+// The total calories carried by the three elves carrying the most.
+pub fn part2<T: BufRead>(r: T) -> i32 {
+    top_elves(r, 3).0
+}
+
+pub fn part2_strict<T: BufRead>(r: T) -> Result<i32, AocError> {
+    Ok(top_elves_strict(r, 3)?.0)
+}
+
+// The total calories carried by the n elves carrying the most, and their 0-based indexes, from the
+// most calories to the least. Ties go to the earlier elf.
+pub fn top_elves<T: BufRead>(r: T, n: usize) -> (i32, Vec<usize>) {
+    top(lenient_elves(r), n)
+}
+
+pub fn top_elves_strict<T: BufRead>(r: T, n: usize) -> Result<(i32, Vec<usize>), AocError> {
+    let mut err = None;
+    let elves = read_elves(r, true).map_while(|elf| elf.map_err(|e| err = Some(e)).ok());
+    let top = top(elves, n);
+    err.map_or(Ok(top), Err)
+}
+
+// Every elf, from the most calories to the least, with ties in the order they're listed.
+pub fn report<T: BufRead>(r: T) -> Vec<Elf> {
+    sorted(lenient_elves(r).collect())
+}
+
+pub fn report_strict<T: BufRead>(r: T) -> Result<Vec<Elf>, AocError> {
+    Ok(sorted(read_elves(r, true).collect::<Result<_, _>>()?))
+}
+
+fn sorted(mut elves: Vec<Elf>) -> Vec<Elf> {
+    elves.sort_by_key(|e| (Reverse(e.calories), e.i));
+    elves
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown report format: {s}")),
+        }
+    }
+}
+
+pub fn write_report(mut w: impl Write, format: ReportFormat, elves: &[Elf]) -> io::Result<()> {
+    match format {
+        ReportFormat::Text => {
+            writeln!(w, "{:>5} {:>5} {:>8}", "elf", "items", "calories")?;
+            for e in elves {
+                writeln!(w, "{:>5} {:>5} {:>8}", e.i, e.items, e.calories)?;
+            }
+        },
+        ReportFormat::Csv => {
+            writeln!(w, "elf,items,calories")?;
+            for e in elves {
+                writeln!(w, "{},{},{}", e.i, e.items, e.calories)?;
+            }
+        },
+        ReportFormat::Json => {
+            writeln!(w, "[")?;
+            for (i, e) in elves.iter().enumerate() {
+                let sep = if i + 1 < elves.len() { "," } else { "" };
+                writeln!(w, "  {{\"elf\": {}, \"items\": {}, \"calories\": {}}}{sep}", e.i, e.items, e.calories)?;
+            }
+            writeln!(w, "]")?;
+        },
+    }
+    Ok(())
+}
+
+fn top(elves: impl Iterator<Item=Elf>, n: usize) -> (i32, Vec<usize>) {
+    if n == 0 {
+        return (0, Vec::new());
+    }
+    let mut top = TopByKey::new(n, |e: &Elf| e.calories);
+    top.extend(elves);
+    // TopByKey iterates from the least to the most.
+    let top: Vec<Elf> = top.into_iter().rev().collect();
+    (top.iter().map(|e| e.calories).sum(), top.iter().map(|e| e.i as usize).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_group() {
+        assert_eq!(parse_group(2, "23\n1\n", 1, true).unwrap(), Elf { i: 2, items: 2, calories: 24 });
+        let err = parse_group(0, "1\n2x\n", 7, true).unwrap_err();
+        assert_eq!(err.to_string(), "line 8: invalid digit found in string: \"2x\"");
+        assert_eq!(parse_group(0, "1\n2x\n", 7, false).unwrap(), Elf { i: 0, items: 1, calories: 1 });
+    }
+
+    #[test]
+    fn test_read_elves() {
+        let elves: Vec<Elf> = lenient_elves("23\n1\n\n1\n2".as_bytes()).collect();
+        assert_eq!(elves, [Elf { i: 0, items: 2, calories: 24 }, Elf { i: 1, items: 2, calories: 3 }]);
+    }
+
+    #[test]
+    fn test_input_variants() {
+        let want = [Elf { i: 0, items: 2, calories: 3 }, Elf { i: 1, items: 1, calories: 4 }];
+        for input in ["1\r\n2\r\n\r\n4\r\n", "1\n2\n\n4\n\n\n", "\n1\n2\n\n\n4", "1\r\n2\n\r\n\n4\n\r\n"] {
+            let elves: Vec<Elf> = lenient_elves(input.as_bytes()).collect();
+            assert_eq!(elves, want, "{input:?}");
+            assert_eq!(report_strict(input.as_bytes()).unwrap().len(), 2, "{input:?}");
+        }
+        let err = part1_strict("1\r\n\r\nx\r\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: invalid digit found in string: \"x\"");
+    }
+
+    #[test]
+    fn test_top_elves() {
+        let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n".as_bytes();
+        assert_eq!(top_elves(input, 1), (24000, vec![3]));
+        assert_eq!(top_elves(input, 3), (45000, vec![3, 2, 4]));
+        assert_eq!(top_elves(input, 10), (55000, vec![3, 2, 4, 0, 1]));
+        assert_eq!(top_elves(input, 0), (0, vec![]));
+        assert_eq!(part2(input), 45000);
+        // Ties go to the earlier elf.
+        assert_eq!(top_elves("5\n\n5\n".as_bytes(), 1), (5, vec![0]));
+    }
+
+    #[test]
+    fn test_report() {
+        let elves = report("1\n2\n\n4\n\n3\nx\n".as_bytes());
+        let elf = |i, items, calories| Elf { i, items, calories };
+        assert_eq!(elves, [elf(1, 1, 4), elf(0, 2, 3), elf(2, 1, 3)]);
+        assert!(report_strict("1\n2\n\n4\n\n3\nx\n".as_bytes()).is_err());
+
+        let write = |format| {
+            let mut w = Vec::new();
+            write_report(&mut w, format, &elves[..2]).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+        assert_eq!(write(ReportFormat::Csv), "elf,items,calories\n1,1,4\n0,2,3\n");
+        assert_eq!(write(ReportFormat::Json), "[\n  {\"elf\": 1, \"items\": 1, \"calories\": 4},\n  {\"elf\": 0, \"items\": 2, \"calories\": 3}\n]\n");
+        assert_eq!(write(ReportFormat::Text), "  elf items calories\n    1     1        4\n    0     2        3\n");
+        assert_eq!("json".parse(), Ok(ReportFormat::Json));
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_strict() {
+        assert_eq!(part1_strict("1\n2\n\n4\n".as_bytes()).unwrap(), 4);
+        assert_eq!(part2_strict("1\n2\n\n4\n".as_bytes()).unwrap(), 7);
+        let err = part1_strict("1\n2\n\n4x\n5\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 4: invalid digit found in string: \"4x\"");
+        // Extra blank lines still count towards the line numbers.
+        let err = part1_strict("1\n\n\n2\n\n3\n 4\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 7: invalid digit found in string: \" 4\"");
+        // The lenient reader skips the bad line.
+        assert_eq!(part1("1\n2\n\n4x\n5\n".as_bytes()), 5);
+
+        let unreadable = &[b'1', b'\n', 0xff, b'\n'][..];
+        assert!(matches!(top_elves_strict(unreadable, 3), Err(AocError::Io(_))));
+    }
+}
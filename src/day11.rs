@@ -0,0 +1,292 @@
+#![allow(dead_code)]
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+use aoclib::iter::{Paragraphs, TopN};
+use crate::error::AocError;
+
+#[derive(PartialEq, Eq, Debug)]
+enum Op {
+    Add(i32),
+    Mul(i32),
+    Square,
+}
+
+type Item = i64;
+
+#[derive(PartialEq, Eq, Debug)]
+struct Monkey {
+    num: usize,
+    items: Vec<Item>,
+    op: Op,
+    test: Item,
+    success: usize,
+    failure: usize,
+}
+
+impl Monkey {
+    pub fn new(
+        num: usize,
+        items: Vec<Item>,
+        op: Op,
+        test: Item,
+        success: usize,
+        failure: usize,
+    ) -> Self
+    {
+        Self { num, items, op, test, success, failure }
+    }
+}
+
+impl FromStr for Monkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+
+        let line = lines.next().ok_or("get monkey number line")?;
+        let monkey_num: usize = line.split_whitespace()
+            .nth(1).ok_or("get monkey number")
+            .and_then(|v| v.trim_matches(':').parse().map_err(|_| "parse monkey number"))?;
+
+        let line = lines.next().ok_or("get starting items")?;
+        let items: Vec<Item> = line.replace(',', "")
+            .split_whitespace().skip(2)
+            .map(|v| v.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("parse items: {}", e))?;
+
+        let line = lines.next().ok_or("get operation line")?;
+        let op = match line.split_whitespace().skip(4).collect::<Vec<&str>>()[..] {
+            ["*", "old"] => Op::Square,
+            ["*", v] => Op::Mul(v.parse().map_err(|e| format!("parse op value: {}", e))?),
+            ["+", v] => Op::Add(v.parse().map_err(|e| format!("parse op value: {}", e))?),
+            _ => return Err(format!("unexpected operation line: {}", line)),
+        };
+
+        let line = lines.next().ok_or("get test line")?;
+        let test: Item = line.split_whitespace()
+            .nth(3).ok_or("get test value")
+            .and_then(|v| v.parse().map_err(|_| "parse test value"))?;
+        if test <= 0 {
+            return Err(format!("test value should be positive: {}", test));
+        }
+
+        let line = lines.next().ok_or("get test success line")?;
+        let success: usize = line.split_whitespace().nth(5)
+            .ok_or("get test success monkey num")
+            .and_then(|v| v.parse().map_err(|_| "parse success monkey num"))?;
+
+        let line = lines.next().ok_or("get test failure line")?;
+        let failure: usize = line.split_whitespace().nth(5)
+            .ok_or("get test failure monkey num")
+            .and_then(|v| v.parse().map_err(|_| "parse failure monkey num"))?;
+
+        Ok(Monkey {
+            num: monkey_num,
+            items,
+            op,
+            test,
+            success,
+            failure,
+        })
+    }
+}
+
+// Read the monkeys, checking that they're numbered in order and only throw to each other.
+fn read_monkeys<T: BufRead>(r: T) -> Result<Vec<Monkey>, String> {
+    let monkeys: Vec<Monkey> = Paragraphs::new(r)
+        .map(|s| Monkey::from_str(&s.map_err(|e| e.to_string())?))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, m) in monkeys.iter().enumerate() {
+        if m.num != i {
+            return Err(format!("expected monkey {}, got {}", i, m.num));
+        }
+        if let Some(dst) = [m.success, m.failure].into_iter().find(|&d| d >= monkeys.len() || d == i) {
+            return Err(format!("monkey {} throws to monkey {}", i, dst));
+        }
+    }
+    Ok(monkeys)
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<u64, AocError> {
+    let mut monkeys = read_monkeys(r)?;
+    let mut inspections: Vec<u64> = vec![0; monkeys.len()];
+    for _round in 0..20 {
+        for i in 0..monkeys.len() {
+            let mut throws: Vec<(Item, usize)> = Vec::new();
+            let monkey = &mut monkeys[i];
+            while let Some(item) = monkey.items.pop() {
+                inspections[monkey.num] += 1;
+                let mut item = match monkey.op {
+                    Op::Add(v) => item + v as Item,
+                    Op::Mul(v) => item * v as Item,
+                    Op::Square => item * item,
+                };
+                item /= 3;
+                let throw_to = if item % monkey.test as Item == 0 {
+                    monkey.success
+                } else {
+                    monkey.failure
+                };
+                throws.push((item, throw_to));
+            }
+            for (item, dst) in throws.into_iter() {
+                monkeys[dst].items.push(item);
+            }
+        }
+    }
+    let mut top = TopN::new(2);
+    top.extend(inspections);
+    Ok(top.into_iter().product())
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<u64, AocError> {
+    let mut monkeys = read_monkeys(r)?;
+
+    let mut inspections: Vec<u64> = vec![0; monkeys.len()];
+
+    // See https://en.wikipedia.org/wiki/Chinese_remainder_theorem
+    let multimodulus: Item = monkeys.iter().map(|m| m.test as Item).product();
+
+    for _round in 0..10_000 {
+        for i in 0..monkeys.len() {
+            let mut throws: Vec<(Item, usize)> = Vec::new();
+            let monkey = &mut monkeys[i];
+            while let Some(item) = monkey.items.pop() {
+                inspections[monkey.num] += 1;
+
+                // (a + b) mod m = ((a mod m) + (b mod m)) mod m
+                // (a * b) mod m = ((a mod m) * (b mod m)) mod m
+                let mut item = match monkey.op {
+                    Op::Add(v) => item + v as Item,
+                    Op::Mul(v) => item * v as Item,
+                    Op::Square => item * item,
+                };
+                item %= multimodulus;
+                let throw_to = if item % monkey.test == 0 { monkey.success } else { monkey.failure };
+                throws.push((item, throw_to));
+            }
+            for (item, dst) in throws.into_iter() {
+                monkeys[dst].items.push(item);
+            }
+
+        }
+    }
+    let mut top = TopN::new(2);
+    top.extend(inspections);
+    Ok(top.into_iter().product())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+    #[test]
+    fn test_parse() {
+        let got: Vec<Monkey> = Paragraphs::new(EXAMPLE.as_bytes())
+            .map(|s| Monkey::from_str(&s.unwrap()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let want: Vec<Monkey> = vec![
+            Monkey::new(0, vec![79, 98], Op::Mul(19), 23, 2, 3),
+            Monkey::new(1, vec![54, 65, 75, 74], Op::Add(6), 19, 2, 0),
+            Monkey::new(2, vec![79, 60, 97], Op::Square, 13, 1, 3),
+            Monkey::new(3, vec![74], Op::Add(3), 17, 0, 1),
+        ];
+        assert_eq!(got, want);
+    }
+
+    fn format_monkey(m: &Monkey) -> String {
+        let items: Vec<String> = m.items.iter().map(|i| i.to_string()).collect();
+        let op = match m.op {
+            Op::Add(v) => format!("+ {}", v),
+            Op::Mul(v) => format!("* {}", v),
+            Op::Square => "* old".to_string(),
+        };
+        format!(
+            "Monkey {}:\n  Starting items: {}\n  Operation: new = old {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+            m.num, items.join(", "), op, m.test, m.success, m.failure,
+        )
+    }
+
+    #[test]
+    fn test_parse_random() {
+        let mut rng = Rng::new(11);
+        for _ in 0..200 {
+            let n = 2 + rng.below(6);
+            let want: Vec<Monkey> = (0..n)
+                .map(|num| {
+                    let items = (0..rng.below(5)).map(|_| rng.range(1..100)).collect();
+                    let op = match rng.below(3) {
+                        0 => Op::Add(rng.range(0..10) as i32),
+                        1 => Op::Mul(rng.range(0..20) as i32),
+                        _ => Op::Square,
+                    };
+                    let test = rng.range(1..30);
+                    let mut other = || (num + 1 + rng.below(n - 1)) % n;
+                    Monkey::new(num, items, op, test, other(), other())
+                })
+                .collect();
+            let input = want.iter().map(format_monkey).collect::<Vec<_>>().join("\n");
+            assert_eq!(read_monkeys(input.as_bytes()).unwrap(), want);
+
+            // Damaged input either still parses or is an error, but never panics.
+            let bad = rng.mutate(&input, "0123456789:, -\n");
+            if let Ok(monkeys) = read_monkeys(bad.as_bytes()) {
+                assert_eq!(monkeys.len(), n, "{bad}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_monkeys_errors() {
+        let input = EXAMPLE.replace("throw to monkey 3", "throw to monkey 4");
+        assert_eq!(read_monkeys(input.as_bytes()).unwrap_err(), "monkey 0 throws to monkey 4");
+        let input = EXAMPLE.replace("Monkey 1:", "Monkey 5:");
+        assert_eq!(read_monkeys(input.as_bytes()).unwrap_err(), "expected monkey 1, got 5");
+        let input = EXAMPLE.replace("divisible by 23", "divisible by 0");
+        assert!(read_monkeys(input.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 10605);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 2713310158);
+    }
+}
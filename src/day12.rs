@@ -0,0 +1,195 @@
+#![allow(dead_code)]  // TODO
+
+use std::io::BufRead;
+
+use aoclib::geom::Point2;
+use aoclib::graph;
+use aoclib::grid::Grid;
+use crate::error::AocError;
+
+type Point = Point2<i32>;
+
+struct Map {
+    heights: Grid<u8>,
+    cols: i32,
+    rows: i32,
+    start: Point,
+    goal: Point,
+}
+
+impl Map {
+    pub fn from_lines<T: BufRead>(r: T) -> Result<Self, String> {
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        let mut start: Option<Point> = None;
+        let mut goal: Option<Point> = None;
+        for (i, line) in r.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            let mut data: Vec<u8> = Vec::new();
+            for (j, b) in line.as_bytes().iter().enumerate() {
+                match *b as char {
+                    'a'..='z' => data.push(Self::height(*b as char)),
+                    'S' => {
+                        if start.is_some() {
+                            return Err("multiple start points found".to_string());
+                        }
+                        start = Some(Point::new(i as i32, j as i32));
+                        data.push(Self::height('a'));
+                    },
+                    'E' => {
+                        if goal.is_some() {
+                            return Err("multiple goal points found".to_string());
+                        }
+                        goal = Some(Point::new(j as i32, i as i32));
+                        data.push(Self::height('z'));
+                    }
+                    '\n' | '\r' => (),
+                    _ => return Err(format!("unexpected char: {}", *b as char)),
+                }
+            }
+            rows.push(data);
+        }
+        if rows.is_empty() {
+            return Err("no lines read".to_string());
+        }
+        let heights = Grid::from_rows(rows).map_err(|e| format!("mismatched line length: {}", e))?;
+        let (cols, rows) = (heights.width() as i32, heights.height() as i32);
+
+        match (start, goal) {
+            (Some(start), Some(goal)) => Ok(Map { heights, start, cols, rows, goal }),
+            (None, _) => Err("no start point found".to_string()),
+            (_, None) => Err("no goal point found".to_string()),
+        }
+    }
+
+    pub fn height(c: char) -> u8 {
+        let offset = match c {
+            'a'..='z' => c as u8,
+            'S' => b'a',
+            'E' => b'z',
+            _ => panic!("unexpected character: {}", c),
+        };
+        offset - b'a'
+    }
+
+    pub fn at(&self, p: &Point) -> u8 {
+        self.heights[(p.x as usize, p.y as usize)]
+    }
+
+    // The points reachable in one move: at most one higher, but any amount lower.
+    fn moves(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        neighbors(p, self.rows, self.cols).filter(move |n| self.at(n) <= self.at(&p) + 1)
+    }
+
+    pub fn min_moves_to_goal(&self, start: Point) -> Option<u32> {
+        let goal = self.goal;
+        let moves = |&p: &Point| self.moves(p).map(|n| (n, 1));
+        let (n, _) = graph::astar(start, moves, |p| p.manhattan(goal) as u32, |&p| p == goal)?;
+        Some(n)
+    }
+}
+
+// The orthogonal neighbors of p that are on a map with the given size.
+fn neighbors(p: Point, rows: i32, cols: i32) -> impl Iterator<Item = Point> {
+    p.neighbors4().filter(move |n| (0..cols).contains(&n.x) && (0..rows).contains(&n.y))
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<u32, AocError> {
+    let map = Map::from_lines(r)?;
+    map.min_moves_to_goal(map.start).ok_or_else(|| AocError::NoSolution("no path to goal found".to_string()))
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<u32, AocError> {
+    let map = Map::from_lines(r)?;
+    // Search from every lowest point at once.
+    let starts = map.heights.cells()
+        .filter(|&(_, &h)| h == 0)
+        .map(|((x, y), _)| Point::new(x as i32, y as i32));
+    let dist = graph::bfs(starts, |&p| map.moves(p));
+    dist.get(&map.goal)
+        .map(|&d| d as u32)
+        .ok_or_else(|| AocError::NoSolution("no paths to the goal were found".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
+
+    fn map() -> Map {
+        Map::from_lines(EXAMPLE.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 31);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 29);
+    }
+
+    #[test]
+    fn test_map_from_lines() {
+        let map = Map::from_lines(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(&map.start, &Point::new(0, 0));
+        assert_eq!(&map.goal, &Point::new(5, 2));
+        assert_eq!(map.heights.iter().last(), Some(&8));
+        assert_eq!(map.cols, 8);
+        assert_eq!(map.rows, 5);
+    }
+
+    #[test]
+    fn test_at() {
+        let map = map();
+        assert_eq!(map.at(&Point::new(4, 1)), Map::height('y'));
+    }
+
+    #[test]
+    fn test_neighbors_upper_left() {
+        let mut it = neighbors(Point::new(0, 0), 2, 2);
+        assert_eq!(it.next(), Some(Point::new(1, 0)));
+        assert_eq!(it.next(), Some(Point::new(0, 1)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_neighbors_upper_right() {
+        let mut it = neighbors(Point::new(1, 0), 2, 2);
+        assert_eq!(it.next(), Some(Point::new(1, 1)));
+        assert_eq!(it.next(), Some(Point::new(0, 0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_neighbors_bottom_right() {
+        let mut it = neighbors(Point::new(1, 1), 2, 2);
+        assert_eq!(it.next(), Some(Point::new(1, 0)));
+        assert_eq!(it.next(), Some(Point::new(0, 1)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_neighbors_bottom_left() {
+        let mut it = neighbors(Point::new(0, 1), 2, 2);
+        assert_eq!(it.next(), Some(Point::new(0, 0)));
+        assert_eq!(it.next(), Some(Point::new(1, 1)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_neighbors_middle() {
+        let mut it = neighbors(Point::new(1, 1), 3, 3);
+        assert_eq!(it.next(), Some(Point::new(1, 0)));
+        assert_eq!(it.next(), Some(Point::new(2, 1)));
+        assert_eq!(it.next(), Some(Point::new(1, 2)));
+        assert_eq!(it.next(), Some(Point::new(0, 1)));
+        assert_eq!(it.next(), None);
+    }
+}
@@ -0,0 +1,371 @@
+use std::cmp::Ordering;
+use std::io::BufRead;
+use std::io;
+
+use crate::error::AocError;
+
+struct NestedList<T>
+where
+    T: Iterator<Item=u8>,
+{
+    stack: Vec<ListItem>,
+    bytes: T,
+    peeked: Option<u8>,
+}
+
+enum ListItem {
+    Int(i32),
+    ListStart,
+    ListEnd,
+}
+
+impl<T> NestedList<T>
+where
+    T: Iterator<Item=u8>,
+{
+    fn new(bytes: T) -> Self {
+        Self { stack: Vec::new(), bytes, peeked: None }
+    }
+
+    fn push(&mut self, item: ListItem) {
+        self.stack.push(item);
+    }
+
+    fn next_byte(&mut self) -> Option<u8> {
+        self.peeked.take().or_else(|| self.bytes.next())
+    }
+}
+
+fn nested_list(s: &str) -> NestedList<impl Iterator<Item=u8> + '_> {
+    NestedList::new(s.bytes())
+}
+
+impl<T> Iterator for NestedList<T>
+where
+    T: Iterator<Item=u8>,
+{
+    type Item = ListItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.stack.pop() {
+            return Some(item);
+        }
+
+        loop {
+            match self.next_byte() {
+                Some(b'[') => {
+                    return Some(ListItem::ListStart);
+                },
+                Some(b']') => {
+                    return Some(ListItem::ListEnd);
+                },
+                Some(c) if c.is_ascii_digit() => {
+                    let mut buf = String::new();
+                    buf.push(c as char);
+                    while let Some(c) = self.next_byte() {
+                        if !c.is_ascii_digit() {
+                            self.peeked = Some(c);
+                            break;
+                        }
+                        buf.push(c as char);
+                    }
+                    return Some(ListItem::Int(buf.parse::<i32>().unwrap()));
+                },
+                Some(b',') => continue,
+                Some(c) => panic!("unexpected byte: {}", c as char),
+                None => return None,
+            };
+        }
+    }
+}
+
+struct PacketPair<T> 
+where
+    T: Iterator<Item=io::Result<String>>,
+{
+    lines: T,
+}
+
+impl<T> PacketPair<T>
+where
+    T: Iterator<Item=io::Result<String>>,
+{
+    fn new(lines: T) -> Self {
+        PacketPair { lines }
+    }
+}
+
+impl<T> Iterator for PacketPair<T>
+where
+    T: Iterator<Item=io::Result<String>>,
+{
+    type Item = Result<(String, String), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lines: Result<Vec<_>, _> = (&mut self.lines).take(3).collect();
+        match lines {
+            Err(e) => Some(Err(e.to_string())),
+            Ok(lines) => {
+                let mut lines = lines.into_iter().filter(|l| !l.is_empty());
+                let a = lines.next();
+                let b = lines.next();
+                match (a, b) {
+                    (Some(a), Some(b)) => Some(Ok((a, b))),
+                    (None, None) => None,
+                    _ => Some(Err("input should contain blank-line-delimited groups of 2 lines".to_string())),
+                }
+            }
+        }
+    }
+}
+
+// Check that s is a well-formed packet, since NestedList and cmp_lists assume it is.
+fn check_packet(s: &str) -> Result<(), String> {
+    let mut depth = 0;
+    let mut prev = b'\0';
+    let mut ndigits = 0;
+    for (i, c) in s.bytes().enumerate() {
+        ndigits = if c.is_ascii_digit() { ndigits + 1 } else { 0 };
+        let ok = (i == 0 || depth > 0) && match c {
+            b'[' => i == 0 || matches!(prev, b'[' | b','),
+            b']' => prev != b',',
+            b',' => prev == b']' || prev.is_ascii_digit(),
+            b'0'..=b'9' => ndigits < 10 && (matches!(prev, b'[' | b',') || prev.is_ascii_digit()),
+            _ => false,
+        };
+        if !ok {
+            return Err(format!("unexpected {:?} at byte {} of packet: {}", c as char, i, s));
+        }
+        match c {
+            b'[' => depth += 1,
+            b']' => depth -= 1,
+            _ => (),
+        }
+        prev = c;
+    }
+    if s.is_empty() || depth != 0 {
+        return Err(format!("unbalanced packet: {}", s));
+    }
+    Ok(())
+}
+
+#[allow(unused)]
+fn cmp_lists<T>(mut a: NestedList<T>, mut b: NestedList<T>) -> Ordering
+where
+    T: Iterator<Item=u8>,
+{
+    use ListItem::*;
+    loop {
+        let (Some(cur_a), Some(cur_b)) = (a.next(), b.next()) else {
+            // Both packets ended together, so they're the same.
+            return Ordering::Equal;
+        };
+        match (cur_a, cur_b) {
+            (ListStart, ListStart) => (),
+            (ListEnd, ListEnd) => (),
+            (ListEnd, _) => return Ordering::Less,
+            (_, ListEnd) => return Ordering::Greater,
+            (Int(a), Int(b)) => {
+                if a != b {
+                    return a.cmp(&b);
+                }
+            },
+            (Int(a_int), ListStart) => {
+                a.push(ListEnd);
+                a.push(Int(a_int));
+                return cmp_lists(a, b);
+            },
+            (ListStart, Int(b_int)) => {
+                b.push(ListEnd);
+                b.push(Int(b_int));
+                return cmp_lists(a, b);
+            },
+        }
+    }
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<u32, AocError> {
+    let sum: Result<u32, String> = PacketPair::new(r.lines())
+        .enumerate()
+        .map(|(i, r)| {
+            let (a, b) = r?;
+            check_packet(&a)?;
+            check_packet(&b)?;
+            if cmp_lists(nested_list(&a), nested_list(&b)) == Ordering::Less {
+                Ok((i + 1) as u32)
+            } else {
+                Ok(0)
+            }
+        })
+        .sum();
+    Ok(sum?)
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<usize, AocError> {
+    let lines: Vec<String> = r.lines().collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut lines: Vec<String> = lines.into_iter().filter(|l| !l.is_empty()).collect();
+    for line in &lines {
+        check_packet(line)?;
+    }
+    let div1 = "[[2]]".to_string();
+    let div2 = "[[6]]".to_string();
+    lines.push(div1.clone());
+    lines.push(div2.clone());
+    lines.sort_by(|a, b| cmp_lists(nested_list(a), nested_list(b)));
+
+    let div1_index = lines.iter()
+        .position(|x| x == &div1)
+        .ok_or("divider packet 1 not found".to_string())?;
+    let div2_index = lines.iter()
+        .position(|x| x == &div2)
+        .ok_or("divider packet 2 not found".to_string())?;
+    Ok((div1_index + 1) * (div2_index + 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]";
+
+    #[test]
+    fn test_cmp_lists() {
+        let a = NestedList::new("[1,2]".bytes());
+        let b = NestedList::new("[1,[2],3]".bytes());
+        assert_eq!(cmp_lists(a, b), Ordering::Less);
+    }
+
+    #[test]
+    fn test_cmp_lists_long() {
+        let a_str = "[[10,[0,7,[],3,[1,6]],[[2,4,5,4]],[]],[],[6,6,[[2,6,7],7,[5],[8,4,10,4,8],[0]],[10],[]],[[[],[6,0,9,10,2],8,[0]]]]";
+        let b_str = "[[[6]],[[3],[[]],[[0,6,8,9,5],[7,9,10,2]]],[],[[[1],[9],5],9,[[],[0],5,1,[5,0]],5]]";
+        let a = NestedList::new(a_str.bytes());
+        let b = NestedList::new(b_str.bytes());
+        assert_eq!(cmp_lists(a, b), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_cmp_lists_multi_promotion() {
+        let a = NestedList::new("[[3]]".bytes());
+        let b = NestedList::new("[[[[],[]]]]".bytes());
+        assert_eq!(cmp_lists(a, b), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_cmp_lists_multi_promotion_long() {
+        let a = NestedList::new("[[3,2,4],[1,[2,3,[5,1,8],7,9]],[[4,[]]]]".bytes());
+        let b = NestedList::new("[[[[],[],6],3]]".bytes());
+        assert_eq!(cmp_lists(a, b), Ordering::Greater);
+    }
+
+    // A packet as a tree, compared the way the puzzle describes, to check cmp_lists against.
+    #[derive(PartialEq, Eq)]
+    enum Packet {
+        Int(i32),
+        List(Vec<Packet>),
+    }
+
+    impl Ord for Packet {
+        fn cmp(&self, other: &Self) -> Ordering {
+            match (self, other) {
+                (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+                (Packet::List(a), Packet::List(b)) => a.cmp(b),
+                (Packet::Int(a), b) => Packet::List(vec![Packet::Int(*a)]).cmp(b),
+                (a, Packet::Int(b)) => a.cmp(&Packet::List(vec![Packet::Int(*b)])),
+            }
+        }
+    }
+
+    impl PartialOrd for Packet {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl std::fmt::Display for Packet {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                Packet::Int(n) => write!(f, "{}", n),
+                Packet::List(items) => {
+                    let items: Vec<String> = items.iter().map(|p| p.to_string()).collect();
+                    write!(f, "[{}]", items.join(","))
+                },
+            }
+        }
+    }
+
+    fn random_packet(rng: &mut Rng, depth: usize) -> Packet {
+        let len = rng.below(5);
+        Packet::List((0..len)
+            .map(|_| match depth < 3 && rng.one_in(3) {
+                true => random_packet(rng, depth + 1),
+                false => Packet::Int(rng.below(11) as i32),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_cmp_lists_random() {
+        let mut rng = Rng::new(13);
+        for _ in 0..500 {
+            let (a, b) = (random_packet(&mut rng, 0), random_packet(&mut rng, 0));
+            let (a_str, b_str) = (a.to_string(), b.to_string());
+            assert_eq!(check_packet(&a_str), Ok(()));
+            let got = cmp_lists(nested_list(&a_str), nested_list(&b_str));
+            assert_eq!(got, a.cmp(&b), "{a_str} vs {b_str}");
+            assert_eq!(cmp_lists(nested_list(&a_str), nested_list(&a_str)), Ordering::Equal);
+
+            // Damaged packets are either still well-formed or rejected, but never panic.
+            let bad = rng.mutate(&a_str, "[],0123456789x");
+            if check_packet(&bad).is_ok() {
+                _ = cmp_lists(nested_list(&bad), nested_list(&b_str));
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_packet() {
+        for packet in ["[]", "[[]]", "[1,[2,[]],10]"] {
+            assert_eq!(check_packet(packet), Ok(()), "{packet}");
+        }
+        for packet in ["", "1", "[", "]", "[1,]", "[,1]", "[1][2]", "[1 2]", "[[1]2]", "[99999999999]"] {
+            assert!(check_packet(packet).is_err(), "{packet:?}");
+        }
+        assert!(part1("[1]\n[x]\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 13);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 140);
+    }
+}
@@ -0,0 +1,444 @@
+use std::io::BufRead;
+use std::fmt;
+use std::ops::Range;
+use std::cmp;
+
+use aoclib::geom::Point2;
+use aoclib::grid::Grid;
+use aoclib::render::{Color, Glyph, NoRender, Scene, Sink};
+use aoclib::scan::Scanner;
+use crate::error::AocError;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Material {
+    Air, Rock, Sand,
+}
+
+#[derive(Debug)]
+enum FinalPosition {
+    Rest(Point),
+    Abyss,
+}
+
+struct Array2D {
+    cave: Grid<Material>,
+    bottom_row: usize,
+}
+
+type Point = Point2<usize>;
+
+// Where sand at p tries to fall to, in order of preference: down, down-left, then down-right.
+fn falls(p: Point) -> [Point; 3] {
+    [0, -1, 1].map(|dx| p.offset(dx, 1).expect("sand fell off the left edge"))
+}
+
+impl Array2D {
+    fn new(cols: usize) -> Self {
+        let rows = 200;
+        let cave = Grid::new(cols, rows, Material::Air).with_origin(500 - cols / 2, 0);
+        Array2D { cave, bottom_row: 0 }
+    }
+
+    fn bottom_row(&self) -> usize {
+        self.cave.iter()
+            .rposition(|&m| m == Material::Rock)
+            .map(|i| self.cave.position(i).1)
+            .unwrap()
+    }
+
+    fn drop_sand(&mut self) -> FinalPosition {
+        let final_pos = self.final_sand_pos(Point::new(500, 0));
+        if let FinalPosition::Rest(p) = final_pos {
+            self.set(&p, Material::Sand);
+        }
+        final_pos
+    }
+
+    fn final_sand_pos(&self, start: Point) -> FinalPosition {
+        let mut cur = start;
+        loop {
+            let prev = cur;
+            cur = self.next_sand_pos(cur);
+            if cur == prev {
+                return FinalPosition::Rest(cur);
+            } else if cur.y >= self.bottom_row() {
+                return FinalPosition::Abyss;
+            }
+        }
+    }
+
+    fn next_sand_pos(&self, p: Point) -> Point {
+        falls(p).into_iter().find(|q| self.get(q) == Material::Air).unwrap_or(p)
+    }
+
+    fn read<T: BufRead>(r: T, cols: usize) -> Result<Self, String> {
+        let mut array = Array2D::new(cols);
+        for line in r.lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            for pair in PointPairs::new(&line) {
+                let (p1, p2) = pair?;
+                if let Some(p) = [p1, p2].into_iter().find(|p| !array.contains(p)) {
+                    return Err(format!("point outside the cave: {p}"));
+                }
+                if p1.x != p2.x && p1.y != p2.y {
+                    return Err(format!("diagonal line: {p1} -> {p2}"));
+                }
+                array.set_line(p1, p2, Material::Rock);
+            }
+        }
+        if !array.cave.iter().any(|&m| m == Material::Rock) {
+            return Err("no rock paths".to_string());
+        }
+        array.bottom_row = array.bottom_row();
+        Ok(array)
+    }
+
+    fn contains(&self, p: &Point) -> bool {
+        self.cave.contains(p.x, p.y)
+    }
+
+    pub fn col_range(&self) -> Range<usize> {
+        self.cave.xs()
+    }
+
+    pub fn get(&self, p: &Point) -> Material {
+        self.cave[(p.x, p.y)]
+    }
+
+    fn point(&self, i: usize) -> Point {
+        let (x, y) = self.cave.position(i);
+        Point::new(x, y)
+    }
+
+    pub fn set(&mut self, p: &Point, m: Material) {
+        self.cave.set(p.x, p.y, m)
+    }
+
+    pub fn set_line(&mut self, p1: Point, p2: Point, m: Material) {
+        let Point { x: x1, y: y1 } = p1;
+        let Point { x: x2, y: y2 } = p2;
+        if x1 == x2 {
+            for y in cmp::min(y1, y2)..=cmp::max(y1, y2) {
+                self.set(&Point::new(x1, y), m);
+            }
+        } else if y1 == y2 {
+            for x in cmp::min(x1, x2)..=cmp::max(x1, x2) {
+                self.set(&Point::new(x, y1), m);
+            }
+        } else {
+            panic!("the line p1 -> p2 should not be diagonal");
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item=&[Material]> + '_ {
+        self.cave.rows()
+    }
+
+    fn active_box(&self) -> (Point, Point) {
+        let start_col: Option<usize> = self.rows()
+            .filter_map(|row| row.iter().position(|&m| m != Material::Air))
+            .min();
+        let end_col: Option<usize> = self.rows()
+            .filter_map(|row| row.iter().rposition(|&m| m != Material::Air))
+            .max();
+        let start_row: Option<usize> = self.cave.iter()
+            .position(|&m| m != Material::Air)
+            .map(|i| self.point(i).y);
+        let end_row: Option<usize> = self.cave.iter()
+            .rposition(|&m| m != Material::Air)
+            .map(|i| self.point(i).y);
+        if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (start_col, start_row, end_col, end_row) {
+            // Add 1 to the end row/col so the range is half-open, so that we can represent an
+            // empty active box.
+            (
+                Point::new(x1 + self.cave.xs().start, y1),
+                Point::new(x2 + self.cave.xs().start + 1, y2 + 1)
+            )
+        } else {
+            let p = Point::new(self.cave.xs().start, 0);
+            (p, p)
+        }
+    }
+}
+
+impl fmt::Display for Array2D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (p1, p2) = self.active_box();
+        if p1 == p2 {
+            writeln!(f, "empty")?;
+            return Ok(());
+        }
+
+        write!(f, "{:4}", " ")?;  // Skip past row headings.
+        for i in (p1.x)..(p2.x) {
+            if i % 10 == 0 {
+                write!(f, "|{:<9}", i)?;
+            }
+        }
+        writeln!(f)?;
+        for row in (p1.y)..(p2.y) {
+            write!(f, "{:3} ", row)?;
+            for col in (p1.x)..(p2.x) {
+                let c = match self.get(&Point::new(col, row)) {
+                    Material::Air => '.',
+                    Material::Rock => '#',
+                    Material::Sand => 'o',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scene for Array2D {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        let (p1, p2) = self.active_box();
+        (p1.x as i64..p2.x as i64, p1.y as i64..p2.y as i64)
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        match self.get(&Point::new(x as usize, y as usize)) {
+            Material::Air => '.'.into(),
+            Material::Rock => Glyph::colored('#', Color::Gray),
+            Material::Sand => Glyph::colored('o', Color::Yellow),
+        }
+    }
+}
+
+struct PointPairs<'a> {
+    scanner: Scanner<'a>,
+    p1: Option<Point>,
+}
+
+impl<'a> PointPairs<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self { scanner: Scanner::new(s), p1: None }
+    }
+
+    fn parse_point(&mut self) -> Result<Point, String> {
+        let x = self.scanner.int()?;
+        self.scanner.expect(",")?;
+        let y = self.scanner.int()?;
+        self.scanner.eat(" -> ");
+        Ok(Point::new(x, y))
+    }
+}
+
+impl Iterator for PointPairs<'_> {
+    type Item = Result<(Point, Point), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.scanner.is_done() {
+            return None;
+        }
+        let pair = match self.p1 {
+            Some(p1) => self.parse_point().map(|p2| (p1, p2)),
+            None => self.parse_point().and_then(|p1| Ok((p1, self.parse_point()?))),
+        };
+        match pair {
+            Ok((_, p2)) => self.p1 = Some(p2),
+            Err(_) => self.scanner.skip_rest(),  // Stop after an error.
+        }
+        Some(pair)
+    }
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<usize, AocError> {
+    part1_render(r, &mut NoRender)
+}
+
+// Send the cave to `sink` each time a grain of sand comes to rest.
+pub fn part1_render<T: BufRead>(r: T, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let mut array = Array2D::read(r, 200)?;
+    let mut i: usize = 0;
+    loop {
+        match array.drop_sand() {
+            FinalPosition::Rest(_) => i +=1,
+            FinalPosition::Abyss => return Ok(i),
+        };
+        sink.frame(format_args!("== Grain {i} =="), &array)?;
+    }
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<usize, AocError> {
+    part2_render(r, &mut NoRender)
+}
+
+// Send the cave to `sink` each time a grain of sand comes to rest.
+pub fn part2_render<T: BufRead>(r: T, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let mut array = part2_array(r)?;
+    let mut i: usize = 0;
+    let sand_start = Point::new(500, 0);
+    loop {
+        let final_pos = array.drop_sand();
+        if let FinalPosition::Rest(_) = final_pos {
+            i += 1;
+            sink.frame(format_args!("== Grain {i} =="), &array)?;
+        }
+        match final_pos {
+            // Include this last bit of sand in the result.
+            FinalPosition::Rest(p) if p == sand_start => return Ok(i),
+            FinalPosition::Rest(_) => (),
+            FinalPosition::Abyss => panic!("sand should not go into the Abyss during part2"),
+        }
+    }
+}
+
+fn part2_array<T: BufRead>(r: T) -> Result<Array2D, String> {
+    let mut array = Array2D::read(r, 400)?;
+    let Range { start: first_col, end: last_col } = array.col_range();
+    let row = array.bottom_row + 2;
+    array.bottom_row = row;
+    let p1 = Point::new(first_col, row);
+    let p2 = Point::new(last_col - 1, row);
+    array.set_line(p1, p2, Material::Rock);
+    Ok(array)
+}
+
+// Use DFS to find all the points that sand can rest instead of simulating every move.
+pub fn part2_fast<T: BufRead>(r: T) -> Result<usize, AocError> {
+    part2_fast_render(r, &mut NoRender)
+}
+
+// The sand doesn't fall in order here, so only the filled cave is sent to `sink`.
+pub fn part2_fast_render<T: BufRead>(r: T, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let mut array = part2_array(r)?;
+    let mut count = 0;
+    let mut unvisited: Vec<Point> = Vec::new();
+    unvisited.push(Point::new(500, 0));
+    while let Some(p) = unvisited.pop() {
+        count += 1;
+        array.set(&p, Material::Sand);
+        for child in falls(p) {
+            if array.get(&child) == Material::Air {
+                unvisited.push(child);
+            }
+        }
+    }
+    sink.frame(format_args!("== {count} grains =="), &array)?;
+    Ok(count)
+}
+
+pub fn print<T: BufRead>(r: T) -> Result<(), AocError> {
+    let array = Array2D::read(r, 200)?;
+    println!("{array}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 24);
+    }
+
+    // Keeps the number of frames and a drawing of the last one.
+    #[derive(Default)]
+    struct LastFrame(usize, String);
+
+    impl Sink for LastFrame {
+        fn frame(&mut self, _: fmt::Arguments, scene: &dyn Scene) -> std::io::Result<()> {
+            *self = LastFrame(self.0 + 1, draw(scene, Viewport::UNLIMITED, false));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_part1_render() {
+        let mut sink = LastFrame::default();
+        assert_eq!(part1_render(EXAMPLE.as_bytes(), &mut sink).unwrap(), 24);
+        assert_eq!(sink.0, 24);
+        // From the puzzle description, without the rows above the sand.
+        assert_eq!(sink.1, "\
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+");
+    }
+
+    #[test] #[ignore]  // Ignore: kinda slow.
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 93);
+    }
+
+    #[test]
+    fn test_part2_fast() {
+        assert_eq!(part2_fast(EXAMPLE.as_bytes()).unwrap(), 93);
+    }
+
+    // A random rock path, as its points, and the line describing it.
+    fn random_path(rng: &mut Rng) -> (Vec<Point>, String) {
+        let mut p = Point::new(rng.range(420..580) as usize, rng.range(1..180) as usize);
+        let mut points = vec![p];
+        for _ in 0..1 + rng.below(4) {
+            let len = rng.range(0..15);
+            let (x, y) = (p.x as i64, p.y as i64);
+            let (x, y) = match rng.below(4) {
+                0 => (x + len, y),
+                1 => (x - len, y),
+                2 => (x, y + len),
+                _ => (x, y - len),
+            };
+            p = Point::new(x.clamp(420, 579) as usize, y.clamp(0, 179) as usize);
+            points.push(p);
+        }
+        let line = points.iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        (points, line)
+    }
+
+    #[test]
+    fn test_read_random() {
+        let mut rng = Rng::new(14);
+        for _ in 0..200 {
+            let (paths, lines): (Vec<_>, Vec<_>) = (0..1 + rng.below(4))
+                .map(|_| random_path(&mut rng))
+                .unzip();
+            let input = lines.join("\n");
+            let array = Array2D::read(input.as_bytes(), 200).unwrap();
+
+            let mut want = std::collections::HashSet::new();
+            for path in &paths {
+                for w in path.windows(2) {
+                    for x in w[0].x.min(w[1].x)..=w[0].x.max(w[1].x) {
+                        for y in w[0].y.min(w[1].y)..=w[0].y.max(w[1].y) {
+                            want.insert((x, y));
+                        }
+                    }
+                }
+            }
+            let rock = array.cave.iter().filter(|&&m| m == Material::Rock).count();
+            assert_eq!(rock, want.len(), "{input}");
+            assert!(want.iter().all(|&(x, y)| array.get(&Point::new(x, y)) == Material::Rock));
+            assert_eq!(array.bottom_row, want.iter().map(|&(_, y)| y).max().unwrap());
+
+            // Damaged input either still parses or is an error, but never panics.
+            let bad = rng.mutate(&input, "0123456789,-> \n");
+            _ = Array2D::read(bad.as_bytes(), 200);
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        for input in ["", "498,4 -> 496,6", "498,4 ->", "498,4 -> 498", "x", "1000,4 -> 1000,6"] {
+            assert!(Array2D::read(input.as_bytes(), 200).is_err(), "{input:?}");
+        }
+    }
+}
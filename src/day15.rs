@@ -0,0 +1,252 @@
+use std::io::BufRead;
+use std::collections::HashSet;
+
+use aoclib::geom::Point2;
+use aoclib::intervals::{DisjointIntervals, Interval};
+use aoclib::scan::Scanner;
+use aoclib::simd::{self, Diamonds};
+use crate::error::AocError;
+
+type Point = Point2<i64>;
+
+#[derive(PartialEq, Debug)]
+struct Pair {
+    sensor: Point,
+    beacon: Point,
+}
+
+impl Pair {
+    pub fn from_coords(sensor_x: i64, sensor_y: i64, beacon_x: i64, beacon_y: i64) -> Self {
+        Self {
+            sensor: Point::new(sensor_x, sensor_y),
+            beacon: Point::new(beacon_x, beacon_y),
+        }
+    }
+
+    pub fn distance_to_beacon(&self) -> u64 {
+        self.sensor.manhattan(self.beacon)
+    }
+
+    pub fn range_covered_at_row(&self, row: i64) -> Option<Interval<i64>> {
+        let ydist = self.sensor.y.abs_diff(row);
+        let beacon_dist = self.distance_to_beacon();
+        if ydist > beacon_dist {
+            return None;
+        }
+        let xlen_at_row: i64 = (beacon_dist - ydist) as i64;
+        let mid = self.sensor.x;
+        Some(Interval::new(mid - xlen_at_row, mid + xlen_at_row))
+    }
+}
+
+pub fn part1(r: impl BufRead, row: i64) -> Result<u64, AocError> {
+    let pairs = read_pairs(r)?;
+    let covered_spots = covered_at_row(&pairs, row).covered_len() as u64;
+    let beacon_spots: u64 = pairs.iter()
+        .map(|p| &p.beacon)
+        .filter(|b| b.y == row)
+        .collect::<HashSet<_>>()
+        .len().try_into().unwrap();
+    Ok(covered_spots - beacon_spots)
+}
+
+pub fn part2(r: impl BufRead, x_max: i64, y_max: i64) -> Result<i64, AocError> {
+    let pairs = read_pairs(r)?;
+    let Some(p) = first_uncovered_point(&pairs, x_max, y_max) else {
+        return Err(AocError::NoSolution("no uncovered point found".to_string()));
+    };
+    let tuning_freq = 4000000 * p.x + p.y;
+    Ok(tuning_freq)
+}
+
+fn read_pairs(r: impl BufRead) -> Result<Vec<Pair>, AocError> {
+    let mut pairs: Vec<Pair> = Vec::new();
+    for (i, line) in r.lines().enumerate() {
+        let line = line?;
+        let pair = parse_pair(&line).map_err(|e| format!("line {}: {e}", i + 1))?;
+        pairs.push(pair);
+    }
+    Ok(pairs)
+}
+
+// eg: Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+fn parse_pair(line: &str) -> Result<Pair, String> {
+    let mut s = Scanner::new(line.trim_end());
+    s.expect("Sensor at x=")?;
+    let sensor_x = s.int()?;
+    s.expect(", y=")?;
+    let sensor_y = s.int()?;
+    s.expect(": closest beacon is at x=")?;
+    let beacon_x = s.int()?;
+    s.expect(", y=")?;
+    let beacon_y = s.int()?;
+    s.end()?;
+    Ok(Pair::from_coords(sensor_x, sensor_y, beacon_x, beacon_y))
+}
+
+fn covered_at_row(pairs: &[Pair], row: i64) -> DisjointIntervals<i64> {
+    pairs.iter().filter_map(|p| p.range_covered_at_row(row)).map(|i| i.half_open()).collect()
+}
+
+fn first_uncovered_point(pairs: &[Pair], x_max: i64, y_max: i64) -> Option<Point> {
+    if let Some(p) = first_uncovered_point_i32(pairs, x_max, y_max) {
+        return p;
+    }
+    for row in 0..y_max {
+        let covered = covered_at_row(pairs, row);
+        if let Some(x) = first_uncovered_x(&covered, x_max) {
+            return Some(Point::new(x, row));
+        }
+    }
+    None
+}
+
+// Like first_uncovered_point, but with the sensors' coverage worked out a few at a time in 32-bit
+// lanes, and clipped to the search area. Returns None if the coordinates don't fit.
+fn first_uncovered_point_i32(pairs: &[Pair], x_max: i64, y_max: i64) -> Option<Option<Point>> {
+    let mut diamonds = Diamonds::new();
+    for p in pairs {
+        diamonds.push(p.sensor.x, p.sensor.y, p.distance_to_beacon() as i64).ok()?;
+    }
+    let (x_end, y_max) = (i32::try_from(x_max + 1).ok()?, i32::try_from(y_max).ok()?);
+    let mut spans = Vec::with_capacity(pairs.len());
+    for row in 0..y_max {
+        spans.clear();
+        simd::row_spans(&diamonds, row, 0..x_end, &mut spans);
+        let covered: DisjointIntervals<i64> = spans.iter().map(|r| r.start as i64..r.end as i64).collect();
+        if let Some(x) = first_uncovered_x(&covered, x_max) {
+            return Some(Some(Point::new(x, row as i64)));
+        }
+    }
+    Some(None)
+}
+
+fn first_uncovered_x(covered: &DisjointIntervals<i64>, max: i64) -> Option<i64> {
+    covered.gaps(0..max + 1).first().map(|gap| gap.start)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    #[test]
+    fn test_read_pairs() {
+        let pairs = read_pairs(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(pairs.len(), 14);
+        assert_eq!(pairs[0], Pair::from_coords(2, 18, -2, 15))
+    }
+
+    #[test]
+    fn test_read_pairs_random() {
+        let mut rng = Rng::new(15);
+        for _ in 0..200 {
+            let want: Vec<Pair> = (0..1 + rng.below(10))
+                .map(|_| {
+                    let mut coord = || rng.range(-4_000_000..4_000_000);
+                    Pair::from_coords(coord(), coord(), coord(), coord())
+                })
+                .collect();
+            let input: String = want.iter()
+                .map(|p| format!(
+                    "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                    p.sensor.x, p.sensor.y, p.beacon.x, p.beacon.y,
+                ))
+                .collect();
+            assert_eq!(read_pairs(input.as_bytes()).unwrap(), want);
+
+            // Damaged input either still parses or is an error, but never panics.
+            let bad = rng.mutate(&input, "0123456789-=,: xy\n");
+            _ = read_pairs(bad.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_read_pairs_errors() {
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\nSensor at x=9, y=16: beacon at x=10, y=16\n";
+        let err = read_pairs(input.as_bytes()).unwrap_err().to_string();
+        assert!(err.starts_with("line 2: col 20: expected \": closest beacon is at x=\""), "{err}");
+        assert!(read_pairs("Sensor at x=2, y=18: closest beacon is at x=-2, y=".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_range_covered_at_row_random() {
+        let mut rng = Rng::new(15);
+        for _ in 0..200 {
+            let mut coord = || rng.range(-20..20);
+            let pair = Pair::from_coords(coord(), coord(), coord(), coord());
+            let row = coord();
+            let dist = pair.distance_to_beacon();
+            let want: Vec<i64> = (-100..100)
+                .filter(|&x| pair.sensor.manhattan(Point::new(x, row)) <= dist)
+                .collect();
+            let got: Vec<i64> = (-100..100)
+                .filter(|&x| pair.range_covered_at_row(row).is_some_and(|i| i.contains_point(x)))
+                .collect();
+            assert_eq!(got, want, "{pair:?} row {row}");
+        }
+    }
+
+    #[test]
+    fn test_range_covered_at_row() {
+        let pair = Pair::from_coords(8, 7, 2, 10);
+        assert_eq!(pair.range_covered_at_row(10), Some(Interval::new(2, 14)));
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes(), 10).unwrap(), 26);
+    }
+
+    #[test]
+    fn test_first_uncovered_x_all_covered() {
+        let covered = [-3..-2, 0..11].into_iter().collect();
+        assert_eq!(first_uncovered_x(&covered, 10), None);
+    }
+
+    #[test]
+    fn test_first_uncovered_x_at_start() {
+        let covered = [-3..-2, 1..11].into_iter().collect();
+        assert_eq!(first_uncovered_x(&covered, 10), Some(0));
+    }
+
+    #[test]
+    fn test_first_uncovered_x_at_middle() {
+        let covered = [-3..-2, 0..5, 6..11].into_iter().collect();
+        assert_eq!(first_uncovered_x(&covered, 10), Some(5));
+    }
+
+    #[test]
+    fn test_first_uncovered_x_at_end() {
+        let covered = [-3..-2, 0..10].into_iter().collect();
+        assert_eq!(first_uncovered_x(&covered, 10), Some(10));
+    }
+
+    #[test]
+    fn test_first_uncovered_point_beyond_i32() {
+        let pairs = [Pair::from_coords(1 << 40, 0, (1 << 40) + 1, 0)];
+        assert_eq!(first_uncovered_point_i32(&pairs, 3, 3), None);
+        assert_eq!(first_uncovered_point(&pairs, 3, 3), Some(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes(), 20, 20).unwrap(), 56000011);
+    }
+}
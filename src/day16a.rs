@@ -0,0 +1,671 @@
+use std::cmp;
+use std::collections::{HashMap, HashSet, BinaryHeap};
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use aoclib::checkpoint::{self, Checkpoint, Checkpointer, Record};
+use aoclib::graph::{AllPairs, DenseGraph};
+use aoclib::memstats::{bytes_of, hash_set_bytes, MemStats};
+use aoclib::parallel::{self, Search};
+use aoclib::progress::Progress;
+use aoclib::scan::Scanner;
+use aoclib::trace;
+
+use crate::runner::phase;
+use crate::error::AocError;
+
+const CHECKPOINT_KIND: &str = "day16a";
+
+struct StateTree {
+    states: Vec<State>,
+    start: StateHandle,
+    volcano: Volcano,
+    shortest_paths: AllPairs<usize>,
+    // Bytes allocated by the states' sets of opened valves.
+    set_bytes: usize,
+    mem: MemStats,
+}
+
+#[derive(Clone)]
+struct State {
+    parent: Option<StateHandle>,
+    room: RoomHandle,
+    choice: Choice,
+    steps_left: u8,
+    opened_valves: HashSet<RoomHandle>,
+    pressure_released: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Choice {
+    Start,
+    Move(RoomHandle),
+    OpenValve,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct StateHandle(usize);
+
+// States waiting to be expanded, highest upper bound first.
+type Queue = BinaryHeap<(usize, StateHandle)>;
+
+impl StateTree {
+    fn new(volcano: Volcano) -> Self {
+        let states = vec![State {
+            parent: None,
+            room: volcano.handle_for["AA"],
+            choice: Choice::Start,
+            steps_left: 30,
+            opened_valves: HashSet::new(),
+            pressure_released: 0,
+        }];
+
+        let shortest_paths = volcano.graph.all_pairs();
+
+        StateTree {
+            states,
+            start: StateHandle(0),
+            volcano,
+            shortest_paths,
+            set_bytes: 0,
+            mem: MemStats::default(),
+        }
+    }
+
+    fn new_state(&self, parent: StateHandle, choice: Choice) -> State {
+        State { parent: Some(parent), ..self.child(self.get(parent), choice) }
+    }
+
+    // The state after making `choice` in `parent_state`, without a link to its parent.
+    fn child(&self, parent_state: &State, choice: Choice) -> State {
+        let steps_left = parent_state.steps_left - 1;
+        State {
+            parent: None,
+            room: match choice {
+                Choice::Move(room) => room,
+                _ => parent_state.room,
+            },
+            choice,
+            steps_left,
+            opened_valves: match choice {
+                Choice::OpenValve => {
+                    let mut opened = parent_state.opened_valves.clone();
+                    opened.insert(parent_state.room);
+                    opened
+                },
+                _ => parent_state.opened_valves.clone(),
+            },
+            pressure_released: {
+                let prev = parent_state.pressure_released;
+                match choice {
+                    Choice::OpenValve => {
+                        let flow = self.volcano.flow_for[&parent_state.room];
+                        prev + flow * (steps_left as usize)
+                    },
+                    _ => prev,
+                }
+            },
+        }
+    }
+
+    fn add(&mut self, state: State) -> StateHandle {
+        self.set_bytes += hash_set_bytes(&state.opened_valves);
+        self.states.push(state);
+        StateHandle(self.states.len() - 1)
+    }
+
+    fn get(&self, sh: StateHandle) -> &State {
+        &self.states[sh.0]
+    }
+
+    fn sample_mem(&mut self, queue: &Queue) {
+        let bytes = bytes_of::<State>(self.states.capacity())
+            + self.set_bytes
+            + bytes_of::<(usize, StateHandle)>(queue.capacity());
+        self.mem.sample(self.states.len(), queue.len(), bytes);
+    }
+
+    // Return the best state and the number of states popped off the queue. How much memory the
+    // search used is left in self.mem. With a checkpointer, the search resumes from its checkpoint
+    // if there is one, and saves the queue and best state to it every so often.
+    fn branch_and_bound(&mut self, mut checkpointer: Option<&mut Checkpointer>) -> Result<(StateHandle, usize), AocError> {
+        let fingerprint = self.volcano.fingerprint();
+        let saved = match checkpointer.as_deref() {
+            Some(c) => c.load(CHECKPOINT_KIND)?,
+            None => None,
+        };
+        let (mut queue, mut best, mut nstates) = match saved {
+            Some(saved) => self.restore(&saved, fingerprint)?,
+            // The upper bound just needs to be greater than 0, since we're popping this state
+            // right away.
+            None => (BinaryHeap::from([(1, self.start)]), self.start, 0),
+        };
+        let mut best_score: usize = self.get(best).pressure_released;
+        let mut choices: Vec<Choice> = Vec::new();
+
+        let mut progress = Progress::new("day16", "best");
+        loop {
+            if let Some(c) = checkpointer.as_deref_mut() {
+                if c.due() {
+                    c.save(&self.checkpoint(fingerprint, &queue, best, nstates))?;
+                }
+            }
+            let Some((upper_bound, sh)) = queue.pop() else {
+                break;
+            };
+            nstates += 1;
+            progress.tick(nstates, best_score);
+
+            // Prune low-scoring branches, since our best score may have changed since it was
+            // pushed.
+            if upper_bound <= best_score {
+                continue;
+            }
+
+            let state = self.get(sh);
+            trace!(
+                "room={:?} {:?} steps_left={} upper={} best={} open={:?} rel={}",
+                state.room, state.choice, state.steps_left, upper_bound, best_score,
+                state.opened_valves, state.pressure_released,
+            );
+
+            // Update the best state, maybe.
+            if state.pressure_released > best_score {
+                best = sh;
+                best_score = state.pressure_released;
+            }
+
+            // We can't do anything useful at this point.
+            if state.steps_left == 1 {
+                continue;
+            }
+
+            // Queue all possible new states from this room.
+            //
+            // Save up new states and push them all at once, since otherwise it's awkward to avoid
+            // having both mutable and immutable borrows of self.
+            let parent = state.parent.map(|sh| (self.get(sh).room, self.get(sh).choice));
+            self.push_choices(state, parent, &mut choices);
+            while let Some(choice) = choices.pop() {
+                let new = self.new_state(sh, choice);
+                let upper_bound = self.upper_bound(&new);
+                if upper_bound <= best_score {
+                    continue;  // Prune low-scoring branches.
+                }
+                let new_handle = self.add(new);
+                queue.push((upper_bound, new_handle));
+            }
+            self.sample_mem(&queue);
+        }
+        progress.print(nstates, best_score);
+        if let Some(c) = checkpointer {
+            c.finish()?;
+        }
+        Ok((best, nstates))
+    }
+
+    // The search's queue and best state, with each state's room and opened valves given by name.
+    // States don't keep their parents, so a resumed search only loses a little pruning.
+    fn checkpoint(&self, fingerprint: u64, queue: &Queue, best: StateHandle, nstates: usize) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new(CHECKPOINT_KIND);
+        checkpoint.push("input", [fingerprint]);
+        checkpoint.push("nstates", [nstates]);
+        checkpoint.push("best", self.state_fields(self.get(best)));
+        for &(upper_bound, sh) in queue.iter() {
+            let fields = self.state_fields(self.get(sh));
+            checkpoint.push("state", std::iter::once(upper_bound.to_string()).chain(fields));
+        }
+        checkpoint
+    }
+
+    fn restore(&mut self, saved: &Checkpoint, fingerprint: u64) -> Result<(Queue, StateHandle, usize), String> {
+        if saved.record("input")?.field::<u64>(0)? != fingerprint {
+            return Err("checkpoint is for a different input".to_string());
+        }
+        let nstates = saved.record("nstates")?.field(0)?;
+        let best = self.parse_state(saved.record("best")?, 0)?;
+        let best = self.add(best);
+        let mut queue = BinaryHeap::new();
+        for record in saved.records("state") {
+            let state = self.parse_state(record, 1)?;
+            queue.push((record.field(0)?, self.add(state)));
+        }
+        Ok((queue, best, nstates))
+    }
+
+    // eg: 20 560 BB DD,JJ
+    fn state_fields(&self, state: &State) -> Vec<String> {
+        let mut opened: Vec<&str> = state.opened_valves.iter().map(|rh| self.volcano.name_for[rh].as_str()).collect();
+        opened.sort();
+        vec![
+            state.steps_left.to_string(),
+            state.pressure_released.to_string(),
+            self.volcano.name_for[&state.room].clone(),
+            if opened.is_empty() { "-".to_string() } else { opened.join(",") },
+        ]
+    }
+
+    // Parse the fields written by state_fields, starting at field `first`.
+    fn parse_state(&self, record: Record, first: usize) -> Result<State, String> {
+        let opened = record.str(first + 3)?;
+        let opened_valves = match opened {
+            "-" => HashSet::new(),
+            _ => opened.split(',').map(|name| self.volcano.room(name)).collect::<Result<_, _>>()?,
+        };
+        Ok(State {
+            parent: None,
+            room: self.volcano.room(record.str(first + 2)?)?,
+            choice: Choice::Start,
+            steps_left: record.field(first)?,
+            opened_valves,
+            pressure_released: record.field(first + 1)?,
+        })
+    }
+
+    // Push the choices that can be made in `state`, whose parent was in the given room after making
+    // the given choice.
+    fn push_choices(&self, state: &State, parent: Option<(RoomHandle, Choice)>, choices: &mut Vec<Choice>) {
+        if !state.opened_valves.contains(&state.room) && state.room != self.volcano.handle_for["AA"] {
+            choices.push(Choice::OpenValve);
+        }
+        for child in self.volcano.child_handles(state.room) {
+            // Don't move back to the previous room without having done anything.
+            if let Some((parent_room, parent_choice)) = parent {
+                if parent_room == child && parent_choice != Choice::OpenValve {
+                    continue;
+                }
+            }
+
+            choices.push(Choice::Move(child));
+        }
+    }
+
+    // To get an upper bound on the pressure released, we know how far closed valves are from our
+    // current locations, but it's not feasible to take the distance between those rooms into
+    // account because it's too complicated. (If it was feasible, we wouldn't need to use branch
+    // and bound.) We're using two somewhat conflicting assumptions. We're pretending that the
+    // rooms with closed valves are right next to each other in descending order of flow rate, so
+    // it only takes 2 minutes to open a valve and move to the next one, but we're also acting like
+    // we had to take the time to walk the minimum distance from the current room to a valve when
+    // calculating the pressure released from it.
+    fn upper_bound(&self, state: &State) -> usize {
+        let mut closed_valves: Vec<_> = self.volcano.flow_for
+            .iter()
+            .filter(|(rh, &flow)| flow > 0 && !state.opened_valves.contains(rh))
+            .map(|(&rh, &flow)| {
+                let min_dist = self.shortest_paths.get(rh.0 as usize, state.room.0 as usize).unwrap_or(usize::MAX);
+                (flow, min_dist)
+            })
+            .collect();
+        // Sort by flow rate, descending.
+        closed_valves.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let mut steps_left: usize = state.steps_left as usize;
+        let mut released: usize = state.pressure_released;
+        let mut closed_valves = closed_valves.iter();
+        while steps_left > 0 {
+            let Some(&(flow, min_dist)) = closed_valves.next() else {
+                break;
+            };
+
+            // Skip valves that are too far away.
+            if min_dist >= steps_left {
+                continue;
+            }
+
+            // Move to the room with the valve, if needed.
+            if min_dist > 0 {
+                steps_left -= 1;
+            }
+
+            // Open the valve, if we have time.
+            if steps_left > 0 {
+                // The max a valve can contribute is the min of:
+                // - if we went to it in order, assuming the valves are only 1 step apart, and used
+                //   the time left after going to other valves. This overestimates most when
+                //   there's a high-value valve far away, since it doesn't take their distance away
+                //   into account.
+                // - if we went to it first and used the time left after traveling directly to it.
+                //   This overestimates most when there's many high-value valves nearby, since it
+                //   doesn't take the time to close other valves into account.
+                released += cmp::min(
+                    steps_left * flow,
+                    (state.steps_left as usize - min_dist - 1) * flow
+                );
+                steps_left -= 1;
+            }
+        }
+        released
+    }
+
+    #[allow(unused)]
+    fn print_path(&self, sh: StateHandle) {
+        let mut choices: Vec<Choice> = Vec::new();
+        let mut cur: Option<StateHandle> = Some(sh);
+        while let Some(sh) = cur {
+            let state = self.get(sh);
+            choices.push(state.choice);
+            cur = state.parent;
+        }
+        choices.reverse();
+        for choice in choices.iter() {
+            match choice {
+                Choice::Start => println!("start"),
+                Choice::Move(rh) => println!("Move({})", self.volcano.name_for[rh]),
+                Choice::OpenValve => println!("OpenValve"),
+            }
+        }
+    }
+}
+
+// A state for the parallel search, which has no arena, so it carries what the pruning needs to
+// know about its parent.
+#[derive(Clone)]
+struct Node {
+    state: State,
+    parent: Option<(RoomHandle, Choice)>,
+}
+
+impl Search for StateTree {
+    type Node = Node;
+
+    fn bound(&self, node: &Node) -> i64 {
+        self.upper_bound(&node.state) as i64
+    }
+
+    fn score(&self, node: &Node) -> Option<i64> {
+        Some(node.state.pressure_released as i64)
+    }
+
+    fn expand(&self, node: &Node, children: &mut Vec<Node>) {
+        let state = &node.state;
+        // We can't do anything useful at this point.
+        if state.steps_left == 1 {
+            return;
+        }
+        let mut choices = Vec::new();
+        self.push_choices(state, node.parent, &mut choices);
+        for choice in choices {
+            children.push(Node { state: self.child(state, choice), parent: Some((state.room, state.choice)) });
+        }
+    }
+}
+
+struct Volcano {
+    graph: DenseGraph<usize>,
+    flow_for: HashMap<RoomHandle, usize>,
+    name_for: HashMap<RoomHandle, String>,
+    handle_for: HashMap<String, RoomHandle>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct RoomHandle(u8);
+
+impl fmt::Debug for RoomHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Volcano {
+    fn room(&self, name: &str) -> Result<RoomHandle, String> {
+        self.handle_for.get(name).copied().ok_or_else(|| format!("no valve named {name:?}"))
+    }
+
+    // Identifies the input, so a checkpoint isn't resumed with a different one.
+    fn fingerprint(&self) -> u64 {
+        let mut s = self.graph.to_string();
+        for i in 0..self.graph.len() {
+            let rh = RoomHandle(i as u8);
+            s += &format!("{} {}\n", self.name_for[&rh], self.flow_for.get(&rh).unwrap_or(&0));
+        }
+        checkpoint::fingerprint(&s)
+    }
+
+    #[allow(unused)]
+    fn flow(&self, name: &str) -> usize {
+        let i = self.handle_for[name];
+        self.flow_for[&i]
+    }
+
+    #[allow(unused)]
+    fn child_names(&self, name: &str) -> Vec<&str> {
+        let src = self.handle_for[name];
+        self.graph.edges(src.0 as usize)
+            .map(|(i, _)| self.name_for[&RoomHandle(i as u8)].as_str())
+            .collect::<Vec<_>>()
+    }
+
+    pub fn child_handles(&self, rh: RoomHandle) -> impl Iterator<Item=RoomHandle> + '_ {
+        self.graph.edges(rh.0 as usize)
+            .map(|(i, _)| RoomHandle(i as u8))
+    }
+
+    // Parse a line at a time, so the input isn't held in memory and a bad line is reported without
+    // reading the rest.
+    fn read(r: impl BufRead) -> Result<Self, AocError> {
+        let mut flow_for = HashMap::new();
+        let mut name_for = HashMap::new();
+        let mut handle_for: HashMap<String, RoomHandle> = HashMap::new();
+        // Tunnels are only added to the graph once the number of rooms is known.
+        let mut edges = Vec::new();
+
+        let get_handle = |name: &str, name_for: &mut HashMap<_, _>, handle_for: &mut HashMap<_, _>| {
+            match handle_for.get(name) {
+                Some(i) => *i,
+                None => {
+                    let i = RoomHandle(handle_for.len() as u8);
+                    handle_for.insert(name.to_string(), i);
+                    name_for.insert(i, name.to_string());
+                    i
+                }
+            }
+        };
+
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let (name, flow_rate, adjacent) = parse_valve(&line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            let src = get_handle(name, &mut name_for, &mut handle_for);
+            flow_for.insert(src, flow_rate);
+
+            for name in adjacent {
+                let dst = get_handle(name, &mut name_for, &mut handle_for);
+                edges.push((src, dst));
+            }
+        }
+
+        let mut graph = DenseGraph::new(handle_for.len());
+        for (src, dst) in edges {
+            graph.add_edge(src.0 as usize, dst.0 as usize, 1);
+        }
+        Ok(Volcano { graph, flow_for, name_for, handle_for })
+    }
+}
+
+impl FromStr for Volcano {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Volcano::read(s.as_bytes())
+    }
+}
+
+// eg: Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+fn parse_valve(line: &str) -> Result<(&str, usize, Vec<&str>), String> {
+    let mut s = Scanner::new(line.trim_end());
+    s.expect("Valve ")?;
+    let name = valve_name(&mut s)?;
+    s.expect(" has flow rate=")?;
+    let flow_rate = s.int()?;
+    s.expect("; tunnel")?;
+    s.eat("s");
+    s.expect(" lead")?;
+    s.eat("s");
+    s.expect(" to valve")?;
+    s.eat("s");
+    s.expect(" ")?;
+    let mut adjacent = vec![valve_name(&mut s)?];
+    while s.eat(", ") {
+        adjacent.push(valve_name(&mut s)?);
+    }
+    s.end()?;
+    Ok((name, flow_rate, adjacent))
+}
+
+fn valve_name<'a>(s: &mut Scanner<'a>) -> Result<&'a str, String> {
+    let err = s.error("expected a two-letter valve name");
+    match s.take_while(|c| c.is_ascii_uppercase()) {
+        name if name.len() == 2 => Ok(name),
+        _ => Err(err),
+    }
+}
+
+pub fn part1(r: impl BufRead) -> Result<usize, AocError> {
+    Ok(part1_stats(r)?.0)
+}
+
+// Return the most pressure that can be released along with the number of states searched and how
+// much memory they took.
+pub fn part1_stats(r: impl BufRead) -> Result<(usize, usize, MemStats), AocError> {
+    solve(r, None)
+}
+
+// Like part1_stats, but resume from the checkpointer's checkpoint, if there is one, and save
+// progress to it as the search goes. The checkpoint is removed once the search finishes.
+pub fn part1_checkpointed(r: impl BufRead, checkpointer: &mut Checkpointer) -> Result<(usize, usize, MemStats), AocError> {
+    solve(r, Some(checkpointer))
+}
+
+// Like part1_stats, but search with `threads` threads. Only the search's queues count towards its
+// memory, since there's no arena.
+pub fn part1_parallel(r: impl BufRead, threads: usize) -> Result<(usize, usize, MemStats), AocError> {
+    let volcano = phase("parse", || Volcano::read(r))?;
+    let state_tree = StateTree::new(volcano);
+    let root = Node { state: state_tree.get(state_tree.start).clone(), parent: None };
+    let outcome = parallel::run(&state_tree, [root], threads);
+    let mut mem = MemStats::default();
+    mem.sample(0, outcome.peak_queue, bytes_of::<Node>(outcome.peak_queue));
+    Ok((outcome.best.map_or(0, |(pressure, _)| pressure as usize), outcome.nstates, mem))
+}
+
+fn solve(r: impl BufRead, checkpointer: Option<&mut Checkpointer>) -> Result<(usize, usize, MemStats), AocError> {
+    let volcano = phase("parse", || Volcano::read(r))?;
+    let mut state_tree = StateTree::new(volcano);
+    let (best, nstates) = state_tree.branch_and_bound(checkpointer)?;
+    Ok((state_tree.get(best).pressure_released, nstates, state_tree.mem))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn test_volcano_from_str() {
+        let volcano = Volcano::from_str(EXAMPLE).unwrap();
+        assert_eq!(volcano.flow("BB"), 13);
+        assert_eq!(volcano.flow("HH"), 22);
+        assert_eq!(volcano.child_names("GG"), vec!["FF", "HH"]);
+        assert_eq!(volcano.child_names("JJ"), vec!["II"]);
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let volcano = Volcano::from_str(EXAMPLE).unwrap();
+        let paths = volcano.graph.all_pairs();
+        let want_matrix = vec![
+        //  a  b  c  d  e  f  g  h  i  j
+            0, 1, 2, 1, 2, 3, 4, 5, 1, 2,  // a
+            1, 0, 1, 2, 3, 4, 5, 6, 2, 3,  // b
+            2, 1, 0, 1, 2, 3, 4, 5, 3, 4,  // c
+            1, 2, 1, 0, 1, 2, 3, 4, 2, 3,  // d
+            2, 3, 2, 1, 0, 1, 2, 3, 3, 4,  // e
+            3, 4, 3, 2, 1, 0, 1, 2, 4, 5,  // f
+            4, 5, 4, 3, 2, 1, 0, 1, 5, 6,  // g
+            5, 6, 5, 4, 3, 2, 1, 0, 6, 7,  // h
+            1, 2, 3, 2, 3, 4, 5, 6, 0, 1,  // i
+            2, 3, 4, 3, 4, 5, 6, 7, 1, 0,  // j
+        ];
+        let names = vec!["AA", "BB", "CC", "DD", "EE", "FF", "GG", "HH", "II", "JJ"];
+        for (src_idx, src_name) in names.iter().enumerate() {
+            for (dst_idx, dst_name) in names.iter().enumerate() {
+                let got = dist(&volcano, &paths, src_name, dst_name);
+                let want = want_matrix[src_idx * names.len() + dst_idx];
+                assert_eq!(got, want, "mismatch at {src_idx},{dst_idx}");
+            }
+        }
+    }
+
+    fn dist(v: &Volcano, paths: &AllPairs<usize>, src: &str, dst: &str) -> usize {
+        let x = v.handle_for[src];
+        let y = v.handle_for[dst];
+        paths.get(x.0 as usize, y.0 as usize).unwrap()
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 1651);
+    }
+
+    #[test]
+    fn test_part1_stats() {
+        let (pressure, nstates, mem) = part1_stats(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(pressure, 1651);
+        // Every state popped was created, and sits in the arena.
+        assert!(mem.arena_len >= nstates);
+        assert!(mem.peak_queue > 0 && mem.peak_queue <= mem.arena_len);
+        assert!(mem.peak_bytes >= mem.arena_len * std::mem::size_of::<State>());
+    }
+
+    #[test]
+    fn test_part1_parallel() {
+        for threads in [1, 4] {
+            let (pressure, nstates, mem) = part1_parallel(EXAMPLE.as_bytes(), threads).unwrap();
+            assert_eq!(pressure, 1651);
+            assert!(nstates > 0 && mem.peak_queue > 0);
+        }
+    }
+
+    #[test]
+    fn test_part1_checkpointed() {
+        let path = std::env::temp_dir().join(format!("aoc-day16a-test-{}", std::process::id()));
+        let mut checkpointer = Checkpointer::new(&path, std::time::Duration::from_secs(3600));
+
+        // Save a checkpoint partway through the search: after moving to DD and opening its valve.
+        let volcano = Volcano::from_str(EXAMPLE).unwrap();
+        let fingerprint = volcano.fingerprint();
+        let mut tree = StateTree::new(volcano);
+        let dd = tree.volcano.handle_for["DD"];
+        let moved = tree.new_state(tree.start, Choice::Move(dd));
+        let moved = tree.add(moved);
+        let opened = tree.new_state(moved, Choice::OpenValve);
+        let opened = tree.add(opened);
+        let queue = BinaryHeap::from([(tree.upper_bound(tree.get(opened)), opened)]);
+        let saved = tree.checkpoint(fingerprint, &queue, moved, 2);
+        assert_eq!(saved.record("state").unwrap().str(4), Ok("DD"));
+        checkpointer.save(&saved).unwrap();
+
+        let (pressure, nstates, _) = part1_checkpointed(EXAMPLE.as_bytes(), &mut checkpointer).unwrap();
+        assert_eq!(pressure, 1651);
+        assert!(nstates > 2);
+        assert_eq!(checkpointer.load(CHECKPOINT_KIND), Ok(None));
+
+        // A checkpoint for a different input isn't resumed.
+        checkpointer.save(&saved).unwrap();
+        let other = EXAMPLE.replace("rate=13", "rate=14");
+        assert!(part1_checkpointed(other.as_bytes(), &mut checkpointer).is_err());
+        checkpointer.finish().unwrap();
+    }
+}
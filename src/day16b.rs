@@ -0,0 +1,809 @@
+use std::cmp;
+use std::collections::{HashMap, HashSet, BinaryHeap};
+use std::fmt;
+use std::io::BufRead;
+use std::str::FromStr;
+
+use aoclib::checkpoint::{self, Checkpoint, Checkpointer, Record};
+use aoclib::log::{self, Level};
+use aoclib::graph::{AllPairs, DenseGraph};
+use aoclib::memstats::{bytes_of, hash_set_bytes, MemStats};
+use aoclib::parallel::{self, Search};
+use aoclib::progress::Progress;
+use aoclib::scan::Scanner;
+use aoclib::{debug, trace};
+
+use crate::runner::phase;
+use crate::error::AocError;
+
+const CHECKPOINT_KIND: &str = "day16b";
+
+struct StateTree {
+    states: Vec<State>,
+    start: StateHandle,
+    volcano: Volcano,
+    shortest_paths: AllPairs<usize>,
+    // Bytes allocated by the states' sets of opened valves.
+    set_bytes: usize,
+    mem: MemStats,
+}
+
+#[derive(Clone)]
+struct State {
+    parent: Option<StateHandle>,
+    rooms: [RoomHandle; 2],
+    choices: [Choice; 2],
+    steps_left: u8,
+    opened_valves: HashSet<RoomHandle>,
+    pressure_released: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Choice {
+    Start,
+    Move(RoomHandle, usize),
+    OpenValve,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct StateHandle(usize);
+
+// States waiting to be expanded, highest upper bound first.
+type Queue = BinaryHeap<(usize, StateHandle)>;
+
+impl StateTree {
+    fn new(volcano: Volcano) -> Self {
+        let start_room = volcano.handle_for["AA"];
+        let states = vec![State {
+            parent: None,
+            rooms: [start_room, start_room],
+            choices: [Choice::Start, Choice::Start],
+            steps_left: 26,
+            opened_valves: HashSet::new(),
+            pressure_released: 0,
+        }];
+
+        let shortest_paths = volcano.graph.all_pairs();
+
+        StateTree {
+            states,
+            start: StateHandle(0),
+            volcano,
+            shortest_paths,
+            set_bytes: 0,
+            mem: MemStats::default(),
+        }
+    }
+
+    fn new_state(&self, parent: StateHandle, choices: [Choice; 2]) -> State {
+        State { parent: Some(parent), ..self.child(self.get(parent), choices) }
+    }
+
+    // The state after making `choices` in `parent_state`, without a link to its parent.
+    fn child(&self, parent_state: &State, choices: [Choice; 2]) -> State {
+        let steps_left = parent_state.steps_left - 1;
+
+        let mut rooms: [RoomHandle; 2] = parent_state.rooms;
+        for i in 0..choices.len() {
+            if let Choice::Move(next, dist) = choices[i] {
+                if dist == 0 {
+                    rooms[i] = next;
+                }
+            }
+        }
+
+        let mut opened_valves = parent_state.opened_valves.clone();
+        let mut pressure_released = parent_state.pressure_released;
+        for (&choice, &room) in choices.iter().zip(parent_state.rooms.iter()) {
+            let Choice::OpenValve = choice else {
+                continue;
+            };
+            opened_valves.insert(room);
+            let flow = self.volcano.flow_for[&room];
+            pressure_released += flow * (steps_left as usize)
+        }
+
+        State {
+            parent: None,
+            rooms,
+            choices,
+            steps_left,
+            opened_valves,
+            pressure_released,
+        }
+    }
+
+    fn add(&mut self, state: State) -> StateHandle {
+        self.set_bytes += hash_set_bytes(&state.opened_valves);
+        self.states.push(state);
+        StateHandle(self.states.len() - 1)
+    }
+
+    fn get(&self, sh: StateHandle) -> &State {
+        &self.states[sh.0]
+    }
+
+    fn sample_mem(&mut self, queue: &Queue) {
+        let bytes = bytes_of::<State>(self.states.capacity())
+            + self.set_bytes
+            + bytes_of::<(usize, StateHandle)>(queue.capacity());
+        self.mem.sample(self.states.len(), queue.len(), bytes);
+    }
+
+    // Return the best state and the number of states popped off the queue. How much memory the
+    // search used is left in self.mem. With a checkpointer, the search resumes from its checkpoint
+    // if there is one, and saves the queue and best state to it every so often.
+    fn branch_and_bound(&mut self, mut checkpointer: Option<&mut Checkpointer>) -> Result<(StateHandle, usize), AocError> {
+        let fingerprint = self.volcano.fingerprint();
+        let saved = match checkpointer.as_deref() {
+            Some(c) => c.load(CHECKPOINT_KIND)?,
+            None => None,
+        };
+        let (mut queue, mut best, mut nstates) = match saved {
+            Some(saved) => self.restore(&saved, fingerprint)?,
+            None => (BinaryHeap::from([(self.upper_bound(self.get(self.start)), self.start)]), self.start, 0),
+        };
+        let mut best_score: usize = self.get(best).pressure_released;
+
+        let mut combos: Vec<[Choice; 2]> = Vec::new();
+
+        let mut progress = Progress::new("day16", "best");
+        loop {
+            if let Some(c) = checkpointer.as_deref_mut() {
+                if c.due() {
+                    c.save(&self.checkpoint(fingerprint, &queue, best, nstates))?;
+                }
+            }
+            let Some((upper_bound, sh)) = queue.pop() else {
+                break;
+            };
+            nstates += 1;
+            progress.tick(nstates, best_score);
+            let state = self.get(sh);
+            trace!("{}", self.format_state(state, upper_bound, best_score));
+
+            // Prune low-scoring branches, since our best score may have changed since it was
+            // pushed.
+            if upper_bound <= best_score {
+                continue;
+            }
+
+            // Update the best state, maybe.
+            if state.pressure_released > best_score {
+                best = sh;
+                best_score = state.pressure_released;
+            }
+
+            // We can't do anything useful at this point.
+            if state.steps_left == 1 {
+                continue;
+            }
+
+            // Queue all possible new states.
+            let parent = state.parent.map(|sh| (self.get(sh).rooms, self.get(sh).choices));
+            self.push_combos(state, parent, &mut combos);
+
+            while let Some(choices) = combos.pop() {
+                let new = self.new_state(sh, choices);
+                let upper_bound = self.upper_bound(&new);
+                if upper_bound <= best_score {
+                    continue;  // Prune low-scoring branches.
+                }
+                let new_handle = self.add(new);
+                queue.push((upper_bound, new_handle));
+            }
+            self.sample_mem(&queue);
+        }
+        if log::enabled(Level::Debug, module_path!()) {
+            self.log_path(best);
+        }
+        progress.print(nstates, best_score);
+        if let Some(c) = checkpointer {
+            c.finish()?;
+        }
+        Ok((best, nstates))
+    }
+
+    // The search's queue and best state, with rooms and opened valves given by name. States don't
+    // keep their parents, so a resumed search only loses a little pruning.
+    fn checkpoint(&self, fingerprint: u64, queue: &Queue, best: StateHandle, nstates: usize) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new(CHECKPOINT_KIND);
+        checkpoint.push("input", [fingerprint]);
+        checkpoint.push("nstates", [nstates]);
+        checkpoint.push("best", self.state_fields(self.get(best)));
+        for &(upper_bound, sh) in queue.iter() {
+            let fields = self.state_fields(self.get(sh));
+            checkpoint.push("state", std::iter::once(upper_bound.to_string()).chain(fields));
+        }
+        checkpoint
+    }
+
+    fn restore(&mut self, saved: &Checkpoint, fingerprint: u64) -> Result<(Queue, StateHandle, usize), String> {
+        if saved.record("input")?.field::<u64>(0)? != fingerprint {
+            return Err("checkpoint is for a different input".to_string());
+        }
+        let nstates = saved.record("nstates")?.field(0)?;
+        let best = self.parse_state(saved.record("best")?, 0)?;
+        let best = self.add(best);
+        let mut queue = BinaryHeap::new();
+        for record in saved.records("state") {
+            let state = self.parse_state(record, 1)?;
+            queue.push((record.field(0)?, self.add(state)));
+        }
+        Ok((queue, best, nstates))
+    }
+
+    // eg: 20 560 BB JJ open JJ+1 DD,JJ
+    //
+    // A move is written as its destination and how many more steps it takes to get there.
+    fn state_fields(&self, state: &State) -> Vec<String> {
+        let name = |rh: &RoomHandle| self.volcano.name_for[rh].as_str();
+        let choice = |c: Choice| match c {
+            Choice::Start => "start".to_string(),
+            Choice::OpenValve => "open".to_string(),
+            Choice::Move(rh, dist) => format!("{}+{dist}", name(&rh)),
+        };
+        let mut opened: Vec<&str> = state.opened_valves.iter().map(name).collect();
+        opened.sort();
+        vec![
+            state.steps_left.to_string(),
+            state.pressure_released.to_string(),
+            name(&state.rooms[0]).to_string(),
+            name(&state.rooms[1]).to_string(),
+            choice(state.choices[0]),
+            choice(state.choices[1]),
+            if opened.is_empty() { "-".to_string() } else { opened.join(",") },
+        ]
+    }
+
+    // Parse the fields written by state_fields, starting at field `first`.
+    fn parse_state(&self, record: Record, first: usize) -> Result<State, String> {
+        let choice = |i: usize| -> Result<Choice, String> {
+            match record.str(first + i)? {
+                "start" => Ok(Choice::Start),
+                "open" => Ok(Choice::OpenValve),
+                s => {
+                    let (name, dist) = s.split_once('+').ok_or_else(|| format!("bad choice: {s:?}"))?;
+                    let dist = dist.parse().map_err(|e| format!("bad choice: {s:?}: {e}"))?;
+                    Ok(Choice::Move(self.volcano.room(name)?, dist))
+                },
+            }
+        };
+        let opened = record.str(first + 6)?;
+        let opened_valves = match opened {
+            "-" => HashSet::new(),
+            _ => opened.split(',').map(|name| self.volcano.room(name)).collect::<Result<_, _>>()?,
+        };
+        Ok(State {
+            parent: None,
+            rooms: [self.volcano.room(record.str(first + 2)?)?, self.volcano.room(record.str(first + 3)?)?],
+            choices: [choice(4)?, choice(5)?],
+            steps_left: record.field(first)?,
+            opened_valves,
+            pressure_released: record.field(first + 1)?,
+        })
+    }
+
+    fn format_state(&self, state: &State, upper_bound: usize, best: usize) -> String {
+        let format_choice = |choice| match choice {
+            Choice::Start => "Start".to_string(),
+            Choice::Move(rh, dist) => format!("Move({}, {dist})", self.volcano.name_for[&rh]),
+            Choice::OpenValve => "OpenValve".to_string(),
+        };
+        format!(
+            "[{}, {}] [{}, {}] steps_left={} upper={upper_bound} best={best} open={:?} rel={}",
+            self.volcano.name_for[&state.rooms[0]],
+            self.volcano.name_for[&state.rooms[1]],
+            format_choice(state.choices[0]),
+            format_choice(state.choices[1]),
+            state.steps_left,
+            state.opened_valves,
+            state.pressure_released,
+        )
+    }
+
+    // Push the pairs of choices the agents can make in `state`, whose parent had the given rooms
+    // after making the given choices.
+    fn push_combos(&self, state: &State, parent: Option<([RoomHandle; 2], [Choice; 2])>, combos: &mut Vec<[Choice; 2]>) {
+        let mut choices_a: Vec<Choice> = Vec::new();
+        let mut choices_b: Vec<Choice> = Vec::new();
+        self.push_new_choices(&mut choices_a, state, parent, 0);
+        self.push_new_choices(&mut choices_b, state, parent, 1);
+        for &a in &choices_a {
+            for &b in &choices_b {
+                if state.rooms[0] == state.rooms[1] {
+                    // Don't have both agents start opening the same valve.
+                    if a == Choice::OpenValve && b == Choice::OpenValve {
+                        continue;
+                    }
+
+                    // If the agents are in the same room 1 moving to B and 2 moving to C is
+                    // the same as 1 -> C and 2 -> B, so skip it.
+                    if let (Choice::Move(_, _), Choice::Move(_, _)) = (a, b) {
+                        if combos.contains(&[b, a]) {
+                            continue;
+                        }
+                    }
+                }
+                combos.push([a, b]);
+            }
+        }
+    }
+
+    fn push_new_choices(&self, choices: &mut Vec<Choice>, state: &State, parent: Option<([RoomHandle; 2], [Choice; 2])>, i: usize) {
+        let room = state.rooms[i];
+
+        // If we're in the middle of a multi-step move, we need to finish it.
+        let choice = state.choices[i];
+        if let Choice::Move(dst, dist) = choice {
+            if dist > 0 {
+                choices.push(Choice::Move(dst, dist - 1));
+                return;
+            }
+        }
+
+        if !state.opened_valves.contains(&room) && self.volcano.flow_for[&room] > 0 {
+            choices.push(Choice::OpenValve);
+        }
+        for child in self.volcano.child_handles(room) {
+            // Don't move back to the previous room without having done anything.
+            if let Some((parent_rooms, parent_choices)) = parent {
+                if parent_rooms[i] == child && parent_choices[i] != Choice::OpenValve {
+                    continue;
+                }
+            }
+            let dist = self.volcano.graph.get(state.rooms[i].as_usize(), child.as_usize()).unwrap();
+            choices.push(Choice::Move(child, dist - 1));
+        }
+    }
+
+    fn upper_bound(&self, state: &State) -> usize {
+        let mut closed_valves: Vec<_> = self.volcano.flow_for
+            .iter()
+            .filter(|(rh, &flow)| flow > 0 && !state.opened_valves.contains(rh))
+            .collect();
+        // Sort by flow rate, descending.
+        closed_valves.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        let mut steps_left: usize = state.steps_left as usize;
+        let mut released: usize = state.pressure_released;
+        let mut closed_valves = closed_valves.iter();
+        while steps_left > 0 {
+            let mut valves_opened = 0;
+            while valves_opened < 2 {
+                let Some(&(&rh, flow)) = closed_valves.by_ref().next() else {
+                    break;
+                };
+                let min_dist = cmp::min(
+                    self.shortest_paths.get(state.rooms[0].as_usize(), rh.as_usize()).unwrap(),
+                    self.shortest_paths.get(state.rooms[1].as_usize(), rh.as_usize()).unwrap(),
+                );
+                // Skip valves that are too far away.
+                if min_dist >= steps_left {
+                    continue;
+                }
+                released += cmp::min(
+                    steps_left * flow,
+                    (state.steps_left as usize - 1) * flow
+                );
+                valves_opened += 1;
+            }
+            // Open a valve and move to the next room.
+            steps_left = steps_left.saturating_sub(2);
+        }
+        released
+    }
+
+    fn log_path(&self, sh: StateHandle) {
+        let mut states: Vec<&State> = Vec::new();
+        let mut cur: Option<StateHandle> = Some(sh);
+        while let Some(sh) = cur {
+            let state = self.get(sh);
+            states.push(state);
+            cur = state.parent;
+        }
+        states.reverse();
+        for state in states.iter() {
+            debug!("{}", self.format_state(state, self.upper_bound(state), 0));
+        }
+    }
+
+    #[allow(unused)]
+    fn print_path_choices(&self, sh: StateHandle) {
+        let mut choices: Vec<[Choice; 2]> = Vec::new();
+        let mut cur: Option<StateHandle> = Some(sh);
+        while let Some(sh) = cur {
+            let state = self.get(sh);
+            choices.push(state.choices);
+            cur = state.parent;
+        }
+        choices.reverse();
+        let print_choice = |c| match c {
+            Choice::Start => print!("start"),
+            Choice::Move(rh, dist) => print!("Move({}, {dist})", self.volcano.name_for[&rh]),
+            Choice::OpenValve => print!("OpenValve"),
+        };
+        for &[a, b] in choices.iter() {
+            print_choice(a);
+            print!(", ");
+            print_choice(b);
+            println!();
+        }
+    }
+}
+
+// A state for the parallel search, which has no arena, so it carries what the pruning needs to
+// know about its parent.
+#[derive(Clone)]
+struct Node {
+    state: State,
+    parent: Option<([RoomHandle; 2], [Choice; 2])>,
+}
+
+impl Search for StateTree {
+    type Node = Node;
+
+    fn bound(&self, node: &Node) -> i64 {
+        self.upper_bound(&node.state) as i64
+    }
+
+    fn score(&self, node: &Node) -> Option<i64> {
+        Some(node.state.pressure_released as i64)
+    }
+
+    fn expand(&self, node: &Node, children: &mut Vec<Node>) {
+        let state = &node.state;
+        // We can't do anything useful at this point.
+        if state.steps_left == 1 {
+            return;
+        }
+        let mut combos = Vec::new();
+        self.push_combos(state, node.parent, &mut combos);
+        for choices in combos {
+            children.push(Node { state: self.child(state, choices), parent: Some((state.rooms, state.choices)) });
+        }
+    }
+}
+
+struct Volcano {
+    graph: DenseGraph<usize>,
+    flow_for: HashMap<RoomHandle, usize>,
+    name_for: HashMap<RoomHandle, String>,
+    handle_for: HashMap<String, RoomHandle>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct RoomHandle(u8);
+
+impl RoomHandle {
+    fn as_usize(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Debug for RoomHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Volcano {
+    fn room(&self, name: &str) -> Result<RoomHandle, String> {
+        self.handle_for.get(name).copied().ok_or_else(|| format!("no valve named {name:?}"))
+    }
+
+    // Identifies the input, so a checkpoint isn't resumed with a different one.
+    fn fingerprint(&self) -> u64 {
+        let mut s = self.graph.to_string();
+        for i in 0..self.graph.len() {
+            let rh = RoomHandle(i as u8);
+            s += &format!("{} {}\n", self.name_for[&rh], self.flow_for.get(&rh).unwrap_or(&0));
+        }
+        checkpoint::fingerprint(&s)
+    }
+
+    #[allow(unused)]
+    fn flow(&self, name: &str) -> usize {
+        let i = self.handle_for[name];
+        self.flow_for[&i]
+    }
+
+    #[allow(unused)]
+    fn child_names(&self, name: &str) -> Vec<&str> {
+        let src = self.handle_for[name];
+        self.graph.edges(src.as_usize())
+            .map(|(i, _)| self.name_for[&RoomHandle(i as u8)].as_str())
+            .collect::<Vec<_>>()
+    }
+
+    pub fn child_handles(&self, rh: RoomHandle) -> impl Iterator<Item=RoomHandle> + '_ {
+        self.graph.edges(rh.as_usize())
+            .map(|(i, _)| RoomHandle(i as u8))
+    }
+
+    // Remove zero-flow rooms from the graph, joining their neighbors with edges as long as the
+    // paths through them.
+    fn compact(&mut self) {
+        let start_room = self.handle_for["AA"];
+        self.graph.compact(|i| {
+            let rh = RoomHandle(i as u8);
+            rh == start_room || self.flow_for[&rh] > 0
+        });
+    }
+
+    // Parse a line at a time, so the input isn't held in memory and a bad line is reported without
+    // reading the rest.
+    fn read(r: impl BufRead) -> Result<Self, AocError> {
+        let mut flow_for = HashMap::new();
+        let mut name_for = HashMap::new();
+        let mut handle_for: HashMap<String, RoomHandle> = HashMap::new();
+        // Tunnels are only added to the graph once the number of rooms is known.
+        let mut edges = Vec::new();
+
+        let get_handle = |name: &str, name_for: &mut HashMap<_, _>, handle_for: &mut HashMap<_, _>| {
+            match handle_for.get(name) {
+                Some(i) => *i,
+                None => {
+                    let i = RoomHandle(handle_for.len() as u8);
+                    handle_for.insert(name.to_string(), i);
+                    name_for.insert(i, name.to_string());
+                    i
+                }
+            }
+        };
+
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            let (name, flow_rate, adjacent) = parse_valve(&line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            let src = get_handle(name, &mut name_for, &mut handle_for);
+            flow_for.insert(src, flow_rate);
+
+            for name in adjacent {
+                let dst = get_handle(name, &mut name_for, &mut handle_for);
+                edges.push((src, dst));
+            }
+        }
+
+        let mut graph = DenseGraph::new(handle_for.len());
+        for (src, dst) in edges {
+            graph.add_edge(src.as_usize(), dst.as_usize(), 1);
+        }
+        Ok(Volcano { graph, flow_for, name_for, handle_for })
+    }
+}
+
+impl FromStr for Volcano {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Volcano::read(s.as_bytes())
+    }
+}
+
+// eg: Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+fn parse_valve(line: &str) -> Result<(&str, usize, Vec<&str>), String> {
+    let mut s = Scanner::new(line.trim_end());
+    s.expect("Valve ")?;
+    let name = valve_name(&mut s)?;
+    s.expect(" has flow rate=")?;
+    let flow_rate = s.int()?;
+    s.expect("; tunnel")?;
+    s.eat("s");
+    s.expect(" lead")?;
+    s.eat("s");
+    s.expect(" to valve")?;
+    s.eat("s");
+    s.expect(" ")?;
+    let mut adjacent = vec![valve_name(&mut s)?];
+    while s.eat(", ") {
+        adjacent.push(valve_name(&mut s)?);
+    }
+    s.end()?;
+    Ok((name, flow_rate, adjacent))
+}
+
+fn valve_name<'a>(s: &mut Scanner<'a>) -> Result<&'a str, String> {
+    let err = s.error("expected a two-letter valve name");
+    match s.take_while(|c| c.is_ascii_uppercase()) {
+        name if name.len() == 2 => Ok(name),
+        _ => Err(err),
+    }
+}
+
+pub fn part2(r: impl BufRead) -> Result<usize, AocError> {
+    Ok(part2_stats(r)?.0)
+}
+
+// Return the most pressure that can be released along with the number of states searched and how
+// much memory they took.
+pub fn part2_stats(r: impl BufRead) -> Result<(usize, usize, MemStats), AocError> {
+    solve(r, None)
+}
+
+// Like part2_stats, but resume from the checkpointer's checkpoint, if there is one, and save
+// progress to it as the search goes. The checkpoint is removed once the search finishes.
+pub fn part2_checkpointed(r: impl BufRead, checkpointer: &mut Checkpointer) -> Result<(usize, usize, MemStats), AocError> {
+    solve(r, Some(checkpointer))
+}
+
+// Like part2_stats, but search with `threads` threads. Only the search's queues count towards its
+// memory, since there's no arena.
+pub fn part2_parallel(r: impl BufRead, threads: usize) -> Result<(usize, usize, MemStats), AocError> {
+    let mut volcano = phase("parse", || Volcano::read(r))?;
+    phase("compact", || volcano.compact());
+    let state_tree = StateTree::new(volcano);
+    let root = Node { state: state_tree.get(state_tree.start).clone(), parent: None };
+    let outcome = parallel::run(&state_tree, [root], threads);
+    let mut mem = MemStats::default();
+    mem.sample(0, outcome.peak_queue, bytes_of::<Node>(outcome.peak_queue));
+    Ok((outcome.best.map_or(0, |(pressure, _)| pressure as usize), outcome.nstates, mem))
+}
+
+fn solve(r: impl BufRead, checkpointer: Option<&mut Checkpointer>) -> Result<(usize, usize, MemStats), AocError> {
+    let mut volcano = phase("parse", || Volcano::read(r))?;
+    phase("compact", || volcano.compact());
+    let mut state_tree = StateTree::new(volcano);
+    let (best, nstates) = state_tree.branch_and_bound(checkpointer)?;
+    Ok((state_tree.get(best).pressure_released, nstates, state_tree.mem))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn test_volcano_from_str() {
+        let volcano = Volcano::from_str(EXAMPLE).unwrap();
+        assert_eq!(volcano.flow("BB"), 13);
+        assert_eq!(volcano.flow("HH"), 22);
+        assert_eq!(volcano.child_names("GG"), vec!["FF", "HH"]);
+        assert_eq!(volcano.child_names("JJ"), vec!["II"]);
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let volcano = Volcano::from_str(EXAMPLE).unwrap();
+        let paths = volcano.graph.all_pairs();
+        let want_matrix = vec![
+        //  a  b  c  d  e  f  g  h  i  j
+            0, 1, 2, 1, 2, 3, 4, 5, 1, 2,  // a
+            1, 0, 1, 2, 3, 4, 5, 6, 2, 3,  // b
+            2, 1, 0, 1, 2, 3, 4, 5, 3, 4,  // c
+            1, 2, 1, 0, 1, 2, 3, 4, 2, 3,  // d
+            2, 3, 2, 1, 0, 1, 2, 3, 3, 4,  // e
+            3, 4, 3, 2, 1, 0, 1, 2, 4, 5,  // f
+            4, 5, 4, 3, 2, 1, 0, 1, 5, 6,  // g
+            5, 6, 5, 4, 3, 2, 1, 0, 6, 7,  // h
+            1, 2, 3, 2, 3, 4, 5, 6, 0, 1,  // i
+            2, 3, 4, 3, 4, 5, 6, 7, 1, 0,  // j
+        ];
+        let names = vec!["AA", "BB", "CC", "DD", "EE", "FF", "GG", "HH", "II", "JJ"];
+        for (src_idx, src_name) in names.iter().enumerate() {
+            for (dst_idx, dst_name) in names.iter().enumerate() {
+                let got = path_dist(&volcano, &paths, src_name, dst_name);
+                let want = want_matrix[src_idx * names.len() + dst_idx];
+                assert_eq!(got, Some(want), "mismatch for {src_name}->{dst_name}");
+            }
+        }
+    }
+
+    fn dist(v: &Volcano, graph: &DenseGraph<usize>, src: &str, dst: &str) -> Option<usize> {
+        graph.get(v.handle_for[src].as_usize(), v.handle_for[dst].as_usize())
+    }
+
+    fn path_dist(v: &Volcano, paths: &AllPairs<usize>, src: &str, dst: &str) -> Option<usize> {
+        paths.get(v.handle_for[src].as_usize(), v.handle_for[dst].as_usize())
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut volcano = Volcano::from_str(EXAMPLE).unwrap();
+        volcano.compact();
+
+        let mut wants: HashMap<(&str, &str), usize> = HashMap::new();
+        wants.insert(("AA", "DD"), 1);
+        wants.insert(("AA", "BB"), 1);
+        wants.insert(("AA", "JJ"), 2);
+        wants.insert(("BB", "CC"), 1);
+        wants.insert(("BB", "AA"), 1);
+        wants.insert(("CC", "DD"), 1);
+        wants.insert(("CC", "BB"), 1);
+        wants.insert(("DD", "CC"), 1);
+        wants.insert(("DD", "AA"), 1);
+        wants.insert(("DD", "EE"), 1);
+        wants.insert(("EE", "DD"), 1);
+        wants.insert(("EE", "HH"), 3);
+        wants.insert(("HH", "EE"), 3);
+        wants.insert(("JJ", "AA"), 2);
+
+        for src in volcano.name_for.values() {
+            for dst in volcano.name_for.values() {
+                let got = dist(&volcano, &volcano.graph, src, dst);
+                let want = wants.get(&(src, dst)).copied();
+                assert_eq!(got, want, "mismatch as ({src}, {dst})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_shortest_paths() {
+        let mut volcano = Volcano::from_str(EXAMPLE).unwrap();
+        volcano.compact();
+        let paths = volcano.graph.all_pairs();
+        let want_matrix = vec![
+        //  a  b  c  d  e  h  j
+            0, 1, 2, 1, 2, 5, 2,  // a
+            1, 0, 1, 2, 3, 6, 3,  // b
+            2, 1, 0, 1, 2, 5, 4,  // c
+            1, 2, 1, 0, 1, 4, 3,  // d
+            2, 3, 2, 1, 0, 3, 4,  // e
+            5, 6, 5, 4, 3, 0, 7,  // h
+            2, 3, 4, 3, 4, 7, 0,  // j
+        ];
+        let names = ["AA", "BB", "CC", "DD", "EE", "HH", "JJ"];
+        for (src_idx, src_name) in names.iter().enumerate() {
+            for (dst_idx, dst_name) in names.iter().enumerate() {
+                let got = path_dist(&volcano, &paths, src_name, dst_name);
+                let want = want_matrix[src_idx * names.len() + dst_idx];
+                assert_eq!(got, Some(want), "mismatch for {src_name}->{dst_name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        let best = part2(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(best, 1707);
+    }
+
+    #[test]
+    fn test_part2_parallel() {
+        for threads in [1, 4] {
+            let (pressure, nstates, _) = part2_parallel(EXAMPLE.as_bytes(), threads).unwrap();
+            assert_eq!(pressure, 1707);
+            assert!(nstates > 0);
+        }
+    }
+
+    #[test]
+    fn test_part2_checkpointed() {
+        let path = std::env::temp_dir().join(format!("aoc-day16b-test-{}", std::process::id()));
+        let mut checkpointer = Checkpointer::new(&path, std::time::Duration::from_secs(3600));
+
+        // Save a checkpoint partway through the search: with one agent on its way to JJ and the
+        // other opening DD's valve.
+        let mut volcano = Volcano::from_str(EXAMPLE).unwrap();
+        volcano.compact();
+        let fingerprint = volcano.fingerprint();
+        let mut tree = StateTree::new(volcano);
+        let (dd, jj) = (tree.volcano.handle_for["DD"], tree.volcano.handle_for["JJ"]);
+        let moved = tree.new_state(tree.start, [Choice::Move(jj, 1), Choice::Move(dd, 0)]);
+        let moved = tree.add(moved);
+        let opened = tree.new_state(moved, [Choice::Move(jj, 0), Choice::OpenValve]);
+        let opened = tree.add(opened);
+        let queue = BinaryHeap::from([(tree.upper_bound(tree.get(moved)), moved), (tree.upper_bound(tree.get(opened)), opened)]);
+        let saved = tree.checkpoint(fingerprint, &queue, tree.start, 3);
+        let reparsed: Vec<Vec<String>> = saved.records("state")
+            .map(|r| tree.state_fields(&tree.parse_state(r, 1).unwrap()))
+            .collect();
+        assert!(reparsed.contains(&tree.state_fields(tree.get(moved))));
+        assert!(reparsed.contains(&tree.state_fields(tree.get(opened))));
+        checkpointer.save(&saved).unwrap();
+
+        let (pressure, _, _) = part2_checkpointed(EXAMPLE.as_bytes(), &mut checkpointer).unwrap();
+        assert_eq!(pressure, 1707);
+        assert_eq!(checkpointer.load(CHECKPOINT_KIND), Ok(None));
+    }
+}
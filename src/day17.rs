@@ -0,0 +1,277 @@
+use std::io;
+use std::ops::Range;
+
+use aoclib::geom::Point2;
+use aoclib::render::{Glyph, NoRender, Scene, Sink};
+
+#[derive(Clone, Copy, Debug)]
+enum Dir {
+    Left, Right,
+}
+
+// y is the height above the floor.
+type Point = Point2<usize>;
+
+struct Rock {
+    shape: Vec<u8>,
+    max_width: usize,
+    max_height: usize,
+}
+
+fn rocks() -> Vec<Rock> {
+    vec![
+        Rock {
+            shape: vec![0b11110000],
+            max_width: 4,
+            max_height: 1,
+        },
+        Rock {
+            shape: vec![
+                0b01000000,
+                0b11100000,
+                0b01000000,
+            ],
+            max_width: 3,
+            max_height: 3,
+        },
+        Rock {
+            // It looks upside down since the bottom row is first in the vec.
+            shape: vec![
+                0b11100000,
+                0b00100000,
+                0b00100000,
+            ],
+            max_width: 3,
+            max_height: 3,
+        },
+        Rock {
+            shape: vec![
+                0b10000000,
+                0b10000000,
+                0b10000000,
+                0b10000000,
+            ],
+            max_width: 1,
+            max_height: 4,
+        },
+        Rock {
+            shape: vec![
+                0b11000000,
+                0b11000000,
+            ],
+            max_width: 2,
+            max_height: 2,
+        },
+    ]
+}
+
+const SHAFT_WIDTH: usize = 7;
+
+struct Shaft {
+    rows: Vec<u8>,
+    highest: Option<usize>,
+}
+
+impl Shaft {
+    pub fn new() -> Self {
+        Shaft { rows: Vec::new(), highest: None }
+    }
+
+    pub fn place_rock(&mut self, rock: &Rock, rock_pos: Point) {
+        for (dy, row) in rock.shape.iter().enumerate() {
+            let y = rock_pos.y + dy;
+            while self.rows.len() <= y {
+                self.rows.push(0);
+            }
+            self.rows[y] |= row >> rock_pos.x;
+            self.highest = Some(self.highest.map_or(y, |highest| highest.max(y)));
+        }
+    }
+
+    fn rock_overlaps_walls(&self, rock: &Rock, rock_pos: Point, dir: Dir) -> bool {
+        match dir {
+            Dir::Left if rock_pos.x == 0 => true,
+            Dir::Right if rock_pos.x + rock.max_width == SHAFT_WIDTH => true,
+            _ => false,
+        }
+    }
+
+    fn rock_overlaps_rocks(&self, rock: &Rock, rock_pos: Point) -> bool {
+        for (dy, rock_row) in rock.shape.iter().enumerate() {
+            let y = rock_pos.y + dy;
+            if self.rows.get(y).is_some_and(|shaft_row| shaft_row & (rock_row >> rock_pos.x) > 0) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// How many rows below the top of the tower or the falling rock to draw.
+const VIEW_ROWS: usize = 10;
+
+// The top of the shaft, drawn the way the puzzle description does, with the falling rock if there
+// is one. Heights grow upward, so row y of the scene is height -y, and the floor is at height -1.
+struct ShaftView<'a> {
+    shaft: &'a Shaft,
+    rock: Option<(&'a Rock, Point)>,
+}
+
+impl ShaftView<'_> {
+    fn is_rock(&self, x: usize, y: usize) -> bool {
+        let mask = 0b10000000 >> x;
+        self.rock.is_some_and(|(rock, pos)| {
+            y >= pos.y && rock.shape.get(y - pos.y).is_some_and(|row| (row >> pos.x) & mask > 0)
+        })
+    }
+}
+
+impl Scene for ShaftView<'_> {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        let mut top = self.shaft.rows.len().saturating_sub(1);
+        if let Some((rock, pos)) = self.rock {
+            top = top.max(pos.y + rock.max_height - 1);
+        }
+        let bottom = top.saturating_sub(VIEW_ROWS) as i64;
+        // Include the floor once it's in view.
+        let floor = if bottom == 0 { 1 } else { 0 };
+        (0..SHAFT_WIDTH as i64 + 2, -(top as i64)..-bottom + 1 + floor)
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        let height = -y;
+        match (x, height) {
+            (0, -1) | (8, -1) => '+'.into(),
+            (_, -1) => '-'.into(),
+            (0, _) | (8, _) => '|'.into(),
+            _ => {
+                let (x, y) = (x as usize - 1, height as usize);
+                if self.is_rock(x, y) {
+                    '@'.into()
+                } else if self.shaft.rows.get(y).is_some_and(|row| row & (0b10000000 >> x) > 0) {
+                    '#'.into()
+                } else {
+                    '.'.into()
+                }
+            },
+        }
+    }
+}
+
+// Drop `nrocks` rocks, sending the shaft to `sink` after each move. With print_highest, print the
+// highest settled rock after each one.
+fn simulate(jets: &str, nrocks: usize, sink: &mut dyn Sink, print_highest: bool) -> io::Result<Shaft> {
+    let rocks = rocks();
+    let mut rocks = rocks.iter().cycle();
+    let mut jets = jets.bytes()
+        .map(|b| match b {
+            b'<' => Dir::Left,
+            b'>' => Dir::Right,
+            _ => panic!("unexpected jet direction: {}", b),
+        }).cycle();
+    let mut shaft = Shaft::new();
+
+    for i in 0..nrocks {
+        let rock = rocks.next().unwrap();
+        let mut rock_pos = Point::new(2, shaft.highest.map_or(3, |h| h + 4));
+
+        sink.frame(format_args!("Rock {}: new", i + 1), &ShaftView { shaft: &shaft, rock: Some((rock, rock_pos)) })?;
+
+        loop {
+            // Move sideways.
+            let dir = jets.next().unwrap();
+            if !shaft.rock_overlaps_walls(rock, rock_pos, dir) {
+                let new_pos = match dir {
+                    Dir::Left => Point::new(rock_pos.x - 1, rock_pos.y),
+                    Dir::Right => Point::new(rock_pos.x + 1, rock_pos.y),
+                };
+                if !shaft.rock_overlaps_rocks(rock, new_pos) {
+                    rock_pos = new_pos;
+                }
+            }
+
+            sink.frame(format_args!("Rock {}: {dir:?}", i + 1), &ShaftView { shaft: &shaft, rock: Some((rock, rock_pos)) })?;
+
+            // Move down.
+            if rock_pos.y == 0 {
+                shaft.place_rock(rock, rock_pos);
+                break;
+            }
+            let new_pos = Point::new(rock_pos.x, rock_pos.y - 1);
+            if shaft.rock_overlaps_rocks(rock, new_pos) {
+                shaft.place_rock(rock, rock_pos);
+                break;
+            }
+            rock_pos = new_pos;
+
+            sink.frame(format_args!("Rock {}: Down", i + 1), &ShaftView { shaft: &shaft, rock: Some((rock, rock_pos)) })?;
+        }
+        sink.frame(format_args!("Rock {}: placed", i + 1), &ShaftView { shaft: &shaft, rock: None })?;
+        if print_highest {
+            println!("{i},{}", shaft.highest.unwrap());
+        }
+    }
+    Ok(shaft)
+}
+
+// Height of the tower after `nrocks` rocks have settled, sending the shaft to `sink` after each
+// move.
+pub fn render(jets: &str, nrocks: usize, sink: &mut dyn Sink) -> io::Result<usize> {
+    let shaft = simulate(jets, nrocks, sink, false)?;
+    Ok(shaft.highest.map_or(0, |h| h + 1))
+}
+
+// Height of the tower after `nrocks` rocks have settled, without drawing anything.
+pub fn height(jets: &str, nrocks: usize) -> usize {
+    render(jets, nrocks, &mut NoRender).expect("NoRender doesn't fail")
+}
+
+pub fn part1(jets: &str) -> usize {
+    height(jets, 2022)
+}
+
+// To answer part2 I wrote a CSV of the highest placed/settled rock position for the first 100k
+// rocks, and loaded that up in pandas. I spoiled myself a bit, in that I saw on [Jukka Jylanki's
+// page](http://clb.confined.space/aoc2022/#day17) that the input must be periodic. In pandas I
+// plotted the height difference after each rock minus the mean difference, which showed a clear
+// repeating signal. I then found where cumax().diff() was greater than normal to find the highest
+// peak for each cycle. For my input the first peak was at 1652, and then every 1690 rocks after
+// that, and the height increased by 2647 each cycle. And then we could just extrapolate
+// out to a trillion:
+//
+//     >>> 1000000000000 // 1690 * 2647 + df.h.iat[1000000000000 % 1690]
+//     1566272189352
+//
+pub fn part2(jets: &str) {
+    _ = simulate(jets, 100_000, &mut NoRender, true);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+    const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE), 3068);
+    }
+
+    #[test]
+    fn test_shaft_view() {
+        let rocks = rocks();
+        let mut shaft = Shaft::new();
+        let falling = ShaftView { shaft: &shaft, rock: Some((&rocks[0], Point::new(2, 3))) };
+        // From the puzzle description.
+        assert_eq!(draw(&falling, Viewport::UNLIMITED, false), "\
+|..@@@@.|
+|.......|
+|.......|
+|.......|
++-------+
+");
+        shaft.place_rock(&rocks[0], Point::new(2, 0));
+        let placed = ShaftView { shaft: &shaft, rock: None };
+        assert_eq!(draw(&placed, Viewport::UNLIMITED, false), "|..####.|\n+-------+\n");
+    }
+}
@@ -0,0 +1,630 @@
+use std::fmt;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+
+use aoclib::checkpoint::{self, Checkpoint, Checkpointer};
+use aoclib::memstats::{bytes_of, MemStats};
+use aoclib::parallel::{self, Search};
+use aoclib::progress::Progress;
+use aoclib::scan::Scanner;
+use aoclib::trace;
+
+use crate::runner::phase;
+
+use Res::*;
+
+use crate::error::AocError;
+
+type Uint = u16;
+
+#[derive(Debug, PartialEq)]
+struct Blueprint {
+    ore_bot: BotCosts,
+    clay_bot: BotCosts,
+    obsidian_bot: BotCosts,
+    geode_bot: BotCosts,
+}
+
+#[derive(Default, Debug, PartialEq)]
+struct BotCosts {
+    ore: Uint,
+    clay: Uint,
+    obsidian: Uint,
+}
+
+struct Global<'a> {
+    nstates: usize,
+    best: Uint,
+    progress: Progress,
+    // The search is depth-first, so the states waiting to be expanded are the ones on the stack.
+    depth: usize,
+    mem: MemStats,
+    saver: Option<Saver<'a>>,
+}
+
+impl<'a> Global<'a> {
+    fn new() -> Global<'a> {
+        Global {
+            nstates: 0,
+            best: 0,
+            progress: Progress::new("day19", "best"),
+            depth: 0,
+            mem: MemStats::default(),
+            saver: None,
+        }
+    }
+
+    fn save_if_due(&mut self) {
+        let Some(saver) = &mut self.saver else {
+            return;
+        };
+        if saver.err.is_some() || !saver.checkpointer.due() {
+            return;
+        }
+        let checkpoint = saver.checkpoint(Some((self.best, self.nstates)));
+        if let Err(e) = saver.checkpointer.save(&checkpoint) {
+            saver.err = Some(e);
+        }
+    }
+
+    // Whether saving a checkpoint failed, in which case the search gives up.
+    fn failed(&self) -> bool {
+        self.saver.as_ref().is_some_and(|s| s.err.is_some())
+    }
+}
+
+// Saves the results for the blueprints done so far, and the best for the current one, which
+// makes a good lower bound when resuming. The search is recursive, so rather than unwinding it, a
+// failed save is kept to be returned once it gives up.
+struct Saver<'a> {
+    checkpointer: &'a mut Checkpointer,
+    kind: &'static str,
+    fingerprint: u64,
+    // Geodes and the number of states searched for each blueprint done so far.
+    done: &'a [(Uint, usize)],
+    err: Option<io::Error>,
+}
+
+impl Saver<'_> {
+    fn checkpoint(&self, current: Option<(Uint, usize)>) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new(self.kind);
+        checkpoint.push("input", [self.fingerprint]);
+        for &(geodes, nstates) in self.done {
+            checkpoint.push("done", [geodes as usize, nstates]);
+        }
+        if let Some((best, nstates)) = current {
+            checkpoint.push("best", [best as usize, nstates]);
+        }
+        checkpoint
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+enum Res {
+    Ore, Clay, Obsidian, Geode, Nothing,
+}
+
+#[derive(Clone, Default, Debug)]
+struct State {
+    ore: Uint,
+    clay: Uint,
+    obsidian: Uint,
+    geode: Uint,
+    ore_bot: Uint,
+    clay_bot: Uint,
+    obsidian_bot: Uint,
+    geode_bot: Uint,
+    ticks_left: u8,
+}
+
+impl State {
+    fn start_part1() -> Self {
+        State {
+            ticks_left: 24,
+            ore_bot: 1,
+            ..Self::default()
+        }
+    }
+
+    fn start_part2() -> Self {
+        State {
+            ticks_left: 32,
+            ore_bot: 1,
+            ..Self::default()
+        }
+    }
+
+    fn collect(&mut self) {
+        self.ore += self.ore_bot;
+        self.clay += self.clay_bot;
+        self.obsidian += self.obsidian_bot;
+        self.geode += self.geode_bot;
+        self.ticks_left -= 1;
+    }
+
+    fn make_bot(&self, res: Res, bp: &Blueprint) -> Option<Self> {
+        match res {
+            Ore => self.make_ore_bot(bp),
+            Clay => self.make_clay_bot(bp),
+            Obsidian => self.make_obsidian_bot(bp),
+            Geode => self.make_geode_bot(bp),
+            Nothing => self.make_nothing(bp),
+        }
+    }
+
+    fn make_ore_bot(&self, bp: &Blueprint) -> Option<Self> {
+        if bp.ore_bot.ore > self.ore {
+            return None;
+        }
+        let mut new = self.clone();
+        new.ore -= bp.ore_bot.ore;
+        new.collect();
+        new.ore_bot += 1;
+        Some(new)
+    }
+
+    fn make_clay_bot(&self, bp: &Blueprint) -> Option<Self> {
+        if bp.clay_bot.ore > self.ore {
+            return None;
+        }
+        let mut new = self.clone();
+        new.ore -= bp.clay_bot.ore;
+        new.collect();
+        new.clay_bot += 1;
+        Some(new)
+    }
+
+    fn make_obsidian_bot(&self, bp: &Blueprint) -> Option<Self> {
+        if bp.obsidian_bot.ore > self.ore || bp.obsidian_bot.clay > self.clay {
+            return None;
+        }
+        let mut new = self.clone();
+        new.ore -= bp.obsidian_bot.ore;
+        new.clay -= bp.obsidian_bot.clay;
+        new.collect();
+        new.obsidian_bot += 1;
+        Some(new)
+    }
+
+    fn make_geode_bot(&self, bp: &Blueprint) -> Option<Self> {
+        if !self.can_make_geode_bot(bp) {
+            return None;
+        }
+        let mut new = self.clone();
+        new.ore -= bp.geode_bot.ore;
+        new.clay -= bp.geode_bot.clay;
+        new.obsidian -= bp.geode_bot.obsidian;
+        new.collect();
+        new.geode_bot += 1;
+        Some(new)
+    }
+
+    fn can_make_geode_bot(&self, bp: &Blueprint) -> bool {
+        self.ore >= bp.geode_bot.ore
+            && self.clay >= bp.geode_bot.clay
+            && self.obsidian >= bp.geode_bot.obsidian
+    }
+
+    fn make_nothing(&self, bp: &Blueprint) -> Option<Self> {
+        if self.can_make_geode_bot(bp) {
+            return None;
+        }
+        let mut new = self.clone();
+        new.collect();
+        Some(new)
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ore={} clay={} obs={} geode={} r_ore={} r_clay={} r_obs={} r_geo={}",
+            self.ore,
+            self.clay,
+            self.obsidian,
+            self.geode,
+            self.ore_bot,
+            self.clay_bot,
+            self.obsidian_bot,
+            self.geode_bot)
+    }
+}
+
+fn geode_upper_bound(state: &State, bp: &Blueprint) -> Uint {
+    // Duplicate collected resources so that each type of bot has its own pool to draw from, so
+    // that the bot-building decisions for different resources don't depend on each other. Also,
+    // bots of different types can be built in the same tick.
+
+    // Map: (have_resource, for_bot) -> count
+    let mut resources: HashMap<(Res, Res), Uint> = HashMap::new();
+    collect_resource(&mut resources, Ore, state.ore);
+    collect_resource(&mut resources, Clay, state.clay);
+    collect_resource(&mut resources, Obsidian, state.obsidian);
+    collect_resource(&mut resources, Geode, state.geode);
+
+    let mut bots: HashMap<Res, Uint> = HashMap::new();
+    bots.insert(Ore, state.ore_bot);
+    bots.insert(Clay, state.clay_bot);
+    bots.insert(Obsidian, state.obsidian_bot);
+    bots.insert(Geode, state.geode_bot);
+
+    let mut new_bots: Vec<Res> = Vec::new();
+    for _ in (1..=state.ticks_left).rev() {
+        // Start building any bots that we can.
+        if bp.ore_bot.ore <= *resources.entry((Ore, Ore)).or_default() {
+            new_bots.push(Ore);
+            resources.entry((Ore, Ore)).and_modify(|n| *n -= bp.ore_bot.ore);
+        }
+        if bp.clay_bot.ore <= *resources.entry((Ore, Clay)).or_default() {
+            new_bots.push(Clay);
+            resources.entry((Ore, Clay)).and_modify(|n| *n -= bp.clay_bot.ore);
+        }
+        if bp.obsidian_bot.ore <= *resources.entry((Ore, Obsidian)).or_default()
+            && bp.obsidian_bot.clay <= *resources.entry((Clay, Obsidian)).or_default()
+        {
+            new_bots.push(Obsidian);
+            resources.entry((Ore, Obsidian)).and_modify(|n| *n -= bp.obsidian_bot.ore);
+            resources.entry((Clay, Obsidian)).and_modify(|n| *n -= bp.obsidian_bot.clay);
+        }
+        if bp.geode_bot.ore <= *resources.entry((Ore, Geode)).or_default()
+            && bp.geode_bot.clay <= *resources.entry((Clay, Geode)).or_default()
+            && bp.geode_bot.obsidian <= *resources.entry((Obsidian, Geode)).or_default()
+        {
+            new_bots.push(Geode);
+            resources.entry((Ore, Geode)).and_modify(|n| *n -= bp.geode_bot.ore);
+            resources.entry((Clay, Geode)).and_modify(|n| *n -= bp.geode_bot.clay);
+            resources.entry((Obsidian, Geode)).and_modify(|n| *n -= bp.geode_bot.obsidian);
+        }
+
+        // Collect resources with bots existing at beginning of tick.
+        for res in [Geode, Obsidian, Clay, Ore] {
+            if let Some(&nbots) = bots.get(&res) {
+                collect_resource(&mut resources, res, nbots);
+            }
+        }
+
+        // Add newly-built bots to inventory at end of tick.
+        while let Some(bot) = new_bots.pop() {
+            bots.entry(bot).and_modify(|n| *n += 1).or_insert(1);
+        }
+    }
+    *resources.entry((Geode, Geode)).or_default()
+}
+
+// The parallel search does the same as cracked_geodes, with the blueprint deciding what each state
+// can lead to.
+impl Search for Blueprint {
+    type Node = State;
+
+    fn bound(&self, state: &State) -> i64 {
+        geode_upper_bound(state, self) as i64
+    }
+
+    fn score(&self, state: &State) -> Option<i64> {
+        Some(state.geode as i64)
+    }
+
+    fn expand(&self, state: &State, children: &mut Vec<State>) {
+        if state.ticks_left > 0 {
+            children.extend([Geode, Obsidian, Clay, Ore, Nothing].into_iter().filter_map(|m| state.make_bot(m, self)));
+        }
+    }
+}
+
+fn collect_resource(resources: &mut HashMap<(Res, Res), Uint>, resource: Res, n: Uint) {
+    for dst in [Geode, Obsidian, Clay, Ore] {
+        resources.entry((resource, dst)).and_modify(|have| *have += n).or_insert(n);
+    }
+}
+
+fn cracked_geodes(state: State, bp: &Blueprint, global: &mut Global) -> Uint {
+    if state.ticks_left == 0 || global.failed() {
+        return state.geode;
+    }
+    // Use a Branch and Bound approach, implemented using recursion.
+    [Geode, Obsidian, Clay, Ore, Nothing]
+        .into_iter()
+        .filter_map(|m| {
+            let new = state.make_bot(m, bp)?;
+            let upper = geode_upper_bound(&new, bp);
+            if upper <= global.best {
+                return None;
+            }
+            trace!("left={} do={m:?} upper={upper} best={} {new}", new.ticks_left, global.best);
+            global.nstates += 1;
+            global.best = global.best.max(new.geode);
+            global.progress.tick(global.nstates, global.best);
+            global.save_if_due();
+            global.depth += 1;
+            global.mem.sample(0, global.depth, bytes_of::<State>(global.depth));
+            let geodes = cracked_geodes(new, bp, global);
+            global.depth -= 1;
+            Some(geodes)
+        })
+        .max().unwrap_or(0)
+}
+
+fn read_blueprints(r: impl BufRead) -> Result<Vec<Blueprint>, AocError> {
+    r.lines()
+        .enumerate()
+        .map(|(i, line)| Ok(parse_blueprint(&line?).map_err(|e| format!("line {}: {e}", i + 1))?))
+        .collect()
+}
+
+// eg: Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 4 ore. Each obsidian robot costs 4 ore and 18 clay. Each geode robot costs 4 ore and 9 obsidian.
+fn parse_blueprint(line: &str) -> Result<Blueprint, String> {
+    let no_cost = BotCosts::default();
+    let mut s = Scanner::new(line.trim_end());
+    s.expect("Blueprint ")?;
+    s.int::<u32>()?;
+    s.expect(": Each ore robot costs ")?;
+    let ore_ore = s.int()?;
+    s.expect(" ore. Each clay robot costs ")?;
+    let clay_ore = s.int()?;
+    s.expect(" ore. Each obsidian robot costs ")?;
+    let obs_ore = s.int()?;
+    s.expect(" ore and ")?;
+    let obs_clay = s.int()?;
+    s.expect(" clay. Each geode robot costs ")?;
+    let geo_ore = s.int()?;
+    s.expect(" ore and ")?;
+    let geo_obs = s.int()?;
+    s.expect(" obsidian.")?;
+    s.end()?;
+    Ok(Blueprint {
+        ore_bot: BotCosts { ore: ore_ore, ..no_cost },
+        clay_bot: BotCosts { ore: clay_ore, ..no_cost },
+        obsidian_bot: BotCosts { ore: obs_ore, clay: obs_clay, ..no_cost },
+        geode_bot: BotCosts { ore: geo_ore, obsidian: geo_obs, ..no_cost },
+    })
+}
+
+pub fn part1(r: impl BufRead) -> Result<Uint, AocError> {
+    Ok(part1_stats(r)?.0)
+}
+
+// Return the answer along with the number of states searched over all the blueprints and the most
+// memory the search for any one blueprint took.
+pub fn part1_stats(r: impl BufRead) -> Result<(Uint, usize, MemStats), AocError> {
+    part1_solve(r, None)
+}
+
+// Like part1_stats, but resume from the checkpointer's checkpoint, if there is one, and save
+// progress to it as the search goes. The checkpoint is removed once the search finishes.
+pub fn part1_checkpointed(r: impl BufRead, checkpointer: &mut Checkpointer) -> Result<(Uint, usize, MemStats), AocError> {
+    part1_solve(r, Some(checkpointer))
+}
+
+fn part1_solve(r: impl BufRead, checkpointer: Option<&mut Checkpointer>) -> Result<(Uint, usize, MemStats), AocError> {
+    let blueprints = phase("parse", || read_blueprints(r))?;
+    let (geodes, nstates, mem) = max_geodes(&blueprints, State::start_part1(), "day19-part1", checkpointer)?;
+    let sum = geodes.iter().enumerate().map(|(i, geodes)| (i as Uint + 1) * geodes).sum();
+    Ok((sum, nstates, mem))
+}
+
+// Like part1_stats, but search each blueprint with `threads` threads.
+pub fn part1_parallel(r: impl BufRead, threads: usize) -> Result<(Uint, usize, MemStats), AocError> {
+    let blueprints = phase("parse", || read_blueprints(r))?;
+    let (geodes, nstates, mem) = max_geodes_parallel(&blueprints, State::start_part1(), threads);
+    let sum = geodes.iter().enumerate().map(|(i, geodes)| (i as Uint + 1) * geodes).sum();
+    Ok((sum, nstates, mem))
+}
+
+pub fn part2(r: impl BufRead) -> Result<Uint, AocError> {
+    Ok(part2_stats(r)?.0)
+}
+
+pub fn part2_stats(r: impl BufRead) -> Result<(Uint, usize, MemStats), AocError> {
+    part2_solve(r, None)
+}
+
+pub fn part2_checkpointed(r: impl BufRead, checkpointer: &mut Checkpointer) -> Result<(Uint, usize, MemStats), AocError> {
+    part2_solve(r, Some(checkpointer))
+}
+
+fn part2_solve(r: impl BufRead, checkpointer: Option<&mut Checkpointer>) -> Result<(Uint, usize, MemStats), AocError> {
+    let blueprints = phase("parse", || read_blueprints(r))?;
+    let blueprints = &blueprints[..blueprints.len().min(3)];
+    let (geodes, nstates, mem) = max_geodes(blueprints, State::start_part2(), "day19-part2", checkpointer)?;
+    Ok((geodes.iter().product(), nstates, mem))
+}
+
+pub fn part2_parallel(r: impl BufRead, threads: usize) -> Result<(Uint, usize, MemStats), AocError> {
+    let blueprints = phase("parse", || read_blueprints(r))?;
+    let blueprints = &blueprints[..blueprints.len().min(3)];
+    let (geodes, nstates, mem) = max_geodes_parallel(blueprints, State::start_part2(), threads);
+    Ok((geodes.iter().product(), nstates, mem))
+}
+
+// Return the most geodes each blueprint can crack, the number of states searched over all of them
+// and the most memory the search for any one blueprint took. With a checkpointer, skip the
+// blueprints its checkpoint says are done.
+fn max_geodes(
+    blueprints: &[Blueprint],
+    start: State,
+    kind: &'static str,
+    mut checkpointer: Option<&mut Checkpointer>,
+) -> Result<(Vec<Uint>, usize, MemStats), AocError> {
+    let fingerprint = checkpoint::fingerprint(&format!("{start:?} {blueprints:?}"));
+    let mut done: Vec<(Uint, usize)> = Vec::new();
+    let mut current = None;
+    if let Some(saved) = checkpointer.as_deref().map(|c| c.load(kind)).transpose()?.flatten() {
+        if saved.record("input")?.field::<u64>(0)? != fingerprint {
+            return Err("checkpoint is for a different input".into());
+        }
+        for record in saved.records("done") {
+            done.push((record.field(0)?, record.field(1)?));
+        }
+        if let Ok(record) = saved.record("best") {
+            current = Some((record.field(0)?, record.field(1)?));
+        }
+        if done.len() > blueprints.len() {
+            return Err("checkpoint has more blueprints than the input".into());
+        }
+    }
+
+    let mut mem = MemStats::default();
+    for bp in &blueprints[done.len()..] {
+        let mut global = Global::new();
+        // The best from the checkpoint is a lower bound, which the search can't always match since
+        // it prunes anything that can't beat it, so use the best instead of what it returns.
+        if let Some((best, nstates)) = current.take() {
+            global.best = best;
+            global.nstates = nstates;
+        }
+        if let Some(c) = checkpointer.as_deref_mut() {
+            global.saver = Some(Saver { checkpointer: c, kind, fingerprint, done: &done, err: None });
+        }
+        cracked_geodes(start.clone(), bp, &mut global);
+        if let Some(e) = global.saver.and_then(|s| s.err) {
+            return Err(e.into());
+        }
+        mem.merge(global.mem);
+        done.push((global.best, global.nstates));
+    }
+    if let Some(c) = checkpointer {
+        c.finish()?;
+    }
+    let nstates = done.iter().map(|&(_, n)| n).sum();
+    Ok((done.into_iter().map(|(geodes, _)| geodes).collect(), nstates, mem))
+}
+
+// Like max_geodes, but with a parallel search for each blueprint, whose queues take the memory
+// instead of the recursion's stack.
+fn max_geodes_parallel(blueprints: &[Blueprint], start: State, threads: usize) -> (Vec<Uint>, usize, MemStats) {
+    let mut geodes = Vec::new();
+    let mut nstates = 0;
+    let mut mem = MemStats::default();
+    for bp in blueprints {
+        let outcome = parallel::run(bp, [start.clone()], threads);
+        geodes.push(outcome.best.map_or(0, |(best, _)| best as Uint));
+        nstates += outcome.nstates;
+        mem.sample(0, outcome.peak_queue, bytes_of::<State>(outcome.peak_queue));
+    }
+    (geodes, nstates, mem)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+
+    fn make_bluprint1() -> Blueprint {
+        let no_cost = BotCosts::default();
+        Blueprint {
+            ore_bot: BotCosts { ore: 4, ..no_cost },
+            clay_bot: BotCosts { ore: 2, ..no_cost },
+            obsidian_bot: BotCosts { ore: 3, clay: 14, ..no_cost },
+            geode_bot: BotCosts { ore: 2, obsidian: 7, ..no_cost },
+        }
+    }
+
+    #[test]
+    fn test_read_blueprints_random() {
+        let mut rng = Rng::new(19);
+        let no_cost = BotCosts::default();
+        for _ in 0..200 {
+            let want: Vec<Blueprint> = (0..1 + rng.below(5))
+                .map(|_| {
+                    let mut cost = || 1 + rng.below(20) as Uint;
+                    Blueprint {
+                        ore_bot: BotCosts { ore: cost(), ..no_cost },
+                        clay_bot: BotCosts { ore: cost(), ..no_cost },
+                        obsidian_bot: BotCosts { ore: cost(), clay: cost(), ..no_cost },
+                        geode_bot: BotCosts { ore: cost(), obsidian: cost(), ..no_cost },
+                    }
+                })
+                .collect();
+            let input: String = want.iter().enumerate()
+                .map(|(i, b)| format!(
+                    "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                    Each obsidian robot costs {} ore and {} clay. \
+                    Each geode robot costs {} ore and {} obsidian.\n",
+                    i + 1, b.ore_bot.ore, b.clay_bot.ore, b.obsidian_bot.ore, b.obsidian_bot.clay,
+                    b.geode_bot.ore, b.geode_bot.obsidian,
+                ))
+                .collect();
+            assert_eq!(read_blueprints(input.as_bytes()).unwrap(), want);
+
+            // Damaged input either still parses or is an error, but never panics.
+            let bad = rng.mutate(&input, "0123456789. :\n");
+            _ = read_blueprints(bad.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_geode_upper_bound() {
+        let state = State {
+            geode_bot: 1,
+            ticks_left: 5,
+            ..State::default()
+        };
+        let blueprint = make_bluprint1();
+        assert_eq!(geode_upper_bound(&state, &blueprint), 5);
+    }
+
+    #[test]
+    fn test_cracked_geodes() {
+        let start = State::start_part1();
+        let blueprint = make_bluprint1();
+        let mut global = Global::new();
+        let max = cracked_geodes(start, &blueprint, &mut global);
+        assert_eq!(max, 9);
+    }
+
+    #[test] #[ignore]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 33);
+    }
+
+    #[test]
+    fn test_part1_stats() {
+        let (_, _, mem) = part1_stats(EXAMPLE.as_bytes()).unwrap();
+        // Each level of the recursion is a minute.
+        assert_eq!(mem.peak_queue, 24);
+        assert_eq!(mem.arena_len, 0);
+    }
+
+    #[test]
+    fn test_part1_parallel() {
+        let (serial, _, _) = part1_stats(EXAMPLE.as_bytes()).unwrap();
+        for threads in [1, 4] {
+            let (answer, nstates, mem) = part1_parallel(EXAMPLE.as_bytes(), threads).unwrap();
+            assert_eq!(answer, serial);
+            assert!(nstates > 0);
+            assert!(mem.peak_queue > 0);
+        }
+    }
+
+    #[test] #[ignore]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 56 * 62);
+    }
+
+    #[test]
+    fn test_part1_checkpointed() {
+        let path = std::env::temp_dir().join(format!("aoc-day19-test-{}", std::process::id()));
+        let mut checkpointer = Checkpointer::new(&path, std::time::Duration::from_secs(3600));
+        let blueprints = read_blueprints(EXAMPLE.as_bytes()).unwrap();
+        let fingerprint = checkpoint::fingerprint(&format!("{:?} {blueprints:?}", State::start_part1()));
+
+        // The first blueprint is done, and the second's search found its best before stopping.
+        let saver = Saver { checkpointer: &mut checkpointer, kind: "day19-part1", fingerprint, done: &[(9, 100)], err: None };
+        let saved = saver.checkpoint(Some((12, 50)));
+        assert_eq!(saved.to_string(), format!("checkpoint day19-part1\ninput {fingerprint}\ndone 9 100\nbest 12 50\n"));
+        checkpointer.save(&saved).unwrap();
+
+        let (answer, nstates, _) = part1_checkpointed(EXAMPLE.as_bytes(), &mut checkpointer).unwrap();
+        assert_eq!(answer, 9 + 2 * 12);
+        assert!(nstates >= 150);
+        assert_eq!(checkpointer.load("day19-part1"), Ok(None));
+
+        // Part 2 doesn't resume part 1's checkpoint.
+        checkpointer.save(&saved).unwrap();
+        assert!(part2_checkpointed(EXAMPLE.as_bytes(), &mut checkpointer).is_err());
+        checkpointer.finish().unwrap();
+    }
+}
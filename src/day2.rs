@@ -0,0 +1,857 @@
+use std::cmp::Reverse;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::error::AocError;
+
+// The standard game, and a bigger one with two more shapes. Each shape line gives its name, the
+// score for playing it and its codes in the strategy guide, and the other lines say which shapes
+// beat which.
+pub const ROCK_PAPER_SCISSORS: &str = "\
+rock 1 A X
+paper 2 B Y
+scissors 3 C Z
+rock beats scissors
+paper beats rock
+scissors beats paper
+";
+
+pub const LIZARD_SPOCK: &str = "\
+rock 1 A X
+paper 2 B Y
+scissors 3 C Z
+lizard 4 D V
+spock 5 E W
+rock beats scissors lizard
+paper beats rock spock
+scissors beats paper lizard
+lizard beats paper spock
+spock beats rock scissors
+";
+
+// A shape that can be played.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape {
+    pub name: String,
+    pub score: u32,
+    // What the strategy guide calls it, for both players.
+    pub codes: Vec<String>,
+}
+
+// Which shapes there are and which ones beat which.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    pub shapes: Vec<Shape>,
+    // (winner, loser) pairs of indexes into shapes. Shapes that don't beat each other draw.
+    pub beats: Vec<(usize, usize)>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::parse(ROCK_PAPER_SCISSORS).unwrap()
+    }
+}
+
+impl Rules {
+    // Parse a rules table like ROCK_PAPER_SCISSORS.
+    pub fn parse(s: &str) -> Result<Rules, String> {
+        let mut rules = Rules { shapes: Vec::new(), beats: Vec::new() };
+        let mut beats = Vec::new();
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [winner, "beats", ref losers @ ..] if !losers.is_empty() => {
+                    beats.extend(losers.iter().map(|&loser| (winner, loser)));
+                },
+                [name, score, ref codes @ ..] if !codes.is_empty() => {
+                    let score = score.parse().map_err(|e| format!("{name}'s score: {e}: {score:?}"))?;
+                    let codes = codes.iter().map(|c| c.to_string()).collect();
+                    rules.shapes.push(Shape { name: name.to_string(), score, codes });
+                },
+                _ => return Err(format!("bad rule: {line}")),
+            }
+        }
+        for (winner, loser) in beats {
+            let pair = (rules.index(winner)?, rules.index(loser)?);
+            rules.beats.push(pair);
+        }
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    fn index(&self, name: &str) -> Result<usize, String> {
+        self.shapes.iter().position(|s| s.name == name).ok_or_else(|| format!("unknown shape: {name}"))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.shapes.is_empty() {
+            return Err("there are no shapes".to_string());
+        }
+        for (i, shape) in self.shapes.iter().enumerate() {
+            let later = &self.shapes[i + 1..];
+            if later.iter().any(|s| s.name == shape.name) {
+                return Err(format!("repeated shape: {}", shape.name));
+            }
+            if let Some(code) = shape.codes.iter().find(|&c| later.iter().any(|s| s.codes.contains(c))) {
+                return Err(format!("repeated code: {code}"));
+            }
+        }
+        for &(a, b) in &self.beats {
+            let (a_name, b_name) = (&self.shapes[a].name, &self.shapes[b].name);
+            if a == b {
+                return Err(format!("{a_name} beats itself"));
+            }
+            if self.beats.contains(&(b, a)) {
+                return Err(format!("{a_name} and {b_name} beat each other"));
+            }
+        }
+        Ok(())
+    }
+
+    // The shape a code in the strategy guide stands for.
+    pub fn shape(&self, code: &str) -> Result<Move, String> {
+        self.shapes.iter()
+            .position(|s| s.codes.iter().any(|c| c == code))
+            .map(Move)
+            .ok_or_else(|| format!("bad move: {}", code))
+    }
+
+    // The shape with a name, ignoring case.
+    pub fn shape_named(&self, name: &str) -> Result<Move, String> {
+        self.shapes.iter()
+            .position(|s| s.name.eq_ignore_ascii_case(name))
+            .map(Move)
+            .ok_or_else(|| format!("bad move: {}", name))
+    }
+
+    // How a round goes for us.
+    pub fn outcome(&self, them: Move, us: Move) -> Outcome {
+        if self.beats.contains(&(us.0, them.0)) {
+            Outcome::Win
+        } else if self.beats.contains(&(them.0, us.0)) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    // score = shape_score + outcome_score
+    pub fn score(&self, them: Move, us: Move) -> u32 {
+        self.shapes[us.0].score + self.outcome(them, us).score()
+    }
+
+    // The shape to play to get the outcome we want, preferring the ones that score more when
+    // there's a choice.
+    pub fn for_outcome(&self, them: Move, outcome: Outcome) -> Result<Move, String> {
+        let mut best: Option<Move> = None;
+        for us in (0..self.shapes.len()).map(Move) {
+            if self.outcome(them, us) == outcome && best.is_none_or(|b| self.shapes[us.0].score > self.shapes[b.0].score) {
+                best = Some(us);
+            }
+        }
+        best.ok_or_else(|| format!("no shape gets a {outcome:?} against {}", self.shapes[them.0].name))
+    }
+
+    // The shape that scores the most against `them`, the earliest one if there's a tie.
+    pub fn best_response(&self, them: Move) -> Move {
+        let mut best = Move(0);
+        for us in (1..self.shapes.len()).map(Move) {
+            if self.score(them, us) > self.score(them, best) {
+                best = us;
+            }
+        }
+        best
+    }
+}
+
+// A shape, as an index into Rules::shapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move(pub usize);
+
+// How a round goes for us, or how part2's strategy guide says it should.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Lose,
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Outcome::*;
+        match s {
+            "X" => Ok(Lose),
+            "Y" => Ok(Draw),
+            "Z" => Ok(Win),
+            s => Err(format!("bad intent: {}", s)),
+        }
+    }
+}
+
+impl Outcome {
+    // Parse an outcome's name, ignoring case.
+    pub fn from_name(s: &str) -> Result<Self, String> {
+        use Outcome::*;
+        match s.to_ascii_lowercase().as_str() {
+            "lose" => Ok(Lose),
+            "draw" => Ok(Draw),
+            "win" => Ok(Win),
+            _ => Err(format!("bad intent: {}", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Outcome::Win => "win",
+            Outcome::Draw => "draw",
+            Outcome::Lose => "lose",
+        }
+    }
+
+    fn score(self) -> u32 {
+        match self {
+            Outcome::Win => 6,
+            Outcome::Draw => 3,
+            Outcome::Lose => 0,
+        }
+    }
+}
+
+// How the strategy guide is written. The AoC guides use letters, like `A Y`, but the same
+// rounds could be written with names, like `Rock Paper` or `rock,win`, separated by whitespace or
+// commas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    // Letters or names, separated by commas if the line has any, or else whitespace.
+    #[default]
+    Auto,
+    Letters,
+    Names,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Format::Auto),
+            "letters" => Ok(Format::Letters),
+            "names" => Ok(Format::Names),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown guide format: {s}")),
+        }
+    }
+}
+
+impl Format {
+    fn fields(self, line: &str) -> Vec<&str> {
+        match self {
+            Format::Csv => line.split(',').map(str::trim).collect(),
+            Format::Auto if line.contains(',') => Format::Csv.fields(line),
+            _ => line.split_whitespace().collect(),
+        }
+    }
+
+    fn shape(self, rules: &Rules, s: &str) -> Result<Move, String> {
+        match self {
+            Format::Letters => rules.shape(s),
+            Format::Names | Format::Csv => rules.shape_named(s),
+            Format::Auto => rules.shape(s).or_else(|_| rules.shape_named(s)),
+        }
+    }
+
+    fn outcome(self, s: &str) -> Result<Outcome, String> {
+        match self {
+            Format::Letters => Outcome::from_str(s),
+            Format::Names | Format::Csv => Outcome::from_name(s),
+            Format::Auto => Outcome::from_str(s).or_else(|_| Outcome::from_name(s)),
+        }
+    }
+}
+
+// A round from the strategy guide, as scored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Round {
+    // The 1-based line of the guide it's on.
+    pub line_no: usize,
+    pub them: Move,
+    pub us: Move,
+    pub outcome: Outcome,
+    pub score: u32,
+    // The score so far, including this round.
+    pub total: u32,
+}
+
+// Score each line of the guide. Bad lines are reported on stderr and skipped.
+pub fn rounds<T, F>(rules: &Rules, format: Format, lines: T, move_converter: F) -> Vec<Round>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+    F: Fn(&Rules, Format, &str) -> Result<(Move, Move), String>,
+{
+    // Errors are only returned when strict.
+    score_rounds(rules, format, lines, move_converter, false).unwrap_or_default()
+}
+
+// Like rounds, but fail on the first bad line, saying which line it is.
+pub fn rounds_strict<T, F>(rules: &Rules, format: Format, lines: T, move_converter: F) -> Result<Vec<Round>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+    F: Fn(&Rules, Format, &str) -> Result<(Move, Move), String>,
+{
+    score_rounds(rules, format, lines, move_converter, true)
+}
+
+fn score_rounds<T, F>(rules: &Rules, format: Format, lines: T, move_converter: F, strict: bool) -> Result<Vec<Round>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+    F: Fn(&Rules, Format, &str) -> Result<(Move, Move), String>,
+{
+    let mut total = 0;
+    let mut rounds = Vec::new();
+    for (i, line) in lines.enumerate() {
+        match move_converter(rules, format, line.as_ref()) {
+            Ok((them, us)) => {
+                let score = rules.score(them, us);
+                total += score;
+                let outcome = rules.outcome(them, us);
+                rounds.push(Round { line_no: i + 1, them, us, outcome, score, total });
+            },
+            Err(e) if strict => return Err(AocError::Parse(format!("line {}: {}", i + 1, e))),
+            Err(e) => eprintln!("line {}: {}", i + 1, e),
+        }
+    }
+    Ok(rounds)
+}
+
+pub fn sum_line_scores<T, F>(rules: &Rules, format: Format, lines: T, move_converter: F) -> u32
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+    F: Fn(&Rules, Format, &str) -> Result<(Move, Move), String>,
+{
+    rounds(rules, format, lines, move_converter).last().map_or(0, |r| r.total)
+}
+
+pub fn sum_line_scores_strict<T, F>(rules: &Rules, format: Format, lines: T, move_converter: F) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+    F: Fn(&Rules, Format, &str) -> Result<(Move, Move), String>,
+{
+    Ok(rounds_strict(rules, format, lines, move_converter)?.last().map_or(0, |r| r.total))
+}
+
+fn line_to_moves_part1(rules: &Rules, format: Format, line: &str) -> Result<(Move, Move), String> {
+    if let [them, us] = format.fields(line)[..] {
+        let them = format.shape(rules, them)?;
+        let us = format.shape(rules, us)?;
+        Ok((them, us))
+    } else {
+        Err(format!("bad line: {}", line))
+    }
+}
+
+fn line_to_moves_part2(rules: &Rules, format: Format, line: &str) -> Result<(Move, Move), String> {
+    if let [them, intent] = format.fields(line)[..] {
+        let them = format.shape(rules, them)?;
+        let intent = format.outcome(intent)?;
+        let us = rules.for_outcome(them, intent)?;
+        Ok((them, us))
+    } else {
+        Err(format!("bad line: {}", line))
+    }
+}
+
+// The opponent's move, ignoring anything after it.
+fn opponent(rules: &Rules, format: Format, line: &str) -> Result<Move, String> {
+    match format.fields(line)[..] {
+        [them, ..] if !them.is_empty() => format.shape(rules, them),
+        _ => Err(format!("bad line: {}", line)),
+    }
+}
+
+fn line_to_moves_best(rules: &Rules, format: Format, line: &str) -> Result<(Move, Move), String> {
+    let them = opponent(rules, format, line)?;
+    Ok((them, rules.best_response(them)))
+}
+
+pub fn part1<T>(rules: &Rules, format: Format, lines: T) -> u32
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    sum_line_scores(rules, format, lines, line_to_moves_part1)
+}
+
+pub fn part2<T>(rules: &Rules, format: Format, lines: T) -> u32
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    sum_line_scores(rules, format, lines, line_to_moves_part2)
+}
+
+pub fn part1_strict<T>(rules: &Rules, format: Format, lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    sum_line_scores_strict(rules, format, lines, line_to_moves_part1)
+}
+
+pub fn part2_strict<T>(rules: &Rules, format: Format, lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    sum_line_scores_strict(rules, format, lines, line_to_moves_part2)
+}
+
+// The most we could score against the opponent's moves, compared with what part1 and part2's
+// readings of the guide score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    // Each round, with our best response to the opponent's move.
+    pub rounds: Vec<Round>,
+    pub best: u32,
+    pub part1: u32,
+    pub part2: u32,
+}
+
+pub fn part3<T>(rules: &Rules, format: Format, lines: T) -> Analysis
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    // Errors are only returned when strict.
+    analyze(rules, format, lines, false).unwrap()
+}
+
+// Like part3, but fail on the first line that any of the readings can't make sense of.
+pub fn part3_strict<T>(rules: &Rules, format: Format, lines: T) -> Result<Analysis, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    analyze(rules, format, lines, true)
+}
+
+fn analyze<T>(rules: &Rules, format: Format, lines: T, strict: bool) -> Result<Analysis, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let lines: Vec<T::Item> = lines.collect();
+    let total = |rounds: Vec<Round>| rounds.last().map_or(0, |r| r.total);
+    let rounds = score_rounds(rules, format, lines.iter(), line_to_moves_best, strict)?;
+    Ok(Analysis {
+        best: rounds.last().map_or(0, |r| r.total),
+        rounds,
+        part1: total(score_rounds(rules, format, lines.iter(), line_to_moves_part1, strict)?),
+        part2: total(score_rounds(rules, format, lines.iter(), line_to_moves_part2, strict)?),
+    })
+}
+
+// A way of choosing our moves, to play against the opponent's column of the guide.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Strategy {
+    Always(Move),
+    // Play whatever the opponent does.
+    Mirror,
+    // Play these shapes in turn, starting over once they run out.
+    Cycle(Vec<Move>),
+    // Read the guide's second column like part1 or part2 does.
+    Part1Guide,
+    Part2Guide,
+    // Our best response to each move, like part3.
+    Best,
+}
+
+impl Strategy {
+    // Parse a strategy like "mirror", "always-rock" or "cycle:rock,paper", with shapes named
+    // like in the rules.
+    pub fn parse(rules: &Rules, s: &str) -> Result<Strategy, String> {
+        let shape = |name: &str| rules.shape_named(name).map_err(|_| format!("unknown shape: {name}"));
+        match s {
+            "mirror" => Ok(Strategy::Mirror),
+            "part1-guide" => Ok(Strategy::Part1Guide),
+            "part2-guide" => Ok(Strategy::Part2Guide),
+            "best" => Ok(Strategy::Best),
+            _ => {
+                if let Some(name) = s.strip_prefix("always-") {
+                    Ok(Strategy::Always(shape(name)?))
+                } else if let Some(names) = s.strip_prefix("cycle:") {
+                    Ok(Strategy::Cycle(names.split(',').map(shape).collect::<Result<_, _>>()?))
+                } else {
+                    Err(format!("unknown strategy: {s}"))
+                }
+            },
+        }
+    }
+
+    // Always playing each of the shapes, then the rest of the strategies that don't need
+    // any shapes.
+    pub fn builtin(rules: &Rules) -> Vec<Strategy> {
+        let mut strategies: Vec<Strategy> = (0..rules.shapes.len()).map(|i| Strategy::Always(Move(i))).collect();
+        strategies.extend([Strategy::Mirror, Strategy::Part1Guide, Strategy::Part2Guide, Strategy::Best]);
+        strategies
+    }
+
+    pub fn name(&self, rules: &Rules) -> String {
+        let name = |m: &Move| rules.shapes[m.0].name.clone();
+        match self {
+            Strategy::Always(m) => format!("always-{}", name(m)),
+            Strategy::Mirror => "mirror".to_string(),
+            Strategy::Cycle(ms) => format!("cycle:{}", ms.iter().map(name).collect::<Vec<_>>().join(",")),
+            Strategy::Part1Guide => "part1-guide".to_string(),
+            Strategy::Part2Guide => "part2-guide".to_string(),
+            Strategy::Best => "best".to_string(),
+        }
+    }
+
+    // Both moves for the i'th round of the guide, counting from 0.
+    fn play(&self, rules: &Rules, format: Format, i: usize, line: &str) -> Result<(Move, Move), String> {
+        match self {
+            Strategy::Part1Guide => line_to_moves_part1(rules, format, line),
+            Strategy::Part2Guide => line_to_moves_part2(rules, format, line),
+            Strategy::Best => line_to_moves_best(rules, format, line),
+            _ => {
+                let them = opponent(rules, format, line)?;
+                let us = match self {
+                    Strategy::Always(m) => *m,
+                    Strategy::Cycle(ms) => ms[i % ms.len()],
+                    _ => them,
+                };
+                Ok((them, us))
+            },
+        }
+    }
+}
+
+// How a strategy did over the whole guide.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Standing {
+    pub strategy: String,
+    pub total: u32,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    // Rounds the strategy couldn't read, such as ones without a second column for the guide
+    // strategies, which score 0.
+    pub skipped: usize,
+}
+
+// Play each strategy against the opponent's moves, returning the standings from the highest
+// score to the lowest, with ties in the order the strategies were given.
+pub fn tournament<T>(rules: &Rules, format: Format, lines: T, strategies: &[Strategy]) -> Vec<Standing>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    // Errors are only returned when strict.
+    play_tournament(rules, format, lines, strategies, false).unwrap()
+}
+
+// Like tournament, but fail on the first round that one of the strategies can't read.
+pub fn tournament_strict<T>(rules: &Rules, format: Format, lines: T, strategies: &[Strategy]) -> Result<Vec<Standing>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    play_tournament(rules, format, lines, strategies, true)
+}
+
+fn play_tournament<T>(rules: &Rules, format: Format, lines: T, strategies: &[Strategy], strict: bool) -> Result<Vec<Standing>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let lines: Vec<T::Item> = lines.collect();
+    let mut standings = Vec::new();
+    for strategy in strategies {
+        let mut standing = Standing { strategy: strategy.name(rules), ..Standing::default() };
+        for (i, line) in lines.iter().enumerate() {
+            match strategy.play(rules, format, i, line.as_ref()) {
+                Ok((them, us)) => {
+                    standing.total += rules.score(them, us);
+                    match rules.outcome(them, us) {
+                        Outcome::Win => standing.wins += 1,
+                        Outcome::Draw => standing.draws += 1,
+                        Outcome::Lose => standing.losses += 1,
+                    }
+                },
+                Err(e) if strict => return Err(AocError::Parse(format!("line {}: {}: {}", i + 1, standing.strategy, e))),
+                Err(_) => standing.skipped += 1,
+            }
+        }
+        standings.push(standing);
+    }
+    standings.sort_by_key(|s| Reverse(s.total));
+    Ok(standings)
+}
+
+pub fn write_standings(mut w: impl Write, standings: &[Standing]) -> io::Result<()> {
+    let width = standings.iter().map(|s| s.strategy.len()).max().unwrap_or(0).max("strategy".len());
+    writeln!(w, "{:<width$} {:>7} {:>5} {:>5} {:>6} {:>7}", "strategy", "total", "wins", "draws", "losses", "skipped")?;
+    for s in standings {
+        writeln!(w, "{:<width$} {:>7} {:>5} {:>5} {:>6} {:>7}", s.strategy, s.total, s.wins, s.draws, s.losses, s.skipped)?;
+    }
+    Ok(())
+}
+
+// Each round of the guide, reading it like part1 or part2.
+pub fn report<T>(rules: &Rules, format: Format, lines: T, part: u32) -> Vec<Round>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    if part == 1 {
+        rounds(rules, format, lines, line_to_moves_part1)
+    } else {
+        rounds(rules, format, lines, line_to_moves_part2)
+    }
+}
+
+pub fn report_strict<T>(rules: &Rules, format: Format, lines: T, part: u32) -> Result<Vec<Round>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    if part == 1 {
+        rounds_strict(rules, format, lines, line_to_moves_part1)
+    } else {
+        rounds_strict(rules, format, lines, line_to_moves_part2)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown report format: {s}")),
+        }
+    }
+}
+
+pub fn write_report(mut w: impl Write, rules: &Rules, format: ReportFormat, rounds: &[Round]) -> io::Result<()> {
+    let name = |m: Move| &rules.shapes[m.0].name;
+    match format {
+        ReportFormat::Text => {
+            writeln!(w, "{:>5} {:<10} {:<10} {:<7} {:>5} {:>7}", "line", "them", "us", "outcome", "score", "total")?;
+            for r in rounds {
+                writeln!(w, "{:>5} {:<10} {:<10} {:<7} {:>5} {:>7}",
+                    r.line_no, name(r.them), name(r.us), r.outcome.name(), r.score, r.total)?;
+            }
+        },
+        ReportFormat::Json => {
+            writeln!(w, "[")?;
+            for (i, r) in rounds.iter().enumerate() {
+                let sep = if i + 1 < rounds.len() { "," } else { "" };
+                writeln!(w, "  {{\"line\": {}, \"them\": \"{}\", \"us\": \"{}\", \"outcome\": \"{}\", \"score\": {}, \"total\": {}}}{sep}",
+                    r.line_no, name(r.them), name(r.us), r.outcome.name(), r.score, r.total)?;
+            }
+            writeln!(w, "]")?;
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn part1() {
+        let lines = ["A Y", "B X", "C Z"];
+        let score = sum_line_scores(&Rules::default(), Format::Letters, lines.iter(), line_to_moves_part1);
+        assert_eq!(score, 15);
+    }
+
+    #[test]
+    fn part2() {
+        let lines = ["A Y", "B X", "C Z"];
+        let score = sum_line_scores(&Rules::default(), Format::Letters, lines.iter(), line_to_moves_part2);
+        assert_eq!(score, 12);
+
+    }
+
+    #[test]
+    fn test_standard_rules() {
+        let rules = Rules::default();
+        let [rock, paper, scissors] = [0, 1, 2].map(Move);
+        for (them, us, want) in [(rock, paper, 8), (paper, rock, 1), (scissors, scissors, 6), (scissors, rock, 7)] {
+            assert_eq!(rules.score(them, us), want, "{them:?} {us:?}");
+        }
+        assert_eq!(rules.for_outcome(rock, Outcome::Lose), Ok(scissors));
+        assert_eq!(rules.for_outcome(paper, Outcome::Draw), Ok(paper));
+    }
+
+    #[test]
+    fn test_lizard_spock() {
+        let rules = Rules::parse(LIZARD_SPOCK).unwrap();
+        let [rock, paper, scissors, lizard, spock] = [0, 1, 2, 3, 4].map(Move);
+        assert_eq!(rules.shape("V"), Ok(lizard));
+        assert_eq!(rules.outcome(spock, lizard), Outcome::Win);
+        assert_eq!(rules.outcome(scissors, spock), Outcome::Win);
+        assert_eq!(rules.outcome(lizard, scissors), Outcome::Win);
+        assert_eq!(rules.outcome(rock, paper), Outcome::Win);
+        // Both paper and spock beat rock, and spock scores more.
+        assert_eq!(rules.for_outcome(rock, Outcome::Win), Ok(spock));
+        assert_eq!(super::part1(&rules, Format::Letters, ["A W", "D Y", "E V"].iter()), 11 + 2 + 10);
+        assert_eq!(super::part2(&rules, Format::Letters, ["A Z", "C X"].iter()), 5 + 6 + 4);
+    }
+
+    #[test]
+    fn test_formats() {
+        let rules = Rules::default();
+        let part1 = |format, lines: &[&str]| super::part1(&rules, format, lines.iter());
+        let part2 = |format, lines: &[&str]| super::part2(&rules, format, lines.iter());
+        for format in [Format::Auto, Format::Names] {
+            assert_eq!(part1(format, &["Rock Paper", "paper  ROCK", "Scissors Scissors"]), 15);
+            assert_eq!(part2(format, &["Rock Draw", "Paper lose", "Scissors WIN"]), 12);
+        }
+        for format in [Format::Auto, Format::Csv] {
+            assert_eq!(part1(format, &["Rock,Paper", "Paper, Rock", "scissors,scissors"]), 15);
+        }
+        assert_eq!(part1(Format::Auto, &["A Paper", "B,X", "Scissors Z"]), 15);
+        assert_eq!(part2(Format::Auto, &["A Y", "Paper X", "C,win"]), 12);
+        // Rounds in the wrong format score nothing.
+        assert_eq!(part1(Format::Letters, &["Rock Paper", "A Y"]), 8);
+        assert_eq!(part1(Format::Names, &["A Y", "Rock,Paper", "Rock Paper"]), 8);
+        assert_eq!("csv".parse(), Ok(Format::Csv));
+        assert!("tsv".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_part3() {
+        let rules = Rules::default();
+        let analysis = part3(&rules, Format::Letters, ["A Y", "B X", "C Z"].iter());
+        let responses: Vec<Move> = analysis.rounds.iter().map(|r| r.us).collect();
+        assert_eq!(responses, [Move(1), Move(2), Move(0)]);
+        assert_eq!((analysis.best, analysis.part1, analysis.part2), (8 + 9 + 7, 15, 12));
+        // Only the opponent's column is needed.
+        assert_eq!(part3(&rules, Format::Auto, ["A", "Paper"].iter()).best, 8 + 9);
+
+        // Spock scores the most, and beats rock and scissors.
+        let rules = Rules::parse(LIZARD_SPOCK).unwrap();
+        assert_eq!(rules.best_response(Move(0)), Move(4));
+        assert_eq!(rules.best_response(Move(4)), Move(3));
+        assert_eq!(rules.best_response(Move(3)), Move(2));
+    }
+
+    #[test]
+    fn test_strict() {
+        let rules = Rules::default();
+        let lines = ["A Y", "B Q", "C Z"];
+        assert_eq!(super::part1(&rules, Format::Letters, lines.iter()), 8 + 6);
+        let err = part1_strict(&rules, Format::Letters, lines.iter()).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: bad move: Q");
+        let err = part2_strict(&rules, Format::Letters, ["A Y", "B Y", "C"].iter()).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: bad line: C");
+        assert_eq!(part2_strict(&rules, Format::Letters, ["A Y", "B X"].iter()).unwrap(), 5);
+        assert!(report_strict(&rules, Format::Letters, lines.iter(), 1).is_err());
+        assert_eq!(report_strict(&rules, Format::Letters, ["A Y"].iter(), 2).unwrap().len(), 1);
+
+        // part3 only needs the first column, but the readings it's compared with need both.
+        assert_eq!(part3(&rules, Format::Letters, ["A", "B X"].iter()).best, 17);
+        let err = part3_strict(&rules, Format::Letters, ["A", "B X"].iter()).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: bad line: A");
+        assert_eq!(part3_strict(&rules, Format::Letters, ["A Y", "B X"].iter()).unwrap().best, 17);
+    }
+
+    #[test]
+    fn test_tournament() {
+        let rules = Rules::default();
+        let lines = ["A Y", "B X", "C Z"];
+        let standings = tournament(&rules, Format::Letters, lines.iter(), &Strategy::builtin(&rules));
+        let totals: Vec<(&str, u32)> = standings.iter().map(|s| (s.strategy.as_str(), s.total)).collect();
+        assert_eq!(totals, [
+            ("best", 24),
+            ("always-scissors", 3 + 9 + 6),
+            ("always-paper", 8 + 5 + 2),
+            ("mirror", 15),
+            ("part1-guide", 15),
+            ("always-rock", 4 + 1 + 7),
+            ("part2-guide", 12),
+        ]);
+        assert_eq!((standings[0].wins, standings[3].draws), (3, 3));
+
+        let cycle = Strategy::parse(&rules, "cycle:scissors,Rock").unwrap();
+        assert_eq!(cycle, Strategy::Cycle(vec![Move(2), Move(0)]));
+        let standings = tournament(&rules, Format::Letters, ["A", "A", "A", "bad"].iter(), &[cycle, Strategy::Part1Guide]);
+        let want = Standing { strategy: "cycle:scissors,rock".to_string(), total: 3 + 4 + 3, wins: 0, draws: 1, losses: 2, skipped: 1 };
+        assert_eq!(standings[0], want);
+        assert_eq!((standings[1].total, standings[1].skipped), (0, 4));
+        let err = tournament_strict(&rules, Format::Letters, ["A"].iter(), &[Strategy::Mirror, Strategy::Part2Guide]).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: part2-guide: bad line: A");
+
+        assert_eq!(Strategy::parse(&rules, "always-paper"), Ok(Strategy::Always(Move(1))));
+        assert_eq!(Strategy::parse(&rules, "always-lizard"), Err("unknown shape: lizard".to_string()));
+        assert!(Strategy::parse(&rules, "random").is_err());
+        assert!(Strategy::parse(&rules, "cycle:").is_err());
+
+        let mut w = Vec::new();
+        write_standings(&mut w, &standings[..1]).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), concat!(
+            "strategy              total  wins draws losses skipped\n",
+            "cycle:scissors,rock      10     0     1      2       1\n",
+        ));
+    }
+
+    #[test]
+    fn test_report() {
+        let rules = Rules::default();
+        let lines = ["A Y", "oops", "B X", "C Z"];
+        let rounds = report(&rules, Format::Letters, lines.iter(), 2);
+        let totals: Vec<(usize, Outcome, u32, u32)> = rounds.iter().map(|r| (r.line_no, r.outcome, r.score, r.total)).collect();
+        assert_eq!(totals, [(1, Outcome::Draw, 4, 4), (3, Outcome::Lose, 1, 5), (4, Outcome::Win, 7, 12)]);
+        assert_eq!(report(&rules, Format::Letters, lines.iter(), 1).last().unwrap().total, 15);
+
+        let write = |format| {
+            let mut w = Vec::new();
+            write_report(&mut w, &rules, format, &rounds[..2]).unwrap();
+            String::from_utf8(w).unwrap()
+        };
+        assert_eq!(write(ReportFormat::Text), concat!(
+            " line them       us         outcome score   total\n",
+            "    1 rock       rock       draw        4       4\n",
+            "    3 paper      rock       lose        1       5\n",
+        ));
+        assert_eq!(write(ReportFormat::Json), "\
+[
+  {\"line\": 1, \"them\": \"rock\", \"us\": \"rock\", \"outcome\": \"draw\", \"score\": 4, \"total\": 4},
+  {\"line\": 3, \"them\": \"paper\", \"us\": \"rock\", \"outcome\": \"lose\", \"score\": 1, \"total\": 5}
+]
+");
+        assert_eq!("json".parse(), Ok(ReportFormat::Json));
+    }
+
+    #[test]
+    fn test_parse_rules_errors() {
+        let err = |s: &str| Rules::parse(s).unwrap_err();
+        assert_eq!(err(""), "there are no shapes");
+        assert_eq!(err("rock 1 A\nrock 2 B\n"), "repeated shape: rock");
+        assert_eq!(err("rock 1 A\npaper 2 A\n"), "repeated code: A");
+        assert_eq!(err("rock 1 A\nrock beats paper\n"), "unknown shape: paper");
+        assert_eq!(err("rock 1 A\nrock beats rock\n"), "rock beats itself");
+        assert_eq!(err("rock 1 A\npaper 2 B\nrock beats paper\npaper beats rock\n"), "rock and paper beat each other");
+        assert_eq!(err("rock x A\n"), "rock's score: invalid digit found in string: \"x\"");
+        assert_eq!(err("rock 1\n"), "bad rule: rock 1");
+        // Nothing beats rock.
+        let rules = Rules::parse("rock 1 A\nscissors 2 B\nrock beats scissors\n").unwrap();
+        assert!(rules.for_outcome(Move(0), Outcome::Win).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use std::io::BufRead;
+use std::collections::HashMap;
+
+use crate::runner::phase;
+use crate::error::AocError;
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+pub struct Monkeys {
+    names: Vec<String>,
+    index_for: HashMap<String, MonkeyIndex>,
+    jobs: Vec<Job>,
+    parents: Vec<Option<MonkeyIndex>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Job {
+    Constant(i64),
+    Expression(MonkeyIndex, Op, MonkeyIndex)
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct MonkeyIndex(u16);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Op {
+    Add, Sub, Mul, Div,
+}
+
+impl Op {
+    // None if the result doesn't fit in an i64, or it's a division by zero.
+    fn apply(self, left: i64, right: i64) -> Option<i64> {
+        match self {
+            Op::Add => left.checked_add(right),
+            Op::Sub => left.checked_sub(right),
+            Op::Mul => left.checked_mul(right),
+            Op::Div => left.checked_div(right),
+        }
+    }
+}
+
+// Maps names to indexes while parsing. Monkeys can be referred to before their own line, so their
+// jobs are filled in as they're found.
+struct MonkeysBuilder {
+    names: Vec<String>,
+    index_for: HashMap<String, MonkeyIndex>,
+    jobs: Vec<Option<Job>>,
+    parents: Vec<Option<MonkeyIndex>>,
+}
+
+impl MonkeysBuilder {
+    fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            index_for: HashMap::new(),
+            jobs: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> Result<MonkeyIndex, AocError> {
+        if let Some(&i) = self.index_for.get(name) {
+            return Ok(i);
+        }
+        let i = MonkeyIndex(u16::try_from(self.names.len()).map_err(|_| "too many monkeys")?);
+        self.names.push(name.to_string());
+        self.index_for.insert(name.to_string(), i);
+        self.jobs.push(None);
+        self.parents.push(None);
+        Ok(i)
+    }
+
+    fn build(self) -> Result<Monkeys, AocError> {
+        let jobs = self.jobs.into_iter()
+            .zip(self.names.iter())
+            .map(|(job, name)| job.ok_or_else(|| format!("monkey {name} has no job")))
+            .collect::<Result<Vec<Job>, String>>()?;
+        Ok(Monkeys {
+            names: self.names,
+            index_for: self.index_for,
+            jobs,
+            parents: self.parents,
+        })
+    }
+}
+
+impl Monkeys {
+    pub fn read(r: impl BufRead) -> Result<Self, AocError> {
+        let mut builder = MonkeysBuilder::new();
+        for line in r.lines() {
+            let line = line?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let name = fields.first()
+                .and_then(|f| f.strip_suffix(':'))
+                .ok_or("no trailing colon on name")?;
+            let i = builder.intern(name)?;
+            let job = match fields.len() {
+                2 => Job::Constant(fields[1].parse()?),
+                4 => {
+                    let left = builder.intern(fields[1])?;
+                    let op: Option<Op> = match fields[2] {
+                        "+" => Some(Op::Add),
+                        "-" => Some(Op::Sub),
+                        "*" => Some(Op::Mul),
+                        "/" => Some(Op::Div),
+                        _ => None,
+                    };
+                    let op = op.ok_or("unexpected operation")?;
+                    let right = builder.intern(fields[3])?;
+
+                    builder.parents[left.0 as usize] = Some(i);
+                    builder.parents[right.0 as usize] = Some(i);
+                    Job::Expression(left, op, right)
+                },
+                _ => return Err("lines should have 2 or 4 words".into()),
+            };
+            if builder.jobs[i.0 as usize].replace(job).is_some() {
+                return Err(format!("monkey {name} has more than one job").into());
+            }
+        }
+        builder.build()
+    }
+
+    pub fn index(&self, name: &str) -> Option<MonkeyIndex> {
+        self.index_for.get(name).copied()
+    }
+
+    pub fn name(&self, i: MonkeyIndex) -> &str {
+        &self.names[i.0 as usize]
+    }
+
+    pub fn job(&self, i: MonkeyIndex) -> &Job {
+        &self.jobs[i.0 as usize]
+    }
+
+    fn parent(&self, i: MonkeyIndex) -> Option<MonkeyIndex> {
+        self.parents[i.0 as usize]
+    }
+
+    fn must_index(&self, name: &str) -> MonkeyIndex {
+        self.index(name).unwrap_or_else(|| panic!("unknown monkey: {name}"))
+    }
+
+    // Panics if `name` isn't defined. A monkey whose number doesn't fit in an i64, or that divides
+    // by zero, is an AocError::NoSolution.
+    pub fn eval(&self, name: &str) -> Result<i64, AocError> {
+        self.eval_index(self.must_index(name), None)
+    }
+
+    // Like eval(), but the human yells `humn_value` instead of whatever the input says.
+    pub fn eval_with_override(&self, name: &str, humn_value: i64) -> Result<i64, AocError> {
+        let humn_override = self.index(HUMAN).map(|humn| (humn, humn_value));
+        self.eval_index(self.must_index(name), humn_override)
+    }
+
+    fn eval_index(&self, i: MonkeyIndex, humn_override: Option<(MonkeyIndex, i64)>) -> Result<i64, AocError> {
+        if let Some((humn, n)) = humn_override {
+            if i == humn {
+                return Ok(n);
+            }
+        }
+        match *self.job(i) {
+            Job::Constant(n) => Ok(n),
+            Job::Expression(left, op, right) => {
+                let left = self.eval_index(left, humn_override)?;
+                let right = self.eval_index(right, humn_override)?;
+                let name = self.name(i);
+                if op == Op::Div && right == 0 {
+                    return Err(AocError::NoSolution(format!("monkey {name} divides by zero")));
+                }
+                op.apply(left, right)
+                    .ok_or_else(|| AocError::NoSolution(format!("monkey {name}'s number doesn't fit in an i64")))
+            }
+        }
+    }
+
+    // Return the indexes from root down to the given monkey.
+    pub fn find_path(&self, i: MonkeyIndex) -> Vec<MonkeyIndex> {
+        let mut cur = i;
+        let mut path = vec![i];
+        while let Some(parent) = self.parent(cur) {
+            path.push(parent);
+            cur = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+pub fn part1(r: impl BufRead) -> Result<i64, AocError> {
+    let monkeys = phase("parse", || Monkeys::read(r))?;
+    let root = monkeys.index(ROOT).ok_or("no root monkey")?;
+    monkeys.eval_index(root, None)
+}
+
+pub fn part2(r: impl BufRead) -> Result<i64, AocError> {
+    let monkeys = phase("parse", || Monkeys::read(r))?;
+    let root = monkeys.index(ROOT).ok_or("no root monkey")?;
+    let target = monkeys.index(HUMAN).ok_or("no humn monkey")?;
+    let path = monkeys.find_path(target);
+    let mut path = path.iter().skip(1);  // Skip root.
+
+    let human_side = *path.next().ok_or("should still have path left")?;
+    let Job::Expression(l, _, r) = *monkeys.job(root) else {
+        return Err("root monkey should have an Expression job".into());
+    };
+    let mut upper: i64 = monkeys.eval_index(if l == human_side { r } else { l }, None)?;
+    let mut cur = human_side;
+
+    while cur != target {
+        let Job::Expression(l, op, r) = *monkeys.job(cur) else {
+            return Err("monkey should have an Expression job".into());
+        };
+        let human_side = *path.next().ok_or("should still have path left")?;
+        let eval = |i| monkeys.eval_index(i, None);
+        upper = match (l == human_side, op) {
+            //upper = l + r, l = upper - r, r = upper - l
+            (true, Op::Add) => upper - eval(r)?,
+            (false, Op::Add) => upper - eval(l)?,
+            // upper = l - r, l = upper + r, r = l - upper
+            (true, Op::Sub) => upper + eval(r)?,
+            (false, Op::Sub) => eval(l)? - upper,
+            // upper = l * r, l = upper / r, r = upper / l
+            (true, Op::Mul) => upper / eval(r)?,
+            (false, Op::Mul) => upper / eval(l)?,
+            // upper = l / r, l = upper * r, r = l / upper
+            (true, Op::Div) => upper * eval(r)?,
+            (false, Op::Div) => eval(l)? / upper,
+        };
+        cur = human_side;
+    }
+    Ok(upper)
+}
+
+// Evaluate both sides of root's expression with the human yelling `humn_value`. For a correct part2
+// answer the two sides are equal. A side that can't be evaluated for `humn_value`, because it
+// overflows or divides by zero, is an AocError::NoSolution.
+pub fn check(r: impl BufRead, humn_value: i64) -> Result<(i64, i64), AocError> {
+    let monkeys = Monkeys::read(r)?;
+    let root = monkeys.index(ROOT).ok_or("no root monkey")?;
+    let Job::Expression(l, _, r) = *monkeys.job(root) else {
+        return Err("root monkey should have an Expression job".into());
+    };
+    Ok((
+        monkeys.eval_with_override(monkeys.name(l), humn_value)?,
+        monkeys.eval_with_override(monkeys.name(r), humn_value)?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32";
+
+    #[test]
+    fn test_monkeys_read_root() {
+        let monkeys = Monkeys::read(EXAMPLE.as_bytes()).unwrap();
+        let job = monkeys.job(monkeys.index("root").unwrap());
+        let &Job::Expression(left, op, right) = job else {
+            panic!("unexpected job: {:?}", job);
+        };
+        assert_eq!(monkeys.name(left), "pppw");
+        assert_eq!(monkeys.name(right), "sjmn");
+        assert_eq!(op, Op::Add);
+    }
+
+    #[test]
+    fn test_monkeys_read_dvpt() {
+        let monkeys = Monkeys::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(monkeys.job(monkeys.index("dvpt").unwrap()), &Job::Constant(3));
+    }
+
+    #[test]
+    fn test_monkeys_read_undefined() {
+        assert!(Monkeys::read("root: pppw + sjmn\npppw: 1".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), 152);
+    }
+
+    #[test]
+    fn test_missing_root() {
+        let input = "pppw: 2\nsjmn: 3";
+        for result in [part1(input.as_bytes()), part2(input.as_bytes()), check(input.as_bytes(), 5).map(|(l, _)| l)] {
+            assert_eq!(result.unwrap_err().to_string(), "no root monkey");
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes()).unwrap(), 301);
+    }
+
+    #[test]
+    fn test_eval_with_override() {
+        let monkeys = Monkeys::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(monkeys.eval_with_override("humn", 5).unwrap(), monkeys.eval("humn").unwrap());
+        assert_eq!(monkeys.eval_with_override("root", 5).unwrap(), 152);
+        assert_eq!(monkeys.eval_with_override("pppw", 301).unwrap(), 150);
+        assert_eq!(monkeys.eval_with_override("sjmn", 301).unwrap(), 150);
+    }
+
+    #[test]
+    fn test_check() {
+        let humn = part2(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(check(EXAMPLE.as_bytes(), humn).unwrap(), (150, 150));
+        let (l, r) = check(EXAMPLE.as_bytes(), humn * 2).unwrap();
+        assert_ne!(l, r);
+    }
+
+    #[test]
+    fn test_check_errors() {
+        // lgvd is 2 * (humn - 3).
+        let err = check(EXAMPLE.as_bytes(), i64::MAX).unwrap_err();
+        assert!(matches!(err, AocError::NoSolution(_)));
+        assert_eq!(err.to_string(), "monkey lgvd's number doesn't fit in an i64");
+
+        let input = "root: pppw + sjmn\npppw: lfqf / humn\nlfqf: 4\nhumn: 2\nsjmn: 2";
+        assert_eq!(check(input.as_bytes(), 2).unwrap(), (2, 2));
+        let err = check(input.as_bytes(), 0).unwrap_err();
+        assert!(matches!(err, AocError::NoSolution(_)));
+        assert_eq!(err.to_string(), "monkey pppw divides by zero");
+    }
+}
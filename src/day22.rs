@@ -0,0 +1,1595 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+use std::io;
+use std::ops::Range;
+use std::str::FromStr;
+
+pub use aoclib::geom::Dir;
+use aoclib::geom::Point2;
+use aoclib::grid::Grid;
+use aoclib::render::{Color, Glyph, NoRender, Scene, Sink};
+use aoclib::trace;
+use crate::error::AocError;
+use crate::runner::phase;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tile {
+    Empty,
+    Open,
+    Wall,
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            Tile::Empty => " ",
+            Tile::Open => ".",
+            Tile::Wall => "#",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+pub struct Board {
+    tiles: Grid<Tile>,
+    // Extent of the non-Empty tiles in each row and column, so wrapping can jump straight to the
+    // opposite edge.
+    row_bounds: Vec<Range<usize>>,
+    col_bounds: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Player {
+    pub dir: Dir,
+    pub pos: Point,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    Forward(usize),
+    TurnRight,
+    TurnLeft,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left, Right, Top, Bottom, Front, Back,
+}
+
+pub struct CubeTopology {
+    side_len: usize,
+    range_for: HashMap<Side, (Range<usize>, Range<usize>)>,
+    neighbor_for: HashMap<(Side, Dir), (Side, Dir)>,
+}
+
+pub type Point = Point2<usize>;
+
+impl Board {
+    pub fn read(s: &str) -> Result<Self, String> {
+        let rows = s.lines().enumerate()
+            .map(|(y, line)| read_row(line, y))
+            .collect::<Result<_, _>>()?;
+        Board::from_rows(rows)
+    }
+
+    // Pad the rows to the same length and find their bounds.
+    fn from_rows(mut rows: Vec<Vec<Tile>>) -> Result<Self, String> {
+        let row_len = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        if row_len == 0 {
+            return Err("board should not be empty".to_string());
+        }
+        for row in rows.iter_mut() {
+            row.resize(row_len, Tile::Empty);
+        }
+        let tiles = Grid::from_rows(rows)?;
+        let mut board = Board { tiles, row_bounds: Vec::new(), col_bounds: Vec::new() };
+        board.row_bounds = (0..board.row_count())
+            .map(|y| board.bounds((0..row_len).map(|x| Point::new(x, y))))
+            .collect::<Result<_, _>>()?;
+        board.col_bounds = (0..row_len)
+            .map(|x| board.bounds((0..board.row_count()).map(|y| Point::new(x, y))))
+            .collect::<Result<_, _>>()?;
+        Ok(board)
+    }
+
+    // Find the range of indexes in `line` that aren't Empty.
+    fn bounds(&self, line: impl Iterator<Item=Point>) -> Result<Range<usize>, String> {
+        let mut bounds: Option<Range<usize>> = None;
+        for (i, p) in line.enumerate() {
+            if self.get(p) == Tile::Empty {
+                continue;
+            }
+            bounds = match bounds {
+                None => Some(i..(i + 1)),
+                Some(b) if b.end == i => Some(b.start..(i + 1)),
+                Some(_) => return Err(format!("gap in the board near {},{}", p.x, p.y)),
+            };
+        }
+        bounds.ok_or_else(|| "board has an empty row or column".to_string())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[Tile]> {
+        self.tiles.rows()
+    }
+
+    pub fn row_len(&self) -> usize {
+        self.tiles.width()
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.tiles.height()
+    }
+
+    fn line(&self, player: &Player) -> Line<'_> {
+        Line { board: self, dir: player.dir, pos: player.pos }
+    }
+
+    // Check that the board could be folded into a cube with the given side length: it needs
+    // exactly six faces' worth of tiles, and every side_len*side_len cell of the lattice has to be
+    // either entirely on or entirely off the board.
+    pub fn validate_net(&self, side_len: usize) -> Result<(), String> {
+        let tile_count = self.tiles.iter().filter(|&&t| t != Tile::Empty).count();
+        if tile_count != 6 * side_len * side_len {
+            return Err(format!(
+                "board has {tile_count} tiles, but a cube with side length {side_len} needs {}",
+                6 * side_len * side_len));
+        }
+        if !self.row_len().is_multiple_of(side_len) || !self.row_count().is_multiple_of(side_len) {
+            return Err(format!(
+                "board dimensions {}x{} aren't multiples of the side length {side_len}",
+                self.row_len(), self.row_count()));
+        }
+        for face_y in (0..self.row_count()).step_by(side_len) {
+            for face_x in (0..self.row_len()).step_by(side_len) {
+                let corner_empty = self.get(Point::new(face_x, face_y)) == Tile::Empty;
+                for y in face_y..(face_y + side_len) {
+                    for x in face_x..(face_x + side_len) {
+                        if (self.get(Point::new(x, y)) == Tile::Empty) != corner_empty {
+                            return Err(format!(
+                                "face at {face_x},{face_y} isn't aligned to the {side_len}x{side_len} lattice: mismatch at {x},{y}"));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Check that the cube's faces are where the board's tiles are: each face is on the board and
+    // covers only tiles, and every tile is on a face. Walking off a face onto anything else would
+    // panic, so this should be checked before walking.
+    pub fn validate_faces(&self, cube: &CubeTopology) -> Result<(), String> {
+        use Side::*;
+        for side in [Left, Right, Top, Bottom, Front, Back] {
+            let Some((x_range, y_range)) = cube.range_for.get(&side) else {
+                return Err(format!("no face for side {side:?}"));
+            };
+            if x_range.end > self.row_len() || y_range.end > self.row_count() {
+                return Err(format!(
+                    "face for side {side:?} at {},{} is off the {}x{} board",
+                    x_range.start, y_range.start, self.row_len(), self.row_count()));
+            }
+            for y in y_range.clone() {
+                for x in x_range.clone() {
+                    if self.get(Point::new(x, y)) == Tile::Empty {
+                        return Err(format!("face for side {side:?} covers {x},{y}, which isn't on the board"));
+                    }
+                }
+            }
+        }
+        for (y, row) in self.rows().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                if tile != Tile::Empty && cube.side(Point::new(x, y)).is_none() {
+                    return Err(format!("tile {x},{y} isn't on any face"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, p: Point) -> Tile {
+        self.tiles[(p.x, p.y)]
+    }
+
+    pub fn start_pos(&self) -> Point {
+        let row = self.rows().next().unwrap();
+        let first_open_tile = row.iter().position(|&t| t == Tile::Open).unwrap();
+        Point::new(first_open_tile, 0)
+    }
+
+    pub fn move_player_part1(&self, player: Player, mv: Move) -> Player {
+        match mv {
+            Move::Forward(n) => self.move_player_forward_wrapping(player, n),
+            Move::TurnLeft => Player::new(player.dir.turn_left(), player.pos),
+            Move::TurnRight => Player::new(player.dir.turn_right(), player.pos),
+        }
+    }
+
+    fn move_player_forward_wrapping(&self, player: Player, n: usize) -> Player {
+        // If the player tries to move into a wall they were already adjacent to, there won't be
+        // anything to take from the iterator and we have to fallback to the original player.
+        self.line(&player)
+            .take_while(|&p| self.get(p) != Tile::Wall)
+            .take(n)
+            .last()
+            .map_or(player, |pos| Player::new(player.dir, pos))
+    }
+
+    pub fn move_player_part2(&self, player: Player, mv: Move, cube: &CubeTopology) -> Player {
+        match mv {
+            Move::Forward(n) => self.move_player_forward_on_cube(player, n, cube),
+            Move::TurnLeft => Player::new(player.dir.turn_left(), player.pos),
+            Move::TurnRight => Player::new(player.dir.turn_right(), player.pos),
+        }
+
+    }
+
+    fn move_player_forward_on_cube(&self, player: Player, n: usize, cube: &CubeTopology) -> Player {
+        cube.ring(player)
+            .filter(|&p| self.get(p.pos) != Tile::Empty)
+            .take_while(|&p| self.get(p.pos) != Tile::Wall)
+            .take(n)
+            .last()
+            .unwrap_or(player)  // Player tried to move into a wall they were already adjacent to.
+    }
+}
+
+// Expand tabs to 8-column tab stops and drop trailing whitespace, including any CR left over from
+// CRLF line endings.
+fn read_row(line: &str, y: usize) -> Result<Vec<Tile>, String> {
+    normalize_board_line(line).chars().enumerate()
+        .map(|(x, c)| match c {
+            ' ' => Ok(Tile::Empty),
+            '.' => Ok(Tile::Open),
+            '#' => Ok(Tile::Wall),
+            _ => Err(format!("unexpected tile {c:?} at {x},{y}")),
+        })
+        .collect()
+}
+
+fn normalize_board_line(line: &str) -> String {
+    let mut normalized = String::new();
+    for c in line.trim_end().chars() {
+        if c == '\t' {
+            let n = 8 - normalized.len() % 8;
+            normalized.extend(std::iter::repeat_n(' ', n));
+        } else {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+struct Line<'a> {
+    board: &'a Board,
+    dir: Dir,
+    pos: Point,
+}
+
+impl<'a> Iterator for Line<'a> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Point { x, y } = self.pos;
+        let row = &self.board.row_bounds[y];
+        let col = &self.board.col_bounds[x];
+        self.pos = match self.dir {
+            Dir::Up => Point::new(x, if y == col.start { col.end - 1 } else { y - 1 }),
+            Dir::Down => Point::new(x, if y == col.end - 1 { col.start } else { y + 1 }),
+            Dir::Right => Point::new(if x == row.end - 1 { row.start } else { x + 1 }, y),
+            Dir::Left => Point::new(if x == row.start { row.end - 1 } else { x - 1 }, y),
+        };
+        Some(self.pos)
+    }
+}
+
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            let row_str: String = row.iter().map(|t| match t {
+                Tile::Empty => ' ',
+                Tile::Open => '.',
+                Tile::Wall => '#',
+            }).collect();
+            writeln!(f, "{}", row_str.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+// The board with the player drawn as an arrow showing which way they're facing, and the tiles
+// they've passed over showing which way they last faced there, like the puzzle description does.
+pub struct PlayerView<'a> {
+    pub board: &'a Board,
+    pub player: Player,
+    pub trail: &'a HashMap<Point, Dir>,
+}
+
+fn arrow(dir: Dir) -> char {
+    match dir {
+        Dir::Up => '^',
+        Dir::Right => '>',
+        Dir::Down => 'v',
+        Dir::Left => '<',
+    }
+}
+
+impl Scene for PlayerView<'_> {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        (0..self.board.row_len() as i64, 0..self.board.row_count() as i64)
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        let p = Point::new(x as usize, y as usize);
+        if p == self.player.pos {
+            return Glyph::colored(arrow(self.player.dir), Color::Red);
+        }
+        if let Some(&dir) = self.trail.get(&p) {
+            return Glyph::colored(arrow(dir), Color::Yellow);
+        }
+        match self.board.get(p) {
+            Tile::Empty => ' '.into(),
+            Tile::Open => '.'.into(),
+            Tile::Wall => Glyph::colored('#', Color::Gray),
+        }
+    }
+
+    fn focus(&self) -> Option<(i64, i64)> {
+        Some((self.player.pos.x as i64, self.player.pos.y as i64))
+    }
+}
+
+impl Player {
+    pub fn new(dir: Dir, pos: Point) -> Self {
+        Player { dir, pos }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({:?} {})", self.dir, self.pos)
+    }
+}
+
+pub struct Moves<'a> {
+    s: &'a str,
+    offset: usize,  // byte offset of `s` in the move line, for error messages
+}
+
+impl<'a> Moves<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Moves { s: s.trim_end(), offset: 0 }
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.s = &self.s[len..];
+        self.offset += len;
+    }
+}
+
+impl<'a> Iterator for Moves<'a> {
+    type Item = Result<Move, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first_char = self.s.as_bytes().first();
+        match first_char {
+            None | Some(b'\n') => None,
+            Some(b'L') => {
+                self.advance(1);
+                Some(Ok(Move::TurnLeft))
+            },
+            Some(b'R') => {
+                self.advance(1);
+                Some(Ok(Move::TurnRight))
+            },
+            Some(c) if c.is_ascii_digit() => {
+                let len = self.s.bytes().take_while(|&c| c.is_ascii_digit()).count();
+                let n = self.s[0..len].parse().map_err(|e| {
+                    format!("bad move count at byte {}: {}: {e}", self.offset, &self.s[0..len])
+                });
+                self.advance(len);
+                Some(n.map(Move::Forward))
+            },
+            Some(_) => {
+                let c = self.s.chars().next().unwrap();
+                let err = format!("unexpected character on move line at byte {}: {c:?}", self.offset);
+                // Stop iterating after an error.
+                self.s = "";
+                Some(Err(err))
+            },
+        }
+    }
+}
+
+impl CubeTopology {
+    pub fn side_len(&self) -> usize {
+        self.side_len
+    }
+
+    // Return the face containing the given point, if any.
+    pub fn side(&self, p: Point) -> Option<Side> {
+        self.range_for.iter().find_map(|(&side, (ref x_range, ref y_range))| {
+            if x_range.contains(&p.x) && y_range.contains(&p.y) {
+                Some(side)
+            } else {
+                None
+            }
+        })
+    }
+
+    // Check that the topology describes a cube: six side_len*side_len faces, and all 24 edges
+    // mapped such that walking back across a seam returns to the side it came from.
+    pub fn validate(&self) -> Result<(), String> {
+        use Side::*;
+        for side in [Left, Right, Top, Bottom, Front, Back] {
+            let Some((x_range, y_range)) = self.range_for.get(&side) else {
+                return Err(format!("no face for side {side:?}"));
+            };
+            if x_range.len() != self.side_len || y_range.len() != self.side_len {
+                return Err(format!("face for side {side:?} isn't {0}x{0}", self.side_len));
+            }
+            let mut dsts: Vec<Side> = Vec::new();
+            for dir in [Dir::Up, Dir::Right, Dir::Down, Dir::Left] {
+                let Some(&(dst_side, dst_dir)) = self.neighbor_for.get(&(side, dir)) else {
+                    return Err(format!("no edge for ({side:?}, {dir:?})"));
+                };
+                if dst_side == side || dsts.contains(&dst_side) {
+                    return Err(format!("({side:?}, {dir:?}) leads to {dst_side:?} more than once"));
+                }
+                dsts.push(dst_side);
+                let back = self.neighbor_for.get(&(dst_side, dst_dir.opposite()));
+                if back != Some(&(side, dir.opposite())) {
+                    return Err(format!(
+                        "({side:?}, {dir:?}) -> ({dst_side:?}, {dst_dir:?}) isn't paired with ({dst_side:?}, {:?}) -> ({side:?}, {:?})",
+                        dst_dir.opposite(), dir.opposite()));
+                }
+            }
+        }
+        if self.range_for.len() != 6 || self.neighbor_for.len() != 24 {
+            return Err("topology should have exactly 6 faces and 24 edges".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn ring(&self, player: Player) -> Ring<'_> {
+        Ring::new(self, player)
+    }
+
+    pub fn next_player(&self, p: Player) -> Player {
+        let (x_mod, y_mod) = (p.pos.x % self.side_len, p.pos.y % self.side_len);
+        let crossing_corner = (x_mod == 0 && p.dir == Dir::Left)
+            || (x_mod == self.side_len - 1 && p.dir == Dir::Right)
+            || (y_mod == 0 && p.dir == Dir::Up)
+            || (y_mod == self.side_len - 1 && p.dir == Dir::Down);
+        trace!("{p} x_mod={x_mod} y_mod={y_mod} cross={crossing_corner}");
+        if crossing_corner {
+            self.across_corner(p)
+        } else {
+            let pos = p.pos.step(p.dir).expect("player should stay on the board");
+            Player::new(p.dir, pos)
+        }
+    }
+
+    // Move the player across the seam they're facing. Panics if the player isn't on a face.
+    pub fn across_corner(&self, p: Player) -> Player {
+        let src_side = self.side(p.pos).expect("player should be on a face");
+        let (dst_side, dst_dir) = self.neighbor_for[&(src_side, p.dir)];
+        let (x_mod, y_mod) = (p.pos.x % self.side_len, p.pos.y % self.side_len);
+        let (x_mod, y_mod) = match p.dir.clockwise_angle(dst_dir) {
+            0 => (x_mod, y_mod),
+            90 => (self.comp(y_mod), x_mod),
+            180 => (self.comp(x_mod), self.comp(y_mod)),
+            270 => (y_mod, self.comp(x_mod)),
+            angle => panic!("unexpected angle: {}", angle),
+        };
+        let (x_range, y_range) = &self.range_for[&dst_side];
+        let (x, y) = match dst_dir {
+            Dir::Up => (x_range.start + x_mod, y_range.end - 1),
+            Dir::Right => (x_range.start, y_range.start + y_mod),
+            Dir::Down => (x_range.start + x_mod, y_range.start),
+            Dir::Left => (x_range.end - 1, y_range.start + y_mod),
+        };
+        trace!(
+            "src_side={src_side:?} src_dir={:?} dst_side={dst_side:?} dst_dir={dst_dir:?} \
+            x_mod={x_mod} y_mod={y_mod} angle={} x_range={x_range:?} y_range={y_range:?}",
+            p.dir, p.dir.clockwise_angle(dst_dir),
+        );
+        Player::new(dst_dir, Point::new(x, y))
+    }
+
+    // side complement
+    fn comp(&self, i: usize) -> usize {
+        self.side_len - 1 - i
+    }
+
+    pub fn example() -> Self {
+        //   1  back
+        // 234  bottom left top
+        //   56 front right
+
+        use Side::*;
+
+        let mut range_for: HashMap<Side, (Range<usize>, Range<usize>)> = HashMap::new();
+        range_for.insert(Back, (8..12, 0..4));
+        range_for.insert(Bottom, (0..4, 4..8));
+        range_for.insert(Left, (4..8, 4..8));
+        range_for.insert(Top, (8..12, 4..8));
+        range_for.insert(Front, (8..12, 8..12));
+        range_for.insert(Right, (12..16, 8..12));
+
+        let mut neighbor_for: HashMap<(Side, Dir), (Side, Dir)> = HashMap::new();
+        neighbor_for.insert((Back, Dir::Up), (Bottom, Dir::Down));
+        neighbor_for.insert((Back, Dir::Right), (Right, Dir::Left));
+        neighbor_for.insert((Back, Dir::Down), (Top, Dir::Down));
+        neighbor_for.insert((Back, Dir::Left), (Left, Dir::Down));
+
+        neighbor_for.insert((Bottom, Dir::Up), (Back, Dir::Down));
+        neighbor_for.insert((Bottom, Dir::Right), (Left, Dir::Right));
+        neighbor_for.insert((Bottom, Dir::Down), (Front, Dir::Up));
+        neighbor_for.insert((Bottom, Dir::Left), (Right, Dir::Up));
+
+        neighbor_for.insert((Left, Dir::Up), (Back, Dir::Right));
+        neighbor_for.insert((Left, Dir::Right), (Top, Dir::Right));
+        neighbor_for.insert((Left, Dir::Down), (Front, Dir::Right));
+        neighbor_for.insert((Left, Dir::Left), (Bottom, Dir::Left));
+
+        neighbor_for.insert((Top, Dir::Up), (Back, Dir::Up));
+        neighbor_for.insert((Top, Dir::Right), (Right, Dir::Down));
+        neighbor_for.insert((Top, Dir::Down), (Front, Dir::Down));
+        neighbor_for.insert((Top, Dir::Left), (Left, Dir::Left));
+
+        neighbor_for.insert((Front, Dir::Up), (Top, Dir::Up));
+        neighbor_for.insert((Front, Dir::Right), (Right, Dir::Right));
+        neighbor_for.insert((Front, Dir::Down), (Bottom, Dir::Up));
+        neighbor_for.insert((Front, Dir::Left), (Left, Dir::Up));
+
+        neighbor_for.insert((Right, Dir::Up), (Top, Dir::Left));
+        neighbor_for.insert((Right, Dir::Right), (Back, Dir::Left));
+        neighbor_for.insert((Right, Dir::Down), (Bottom, Dir::Right));
+        neighbor_for.insert((Right, Dir::Left), (Front, Dir::Left));
+
+        CubeTopology { side_len: 4, range_for, neighbor_for }
+    }
+
+    pub fn part2() -> Self {
+        //  12  back, right
+        //  3   top
+        // 45   front, left
+        // 6    bottom
+
+        use Side::*;
+
+        let mut range_for: HashMap<Side, (Range<usize>, Range<usize>)> = HashMap::new();
+        range_for.insert(Back, (50..100, 0..50));
+        range_for.insert(Right, (100..150, 0..50));
+        range_for.insert(Top, (50..100, 50..100));
+        range_for.insert(Left, (0..50, 100..150));
+        range_for.insert(Front, (50..100, 100..150));
+        range_for.insert(Bottom, (0..50, 150..200));
+
+        let mut neighbor_for: HashMap<(Side, Dir), (Side, Dir)> = HashMap::new();
+        neighbor_for.insert((Back, Dir::Up), (Bottom, Dir::Right));
+        neighbor_for.insert((Back, Dir::Right), (Right, Dir::Right));
+        neighbor_for.insert((Back, Dir::Down), (Top, Dir::Down));
+        neighbor_for.insert((Back, Dir::Left), (Left, Dir::Right));
+
+        neighbor_for.insert((Right, Dir::Up), (Bottom, Dir::Up));
+        neighbor_for.insert((Right, Dir::Right), (Front, Dir::Left));
+        neighbor_for.insert((Right, Dir::Down), (Top, Dir::Left));
+        neighbor_for.insert((Right, Dir::Left), (Back, Dir::Left));
+
+        neighbor_for.insert((Top, Dir::Up), (Back, Dir::Up));
+        neighbor_for.insert((Top, Dir::Right), (Right, Dir::Up));
+        neighbor_for.insert((Top, Dir::Down), (Front, Dir::Down));
+        neighbor_for.insert((Top, Dir::Left), (Left, Dir::Down));
+
+        neighbor_for.insert((Left, Dir::Up), (Top, Dir::Right));
+        neighbor_for.insert((Left, Dir::Right), (Front, Dir::Right));
+        neighbor_for.insert((Left, Dir::Down), (Bottom, Dir::Down));
+        neighbor_for.insert((Left, Dir::Left), (Back, Dir::Right));
+
+        neighbor_for.insert((Front, Dir::Up), (Top, Dir::Up));
+        neighbor_for.insert((Front, Dir::Right), (Right, Dir::Left));
+        neighbor_for.insert((Front, Dir::Down), (Bottom, Dir::Left));
+        neighbor_for.insert((Front, Dir::Left), (Left, Dir::Left));
+
+        neighbor_for.insert((Bottom, Dir::Up), (Left, Dir::Up));
+        neighbor_for.insert((Bottom, Dir::Right), (Front, Dir::Up));
+        neighbor_for.insert((Bottom, Dir::Down), (Right, Dir::Down));
+        neighbor_for.insert((Bottom, Dir::Left), (Back, Dir::Down));
+
+        CubeTopology { side_len: 50, range_for, neighbor_for }
+    }
+
+    // Read a topology description like the one in day22's USAGE. Face positions are given in units of the
+    // side length, so the same description works for any size of net with the same shape.
+    pub fn read(s: &str) -> Result<Self, String> {
+        let mut side_len: Option<usize> = None;
+        let mut range_for: HashMap<Side, (Range<usize>, Range<usize>)> = HashMap::new();
+        let mut lattice_for: HashMap<Side, (usize, usize)> = HashMap::new();
+        let mut neighbor_for: HashMap<(Side, Dir), (Side, Dir)> = HashMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let err = |msg: &str| format!("topology line {lineno}: {msg}: {line:?}");
+            match fields[..] {
+                [] => (),
+                ["side_len", n] => {
+                    side_len = Some(n.parse().map_err(|_| err("bad side length"))?);
+                },
+                ["face", side, col, row] => {
+                    let side: Side = side.parse().map_err(|e: String| err(&e))?;
+                    let col: usize = col.parse().map_err(|_| err("bad face column"))?;
+                    let row: usize = row.parse().map_err(|_| err("bad face row"))?;
+                    if lattice_for.insert(side, (col, row)).is_some() {
+                        return Err(err("duplicate face"));
+                    }
+                },
+                ["edge", src_side, src_dir, dst_side, dst_dir] => {
+                    let src_side: Side = src_side.parse().map_err(|e: String| err(&e))?;
+                    let src_dir: Dir = src_dir.parse().map_err(|e: String| err(&e))?;
+                    let dst_side: Side = dst_side.parse().map_err(|e: String| err(&e))?;
+                    let dst_dir: Dir = dst_dir.parse().map_err(|e: String| err(&e))?;
+                    if neighbor_for.insert((src_side, src_dir), (dst_side, dst_dir)).is_some() {
+                        return Err(err("duplicate edge"));
+                    }
+                },
+                _ => return Err(err("unexpected line")),
+            }
+        }
+        let side_len = side_len.ok_or("topology should give a side_len")?;
+        if side_len == 0 {
+            return Err("side_len should be positive".to_string());
+        }
+        if lattice_for.len() != 6 {
+            return Err(format!("topology should have 6 faces, got {}", lattice_for.len()));
+        }
+        for (side, (col, row)) in lattice_for {
+            let (x, y) = (col * side_len, row * side_len);
+            range_for.insert(side, (x..(x + side_len), y..(y + side_len)));
+        }
+        let cube = CubeTopology { side_len, range_for, neighbor_for };
+        cube.validate()?;
+        Ok(cube)
+    }
+
+    // Rescale the face ranges to a different side length, keeping the net's shape.
+    pub fn with_side_len(self, side_len: usize) -> Self {
+        let old = self.side_len;
+        let scale = |r: &Range<usize>| (r.start / old * side_len)..(r.end / old * side_len);
+        let range_for = self.range_for.iter()
+            .map(|(&side, (x_range, y_range))| (side, (scale(x_range), scale(y_range))))
+            .collect();
+        CubeTopology { side_len, range_for, neighbor_for: self.neighbor_for }
+    }
+}
+
+impl FromStr for Side {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Side::*;
+        match s {
+            "left" => Ok(Left),
+            "right" => Ok(Right),
+            "top" => Ok(Top),
+            "bottom" => Ok(Bottom),
+            "front" => Ok(Front),
+            "back" => Ok(Back),
+            _ => Err(format!("unexpected side: {s}")),
+        }
+    }
+}
+
+// A ring-like path around the perimeter of the given cube.
+pub struct Ring<'a> {
+    cube_topology: &'a CubeTopology,
+    player: Player,
+}
+
+impl<'a> Ring<'a> {
+    fn new(cube_topology: &'a CubeTopology, player: Player) -> Self {
+        Ring { player, cube_topology }
+    }
+}
+
+impl<'a> Iterator for Ring<'a> {
+    type Item = Player;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.player = self.cube_topology.next_player(self.player);
+        Some(self.player)
+    }
+}
+
+// Where a face ends up when the net is folded into a cube with corners at (0,0,0) and
+// (side_len,side_len,side_len). The face's tile (x_mod, y_mod) covers origin + x_mod*u + y_mod*v to
+// origin + (x_mod+1)*u + (y_mod+1)*v, and `w` points into the cube.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub origin: [i64; 3],
+    pub u: [i64; 3],
+    pub v: [i64; 3],
+    pub w: [i64; 3],
+}
+
+impl Frame {
+    // Map a point given in tile units relative to the face's corner into 3D, raised `lift` tiles
+    // off the surface.
+    fn point_3d(&self, x: f64, y: f64, lift: f64) -> [f64; 3] {
+        let mut p = [0.0; 3];
+        for (i, c) in p.iter_mut().enumerate() {
+            *c = self.origin[i] as f64 + x * self.u[i] as f64 + y * self.v[i] as f64
+                - lift * self.w[i] as f64;
+        }
+        p
+    }
+}
+
+fn scaled(a: [i64; 3], n: i64) -> [i64; 3] {
+    [a[0] * n, a[1] * n, a[2] * n]
+}
+
+fn added(a: [i64; 3], b: [i64; 3]) -> [i64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn negated(a: [i64; 3]) -> [i64; 3] {
+    scaled(a, -1)
+}
+
+impl CubeTopology {
+    // Fold the net the way you would with paper: faces that touch on the board are joined along
+    // their shared edge and bent 90 degrees into the cube. This only uses the face positions, so
+    // it's independent of the edge pairings and can be used to check them visually.
+    pub fn fold(&self) -> Result<HashMap<Side, Frame>, String> {
+        let n = self.side_len as i64;
+        let lattice_for: HashMap<(usize, usize), Side> = self.range_for.iter()
+            .map(|(&side, (x_range, y_range))| {
+                ((x_range.start / self.side_len, y_range.start / self.side_len), side)
+            })
+            .collect();
+        let Some((&first_pos, &first_side)) = lattice_for.iter().min_by_key(|(&(x, y), _)| (y, x)) else {
+            return Err("topology has no faces".to_string());
+        };
+
+        let mut frame_for: HashMap<Side, Frame> = HashMap::new();
+        frame_for.insert(first_side, Frame { origin: [0, 0, 0], u: [1, 0, 0], v: [0, 1, 0], w: [0, 0, 1] });
+        let mut stack = vec![first_pos];
+        while let Some((col, row)) = stack.pop() {
+            let f = frame_for[&lattice_for[&(col, row)]];
+            let neighbors = [
+                // Right: shares our x=n edge and bends away from us into the cube.
+                (col.checked_add(1), Some(row), Frame { origin: added(f.origin, scaled(f.u, n)), u: f.w, v: f.v, w: negated(f.u) }),
+                // Left
+                (col.checked_sub(1), Some(row), Frame { origin: added(f.origin, scaled(f.w, n)), u: negated(f.w), v: f.v, w: f.u }),
+                // Down
+                (Some(col), row.checked_add(1), Frame { origin: added(f.origin, scaled(f.v, n)), u: f.u, v: f.w, w: negated(f.v) }),
+                // Up
+                (Some(col), row.checked_sub(1), Frame { origin: added(f.origin, scaled(f.w, n)), u: f.u, v: negated(f.w), w: f.v }),
+            ];
+            for (col, row, frame) in neighbors {
+                let (Some(col), Some(row)) = (col, row) else {
+                    continue;
+                };
+                let Some(&side) = lattice_for.get(&(col, row)) else {
+                    continue;
+                };
+                if let std::collections::hash_map::Entry::Vacant(e) = frame_for.entry(side) {
+                    e.insert(frame);
+                    stack.push((col, row));
+                }
+            }
+        }
+
+        if frame_for.len() != self.range_for.len() {
+            return Err("faces of the net aren't all connected".to_string());
+        }
+        let mut normals: Vec<[i64; 3]> = frame_for.values().map(|f| f.w).collect();
+        normals.sort();
+        normals.dedup();
+        if normals.len() != 6 {
+            return Err("net doesn't fold into a cube: faces overlap".to_string());
+        }
+        Ok(frame_for)
+    }
+}
+
+// Walk the moves on the cube, recording every tile the player visits (and every turn).
+pub fn walk_part2(board: &Board, cube: &CubeTopology, moves: Moves, start: Player) -> Result<Vec<Player>, String> {
+    let mut player = start;
+    let mut path = vec![player];
+    for mv in moves {
+        match mv? {
+            Move::Forward(n) => {
+                for _ in 0..n {
+                    let next = board.move_player_part2(player, Move::Forward(1), cube);
+                    if next == player {
+                        break;  // Hit a wall.
+                    }
+                    player = next;
+                    path.push(player);
+                }
+            },
+            mv => {
+                player = board.move_player_part2(player, mv, cube);
+                path.push(player);
+            },
+        }
+    }
+    Ok(path)
+}
+
+// Write the folded cube, its walls, and the path walked over it as a Wavefront OBJ file. Each face
+// and the walls and path are put in their own groups so they can be styled separately.
+pub fn write_obj(mut w: impl io::Write, board: &Board, cube: &CubeTopology, path: &[Player]) -> Result<(), AocError> {
+    let frame_for = cube.fold()?;
+    let n = cube.side_len;
+    let local = |p: Point| -> Result<(Frame, f64, f64), String> {
+        let side = cube.side(p).ok_or_else(|| format!("{},{} isn't on a face", p.x, p.y))?;
+        Ok((frame_for[&side], (p.x % n) as f64, (p.y % n) as f64))
+    };
+    let mut nvertices = 0;
+    let mut quad = |w: &mut dyn io::Write, frame: &Frame, x: f64, y: f64, size: f64, lift: f64| -> io::Result<()> {
+        for (dx, dy) in [(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)] {
+            let [a, b, c] = frame.point_3d(x + dx, y + dy, lift);
+            writeln!(w, "v {a} {b} {c}")?;
+        }
+        writeln!(w, "f {} {} {} {}", nvertices + 1, nvertices + 2, nvertices + 3, nvertices + 4)?;
+        nvertices += 4;
+        Ok(())
+    };
+
+    writeln!(w, "# day22 cube walk, side length {n}")?;
+    let mut sides: Vec<Side> = frame_for.keys().copied().collect();
+    sides.sort_by_key(|s| format!("{s:?}"));
+    for side in sides {
+        writeln!(w, "g {side:?}")?;
+        quad(&mut w, &frame_for[&side], 0.0, 0.0, n as f64, 0.0)?;
+    }
+
+    writeln!(w, "g walls")?;
+    for (y, row) in board.rows().enumerate() {
+        for (x, &tile) in row.iter().enumerate() {
+            if tile == Tile::Wall {
+                let (frame, x, y) = local(Point::new(x, y))?;
+                quad(&mut w, &frame, x + 0.1, y + 0.1, 0.8, 0.05)?;
+            }
+        }
+    }
+
+    writeln!(w, "g path")?;
+    let mut indexes: Vec<usize> = Vec::new();
+    let mut vertex = |w: &mut dyn io::Write, p: [f64; 3]| -> io::Result<usize> {
+        writeln!(w, "v {} {} {}", p[0], p[1], p[2])?;
+        nvertices += 1;
+        Ok(nvertices)
+    };
+    for (i, player) in path.iter().enumerate() {
+        let (frame, x, y) = local(player.pos)?;
+        let center = frame.point_3d(x + 0.5, y + 0.5, 0.1);
+        // Going over a seam, add a point on the cube's edge so the path doesn't cut the corner.
+        if let Some(prev) = i.checked_sub(1).map(|i| path[i]) {
+            if cube.side(prev.pos) != cube.side(player.pos) {
+                let (frame, x, y) = local(prev.pos)?;
+                let (dx, dy) = match prev.dir {
+                    Dir::Up => (0.5, 0.0),
+                    Dir::Right => (1.0, 0.5),
+                    Dir::Down => (0.5, 1.0),
+                    Dir::Left => (0.0, 0.5),
+                };
+                indexes.push(vertex(&mut w, frame.point_3d(x + dx, y + dy, 0.1))?);
+            }
+        }
+        indexes.push(vertex(&mut w, center)?);
+    }
+    if indexes.len() > 1 {
+        let indexes: Vec<String> = indexes.iter().map(|i| i.to_string()).collect();
+        writeln!(w, "l {}", indexes.join(" "))?;
+    }
+    Ok(())
+}
+
+pub fn export_obj(r: impl BufRead, w: impl io::Write, cube: CubeTopology, start: Start) -> Result<(), AocError> {
+    let (board, moves_str) = read_input(r)?;
+    board.validate_net(cube.side_len)?;
+    board.validate_faces(&cube).map_err(AocError::BadArgs)?;
+    let path = walk_part2(&board, &cube, Moves::new(&moves_str), start.player(&board)?)?;
+    write_obj(w, &board, &cube, &path)
+}
+
+// Where the walk begins. By default that's the first open tile of the first row, facing Right.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Start {
+    pub pos: Option<Point>,
+    pub facing: Option<Dir>,
+}
+
+impl Start {
+    pub fn player(&self, board: &Board) -> Result<Player, String> {
+        let pos = match self.pos {
+            Some(p) if !board.tiles.contains(p.x, p.y) => {
+                return Err(format!("start position {},{} is off the board", p.x, p.y));
+            },
+            Some(p) if board.get(p) != Tile::Open => {
+                return Err(format!("start position {},{} isn't an open tile", p.x, p.y));
+            },
+            Some(p) => p,
+            None => board.start_pos(),
+        };
+        Ok(Player::new(self.facing.unwrap_or(Dir::Right), pos))
+    }
+}
+
+// Read the board a row at a time, and then the move line. Tolerates CRLF line endings, and
+// whitespace on the blank line separating the two sections.
+pub fn read_input(r: impl BufRead) -> Result<(Board, String), AocError> {
+    let mut lines = r.lines();
+    let mut rows = Vec::new();
+    let mut separated = false;
+    for line in lines.by_ref() {
+        let line = line?;
+        if line.trim().is_empty() {
+            if rows.is_empty() {
+                continue;  // Tolerate leading blank lines.
+            }
+            separated = true;
+            break;
+        }
+        rows.push(read_row(&line, rows.len())?);
+    }
+    if !separated {
+        return Err("input should consist of two paragraphs".into());
+    }
+    let board = Board::from_rows(rows)?;
+    for line in lines {
+        let line = line?;
+        if !line.trim().is_empty() {
+            return Ok((board, line.trim().to_string()));
+        }
+    }
+    Ok((board, String::new()))
+}
+
+pub fn part1(r: impl BufRead, start: Start) -> Result<usize, AocError> {
+    part1_render(r, start, &mut NoRender)
+}
+
+// Send the board to `sink` after each move.
+pub fn part1_render(r: impl BufRead, start: Start, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let (board, moves_str) = phase("parse", || read_input(r))?;
+    let moves = Moves::new(&moves_str);
+    let mut player = start.player(&board).map_err(AocError::BadArgs)?;
+    let mut trail = HashMap::from([(player.pos, player.dir)]);
+    for (i, mv) in moves.enumerate() {
+        let mv = mv?;
+        player = follow(player, mv, &mut trail, |p, mv| board.move_player_part1(p, mv));
+        let view = PlayerView { board: &board, player, trail: &trail };
+        sink.frame(format_args!("== Move {}: {mv:?} ==", i + 1), &view)?;
+    }
+    Ok(password(player))
+}
+
+// Make a move with `step` a tile at a time, recording each tile the player passes over in `trail`
+// along with the direction they were facing. Only the rendering needs the trail, so the solvers
+// move in one go instead.
+fn follow(
+    mut player: Player,
+    mv: Move,
+    trail: &mut HashMap<Point, Dir>,
+    step: impl Fn(Player, Move) -> Player,
+) -> Player {
+    let n = match mv {
+        Move::Forward(n) => n,
+        _ => 1,
+    };
+    let mv = match mv {
+        Move::Forward(_) => Move::Forward(1),
+        turn => turn,
+    };
+    for _ in 0..n {
+        let next = step(player, mv);
+        if next == player {
+            break;
+        }
+        player = next;
+        trail.insert(player.pos, player.dir);
+    }
+    player
+}
+
+pub fn password(player: Player) -> usize {
+    (player.pos.y + 1) * 1000 + (player.pos.x + 1) * 4 + match player.dir {
+        Dir::Right => 0,
+        Dir::Down => 1,
+        Dir::Left => 2,
+        Dir::Up => 3,
+    }
+}
+
+pub fn part2(r: impl BufRead, cube: CubeTopology, start: Start) -> Result<usize, AocError> {
+    part2_render(r, cube, start, &mut NoRender)
+}
+
+// Send the board to `sink` after each move.
+pub fn part2_render(r: impl BufRead, cube: CubeTopology, start: Start, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let (board, moves_str) = phase("parse", || read_input(r))?;
+    board.validate_net(cube.side_len)?;
+    board.validate_faces(&cube).map_err(AocError::BadArgs)?;
+    let moves = Moves::new(&moves_str);
+    let mut player = start.player(&board).map_err(AocError::BadArgs)?;
+    let mut trail = HashMap::from([(player.pos, player.dir)]);
+    for (i, mv) in moves.enumerate() {
+        let mv = mv?;
+        player = follow(player, mv, &mut trail, |p, mv| board.move_player_part2(p, mv, &cube));
+        let view = PlayerView { board: &board, player, trail: &trail };
+        sink.frame(format_args!("== Move {}: {mv:?} ==", i + 1), &view)?;
+    }
+    Ok(password(player))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+    use aoclib::rng::Rng;
+
+    const EXAMPLE_MOVES: &str = "10R5L5R10L4R5L5";
+
+    fn example_board() -> &'static str {
+        static BOARD: &str = "
+        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+";
+        BOARD.trim_start_matches('\n')
+    }
+
+
+    #[test]
+    fn test_board_read() {
+        let board = Board::read(example_board()).unwrap();
+        assert_eq!(format!("{board}"), example_board());
+    }
+
+    #[test]
+    fn test_board_bounds() {
+        let board = Board::read(example_board()).unwrap();
+        assert_eq!(board.row_bounds[0], 8..12);
+        assert_eq!(board.row_bounds[4], 0..12);
+        assert_eq!(board.row_bounds[11], 8..16);
+        assert_eq!(board.col_bounds[0], 4..8);
+        assert_eq!(board.col_bounds[8], 0..12);
+        assert_eq!(board.col_bounds[15], 8..12);
+    }
+
+    #[test]
+    fn test_board_start_pos() {
+        let board = Board::read(example_board()).unwrap();
+        assert_eq!(board.start_pos(), Point::new(8, 0));
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut moves = Moves::new(EXAMPLE_MOVES);
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(10))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnRight)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(5))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnLeft)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(5))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnRight)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(10))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnLeft)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(4))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnRight)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(5))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnLeft)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(5))));
+        assert_eq!(moves.next(), None);
+    }
+
+    #[test]
+    fn test_moves_errors() {
+        let mut moves = Moves::new("10R5X3");
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(10))));
+        assert_eq!(moves.next(), Some(Ok(Move::TurnRight)));
+        assert_eq!(moves.next(), Some(Ok(Move::Forward(5))));
+        assert_eq!(moves.next(), Some(Err("unexpected character on move line at byte 4: 'X'".to_string())));
+        assert_eq!(moves.next(), None);
+
+        let mut moves = Moves::new("R99999999999999999999999");
+        assert_eq!(moves.next(), Some(Ok(Move::TurnRight)));
+        assert!(moves.next().unwrap().unwrap_err().contains("at byte 1"));
+    }
+
+    #[test]
+    fn test_moves_random() {
+        let mut rng = Rng::new(22);
+        for _ in 0..200 {
+            let want: Vec<Move> = (0..rng.below(20))
+                .map(|i| match i % 2 {
+                    0 => Move::Forward(rng.below(100)),
+                    _ => *rng.choose(&[Move::TurnLeft, Move::TurnRight]),
+                })
+                .collect();
+            let line: String = want.iter()
+                .map(|m| match m {
+                    Move::Forward(n) => n.to_string(),
+                    Move::TurnLeft => "L".to_string(),
+                    Move::TurnRight => "R".to_string(),
+                })
+                .collect();
+            let got: Result<Vec<Move>, String> = Moves::new(&line).collect();
+            assert_eq!(got.unwrap(), want, "{line}");
+
+            let bad = rng.mutate(&line, "0123456789LRX \n");
+            assert!(Moves::new(&bad).count() <= bad.len());
+        }
+    }
+
+    #[test]
+    fn test_board_read_random() {
+        let mut rng = Rng::new(22);
+        for _ in 0..200 {
+            // Rows grow and then shrink, so that every row and column is contiguous.
+            let mut row = 10..11;
+            let mut rows = Vec::new();
+            let peak = rng.below(6);
+            for y in 0..peak + 1 + rng.below(6) {
+                if y <= peak {
+                    row = row.start - rng.below(3)..row.end + rng.below(3);
+                } else {
+                    let start = (row.start + rng.below(3)).min(row.end - 1);
+                    row = start..(row.end - rng.below(3)).max(start + 1);
+                }
+                rows.push(row.clone());
+            }
+            let left = rows.iter().map(|r| r.start).min().unwrap();
+            let lines: Vec<String> = rows.iter()
+                .map(|r| {
+                    let tiles: String = r.clone().map(|_| *rng.choose(&['.', '#'])).collect();
+                    " ".repeat(r.start - left) + &tiles
+                })
+                .collect();
+            let input = lines.join("\n") + "\n";
+            let board = Board::read(&input).unwrap_or_else(|e| panic!("{e}\n{input}"));
+            assert_eq!(format!("{board}"), input);
+            for (y, line) in lines.iter().enumerate() {
+                let start = line.len() - line.trim_start().len();
+                assert_eq!(board.row_bounds[y], start..line.len(), "{input}");
+            }
+
+            let bad = rng.mutate(&input, ".# \nx");
+            _ = Board::read(&bad);
+        }
+    }
+
+    #[test]
+    fn test_board_read_error() {
+        let err = Board::read("  ..\n  .x\n").err().unwrap();
+        assert_eq!(err, "unexpected tile 'x' at 3,1");
+    }
+
+    #[test]
+    fn test_read_input() {
+        let (board, moves) = read_input("\n..\n.#\n \r\n\n1R2\n".as_bytes()).unwrap();
+        assert_eq!(board.to_string(), "..\n.#\n");
+        assert_eq!(moves, "1R2");
+        let err = |s: &str| read_input(s.as_bytes()).err().unwrap().to_string();
+        assert_eq!(err("  ..\n  .x\n\n1R2\n"), "unexpected tile 'x' at 3,1");
+        assert_eq!(err("  ..\n  ..\n"), "input should consist of two paragraphs");
+    }
+
+    #[test]
+    fn test_board_validate_net() {
+        let board = Board::read(example_board()).unwrap();
+        assert!(board.validate_net(4).is_ok());
+        assert!(board.validate_net(2).is_err());
+        assert!(board.validate_net(50).is_err());
+
+        // Right tile count, but the bottom faces are shifted off the lattice.
+        let mut misaligned: Vec<String> = example_board().lines().map(|l| l.to_string()).collect();
+        for line in misaligned.iter_mut().skip(8) {
+            *line = format!(" {line}");
+        }
+        let board = Board::read(&misaligned.join("\n")).unwrap();
+        assert!(board.validate_net(4).is_err());
+    }
+
+    #[test]
+    fn test_moving_player_part1() {
+        let board = Board::read(example_board()).unwrap();
+        let moves = Moves::new(EXAMPLE_MOVES).map(Result::unwrap);
+        let mut player = Player::new(Dir::Right, board.start_pos());
+        let wants = vec![
+            Player::new(Dir::Right, Point::new(10, 0)),
+            Player::new(Dir::Down, Point::new(10, 0)),
+            Player::new(Dir::Down, Point::new(10, 5)),
+            Player::new(Dir::Right, Point::new(10, 5)),
+            Player::new(Dir::Right, Point::new(3, 5)),
+            Player::new(Dir::Down, Point::new(3, 5)),
+            Player::new(Dir::Down, Point::new(3, 7)),
+            Player::new(Dir::Right, Point::new(3, 7)),
+            Player::new(Dir::Right, Point::new(7, 7)),
+            Player::new(Dir::Down, Point::new(7, 7)),
+            Player::new(Dir::Down, Point::new(7, 5)),
+            Player::new(Dir::Right, Point::new(7, 5)),
+            Player::new(Dir::Right, Point::new(7, 5)),
+        ];
+
+        for (i, (mv, want)) in moves.zip(wants).enumerate() {
+            player = board.move_player_part1(player, mv);
+            assert_eq!(player, want, "mismatch at move {i}: {mv:?}");
+        }
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        assert_eq!(part1(input.as_bytes(), Start::default()).unwrap(), 6032);
+    }
+
+    #[test]
+    fn test_player_view() {
+        let board = Board::read(example_board()).unwrap();
+        let player = Player::new(Dir::Down, board.start_pos());
+        let trail = HashMap::new();
+        let view = PlayerView { board: &board, player, trail: &trail };
+        let drawn = draw(&view, Viewport::UNLIMITED, false);
+        assert_eq!(drawn.lines().next(), Some("        v..#    "));
+        assert_eq!(drawn.lines().count(), 12);
+        // A small viewport stays around the player.
+        assert_eq!(draw(&view, Viewport { cols: 3, rows: 2 }, false), " v.\n .#\n");
+    }
+
+    // Keeps a drawing of the last frame.
+    struct LastFrame(String);
+
+    impl Sink for LastFrame {
+        fn frame(&mut self, _: fmt::Arguments, scene: &dyn Scene) -> io::Result<()> {
+            self.0 = draw(scene, Viewport::UNLIMITED, false);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_part1_render_trail() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        let mut sink = LastFrame(String::new());
+        assert_eq!(part1_render(input.as_bytes(), Start::default(), &mut sink).unwrap(), 6032);
+        // From the puzzle description.
+        let want = "
+        >>v#    
+        .#v.    
+        #.v.    
+        ..v.    
+...#...v..v#    
+>>>v...>#.>>    
+..#v...#....    
+...>>>>v..#.    
+        ...#....
+        .....#..
+        .#......
+        ......#.
+";
+        assert_eq!(sink.0, want.trim_start_matches('\n'));
+    }
+
+    #[test]
+    fn test_part1_crlf() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES).replace('\n', " \r\n");
+        assert_eq!(part1(input.as_bytes(), Start::default()).unwrap(), 6032);
+    }
+
+    #[test]
+    fn test_board_read_tabs() {
+        let tabbed = example_board().replace("        ", "\t");
+        let board = Board::read(&tabbed).unwrap();
+        assert_eq!(format!("{board}"), example_board());
+    }
+
+    #[test]
+    fn test_start_override() {
+        let board = Board::read(example_board()).unwrap();
+        let start = Start { pos: Some(Point::new(3, 5)), facing: Some(Dir::Down) };
+        assert_eq!(start.player(&board).unwrap(), Player::new(Dir::Down, Point::new(3, 5)));
+        let wall = Start { pos: Some(Point::new(11, 0)), facing: None };
+        assert!(wall.player(&board).is_err());
+        let off_board = Start { pos: Some(Point::new(0, 0)), facing: None };
+        assert!(off_board.player(&board).is_err());
+
+        // Replay the tail of the part1 example from the middle of the walk.
+        let input = format!("{}\n{}\n", example_board(), "4R5L5");
+        let start = Start { pos: Some(Point::new(3, 7)), facing: Some(Dir::Right) };
+        assert_eq!(part1(input.as_bytes(), start).unwrap(), 6032);
+    }
+
+    #[test]
+    fn test_dir_angle_between() {
+        assert_eq!(Dir::Up.clockwise_angle(Dir::Up), 0);
+        assert_eq!(Dir::Up.clockwise_angle(Dir::Right), 90);
+        assert_eq!(Dir::Up.clockwise_angle(Dir::Down), 180);
+        assert_eq!(Dir::Up.clockwise_angle(Dir::Left), 270);
+
+        assert_eq!(Dir::Left.clockwise_angle(Dir::Up), 90);
+        assert_eq!(Dir::Left.clockwise_angle(Dir::Right), 180);
+        assert_eq!(Dir::Left.clockwise_angle(Dir::Down), 270);
+        assert_eq!(Dir::Left.clockwise_angle(Dir::Left), 0);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        assert_eq!(part2(input.as_bytes(), CubeTopology::example(), Start::default()).unwrap(), 5031);
+    }
+
+    #[test]
+    fn test_part2_faces_off_net() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        let err = |cube| part2(input.as_bytes(), cube, Start::default()).unwrap_err();
+
+        // The example board has the right side length for the author's layout, but not its shape.
+        let e = err(CubeTopology::part2().with_side_len(4));
+        assert!(matches!(e, AocError::BadArgs(_)));
+        assert_eq!(e.to_string(), "face for side Left covers 0,8, which isn't on the board");
+
+        let mut cube = CubeTopology::example();
+        cube.range_for.insert(Side::Right, (16..20, 8..12));
+        assert_eq!(err(cube).to_string(), "face for side Right at 16,8 is off the 16x12 board");
+
+        // Right is moved up beside top, where there are no tiles.
+        let mut cube = CubeTopology::example();
+        cube.range_for.insert(Side::Right, (12..16, 4..8));
+        let e = err(cube);
+        assert!(matches!(e, AocError::BadArgs(_)));
+        assert_eq!(e.to_string(), "face for side Right covers 12,4, which isn't on the board");
+
+        let mut cube = CubeTopology::example();
+        cube.range_for.insert(Side::Right, (8..12, 8..12));
+        let board = Board::read(example_board()).unwrap();
+        assert_eq!(board.validate_faces(&cube), Err("tile 12,8 isn't on any face".to_string()));
+        let mut obj = Vec::new();
+        assert!(export_obj(input.as_bytes(), &mut obj, cube, Start::default()).is_err());
+    }
+
+    #[test]
+    fn test_moving_player_part2() {
+        let board = Board::read(example_board()).unwrap();
+        let moves = Moves::new(EXAMPLE_MOVES).map(Result::unwrap);
+        let mut player = Player::new(Dir::Right, board.start_pos());
+        let wants = vec![
+            Player::new(Dir::Right, Point::new(10, 0)),
+            Player::new(Dir::Down, Point::new(10, 0)),
+            Player::new(Dir::Down, Point::new(10, 5)),
+            Player::new(Dir::Right, Point::new(10, 5)),
+            Player::new(Dir::Down, Point::new(14, 10)),
+            Player::new(Dir::Left, Point::new(14, 10)),
+            Player::new(Dir::Left, Point::new(10, 10)),
+            Player::new(Dir::Down, Point::new(10, 10)),
+            Player::new(Dir::Up, Point::new(1, 5)),
+            Player::new(Dir::Right, Point::new(1, 5)),
+            Player::new(Dir::Right, Point::new(6, 5)),
+            Player::new(Dir::Up, Point::new(6, 5)),
+            Player::new(Dir::Up, Point::new(6, 4)),
+        ];
+
+        let cube = CubeTopology::example();
+        for (i, (mv, want)) in moves.zip(wants).enumerate() {
+            let new_player = board.move_player_part2(player, mv, &cube);
+            assert_eq!(new_player, want, "mismatch at move {i}: {player} {mv:?}");
+            player = new_player;
+        }
+    }
+
+    const EXAMPLE_TOPOLOGY: &str = "\
+#   1  back
+# 234  bottom left top
+#   56 front right
+side_len 4
+face back 2 0
+face bottom 0 1
+face left 1 1
+face top 2 1
+face front 2 2
+face right 3 2
+
+edge back up bottom down
+edge back right right left
+edge back down top down
+edge back left left down
+edge bottom up back down
+edge bottom right left right
+edge bottom down front up
+edge bottom left right up
+edge left up back right
+edge left right top right
+edge left down front right
+edge left left bottom left
+edge top up back up
+edge top right right down
+edge top down front down
+edge top left left left
+edge front up top up
+edge front right right right
+edge front down bottom up
+edge front left left up
+edge right up top left
+edge right right back left
+edge right down bottom right
+edge right left front left
+";
+
+    #[test]
+    fn test_cube_topology_read() {
+        let cube = CubeTopology::read(EXAMPLE_TOPOLOGY).unwrap();
+        let want = CubeTopology::example();
+        assert_eq!(cube.side_len, want.side_len);
+        assert_eq!(cube.range_for, want.range_for);
+        assert_eq!(cube.neighbor_for, want.neighbor_for);
+
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        assert_eq!(part2(input.as_bytes(), cube, Start::default()).unwrap(), 5031);
+    }
+
+    #[test]
+    fn test_cube_topology_read_errors() {
+        assert!(CubeTopology::read("face back 2 0").is_err());
+        let missing_edge = EXAMPLE_TOPOLOGY.replace("edge right left front left\n", "");
+        assert!(CubeTopology::read(&missing_edge).is_err());
+        let bad_side = EXAMPLE_TOPOLOGY.replace("face right", "face rihgt");
+        assert!(CubeTopology::read(&bad_side).is_err());
+    }
+
+    #[test]
+    fn test_cube_topology_with_side_len() {
+        let cube = CubeTopology::part2().with_side_len(4);
+        assert_eq!(cube.side_len, 4);
+        assert_eq!(cube.range_for[&Side::Back], (4..8, 0..4));
+        assert_eq!(cube.range_for[&Side::Bottom], (0..4, 12..16));
+    }
+
+    #[test]
+    fn test_cube_topology_validate() {
+        assert_eq!(CubeTopology::example().validate(), Ok(()));
+        assert_eq!(CubeTopology::part2().validate(), Ok(()));
+
+        let mut cube = CubeTopology::example();
+        cube.neighbor_for.insert((Side::Back, Dir::Up), (Side::Bottom, Dir::Up));
+        assert!(cube.validate().is_err());
+
+        let mut cube = CubeTopology::example();
+        cube.neighbor_for.remove(&(Side::Front, Dir::Left));
+        assert!(cube.validate().is_err());
+
+        let mut cube = CubeTopology::example();
+        cube.range_for.insert(Side::Front, (8..12, 8..13));
+        assert!(cube.validate().is_err());
+
+        let swapped = EXAMPLE_TOPOLOGY
+            .replace("edge back up bottom down", "edge back up top down")
+            .replace("edge back down top down", "edge back down bottom down");
+        assert!(CubeTopology::read(&swapped).is_err());
+    }
+
+    // Cross every seam from every tile on each edge, then turn around and cross back, which
+    // should return the player to where they started.
+    fn walk_all_seams(cube: &CubeTopology) {
+        use Dir::*;
+        let n = cube.side_len();
+        let mut crossings = 0;
+        for (&side, (x_range, y_range)) in cube.range_for.iter() {
+            for dir in [Up, Right, Down, Left] {
+                for i in 0..n {
+                    let pos = match dir {
+                        Up => Point::new(x_range.start + i, y_range.start),
+                        Right => Point::new(x_range.end - 1, y_range.start + i),
+                        Down => Point::new(x_range.start + i, y_range.end - 1),
+                        Left => Point::new(x_range.start, y_range.start + i),
+                    };
+                    let player = Player::new(dir, pos);
+                    let crossed = cube.next_player(player);
+                    let (dst_side, dst_dir) = cube.neighbor_for[&(side, dir)];
+                    assert_eq!(cube.side(crossed.pos), Some(dst_side), "{player}");
+                    assert_eq!(crossed.dir, dst_dir, "{player}");
+
+                    let turned = Player::new(crossed.dir.opposite(), crossed.pos);
+                    let back = cube.next_player(turned);
+                    assert_eq!(back, Player::new(dir.opposite(), pos), "{player} -> {crossed}");
+                    crossings += 1;
+                }
+            }
+        }
+        assert_eq!(crossings, 24 * n);
+    }
+
+    #[test]
+    fn test_walk_all_seams_example() {
+        walk_all_seams(&CubeTopology::example());
+    }
+
+    #[test]
+    fn test_walk_all_seams_part2() {
+        walk_all_seams(&CubeTopology::part2());
+        walk_all_seams(&CubeTopology::part2().with_side_len(3));
+    }
+
+    #[test]
+    fn test_fold() {
+        for cube in [CubeTopology::example(), CubeTopology::part2()] {
+            let frame_for = cube.fold().unwrap();
+            let n = cube.side_len() as i64;
+            assert_eq!(frame_for.len(), 6);
+            // Corners of every face should be corners of the cube.
+            for frame in frame_for.values() {
+                for (x, y) in [(0, 0), (n, 0), (0, n), (n, n)] {
+                    let p = added(added(frame.origin, scaled(frame.u, x)), scaled(frame.v, y));
+                    assert!(p.iter().all(|&c| c == 0 || c == n), "{frame:?} {p:?}");
+                }
+            }
+            // Faces are paired the same way by the fold and by the topology: the edge a player
+            // crosses is at the same place in 3D on both sides.
+            walk_all_seams(&cube);
+            for (&(side, dir), &(dst_side, _)) in cube.neighbor_for.iter() {
+                let (src, dst) = (frame_for[&side], frame_for[&dst_side]);
+                let edge_mid = |f: Frame, d: Dir| -> [f64; 3] {
+                    let h = n as f64 / 2.0;
+                    match d {
+                        Dir::Up => f.point_3d(h, 0.0, 0.0),
+                        Dir::Right => f.point_3d(n as f64, h, 0.0),
+                        Dir::Down => f.point_3d(h, n as f64, 0.0),
+                        Dir::Left => f.point_3d(0.0, h, 0.0),
+                    }
+                };
+                let mid = edge_mid(src, dir);
+                let shared = [Dir::Up, Dir::Right, Dir::Down, Dir::Left].iter()
+                    .any(|&d| edge_mid(dst, d) == mid);
+                assert!(shared, "{side:?} {dir:?} -> {dst_side:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_overlapping_net() {
+        // Six faces in a row fold onto themselves.
+        let cube = CubeTopology {
+            side_len: 1,
+            range_for: [Side::Left, Side::Right, Side::Top, Side::Bottom, Side::Front, Side::Back]
+                .iter().enumerate().map(|(i, &s)| (s, (i..(i + 1), 0..1))).collect(),
+            neighbor_for: HashMap::new(),
+        };
+        assert!(cube.fold().is_err());
+    }
+
+    #[test]
+    fn test_export_obj() {
+        let input = format!("{}\n{}\n", example_board(), EXAMPLE_MOVES);
+        let mut obj: Vec<u8> = Vec::new();
+        export_obj(input.as_bytes(), &mut obj, CubeTopology::example(), Start::default()).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        let walls = example_board().chars().filter(|&c| c == '#').count();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6 + walls);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("l ")).count(), 1);
+        let nvertices = obj.lines().filter(|l| l.starts_with("v ")).count();
+        let last_index: usize = obj.lines().next_back().unwrap().split(' ').next_back().unwrap().parse().unwrap();
+        assert_eq!(last_index, nvertices);
+    }
+
+    #[test]
+    fn test_ring() {
+        let cube = CubeTopology::example();
+        let player = Player::new(Dir::Right, Point::new(11, 4));
+        assert_eq!(cube.next_player(player), Player::new(Dir::Down, Point::new(15, 8)));
+    }
+}
@@ -0,0 +1,952 @@
+use std::collections::{HashSet, HashMap};
+use std::hash::{BuildHasherDefault, Hasher};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use aoclib::geom::Point2;
+use aoclib::image::{GifEncoder, Image, Rgb};
+use aoclib::render::{Color, Glyph, Scene};
+use crate::error::AocError;
+
+// x increases to the east and y to the south, so elves keep the column and row they have in the
+// input.
+pub type Point = Point2<i64>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dir {
+    N, NE, E, SE, S, SW, W, NW,
+}
+
+impl Dir {
+    // The cardinal direction and its two neighbors, which all need to be free for an elf to propose
+    // moving in that direction.
+    fn sweep(self) -> [Dir; 3] {
+        use Dir::*;
+        match self {
+            N => [N, NE, NW],
+            S => [S, SE, SW],
+            W => [W, NW, SW],
+            E => [E, NE, SE],
+            _ => panic!("elves only propose moving in cardinal directions"),
+        }
+    }
+}
+
+// Which variant of the diffusion rules to simulate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    // Cardinal directions that elves propose moving in, in order of preference for the first
+    // round. Each round the first direction moves to the end.
+    pub order: Vec<Dir>,
+    // How many directions, starting from the front of the order, each elf considers per round.
+    pub ndirs: usize,
+    // Elves don't move if there are no other elves within this many tiles in any direction,
+    // including diagonally.
+    pub alone_radius: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        use Dir::*;
+        Rules { order: vec![N, S, W, E], ndirs: 4, alone_radius: 1 }
+    }
+}
+
+impl Rules {
+    // Parse a direction order like "NSWE".
+    pub fn parse_order(s: &str) -> Result<Vec<Dir>, String> {
+        use Dir::*;
+        let mut order = Vec::new();
+        for c in s.chars() {
+            let dir = match c.to_ascii_uppercase() {
+                'N' => N,
+                'S' => S,
+                'W' => W,
+                'E' => E,
+                _ => return Err(format!("unexpected direction: {c}")),
+            };
+            if order.contains(&dir) {
+                return Err(format!("repeated direction: {c}"));
+            }
+            order.push(dir);
+        }
+        if order.is_empty() {
+            return Err("direction order is empty".to_string());
+        }
+        Ok(order)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ndirs == 0 || self.ndirs > self.order.len() {
+            return Err(format!(
+                "number of directions should be between 1 and {}, got {}",
+                self.order.len(), self.ndirs,
+            ));
+        }
+        Ok(())
+    }
+
+    // The directions to consider in the given round, in order of preference.
+    fn proposals(&self, round: usize) -> impl Iterator<Item=Dir> + Clone + '_ {
+        self.order.iter().copied().cycle().skip(round % self.order.len()).take(self.ndirs)
+    }
+}
+
+fn neighbor(p: Point, dir: Dir) -> Point {
+    use Dir::*;
+    let (dx, dy) = match dir {
+        N => (0, -1),
+        NE => (1, -1),
+        E => (1, 0),
+        SE => (1, 1),
+        S => (0, 1),
+        SW => (-1, 1),
+        W => (-1, 0),
+        NW => (-1, -1),
+    };
+    p + Point::new(dx, dy)
+}
+
+// The default SipHash dominates the sparse backend's running time, and points don't need
+// protection from collision attacks, so mix the coordinates with a multiply instead.
+#[derive(Default)]
+struct PointHasher(u64);
+
+impl Hasher for PointHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_u64(b as u64);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
+    }
+}
+
+type PointSet = HashSet<Point, BuildHasherDefault<PointHasher>>;
+type PointMap<V> = HashMap<Point, V, BuildHasherDefault<PointHasher>>;
+
+pub struct Board {
+    elves: PointSet,
+    round: usize,
+    rules: Rules,
+    // Elves that might move next round. An elf that was alone stays put until some elf moves near
+    // it, so most of the field drops out of this once it settles.
+    active: PointSet,
+    // Buffers for step(), kept to avoid reallocating them every round.
+    next_active: PointSet,
+    count_for: PointMap<u8>,
+    proposed: Vec<(Point, Point)>,
+    moved: Vec<(Point, Point)>,
+}
+
+impl Board {
+    pub fn read(r: impl BufRead) -> Result<Board, AocError> {
+        let mut elves = PointSet::default();
+        for (y, line) in r.lines().enumerate() {
+            let line = line?;
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '.' => (),
+                    '#' => {
+                        elves.insert(Point::new(x as i64, y as i64));
+                    },
+                    c => return Err(format!("unexpected board char: {}", c).into()),
+                };
+            }
+        }
+        Ok(Board {
+            active: elves.clone(),
+            elves,
+            round: 0,
+            rules: Rules::default(),
+            next_active: PointSet::default(),
+            count_for: PointMap::default(),
+            proposed: Vec::new(),
+            moved: Vec::new(),
+        })
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn elves(&self) -> impl Iterator<Item=Point> + '_ {
+        self.elves.iter().copied()
+    }
+
+    // Return a mask of which of the 8 tiles around `elf` have elves, with bit `d as u8` set for
+    // each occupied direction d.
+    fn neighbor_mask(&self, elf: Point) -> u8 {
+        use Dir::*;
+        [N, NE, E, SE, S, SW, W, NW]
+            .iter()
+            .filter(|&&d| self.elves.contains(&neighbor(elf, d)))
+            .fold(0, |mask, &d| mask | 1 << d as u8)
+    }
+
+    fn is_alone(&self, elf: Point) -> bool {
+        let r = self.rules.alone_radius as i64;
+        (-r..=r).all(|dy| (-r..=r).all(|dx| {
+            (dx, dy) == (0, 0) || !self.elves.contains(&(elf + Point::new(dx, dy)))
+        }))
+    }
+
+    // Play a round and return the number of elves that moved.
+    pub fn step(&mut self) -> usize {
+        let mut count_for = std::mem::take(&mut self.count_for);
+        let mut proposed = std::mem::take(&mut self.proposed);
+        let mut moved = std::mem::take(&mut self.moved);
+        let mut next_active = std::mem::take(&mut self.next_active);
+        count_for.clear();
+        proposed.clear();
+        moved.clear();
+        next_active.clear();
+
+        let dir_order = self.rules.proposals(self.round);
+
+        for &elf in self.active.iter() {
+            let mask = self.neighbor_mask(elf);
+            let alone = match self.rules.alone_radius {
+                1 => mask == 0,
+                _ => self.is_alone(elf),
+            };
+            if alone {
+                continue;
+            }
+            next_active.insert(elf);
+            for dir in dir_order.clone() {
+                if dir.sweep().iter().all(|&d| mask & (1 << d as u8) == 0) {
+                    let dst = neighbor(elf, dir);
+                    *count_for.entry(dst).or_insert(0) += 1;
+                    proposed.push((elf, dst));
+                    break;
+                }
+            }
+        }
+
+        for &(elf, dst) in proposed.iter() {
+            if count_for[&dst] == 1 {
+                self.elves.remove(&elf);
+                self.elves.insert(dst);
+                next_active.remove(&elf);
+                moved.push((elf, dst));
+            }
+        }
+
+        // Wake up any elf that might see a different neighborhood next round. Proposals only look
+        // one tile away, so that's the minimum radius to check even if the alone radius is less.
+        let r = self.rules.alone_radius.max(1) as i64;
+        for &(src, dst) in moved.iter() {
+            // The source and destination are adjacent, so cover both with one rectangle.
+            for y in (src.y.min(dst.y) - r)..=(src.y.max(dst.y) + r) {
+                for x in (src.x.min(dst.x) - r)..=(src.x.max(dst.x) + r) {
+                    let q = Point::new(x, y);
+                    if self.elves.contains(&q) {
+                        next_active.insert(q);
+                    }
+                }
+            }
+        }
+
+        let nmoved = moved.len();
+        self.next_active = std::mem::replace(&mut self.active, next_active);
+        self.count_for = count_for;
+        self.proposed = proposed;
+        self.moved = moved;
+        self.round += 1;
+        nmoved
+    }
+
+    // Return the smallest (x_range, y_range) containing all the elves, or None if there aren't
+    // any.
+    pub fn bounding_box(&self) -> Option<(Range<i64>, Range<i64>)> {
+        let first = self.elves.iter().next()?;
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = (first.x, first.x, first.y, first.y);
+        for elf in self.elves.iter() {
+            min_x = min_x.min(elf.x);
+            min_y = min_y.min(elf.y);
+            max_x = max_x.max(elf.x);
+            max_y = max_y.max(elf.y);
+        }
+        Some((min_x..max_x + 1, min_y..max_y + 1))
+    }
+
+    // Return the number of empty tiles in the bounding box.
+    pub fn empty_count(&self) -> usize {
+        bounding_box_area(self.bounding_box()) - self.elves.len()
+    }
+}
+
+fn bounding_box_area(bounds: Option<(Range<i64>, Range<i64>)>) -> usize {
+    bounds.map_or(0, |(x_range, y_range)| {
+        ((x_range.end - x_range.start) * (y_range.end - y_range.start)) as usize
+    })
+}
+
+// A simulation of the elves' diffusion, so the different representations of the board can be
+// swapped for each other.
+pub trait Elves: fmt::Display + Scene {
+    // Play a round and return the number of elves that moved.
+    fn step(&mut self) -> usize;
+    fn round(&self) -> usize;
+    fn bounding_box(&self) -> Option<(Range<i64>, Range<i64>)>;
+    fn empty_count(&self) -> usize;
+    fn elves(&self) -> Box<dyn Iterator<Item=Point> + '_>;
+
+    fn bounding_box_area(&self) -> usize {
+        bounding_box_area(self.bounding_box())
+    }
+}
+
+impl Elves for Board {
+    fn step(&mut self) -> usize {
+        Board::step(self)
+    }
+
+    fn round(&self) -> usize {
+        self.round
+    }
+
+    fn bounding_box(&self) -> Option<(Range<i64>, Range<i64>)> {
+        Board::bounding_box(self)
+    }
+
+    fn empty_count(&self) -> usize {
+        Board::empty_count(self)
+    }
+
+    fn elves(&self) -> Box<dyn Iterator<Item=Point> + '_> {
+        Box::new(Board::elves(self))
+    }
+}
+
+// Dense representation of the board as rows of bits, where bit x of a row is set if there's an elf
+// there. Rows go from north to south. Instead of looking up each elf's neighbors individually,
+// whole rows are shifted and combined with bitwise ops to find all the elves that can move in a
+// given direction at once.
+pub struct DenseBoard {
+    rows: Vec<Vec<u64>>,
+    nwords: usize,
+    // Position of bit 0 of the first row.
+    origin: Point,
+    round: usize,
+    rules: Rules,
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// The value of word k of `row` if every bit was moved one to the east, so bit x holds the elf at
+// x-1: for each position, whether there's an elf to its west.
+fn west(row: &[u64], k: usize) -> u64 {
+    let carry = if k == 0 { 0 } else { row[k - 1] >> (WORD_BITS - 1) };
+    (row[k] << 1) | carry
+}
+
+// Like west(), but bit x holds the elf at x+1.
+fn east(row: &[u64], k: usize) -> u64 {
+    let carry = if k + 1 == row.len() { 0 } else { row[k + 1] << (WORD_BITS - 1) };
+    (row[k] >> 1) | carry
+}
+
+fn shifted(row: &[u64], shift: fn(&[u64], usize) -> u64) -> Vec<u64> {
+    (0..row.len()).map(|k| shift(row, k)).collect()
+}
+
+impl DenseBoard {
+    // How much empty space to add when the elves get near the edge of the grid.
+    const ROW_PADDING: usize = 16;
+
+    pub fn from_board(board: &Board) -> Self {
+        let (x_range, y_range) = board.bounding_box().unwrap_or((0..0, 0..0));
+        let pad = Self::ROW_PADDING;
+        let width = (x_range.end - x_range.start) as usize + 2 * pad;
+        let nwords = width.div_ceil(WORD_BITS);
+        let mut rows = vec![vec![0u64; nwords]; (y_range.end - y_range.start) as usize + 2 * pad];
+        let origin = Point::new(x_range.start - pad as i64, y_range.start - pad as i64);
+        for elf in board.elves.iter() {
+            let y = (elf.y - origin.y) as usize;
+            let x = (elf.x - origin.x) as usize;
+            rows[y][x / WORD_BITS] |= 1 << (x % WORD_BITS);
+        }
+        DenseBoard { rows, nwords, origin, round: board.round, rules: board.rules.clone() }
+    }
+
+    // Make sure there's at least one empty row and column around the elves, so they have
+    // somewhere to move and the shifts in step() don't lose anyone off the edge.
+    fn ensure_margin(&mut self) {
+        let h = self.rows.len();
+        if self.rows[0].iter().any(|&w| w != 0) {
+            let padding = vec![vec![0u64; self.nwords]; Self::ROW_PADDING];
+            self.rows.splice(0..0, padding);
+            self.origin.y -= Self::ROW_PADDING as i64;
+        }
+        if self.rows[h - 1].iter().any(|&w| w != 0) {
+            let nwords = self.nwords;
+            self.rows.extend((0..Self::ROW_PADDING).map(|_| vec![0u64; nwords]));
+        }
+        if self.rows.iter().any(|row| row[0] & 1 != 0) {
+            for row in self.rows.iter_mut() {
+                row.insert(0, 0);
+            }
+            self.nwords += 1;
+            self.origin.x -= WORD_BITS as i64;
+        }
+        if self.rows.iter().any(|row| row[self.nwords - 1] >> (WORD_BITS - 1) != 0) {
+            for row in self.rows.iter_mut() {
+                row.push(0);
+            }
+            self.nwords += 1;
+        }
+    }
+
+    // For each row, a mask of the elves that have another elf within `radius` tiles.
+    fn crowded(&self, radius: usize) -> Vec<Vec<u64>> {
+        let h = self.rows.len();
+        let dilate = |row: &[u64]| {
+            let mut acc = vec![0u64; self.nwords];
+            let (mut w, mut e) = (row.to_vec(), row.to_vec());
+            for _ in 0..radius {
+                w = shifted(&w, west);
+                e = shifted(&e, east);
+                for k in 0..self.nwords {
+                    acc[k] |= w[k] | e[k];
+                }
+            }
+            acc
+        };
+        (0..h)
+            .map(|y| {
+                // Elves in the rows above and below, including straight above and below.
+                let mut vertical = vec![0u64; self.nwords];
+                for other in y.saturating_sub(radius)..(y + radius + 1).min(h) {
+                    if other != y {
+                        for (v, &w) in vertical.iter_mut().zip(self.rows[other].iter()) {
+                            *v |= w;
+                        }
+                    }
+                }
+                let around = dilate(&vertical);
+                let beside = dilate(&self.rows[y]);
+                (0..self.nwords)
+                    .map(|k| self.rows[y][k] & (vertical[k] | around[k] | beside[k]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn step(&mut self) -> usize {
+        self.ensure_margin();
+        let h = self.rows.len();
+        let nwords = self.nwords;
+        let zero = vec![0u64; nwords];
+
+        // Proposals for each direction, indexed [N, S, W, E][y][word]
+        let mut proposed: [Vec<Vec<u64>>; 4] = core::array::from_fn(|_| vec![zero.clone()]);
+        let dir_order = self.rules.proposals(self.round).map(|d| match d {
+            Dir::N => 0,
+            Dir::S => 1,
+            Dir::W => 2,
+            Dir::E => 3,
+            _ => panic!("elves only propose moving in cardinal directions"),
+        });
+        let crowded = self.crowded(self.rules.alone_radius);
+        for (y, rows) in self.rows.windows(3).enumerate().map(|(i, rows)| (i + 1, rows)) {
+            let (n, c, s) = (&rows[0], &rows[1], &rows[2]);
+            let mut row_proposed: [Vec<u64>; 4] = core::array::from_fn(|_| zero.clone());
+            for k in 0..nwords {
+                let (wn, wc, ws) = (west(n, k), west(c, k), west(s, k));
+                let (en, ec, es) = (east(n, k), east(c, k), east(s, k));
+                let free = [
+                    !(n[k] | wn | en),
+                    !(s[k] | ws | es),
+                    !(wn | wc | ws),
+                    !(en | ec | es),
+                ];
+                let mut remaining = crowded[y][k];
+                for d in dir_order.clone() {
+                    let p = remaining & free[d];
+                    row_proposed[d][k] = p;
+                    remaining &= !p;
+                }
+            }
+            for (d, row) in row_proposed.into_iter().enumerate() {
+                proposed[d].push(row);
+            }
+        }
+        for dir_proposed in proposed.iter_mut() {
+            dir_proposed.push(zero.clone());
+        }
+
+        // Two elves can only propose the same spot if they're approaching it from opposite
+        // directions, since an elf blocks any perpendicular proposal into a spot next to it.
+        let [north, south, west_, east_] = &proposed;
+        let mut new_rows = self.rows.clone();
+        let mut nmoved = 0;
+        for y in 1..(h - 1) {
+            let south_from_above = if y >= 2 { &south[y - 2] } else { &zero };
+            let north_from_below = if y + 2 < h { &north[y + 2] } else { &zero };
+            let west_twice = shifted(&shifted(&east_[y], west), west);
+            let east_twice = shifted(&shifted(&west_[y], east), east);
+            let moved_n: Vec<u64> = (0..nwords).map(|k| north[y][k] & !south_from_above[k]).collect();
+            let moved_s: Vec<u64> = (0..nwords).map(|k| south[y][k] & !north_from_below[k]).collect();
+            let moved_w: Vec<u64> = (0..nwords).map(|k| west_[y][k] & !west_twice[k]).collect();
+            let moved_e: Vec<u64> = (0..nwords).map(|k| east_[y][k] & !east_twice[k]).collect();
+            for k in 0..nwords {
+                let moved = moved_n[k] | moved_s[k] | moved_w[k] | moved_e[k];
+                nmoved += moved.count_ones() as usize;
+                new_rows[y][k] &= !moved;
+                new_rows[y - 1][k] |= moved_n[k];
+                new_rows[y + 1][k] |= moved_s[k];
+                new_rows[y][k] |= west(&moved_e, k) | east(&moved_w, k);
+            }
+        }
+        self.rows = new_rows;
+        self.round += 1;
+        nmoved
+    }
+
+    // Return the bounding box of the elves as (x_range, y_range) in grid coordinates.
+    fn ranges(&self) -> Option<(Range<usize>, Range<usize>)> {
+        let occupied: Vec<usize> = (0..self.rows.len())
+            .filter(|&y| self.rows[y].iter().any(|&w| w != 0))
+            .collect();
+        let (&min_y, &max_y) = (occupied.first()?, occupied.last()?);
+        let mut min_x = usize::MAX;
+        let mut max_x = 0;
+        for row in self.rows.iter() {
+            for (k, &word) in row.iter().enumerate() {
+                if word == 0 {
+                    continue;
+                }
+                min_x = min_x.min(k * WORD_BITS + word.trailing_zeros() as usize);
+                max_x = max_x.max(k * WORD_BITS + (WORD_BITS - 1 - word.leading_zeros() as usize));
+            }
+        }
+        Some((min_x..(max_x + 1), min_y..(max_y + 1)))
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        self.rows.get(y)
+            .and_then(|row| row.get(x / WORD_BITS))
+            .is_some_and(|word| word & (1 << (x % WORD_BITS)) != 0)
+    }
+
+    pub fn bounding_box(&self) -> Option<(Range<i64>, Range<i64>)> {
+        let (x_range, y_range) = self.ranges()?;
+        let (x0, y0) = (self.origin.x, self.origin.y);
+        Some((
+            (x0 + x_range.start as i64)..(x0 + x_range.end as i64),
+            (y0 + y_range.start as i64)..(y0 + y_range.end as i64),
+        ))
+    }
+
+    pub fn empty_count(&self) -> usize {
+        let nelves: usize = self.rows.iter()
+            .flat_map(|row| row.iter())
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        Elves::bounding_box_area(self) - nelves
+    }
+
+    pub fn elves(&self) -> impl Iterator<Item=Point> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(y, row)| {
+            row.iter().enumerate().flat_map(move |(k, &word)| {
+                (0..WORD_BITS)
+                    .filter(move |i| word & (1 << i) != 0)
+                    .map(move |i| Point::new(
+                        self.origin.x + (k * WORD_BITS + i) as i64,
+                        self.origin.y + y as i64,
+                    ))
+            })
+        })
+    }
+}
+
+impl fmt::Display for DenseBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((x_range, y_range)) = self.ranges() else {
+            return Ok(());
+        };
+        // Use the same margins as Board.
+        for y in (y_range.start as i64 - 2)..(y_range.end as i64 + 2) {
+            for x in (x_range.start as i64 - 3)..(x_range.end as i64 + 3) {
+                let elf = x >= 0 && y >= 0 && self.contains(x as usize, y as usize);
+                write!(f, "{}", if elf { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Elves for DenseBoard {
+    fn step(&mut self) -> usize {
+        DenseBoard::step(self)
+    }
+
+    fn round(&self) -> usize {
+        self.round
+    }
+
+    fn bounding_box(&self) -> Option<(Range<i64>, Range<i64>)> {
+        DenseBoard::bounding_box(self)
+    }
+
+    fn empty_count(&self) -> usize {
+        DenseBoard::empty_count(self)
+    }
+
+    fn elves(&self) -> Box<dyn Iterator<Item=Point> + '_> {
+        Box::new(DenseBoard::elves(self))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Sparse,
+    Dense,
+}
+
+impl Backend {
+    pub fn read(self, r: impl BufRead, rules: &Rules) -> Result<Box<dyn Elves>, AocError> {
+        rules.validate()?;
+        let board = Board::read(r)?.with_rules(rules.clone());
+        Ok(match self {
+            Backend::Sparse => Box::new(board),
+            Backend::Dense => Box::new(DenseBoard::from_board(&board)),
+        })
+    }
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sparse" => Ok(Backend::Sparse),
+            "dense" => Ok(Backend::Dense),
+            _ => Err(format!("unexpected backend: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((x_range, y_range)) = self.bounding_box() else {
+            return Ok(());
+        };
+        for y in (y_range.start - 2)..(y_range.end + 2) {
+            for x in (x_range.start - 3)..(x_range.end + 3) {
+                let c = match self.elves.contains(&Point::new(x, y)) {
+                    true => '#',
+                    false => '.',
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Both boards are drawn with the same margins around the elves as their Display impls.
+fn extent_with_margins(bounds: Option<(Range<i64>, Range<i64>)>) -> (Range<i64>, Range<i64>) {
+    match bounds {
+        Some((xs, ys)) => ((xs.start - 3)..(xs.end + 3), (ys.start - 2)..(ys.end + 2)),
+        None => (0..0, 0..0),
+    }
+}
+
+fn elf_glyph(elf: bool) -> Glyph {
+    if elf { Glyph::colored('#', Color::Green) } else { '.'.into() }
+}
+
+impl Scene for Board {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        extent_with_margins(self.bounding_box())
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        elf_glyph(self.elves.contains(&Point::new(x, y)))
+    }
+}
+
+impl Scene for DenseBoard {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        extent_with_margins(self.bounding_box())
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        let (x, y) = (x - self.origin.x, y - self.origin.y);
+        elf_glyph(x >= 0 && y >= 0 && self.contains(x as usize, y as usize))
+    }
+}
+
+// Colors for the GIF: background, then elves.
+const GIF_PALETTE: [Rgb; 2] = [[16, 24, 32], [60, 200, 90]];
+// Hundredths of a second to show each frame, and the last one.
+const GIF_DELAY: u16 = 8;
+const GIF_FINAL_DELAY: u16 = 300;
+
+// Write an animated GIF with a frame for each set of elf positions. Every frame covers the
+// bounding box of all the frames, so the view stays put while the elves spread out.
+pub fn write_gif(w: impl Write, frames: &[Vec<Point>]) -> io::Result<()> {
+    let mut elves = frames.iter().flatten();
+    let Some(first) = elves.next() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no elves to draw"));
+    };
+    let (mut min, mut max) = (*first, *first);
+    for elf in elves {
+        min = Point::new(min.x.min(elf.x), min.y.min(elf.y));
+        max = Point::new(max.x.max(elf.x), max.y.max(elf.y));
+    }
+    // Leave a tile of margin, and make small boards big enough to see.
+    let margin = 1;
+    let cols = (max.x - min.x + 1 + 2 * margin) as usize;
+    let rows = (max.y - min.y + 1 + 2 * margin) as usize;
+    let scale = (512 / cols.max(rows)).clamp(1, 16);
+
+    let mut gif = GifEncoder::new(w, cols * scale, rows * scale, &GIF_PALETTE)?;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut image = Image::new(cols * scale, rows * scale);
+        for elf in frame {
+            let x = (elf.x - min.x + margin) as usize;
+            let y = (elf.y - min.y + margin) as usize;
+            image.fill_cell(x, y, scale, 1);
+        }
+        let delay = if i + 1 == frames.len() { GIF_FINAL_DELAY } else { GIF_DELAY };
+        gif.add_frame(&image, delay)?;
+    }
+    gif.finish()?;
+    Ok(())
+}
+
+pub const PART1_ROUNDS: usize = 10;
+
+// Play `rounds` rounds and return the number of empty tiles in the bounding box. `after_round` is
+// called with the board and the number of elves that moved at the end of each round.
+pub fn part1(
+    board: &mut dyn Elves,
+    rounds: usize,
+    mut after_round: impl FnMut(&dyn Elves, usize),
+) -> usize {
+    for _ in 0..rounds {
+        let nmoved = board.step();
+        after_round(board, nmoved);
+    }
+    board.empty_count()
+}
+
+// Return the first round in which no elves move.
+pub fn part2(
+    board: &mut dyn Elves,
+    mut after_round: impl FnMut(&dyn Elves, usize),
+) -> Result<usize, AocError> {
+    let max_rounds = 1_000_000;
+    for _ in 0..max_rounds {
+        let nmoved = board.step();
+        after_round(board, nmoved);
+        if nmoved == 0 {
+            return Ok(board.round());
+        }
+    }
+    Err(AocError::NoSolution(format!("Elves still moving after round {}", max_rounds)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+
+    const EXAMPLE: &str = "\
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..";
+
+    fn read(backend: Backend, input: &str, rules: &Rules) -> Box<dyn Elves> {
+        backend.read(input.as_bytes(), rules).unwrap()
+    }
+
+    #[test]
+    fn test_part1() {
+        for backend in [Backend::Sparse, Backend::Dense] {
+            let mut board = read(backend, EXAMPLE, &Rules::default());
+            assert_eq!(part1(board.as_mut(), PART1_ROUNDS, |_, _| ()), 110, "{backend:?}");
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        for backend in [Backend::Sparse, Backend::Dense] {
+            let mut board = read(backend, EXAMPLE, &Rules::default());
+            let mut nmoved = Vec::new();
+            assert_eq!(part2(board.as_mut(), |_, n| nmoved.push(n)).unwrap(), 20, "{backend:?}");
+            assert_eq!(nmoved.len(), 20);
+            assert_eq!(nmoved.last(), Some(&0));
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut board = Board::read(".#.\n##.\n...".as_bytes()).unwrap();
+        assert_eq!(board.bounding_box(), Some((0..2, 0..2)));
+        assert_eq!(board.empty_count(), 1);
+        let mut elves: Vec<Point> = board.elves().collect();
+        elves.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(elves, vec![Point::new(1, 0), Point::new(0, 1), Point::new(1, 1)]);
+
+        // Going north means decreasing y.
+        assert_eq!(board.step(), 3);
+        assert_eq!(board.round(), 1);
+        let mut elves: Vec<Point> = board.elves().collect();
+        elves.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(elves, vec![Point::new(1, -1), Point::new(0, 2), Point::new(1, 2)]);
+
+        assert_eq!(Board::read("".as_bytes()).unwrap().bounding_box(), None);
+        assert_eq!(Board::read("".as_bytes()).unwrap().empty_count(), 0);
+    }
+
+    #[test]
+    fn test_display_backends_agree() {
+        let mut sparse = read(Backend::Sparse, EXAMPLE, &Rules::default());
+        let mut dense = read(Backend::Dense, EXAMPLE, &Rules::default());
+        for _ in 0..3 {
+            assert_eq!(sparse.to_string(), dense.to_string());
+            assert_eq!(draw(sparse.as_ref(), Viewport::UNLIMITED, false), sparse.to_string());
+            assert_eq!(draw(dense.as_ref(), Viewport::UNLIMITED, false), dense.to_string());
+            sparse.step();
+            dense.step();
+        }
+    }
+
+    #[test]
+    fn test_shifts() {
+        let row = [1 << 63, 1];
+        assert_eq!(shifted(&row, west), vec![0, 0b11]);
+        assert_eq!(shifted(&row, east), vec![(1 << 62) | (1 << 63), 0]);
+    }
+
+    // Scatter elves pseudo-randomly over a square board.
+    fn random_board(size: usize, seed: u64) -> String {
+        let mut state = seed;
+        let mut s = String::new();
+        for _ in 0..size {
+            for _ in 0..size {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                s.push(if (state >> 33).is_multiple_of(3) { '#' } else { '.' });
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    fn sorted_elves(board: &dyn Elves) -> Vec<Point> {
+        let mut elves: Vec<Point> = board.elves().collect();
+        elves.sort_by_key(|p| (p.y, p.x));
+        elves
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let input = random_board(40, 23);
+        let mut sparse = read(Backend::Sparse, &input, &Rules::default());
+        let mut dense = read(Backend::Dense, &input, &Rules::default());
+        assert_eq!(sorted_elves(sparse.as_ref()), sorted_elves(dense.as_ref()));
+        for round in 0..100 {
+            assert_eq!(sparse.step(), dense.step(), "round {round}");
+            assert_eq!(sparse.bounding_box(), dense.bounding_box(), "round {round}");
+            assert_eq!(sparse.empty_count(), dense.empty_count(), "round {round}");
+        }
+        assert_eq!(sorted_elves(sparse.as_ref()), sorted_elves(dense.as_ref()));
+    }
+
+    #[test]
+    fn test_variant_backends_agree() {
+        let input = random_board(30, 5);
+        let variants = [
+            Rules { order: Rules::parse_order("WE").unwrap(), ndirs: 2, alone_radius: 1 },
+            Rules { order: Rules::parse_order("ENSW").unwrap(), ndirs: 3, alone_radius: 2 },
+            Rules { order: Rules::parse_order("S").unwrap(), ndirs: 1, alone_radius: 0 },
+        ];
+        for rules in variants {
+            let mut sparse = read(Backend::Sparse, &input, &rules);
+            let mut dense = read(Backend::Dense, &input, &rules);
+            for round in 0..30 {
+                assert_eq!(sparse.step(), dense.step(), "{rules:?} round {round}");
+                assert_eq!(sparse.to_string(), dense.to_string(), "{rules:?} round {round}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_settled_elves_inactive() {
+        let mut board = Board::read(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(board.active.len(), board.elves.len());
+        while board.step() > 0 {}
+        let alone = board.elves.iter().filter(|&&elf| board.is_alone(elf)).count();
+        assert!(alone > 0);
+        assert_eq!(board.active.len(), board.elves.len() - alone);
+    }
+
+    #[test]
+    fn test_rules_parse() {
+        use Dir::*;
+        assert_eq!(Rules::parse_order("nswe").unwrap(), vec![N, S, W, E]);
+        assert!(Rules::parse_order("NN").is_err());
+        assert!(Rules::parse_order("NX").is_err());
+        assert!(Rules::parse_order("").is_err());
+        assert!(Rules { ndirs: 5, ..Rules::default() }.validate().is_err());
+        let rules = Rules { ndirs: 2, ..Rules::default() };
+        assert_eq!(rules.proposals(3).collect::<Vec<_>>(), vec![E, N]);
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let mut board = Board::read(EXAMPLE.as_bytes()).unwrap();
+        let mut frames = vec![board.elves().collect::<Vec<_>>()];
+        while board.step() > 0 {
+            frames.push(board.elves().collect());
+        }
+        let mut gif = Vec::new();
+        write_gif(&mut gif, &frames).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        // Over the whole run the elves cover 14x12 tiles, so with the margin and scaling each tile
+        // to 16x16 pixels the canvas is 256x224.
+        assert_eq!(&gif[6..10], &[0, 1, 224, 0]);
+        // One graphic control extension per frame.
+        assert_eq!(gif.windows(3).filter(|w| w == &[0x21, 0xf9, 4]).count(), frames.len());
+        assert!(write_gif(&mut Vec::new(), &[]).is_err());
+    }
+
+    // Run with `cargo test --release --lib day23 -- --ignored --nocapture`.
+    #[test] #[ignore]
+    fn bench_backends() {
+        let input = random_board(100, 7);
+        for backend in [Backend::Sparse, Backend::Dense] {
+            let start = std::time::Instant::now();
+            let mut board = read(backend, &input, &Rules::default());
+            let rounds = part2(board.as_mut(), |_, _| ()).unwrap();
+            println!("{backend:?}: {rounds} rounds in {:?}", start.elapsed());
+        }
+    }
+}
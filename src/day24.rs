@@ -0,0 +1,1342 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::ops::{AddAssign, Range};
+use std::sync::Arc;
+
+use aoclib::checkpoint::{self, Checkpoint, Checkpointer};
+pub use aoclib::geom::Dir;
+use aoclib::geom::Point2;
+use aoclib::parallel;
+use aoclib::progress::Progress;
+use aoclib::render::{Color, Glyph, Scene};
+use crate::error::AocError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tile {
+    Wall,
+    Open,
+    Blizzard,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Blizzard {
+    start: Point,
+    dir: Dir,
+}
+
+impl Blizzard {
+    fn new(start: Point, dir: Dir) -> Self {
+        Self { start, dir }
+    }
+}
+
+pub type Point = Point2<usize>;
+
+pub struct Board {
+    width: usize,  // including walls
+    height: usize,  // including walls
+    row_blizzards: Vec<Vec<Blizzard>>,
+    col_blizzards: Vec<Vec<Blizzard>>,
+    start_pos: Point,
+    end_pos: Point,
+    // Blizzards return to their starting positions after this many rounds.
+    period: usize,
+    // Words per row of `horizontal` and `vertical`.
+    nwords: usize,
+    // Blizzard occupancy for each row, as bitsets of the open part of the row, so bit i is set if
+    // there's a blizzard at x=i+1. The blizzards going right or left repeat every open width
+    // rounds, and those going up or down every open height rounds, so they're kept apart rather
+    // than for every round of the period. Indexed by (round * height + y) * nwords + word, with
+    // the round modulo the open width or height.
+    horizontal: Vec<u64>,
+    vertical: Vec<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardErrorKind {
+    TooSmall,
+    WrongWidth { expected: usize, got: usize },
+    UnexpectedTile(char),
+    // Something other than a wall on the edge of the valley, other than the openings.
+    BrokenWall(char),
+    InnerWall,
+    NoOpening,
+    ExtraOpening,
+    BlizzardInOpeningColumn,
+}
+
+// A problem with the board, at a 1-based line and column of the input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: BoardErrorKind,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use BoardErrorKind::*;
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            TooSmall => write!(f, "the valley should be at least 3x3, including the walls"),
+            WrongWidth { expected, got } => write!(f, "row has {got} tiles, expected {expected}"),
+            UnexpectedTile(c) => write!(f, "unexpected tile {c:?}"),
+            BrokenWall(c) => write!(f, "expected a wall, got {c:?}"),
+            InnerWall => write!(f, "wall inside the valley"),
+            NoOpening => write!(f, "no opening in the wall"),
+            ExtraOpening => write!(f, "more than one opening in the wall"),
+            BlizzardInOpeningColumn => write!(f, "vertical blizzard in line with an opening"),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+impl From<BoardError> for AocError {
+    fn from(e: BoardError) -> Self {
+        AocError::Parse(e.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Move(Dir),
+    Wait
+}
+
+impl Action {
+    // Return the action that gets from `a` to `b`, which should be at most one step apart.
+    pub fn between(a: Point, b: Point) -> Self {
+        use std::cmp::Ordering::*;
+        match (b.x.cmp(&a.x), b.y.cmp(&a.y)) {
+            (Equal, Less) => Action::Move(Dir::Up),
+            (Greater, Equal) => Action::Move(Dir::Right),
+            (Equal, Greater) => Action::Move(Dir::Down),
+            (Less, Equal) => Action::Move(Dir::Left),
+            (Equal, Equal) => Action::Wait,
+            _ => panic!("{a:?} and {b:?} aren't adjacent"),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Action::Move(Dir::Up) => "move up",
+            Action::Move(Dir::Right) => "move right",
+            Action::Move(Dir::Down) => "move down",
+            Action::Move(Dir::Left) => "move left",
+            Action::Wait => "wait",
+        };
+        f.write_str(s)
+    }
+}
+
+// Checks the rows of a board as they're read. Whether a row is the bottom wall isn't known until
+// the next one comes, so each row is held back until then.
+#[derive(Default)]
+struct BoardReader {
+    width: usize,
+    nrows: usize,
+    pending: Option<String>,
+    col_blizzards: Vec<Vec<Blizzard>>,
+    row_blizzards: Vec<Vec<Blizzard>>,
+    // The x of the opening in the first and last rows.
+    openings: [Option<usize>; 2],
+}
+
+fn board_err(y: usize, x: usize, kind: BoardErrorKind) -> BoardError {
+    BoardError { line: y + 1, column: x + 1, kind }
+}
+
+impl BoardReader {
+    fn push(&mut self, line: &str) -> Result<(), BoardError> {
+        use BoardErrorKind::*;
+        let line = line.trim_end_matches('\r');
+        let len = line.chars().count();
+        if self.nrows == 0 {
+            if len < 3 {
+                return Err(board_err(0, 0, TooSmall));
+            }
+            self.width = len;
+            self.col_blizzards = vec![Vec::new(); len];
+        } else if len != self.width {
+            let kind = WrongWidth { expected: self.width, got: len };
+            return Err(board_err(self.nrows, len.min(self.width), kind));
+        }
+        if let Some(prev) = self.pending.take() {
+            self.row(&prev, false)?;
+        }
+        self.pending = Some(line.to_string());
+        self.nrows += 1;
+        Ok(())
+    }
+
+    fn row(&mut self, line: &str, last: bool) -> Result<(), BoardError> {
+        use BoardErrorKind::*;
+        let (y, width) = (self.row_blizzards.len(), self.width);
+        let err = |x: usize, kind| board_err(y, x, kind);
+        self.row_blizzards.push(Vec::new());
+        for (x, c) in line.chars().enumerate() {
+            let on_edge = x == 0 || x == width - 1 || y == 0 || last;
+            match c {
+                '#' if on_edge => (),
+                '.' if (y == 0 || last) && (x != 0 && x != width - 1) => {
+                    let opening = &mut self.openings[if y == 0 { 0 } else { 1 }];
+                    if opening.replace(x).is_some() {
+                        return Err(err(x, ExtraOpening));
+                    }
+                },
+                '#' | '.' | '^' | '>' | 'v' | '<' if on_edge => return Err(err(x, BrokenWall(c))),
+                '#' => return Err(err(x, InnerWall)),
+                '.' => (),
+                '^' => self.col_blizzards[x].push(Blizzard::new(Point::new(x, y), Dir::Up)),
+                '>' => self.row_blizzards[y].push(Blizzard::new(Point::new(x, y), Dir::Right)),
+                'v' => self.col_blizzards[x].push(Blizzard::new(Point::new(x, y), Dir::Down)),
+                '<' => self.row_blizzards[y].push(Blizzard::new(Point::new(x, y), Dir::Left)),
+                _ => return Err(err(x, UnexpectedTile(c))),
+            };
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Board, BoardError> {
+        use BoardErrorKind::*;
+        let height = self.nrows;
+        if height < 3 {
+            return Err(board_err(0, 0, TooSmall));
+        }
+        let last = self.pending.take().expect("the last row is pending");
+        self.row(&last, true)?;
+        let [Some(start_x), Some(end_x)] = self.openings else {
+            let y = if self.openings[0].is_none() { 0 } else { height - 1 };
+            return Err(board_err(y, 0, NoOpening));
+        };
+        // Blizzards wrap around inside the walls, so one going up or down in line with an
+        // opening would have to leave the valley.
+        for x in [start_x, end_x] {
+            if let Some(b) = self.col_blizzards[x].first() {
+                return Err(board_err(b.start.y, x, BlizzardInOpeningColumn));
+            }
+        }
+        Ok(Board::new(self.row_blizzards, self.col_blizzards, start_x, end_x))
+    }
+}
+
+impl Board {
+    pub fn read(s: &str) -> Result<Self, BoardError> {
+        let mut reader = BoardReader::default();
+        for line in s.lines() {
+            reader.push(line)?;
+        }
+        reader.finish()
+    }
+
+    // Like read, but a line at a time, so a bad row is reported without reading the rest.
+    pub fn read_from(r: impl io::BufRead) -> Result<Self, AocError> {
+        let mut reader = BoardReader::default();
+        for line in r.lines() {
+            reader.push(&line?)?;
+        }
+        Ok(reader.finish()?)
+    }
+
+    fn new(
+        row_blizzards: Vec<Vec<Blizzard>>,
+        col_blizzards: Vec<Vec<Blizzard>>,
+        start_x: usize,
+        end_x: usize,
+    ) -> Self {
+        let (width, height) = (col_blizzards.len(), row_blizzards.len());
+        let start_pos = Point::new(start_x, 0);
+        let end_pos = Point::new(end_x, height - 1);
+        let period = lcm(width - 2, height - 2);
+        let mut board = Board {
+            width,
+            height,
+            row_blizzards,
+            col_blizzards,
+            start_pos,
+            end_pos,
+            period,
+            nwords: (width - 2).div_ceil(WORD_BITS),
+            horizontal: Vec::new(),
+            vertical: Vec::new(),
+        };
+        (board.horizontal, board.vertical) = board.occupancy();
+        board
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn start_pos(&self) -> Point {
+        self.start_pos
+    }
+
+    pub fn end_pos(&self) -> Point {
+        self.end_pos
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    // Find where the blizzards are each round by rotating the initial positions of the blizzards
+    // going each direction: horizontal ones rotate within their row, and vertical ones are in the
+    // row they started `round` rows away from.
+    fn occupancy(&self) -> (Vec<u64>, Vec<u64>) {
+        let (open_width, open_height) = (self.width - 2, self.height - 2);
+        let mut initial: [Vec<Vec<u64>>; 4] = core::array::from_fn(|_| vec![vec![0; self.nwords]; self.height]);
+        for &b in self.row_blizzards.iter().chain(self.col_blizzards.iter()).flatten() {
+            let i = b.start.x - 1;
+            initial[b.dir as usize][b.start.y][i / WORD_BITS] |= 1 << (i % WORD_BITS);
+        }
+        let [up, right, down, left] = &initial;
+        let mut horizontal = vec![0; open_width * self.height * self.nwords];
+        for round in 0..open_width {
+            for y in 1..(self.height - 1) {
+                let right = rotate_left(&right[y], round, open_width);
+                let left = rotate_left(&left[y], (open_width - round) % open_width, open_width);
+                let start = (round * self.height + y) * self.nwords;
+                for k in 0..self.nwords {
+                    horizontal[start + k] = right[k] | left[k];
+                }
+            }
+        }
+        let mut vertical = vec![0; open_height * self.height * self.nwords];
+        for round in 0..open_height {
+            for y in 1..(self.height - 1) {
+                let from_below = _mod(y - 1, round as isize, open_height) + 1;
+                let from_above = _mod(y - 1, -(round as isize), open_height) + 1;
+                let start = (round * self.height + y) * self.nwords;
+                for k in 0..self.nwords {
+                    vertical[start + k] = up[from_below][k] | down[from_above][k];
+                }
+            }
+        }
+        (horizontal, vertical)
+    }
+
+    fn has_blizzard(&self, p: Point, round: usize) -> bool {
+        if p.x == 0 || p.x >= self.width - 1 {
+            return false;
+        }
+        let i = p.x - 1;
+        let index = |repeat: usize| ((round % repeat) * self.height + p.y) * self.nwords + i / WORD_BITS;
+        let word = self.horizontal[index(self.width - 2)] | self.vertical[index(self.height - 2)];
+        word & (1 << (i % WORD_BITS)) != 0
+    }
+
+    fn blizzard_position(&self, b: Blizzard, round: usize) -> Point {
+        // #>....#
+        use Dir::*;
+        let open_width = self.width - 2;
+        let open_height = self.height - 2;
+        let round = round as isize;
+        // We need to remove and add the walls back in when calculating blizzard positions.
+        match b.dir {
+            Up => Point::new(b.start.x, _mod(b.start.y - 1, -round, open_height) + 1),
+            Right => Point::new(_mod(b.start.x - 1, round, open_width) + 1, b.start.y),
+            Down => Point::new(b.start.x, _mod(b.start.y - 1, round, open_height) + 1),
+            Left => Point::new(_mod(b.start.x - 1, -round, open_width) + 1, b.start.y),
+        }
+    }
+
+    pub fn get(&self, p: Point, round: usize) -> Tile {
+        if p == self.start_pos || p == self.end_pos {
+            return Tile::Open;
+        }
+
+        if self.has_blizzard(p, round) {
+            return Tile::Blizzard;
+        }
+
+        if self.is_wall(p) {
+            return Tile::Wall;
+        }
+
+        Tile::Open
+    }
+
+    pub fn is_wall(&self, p: Point) -> bool {
+        let on_edge = p.x == 0
+            || p.x == self.width - 1
+            || p.y == 0
+            || p.y == self.height - 1;
+        on_edge && p != self.start_pos && p != self.end_pos
+    }
+
+    pub fn move_player(&self, p: Point, dir: Dir) -> Option<Point> {
+        p.step(dir).filter(|q| q.x < self.width && q.y < self.height)
+    }
+}
+
+// The valley at a given round, drawn the way the puzzle description does.
+pub struct Frame<'a> {
+    pub board: &'a Board,
+    pub round: usize,
+    pub expedition: Option<Point>,
+}
+
+impl Frame<'_> {
+    fn tile(&self, p: Point) -> char {
+        let board = self.board;
+        // The number of blizzards on the tile, and the direction of the last one. Only blizzards
+        // that started in the same row or column can be there.
+        let (n, dir) = board.row_blizzards[p.y].iter()
+            .chain(&board.col_blizzards[p.x])
+            .filter(|&&b| board.blizzard_position(b, self.round) == p)
+            .fold((0u8, Dir::Up), |(n, _), b| (n.saturating_add(1), b.dir));
+        match (n, dir) {
+            _ if Some(p) == self.expedition => 'E',
+            _ if board.is_wall(p) => '#',
+            (0, _) => '.',
+            (1, Dir::Up) => '^',
+            (1, Dir::Right) => '>',
+            (1, Dir::Down) => 'v',
+            (1, Dir::Left) => '<',
+            (n, _) => char::from_digit(n.min(9) as u32, 10).unwrap(),
+        }
+    }
+}
+
+impl fmt::Display for Frame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.board.height {
+            for x in 0..self.board.width {
+                write!(f, "{}", self.tile(Point::new(x, y)))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Scene for Frame<'_> {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        (0..self.board.width as i64, 0..self.board.height as i64)
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        match self.tile(Point::new(x as usize, y as usize)) {
+            '.' => '.'.into(),
+            'E' => Glyph::colored('E', Color::Green),
+            '#' => Glyph::colored('#', Color::Gray),
+            c @ ('^' | '>' | 'v' | '<') => Glyph::colored(c, Color::Cyan),
+            // Tiles with more than one blizzard stand out.
+            c => Glyph::colored(c, Color::Blue),
+        }
+    }
+
+    fn focus(&self) -> Option<(i64, i64)> {
+        self.expedition.map(|p| (p.x as i64, p.y as i64))
+    }
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// Rotate a bitset of `len` bits towards the higher bits by `n`, which should be less than `len`.
+fn rotate_left(bits: &[u64], n: usize, len: usize) -> Vec<u64> {
+    let up = shift_up(bits, n);
+    let wrapped = shift_down(bits, (len - n) % len);
+    let mut out: Vec<u64> = up.iter().zip(wrapped.iter()).map(|(a, b)| a | b).collect();
+    // Drop the bits that were shifted past the end.
+    if let Some(last) = out.last_mut() {
+        if !len.is_multiple_of(WORD_BITS) {
+            *last &= (1 << (len % WORD_BITS)) - 1;
+        }
+    }
+    out
+}
+
+// Move bit i to i+n.
+fn shift_up(bits: &[u64], n: usize) -> Vec<u64> {
+    let (words, shift) = (n / WORD_BITS, n % WORD_BITS);
+    (0..bits.len())
+        .map(|k| {
+            let word = |i: usize| if i >= words { bits[i - words] } else { 0 };
+            let carry = if shift > 0 && k > words { bits[k - words - 1] >> (WORD_BITS - shift) } else { 0 };
+            (word(k) << shift) | carry
+        })
+        .collect()
+}
+
+// Move bit i to i-n.
+fn shift_down(bits: &[u64], n: usize) -> Vec<u64> {
+    let (words, shift) = (n / WORD_BITS, n % WORD_BITS);
+    let word = |i: usize| bits.get(i).copied().unwrap_or(0);
+    (0..bits.len())
+        .map(|k| {
+            let carry = if shift > 0 { word(k + words + 1) << (WORD_BITS - shift) } else { 0 };
+            (word(k + words) >> shift) | carry
+        })
+        .collect()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+fn _mod(start: usize, change: isize, modulus: usize) -> usize {
+    let modulus = modulus as isize;
+    let mut rem = (start as isize + change) % modulus;
+    if rem < 0 {
+        rem += modulus;
+    }
+    rem as usize
+}
+
+// Counts of the work a search did, for comparing strategies.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    // States taken off the frontier and expanded.
+    pub nstates: usize,
+    // States added to the frontier.
+    pub npushed: usize,
+    // The most states on the frontier at once.
+    pub max_frontier: usize,
+}
+
+impl AddAssign for SearchStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nstates += other.nstates;
+        self.npushed += other.npushed;
+        self.max_frontier = self.max_frontier.max(other.max_frontier);
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nstates={} npushed={} max_frontier={}", self.nstates, self.npushed, self.max_frontier)
+    }
+}
+
+// The result of looking for a route between two positions. `path` is the position at the end of
+// each round, starting with the start position, or None if the end can't be reached.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Search {
+    pub path: Option<Vec<Point>>,
+    pub stats: SearchStats,
+}
+
+// Estimates the number of rounds needed to get from one position to another, to guide A*. The
+// route found is only the quickest if the estimate never overshoots and drops by at most one per
+// step, as Zero and Manhattan do.
+pub trait Heuristic {
+    fn estimate(&self, from: Point, to: Point) -> usize;
+}
+
+// Treat every position as equally promising, which makes A* a uniform-cost search.
+pub struct Zero;
+
+impl Heuristic for Zero {
+    fn estimate(&self, _from: Point, _to: Point) -> usize {
+        0
+    }
+}
+
+// The number of moves needed if there weren't any blizzards.
+pub struct Manhattan;
+
+impl Heuristic for Manhattan {
+    fn estimate(&self, from: Point, to: Point) -> usize {
+        from.manhattan(to) as usize
+    }
+}
+
+impl<F: Fn(Point, Point) -> usize> Heuristic for F {
+    fn estimate(&self, from: Point, to: Point) -> usize {
+        self(from, to)
+    }
+}
+
+// How to look for routes.
+#[derive(Default)]
+pub enum Strategy {
+    #[default]
+    Bfs,
+    AStar(Box<dyn Heuristic>),
+    // A* with the Manhattan heuristic, spread over this many threads.
+    Parallel(usize),
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Strategy::Bfs),
+            "zero" => Ok(Strategy::AStar(Box::new(Zero))),
+            "manhattan" => Ok(Strategy::AStar(Box::new(Manhattan))),
+            _ => Err(format!("unknown search strategy: {s}")),
+        }
+    }
+}
+
+impl Strategy {
+    pub fn find_path(&self, board: &Board, start: Point, end: Point, initial_round: usize) -> Search {
+        match self {
+            Strategy::Bfs => find_path(board, start, end, initial_round),
+            Strategy::AStar(heuristic) => find_path_astar(board, start, end, initial_round, heuristic.as_ref()),
+            Strategy::Parallel(threads) => find_path_parallel(board, start, end, initial_round, *threads),
+        }
+    }
+}
+
+// Positions reachable from `pos` in one round, ignoring blizzards.
+fn branches(board: &Board, pos: Point) -> impl Iterator<Item=Point> + '_ {
+    use Dir::*;
+    use Action::*;
+    [Move(Up), Move(Right), Move(Down), Move(Left), Wait]
+        .into_iter()
+        .filter_map(move |a| match a {
+            Move(d) => board.move_player(pos, d),
+            Wait => Some(pos),
+        })
+}
+
+// Indexes for the search states, which are a position and a round modulo the period, since the
+// blizzards repeat.
+struct States<'a> {
+    board: &'a Board,
+}
+
+impl States<'_> {
+    fn area(&self) -> usize {
+        self.board.width * self.board.height
+    }
+
+    fn tile(&self, p: Point) -> u32 {
+        (p.y * self.board.width + p.x) as u32
+    }
+
+    fn point(&self, tile: u32) -> Point {
+        let tile = tile as usize;
+        Point::new(tile % self.board.width, tile / self.board.width)
+    }
+
+    fn index(&self, p: Point, round: usize) -> usize {
+        (round % self.board.period) * self.area() + self.tile(p) as usize
+    }
+
+    // Walk back from `end` at `round` to `initial_round`, asking `prev` for the position before
+    // each one.
+    fn path(&self, end: Point, initial_round: usize, round: usize, prev: impl Fn(Point, usize) -> Point) -> Vec<Point> {
+        let mut path = vec![end];
+        for r in ((initial_round + 1)..=round).rev() {
+            path.push(prev(*path.last().unwrap(), r));
+        }
+        path.reverse();
+        path
+    }
+}
+
+// Where a breadth-first search is at: the positions reached by the end of `round`, and the round
+// each state seen so far was first reached in, by its index. Only the states that are reached are
+// kept, since there can be far more of them than a wide valley has room for in memory.
+struct Bfs {
+    round: usize,
+    frontier: Vec<Point>,
+    reached: HashMap<usize, usize>,
+    stats: SearchStats,
+}
+
+impl Bfs {
+    fn new(states: &States, start: Point, initial_round: usize) -> Self {
+        Bfs {
+            round: initial_round,
+            frontier: vec![start],
+            reached: HashMap::from([(states.index(start, initial_round), initial_round)]),
+            stats: SearchStats { npushed: 1, max_frontier: 1, ..SearchStats::default() },
+        }
+    }
+
+    // The route to `end`, which was reached this round. Each position was reached the round before
+    // the next one: reaching it in an earlier period would have got to `end` sooner, too.
+    fn path(&self, states: &States, end: Point, initial_round: usize) -> Vec<Point> {
+        states.path(end, initial_round, self.round, |pos, round| {
+            branches(states.board, pos)
+                .find(|&p| self.reached.get(&states.index(p, round - 1)) == Some(&(round - 1)))
+                .expect("every position on the route was reached the round before")
+        })
+    }
+
+    // Add the search's records to a checkpoint. Only the states that have been seen are saved.
+    fn save(&self, checkpoint: &mut Checkpoint) {
+        let SearchStats { nstates, npushed, max_frontier } = self.stats;
+        checkpoint.push("bfs", [self.round, nstates, npushed, max_frontier]);
+        checkpoint.push("frontier", self.frontier.iter().map(|&p| format_point(p)));
+        checkpoint.push("seen", self.reached.iter().map(|(i, round)| format!("{i}:{round}")));
+    }
+
+    fn restore(saved: &Checkpoint, states: &States) -> Result<Self, String> {
+        let record = saved.record("bfs")?;
+        let stats = SearchStats { nstates: record.field(1)?, npushed: record.field(2)?, max_frontier: record.field(3)? };
+        let frontier = saved.record("frontier")?;
+        let frontier = (0..frontier.len()).map(|i| parse_point(frontier.str(i)?)).collect::<Result<_, _>>()?;
+        let round = record.field(0)?;
+        let seen = saved.record("seen")?;
+        let mut reached = HashMap::with_capacity(seen.len());
+        for i in 0..seen.len() {
+            let entry = seen.str(i)?;
+            let (index, r) = entry.split_once(':')
+                .and_then(|(i, r)| Some((i.parse::<usize>().ok()?, r.parse::<usize>().ok()?)))
+                .filter(|&(i, r)| i / states.area() < states.board.period && r <= round)
+                .ok_or_else(|| format!("bad seen state: {entry:?}"))?;
+            reached.insert(index, r);
+        }
+        Ok(Bfs { round, frontier, reached, stats })
+    }
+}
+
+// Return the quickest route from `start` to `end`, leaving `start` after `initial_round`.
+//
+// Search breadth-first, one round at a time. Since the blizzards repeat, being at the same position
+// at rounds that are equal modulo the period is the same state, and the first time we see a state
+// is the earliest we can be in it.
+pub fn find_path(board: &Board, start: Point, end: Point, initial_round: usize) -> Search {
+    let bfs = Bfs::new(&States { board }, start, initial_round);
+    continue_bfs(board, bfs, end, initial_round, &mut |_| Ok(())).expect("nothing to save")
+}
+
+// Carry on with a breadth-first search, calling `save` between rounds so it can be checkpointed.
+fn continue_bfs(
+    board: &Board,
+    mut bfs: Bfs,
+    end: Point,
+    initial_round: usize,
+    save: &mut dyn FnMut(&Bfs) -> io::Result<()>,
+) -> io::Result<Search> {
+    let states = States { board };
+    let mut progress = Progress::new("day24", "round");
+
+    while !bfs.frontier.is_empty() {
+        if bfs.frontier.contains(&end) {
+            let path = bfs.path(&states, end, initial_round);
+            return Ok(Search { path: Some(path), stats: bfs.stats });
+        }
+        save(&bfs)?;
+        bfs.round += 1;
+        let round = bfs.round;
+        let mut next = Vec::new();
+        for &pos in bfs.frontier.iter() {
+            bfs.stats.nstates += 1;
+            progress.tick(bfs.stats.nstates, round);
+            for p in branches(board, pos) {
+                if board.get(p, round) != Tile::Open {
+                    continue;
+                }
+                if let Entry::Vacant(e) = bfs.reached.entry(states.index(p, round)) {
+                    e.insert(round);
+                    next.push(p);
+                }
+            }
+        }
+        bfs.stats.npushed += next.len();
+        bfs.stats.max_frontier = bfs.stats.max_frontier.max(next.len());
+        bfs.frontier = next;
+    }
+    Ok(Search { path: None, stats: bfs.stats })
+}
+
+// Like find_path(), but expand the states that look closest to `end` according to `heuristic`
+// first.
+pub fn find_path_astar(
+    board: &Board,
+    start: Point,
+    end: Point,
+    initial_round: usize,
+    heuristic: &dyn Heuristic,
+) -> Search {
+    let states = States { board };
+    let mut stats = SearchStats::default();
+    // For each state reached, by its index, the fewest rounds after `initial_round` it's been
+    // reached in, and the tile of the position the round before.
+    let mut reached: HashMap<usize, (usize, u32)> = HashMap::new();
+    let mut expanded = HashSet::new();
+    // Ordered by lowest estimated total, then by most rounds elapsed, to prefer the states that
+    // have made the most progress.
+    let mut frontier: BinaryHeap<(Reverse<usize>, usize, u32)> = BinaryHeap::new();
+
+    reached.insert(states.index(start, initial_round), (0, states.tile(start)));
+    frontier.push((Reverse(heuristic.estimate(start, end)), 0, states.tile(start)));
+    let mut progress = Progress::new("day24", "round");
+    stats.npushed += 1;
+    stats.max_frontier = 1;
+
+    while let Some((_, g, tile)) = frontier.pop() {
+        let pos = states.point(tile);
+        let round = initial_round + g;
+        let i = states.index(pos, round);
+        if g > reached[&i].0 || !expanded.insert(i) {
+            continue;
+        }
+        stats.nstates += 1;
+        progress.tick(stats.nstates, round);
+        if pos == end {
+            let path = states.path(end, initial_round, round, |p, r| states.point(reached[&states.index(p, r)].1));
+            return Search { path: Some(path), stats };
+        }
+        for p in branches(board, pos) {
+            if board.get(p, round + 1) != Tile::Open {
+                continue;
+            }
+            let j = states.index(p, round + 1);
+            if reached.get(&j).is_none_or(|&(elapsed, _)| g + 1 < elapsed) {
+                reached.insert(j, (g + 1, tile));
+                frontier.push((Reverse(g + 1 + heuristic.estimate(p, end)), g + 1, states.tile(p)));
+                stats.npushed += 1;
+            }
+        }
+        stats.max_frontier = stats.max_frontier.max(frontier.len());
+    }
+    Search { path: None, stats }
+}
+
+// The positions that led to a state in the parallel search, most recent first. Children share their
+// parent's trail, so it's cheap to copy.
+struct Trail {
+    pos: Point,
+    prev: Option<Arc<Trail>>,
+}
+
+#[derive(Clone)]
+struct Node {
+    // Rounds since leaving the start.
+    elapsed: usize,
+    trail: Arc<Trail>,
+}
+
+struct ParallelSearch<'a> {
+    board: &'a Board,
+    end: Point,
+    initial_round: usize,
+}
+
+// Scores are negated round counts, so the best score is the quickest route.
+impl parallel::Search for ParallelSearch<'_> {
+    type Node = Node;
+
+    fn bound(&self, node: &Node) -> i64 {
+        -((node.elapsed + Manhattan.estimate(node.trail.pos, self.end)) as i64)
+    }
+
+    fn score(&self, node: &Node) -> Option<i64> {
+        (node.trail.pos == self.end).then_some(-(node.elapsed as i64))
+    }
+
+    fn expand(&self, node: &Node, children: &mut Vec<Node>) {
+        let round = self.initial_round + node.elapsed + 1;
+        for p in branches(self.board, node.trail.pos) {
+            if self.board.get(p, round) == Tile::Open {
+                let trail = Arc::new(Trail { pos: p, prev: Some(node.trail.clone()) });
+                children.push(Node { elapsed: node.elapsed + 1, trail });
+            }
+        }
+    }
+
+    // Reaching the same state sooner is at least as good.
+    fn key(&self, node: &Node) -> Option<(u64, i64)> {
+        let states = States { board: self.board };
+        Some((states.index(node.trail.pos, self.initial_round + node.elapsed) as u64, -(node.elapsed as i64)))
+    }
+}
+
+// Like find_path_astar() with the Manhattan heuristic, but expand states on `threads` threads.
+pub fn find_path_parallel(board: &Board, start: Point, end: Point, initial_round: usize, threads: usize) -> Search {
+    let search = ParallelSearch { board, end, initial_round };
+    let root = Node { elapsed: 0, trail: Arc::new(Trail { pos: start, prev: None }) };
+    let outcome = parallel::run(&search, [root], threads);
+    let stats = SearchStats { nstates: outcome.nstates, npushed: outcome.npushed, max_frontier: outcome.peak_queue };
+    let path = outcome.best.map(|(_, node)| {
+        let mut path = Vec::new();
+        let mut trail = Some(&node.trail);
+        while let Some(t) = trail {
+            path.push(t.pos);
+            trail = t.prev.as_ref();
+        }
+        path.reverse();
+        path
+    });
+    Search { path, stats }
+}
+
+// A route that visits a list of waypoints. Each leg is given as the position at the end of each
+// round, starting with the waypoint it leaves from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub legs: Vec<Vec<Point>>,
+    pub stats: SearchStats,
+}
+
+impl Route {
+    // The position at the end of each round, starting with the first waypoint.
+    pub fn positions(&self) -> Vec<Point> {
+        // Join the legs, dropping the repeated waypoints.
+        let mut positions: Vec<Point> = self.legs.iter().take(1).flat_map(|leg| leg.first()).copied().collect();
+        for leg in &self.legs {
+            positions.extend_from_slice(&leg[1..]);
+        }
+        positions
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.legs.iter().map(|leg| leg.len() - 1).sum()
+    }
+}
+
+// Return the quickest route that visits each of the waypoints in order, starting at the first one.
+pub fn travel(board: &Board, waypoints: &[Point], strategy: &Strategy) -> Result<Route, AocError> {
+    travel_from(board, waypoints, strategy, None)
+}
+
+// Like travel, but resume from the checkpointer's checkpoint, if there is one, and save progress
+// to it as the search goes: the legs found so far, and where a breadth-first search for the next
+// one is at. A* searches aren't saved partway through, so an interrupted leg starts over. The
+// checkpoint is removed once the route is found.
+pub fn travel_checkpointed(
+    board: &Board,
+    waypoints: &[Point],
+    strategy: &Strategy,
+    checkpointer: &mut Checkpointer,
+) -> Result<Route, AocError> {
+    travel_from(board, waypoints, strategy, Some(checkpointer))
+}
+
+const CHECKPOINT_KIND: &str = "day24";
+
+fn travel_from(
+    board: &Board,
+    waypoints: &[Point],
+    strategy: &Strategy,
+    mut checkpointer: Option<&mut Checkpointer>,
+) -> Result<Route, AocError> {
+    for &p in waypoints {
+        if p.x >= board.width || p.y >= board.height || board.is_wall(p) {
+            return Err(AocError::BadArgs(format!("waypoint {},{} isn't in the valley", p.x, p.y)));
+        }
+    }
+    let states = States { board };
+    let fingerprint = fingerprint(board, waypoints);
+    let mut route = Route { legs: Vec::new(), stats: SearchStats::default() };
+    let mut bfs = None;
+    if let Some(saved) = checkpointer.as_deref().map(|c| c.load(CHECKPOINT_KIND)).transpose()?.flatten() {
+        route = restore_route(&saved, fingerprint, waypoints)?;
+        if saved.record("bfs").is_ok() {
+            bfs = Some(Bfs::restore(&saved, &states)?);
+        }
+    }
+
+    let mut round = route.rounds();
+    for leg in waypoints.windows(2).skip(route.legs.len()) {
+        let search = match (strategy, checkpointer.as_deref_mut()) {
+            (Strategy::Bfs, Some(c)) => {
+                let bfs = bfs.take().unwrap_or_else(|| Bfs::new(&states, leg[0], round));
+                continue_bfs(board, bfs, leg[1], round, &mut |bfs| {
+                    if !c.due_now() {
+                        return Ok(());
+                    }
+                    let mut saved = save_route(fingerprint, &route);
+                    bfs.save(&mut saved);
+                    c.save(&saved)
+                })?
+            },
+            _ => strategy.find_path(board, leg[0], leg[1], round),
+        };
+        route.stats += search.stats;
+        let path = search.path
+            .ok_or_else(|| {
+                let msg = format!("no path from {},{} to {},{}", leg[0].x, leg[0].y, leg[1].x, leg[1].y);
+                AocError::NoSolution(msg)
+            })?;
+        round += path.len() - 1;
+        route.legs.push(path);
+        if let Some(c) = checkpointer.as_deref_mut() {
+            c.save(&save_route(fingerprint, &route))?;
+        }
+    }
+    if let Some(c) = checkpointer {
+        c.finish()?;
+    }
+    Ok(route)
+}
+
+// Identifies the valley and waypoints, so a checkpoint isn't resumed with different ones.
+fn fingerprint(board: &Board, waypoints: &[Point]) -> u64 {
+    let frame = Frame { board, round: 0, expedition: None };
+    let waypoints: Vec<String> = waypoints.iter().map(|&p| format_point(p)).collect();
+    checkpoint::fingerprint(&format!("{frame}{}", waypoints.join(" ")))
+}
+
+// A checkpoint with the legs found so far, each as the positions along it, and their stats.
+fn save_route(fingerprint: u64, route: &Route) -> Checkpoint {
+    let mut saved = Checkpoint::new(CHECKPOINT_KIND);
+    saved.push("input", [fingerprint]);
+    let SearchStats { nstates, npushed, max_frontier } = route.stats;
+    saved.push("stats", [nstates, npushed, max_frontier]);
+    for leg in &route.legs {
+        saved.push("leg", leg.iter().map(|&p| format_point(p)));
+    }
+    saved
+}
+
+fn restore_route(saved: &Checkpoint, fingerprint: u64, waypoints: &[Point]) -> Result<Route, String> {
+    if saved.record("input")?.field::<u64>(0)? != fingerprint {
+        return Err("checkpoint is for a different input".to_string());
+    }
+    let stats = saved.record("stats")?;
+    let stats = SearchStats { nstates: stats.field(0)?, npushed: stats.field(1)?, max_frontier: stats.field(2)? };
+    let mut legs = Vec::new();
+    for record in saved.records("leg") {
+        let leg: Vec<Point> = (0..record.len()).map(|i| parse_point(record.str(i)?)).collect::<Result<_, _>>()?;
+        let i = legs.len();
+        if i + 1 >= waypoints.len() || leg.first() != Some(&waypoints[i]) || leg.last() != Some(&waypoints[i + 1]) {
+            return Err(format!("checkpoint's leg {} doesn't match the waypoints", i + 1));
+        }
+        legs.push(leg);
+    }
+    Ok(Route { legs, stats })
+}
+
+fn format_point(p: Point) -> String {
+    format!("{},{}", p.x, p.y)
+}
+
+fn parse_point(s: &str) -> Result<Point, String> {
+    s.split_once(',')
+        .and_then(|(x, y)| Some(Point::new(x.parse().ok()?, y.parse().ok()?)))
+        .ok_or_else(|| format!("bad position: {s:?}"))
+}
+
+// Waypoints for crossing the valley `trips` times, alternating between the start and the end.
+pub fn trips(board: &Board, trips: usize) -> Vec<Point> {
+    (0..=trips)
+        .map(|i| if i % 2 == 0 { board.start_pos } else { board.end_pos })
+        .collect()
+}
+
+// Write what to do each minute, leg by leg.
+pub fn write_moves(mut w: impl io::Write, legs: &[Vec<Point>]) -> io::Result<()> {
+    let mut minute = 0;
+    for (i, leg) in legs.iter().enumerate() {
+        let (from, to) = (leg[0], leg[leg.len() - 1]);
+        writeln!(w, "Leg {}: {},{} to {},{} in {} minutes", i + 1, from.x, from.y, to.x, to.y, leg.len() - 1)?;
+        for step in leg.windows(2) {
+            minute += 1;
+            writeln!(w, "Minute {minute}, {}.", Action::between(step[0], step[1]))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn part1(r: impl io::BufRead, strategy: &Strategy) -> Result<usize, AocError> {
+    let board = Board::read_from(r)?;
+    let route = travel(&board, &trips(&board, 1), strategy)?;
+    Ok(route.rounds())
+}
+
+pub fn part2(r: impl io::BufRead, strategy: &Strategy) -> Result<usize, AocError> {
+    let board = Board::read_from(r)?;
+    let route = travel(&board, &trips(&board, 3), strategy)?;
+    Ok(route.rounds())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+    use aoclib::rng::Rng;
+
+    const EXAMPLE: &str = "\
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+
+    #[test]
+    fn test_blizzard_position_horizontal() {
+        let board = Board::read(EXAMPLE).unwrap();
+        let start = Point::new(6, 1);
+        let blizzard = Blizzard::new(start, Dir::Right);
+        assert_eq!(board.blizzard_position(blizzard, 0), start);
+        assert_eq!(board.blizzard_position(blizzard, 1), Point::new(1, 1));
+        assert_eq!(board.blizzard_position(blizzard, 2), Point::new(2, 1));
+    }
+
+    #[test]
+    fn test_blizzard_position_vertical() {
+        let board = Board::read(EXAMPLE).unwrap();
+        let start = Point::new(4, 1);
+        let blizzard = Blizzard::new(start, Dir::Up);
+        assert_eq!(board.blizzard_position(blizzard, 0), start);
+        assert_eq!(board.blizzard_position(blizzard, 1), Point::new(4, 4));
+        assert_eq!(board.blizzard_position(blizzard, 2), Point::new(4, 3));
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let board = Board::read(EXAMPLE).unwrap();
+        assert_eq!(board.period, 12);
+        assert_eq!(board.horizontal.len(), 6 * board.height * board.nwords);
+        assert_eq!(board.vertical.len(), 4 * board.height * board.nwords);
+        for round in 0..(2 * board.period) {
+            for y in 0..board.height {
+                for x in 0..board.width {
+                    let p = Point::new(x, y);
+                    let scanned = board.row_blizzards[y].iter()
+                        .chain(board.col_blizzards[x].iter())
+                        .any(|&b| board.blizzard_position(b, round) == p);
+                    assert_eq!(board.get(p, round) == Tile::Blizzard, scanned, "{p:?} round {round}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_left() {
+        // Compare against rotating one bit at a time, across word boundaries.
+        for len in [1usize, 5, 64, 70, 130] {
+            let nwords = len.div_ceil(WORD_BITS);
+            let mut bits = vec![0u64; nwords];
+            for i in (0..len).filter(|i| i % 3 == 0 || i % 7 == 1) {
+                bits[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+            }
+            for n in 0..len {
+                let mut expected = vec![0u64; nwords];
+                for i in (0..len).filter(|i| bits[i / WORD_BITS] & (1 << (i % WORD_BITS)) != 0) {
+                    let j = (i + n) % len;
+                    expected[j / WORD_BITS] |= 1 << (j % WORD_BITS);
+                }
+                assert_eq!(rotate_left(&bits, n, len), expected, "len {len} n {n}");
+            }
+        }
+    }
+
+    // A random valid valley, as the text describing it.
+    fn random_board(rng: &mut Rng) -> String {
+        let (width, height) = (3 + rng.below(10), 3 + rng.below(8));
+        let openings = [1 + rng.below(width - 2), 1 + rng.below(width - 2)];
+        let rows: Vec<Vec<char>> = (0..height)
+            .map(|y| (0..width)
+                .map(|x| match (x, y) {
+                    _ if y == 0 && x == openings[0] => '.',
+                    _ if y == height - 1 && x == openings[1] => '.',
+                    _ if x == 0 || x == width - 1 || y == 0 || y == height - 1 => '#',
+                    _ if openings.contains(&x) => *rng.choose(&['.', '.', '<', '>']),
+                    _ => *rng.choose(&['.', '.', '<', '>', '^', 'v']),
+                })
+                .collect())
+            .collect();
+        rows.iter().map(|r| r.iter().collect::<String>() + "\n").collect()
+    }
+
+    #[test]
+    fn test_read_random() {
+        let mut rng = Rng::new(24);
+        for _ in 0..200 {
+            let input = random_board(&mut rng);
+            let board = Board::read(&input).unwrap_or_else(|e| panic!("{e}\n{input}"));
+            let frame = Frame { board: &board, round: 0, expedition: None };
+            assert_eq!(frame.to_string(), input);
+
+            // Damaged valleys are either still valid or an error, but never panic.
+            let bad = rng.mutate(&input, "#.<>^vx\n");
+            _ = Board::read(&bad);
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        use BoardErrorKind::*;
+        let kind = |s: &str| Board::read(s).map(|_| ()).unwrap_err().kind;
+        let at = |s: &str| Board::read(s).map(|_| ()).map_err(|e| (e.line, e.column)).unwrap_err();
+        assert_eq!(kind(""), TooSmall);
+        assert_eq!(kind("#.#\n#.#"), TooSmall);
+        assert_eq!(kind("#.##\n#..\n##.#"), WrongWidth { expected: 4, got: 3 });
+        assert_eq!(kind("#.##\n#.x#\n##.#"), UnexpectedTile('x'));
+        assert_eq!(at("#.##\n#.x#\n##.#"), (2, 3));
+        assert_eq!(kind("#.##\n..>#\n##.#"), BrokenWall('.'));
+        assert_eq!(kind("#.##\n#>.>\n##.#"), BrokenWall('>'));
+        assert_eq!(kind("#.###\n#.#.#\n###.#"), InnerWall);
+        assert_eq!(kind("####\n#..#\n##.#"), NoOpening);
+        assert_eq!(at("#.##\n#..#\n####"), (3, 1));
+        assert_eq!(kind("#..#\n#..#\n##.#"), ExtraOpening);
+        assert_eq!(kind("#.##\n#v.#\n##.#"), BlizzardInOpeningColumn);
+        assert!(Board::read(&EXAMPLE.replace('\n', "\r\n")).is_ok());
+        assert!(Board::read_from(EXAMPLE.as_bytes()).is_ok());
+        assert_eq!(
+            Board::read_from("#.##\n#.x#\n##.#".as_bytes()).map(|_| ()).unwrap_err().to_string(),
+            "line 2, column 3: unexpected tile 'x'",
+        );
+        assert_eq!(
+            Board::read("#.##\n#.x#\n##.#").map(|_| ()).unwrap_err().to_string(),
+            "line 2, column 3: unexpected tile 'x'",
+        );
+    }
+
+    #[test]
+    fn test_find_path() {
+        let board = Board::read(EXAMPLE).unwrap();
+        assert_eq!(find_path(&board, board.start_pos, board.start_pos, 0).path, Some(vec![board.start_pos]));
+        let path = find_path(&board, board.end_pos, board.start_pos, 18).path.unwrap();
+        assert_eq!(path.len(), 24);
+        assert_eq!((path[0], path[23]), (board.end_pos, board.start_pos));
+        for (i, step) in path.windows(2).enumerate() {
+            assert!(step[0].manhattan(step[1]) <= 1);
+            assert_eq!(board.get(step[1], 18 + i + 1), Tile::Open, "{:?} round {}", step[1], 18 + i + 1);
+        }
+
+        // The two blizzards trade places every round, so the valley is always full.
+        let board = Board::read("#.##\n#><#\n##.#").unwrap();
+        assert_eq!(find_path(&board, board.start_pos, board.end_pos, 0).path, None);
+    }
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes(), &Strategy::Bfs).unwrap(), 18);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2(EXAMPLE.as_bytes(), &Strategy::Bfs).unwrap(), 54);
+    }
+
+    #[test]
+    fn test_strategies() {
+        let board = Board::read(EXAMPLE).unwrap();
+        let waypoints = trips(&board, 3);
+        let bfs = travel(&board, &waypoints, &Strategy::Bfs).unwrap();
+        let manhattan = travel(&board, &waypoints, &"manhattan".parse().unwrap()).unwrap();
+        for s in ["bfs", "zero", "manhattan"] {
+            let route = travel(&board, &waypoints, &s.parse().unwrap()).unwrap();
+            assert_eq!(route.rounds(), 54, "{s}");
+            assert_eq!(route.positions().len(), 55, "{s}");
+        }
+        // A custom heuristic that's 0 everywhere searches the same states as Zero.
+        let custom = Strategy::AStar(Box::new(|_: Point, _: Point| 0));
+        let zero = travel(&board, &waypoints, &"zero".parse().unwrap()).unwrap();
+        assert_eq!(travel(&board, &waypoints, &custom).unwrap().stats, zero.stats);
+        assert!(manhattan.stats.nstates < zero.stats.nstates);
+        assert!(bfs.stats.npushed >= bfs.stats.nstates);
+        assert!("dfs".parse::<Strategy>().is_err());
+
+        let board = Board::read("#.##\n#><#\n##.#").unwrap();
+        let search = find_path_astar(&board, board.start_pos, board.end_pos, 0, &Manhattan);
+        assert_eq!(search.path, None);
+        assert_eq!(search.stats.nstates, board.period);
+    }
+
+    #[test]
+    fn test_find_path_parallel() {
+        let board = Board::read(EXAMPLE).unwrap();
+        let waypoints = trips(&board, 3);
+        for threads in [1, 4] {
+            let route = travel(&board, &waypoints, &Strategy::Parallel(threads)).unwrap();
+            assert_eq!(route.rounds(), 54, "threads={threads}");
+            for (i, step) in route.positions().windows(2).enumerate() {
+                assert!(step[0].manhattan(step[1]) <= 1);
+                assert_eq!(board.get(step[1], i + 1), Tile::Open);
+            }
+        }
+
+        let board = Board::read("#.##\n#><#\n##.#").unwrap();
+        assert_eq!(find_path_parallel(&board, board.start_pos, board.end_pos, 0, 2).path, None);
+    }
+
+    #[test]
+    fn test_travel_checkpointed() {
+        let path = std::env::temp_dir().join(format!("aoc-day24-test-{}", std::process::id()));
+        let mut checkpointer = Checkpointer::new(&path, std::time::Duration::ZERO);
+        let board = Board::read(EXAMPLE).unwrap();
+        let waypoints = trips(&board, 3);
+        let want = travel(&board, &waypoints, &Strategy::Bfs).unwrap();
+
+        // Interrupt the search for the second leg after a few rounds, right after saving.
+        let first = travel(&board, &waypoints[..2], &Strategy::Bfs).unwrap();
+        let fingerprint = fingerprint(&board, &waypoints);
+        let bfs = Bfs::new(&States { board: &board }, waypoints[1], 18);
+        let interrupted = continue_bfs(&board, bfs, waypoints[2], 18, &mut |bfs| {
+            if bfs.round < 25 {
+                return Ok(());
+            }
+            let mut saved = save_route(fingerprint, &first);
+            bfs.save(&mut saved);
+            checkpointer.save(&saved)?;
+            Err(io::Error::other("interrupted"))
+        });
+        assert!(interrupted.is_err());
+
+        let route = travel_checkpointed(&board, &waypoints, &Strategy::Bfs, &mut checkpointer).unwrap();
+        assert_eq!(route, want);
+        assert_eq!(checkpointer.load(CHECKPOINT_KIND), Ok(None));
+
+        // A checkpoint for other waypoints isn't resumed.
+        checkpointer.save(&save_route(fingerprint, &first)).unwrap();
+        assert!(travel_checkpointed(&board, &waypoints[..2], &Strategy::Bfs, &mut checkpointer).is_err());
+        checkpointer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_write_moves() {
+        let board = Board::read(EXAMPLE).unwrap();
+        let route = travel(&board, &trips(&board, 1), &Strategy::Bfs).unwrap();
+        let mut out = Vec::new();
+        write_moves(&mut out, &route.legs).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[0], "Leg 1: 1,0 to 6,5 in 18 minutes");
+        // Of the equally quick routes, the one rebuilt from the end waits at the start.
+        assert_eq!(lines[1], "Minute 1, wait.");
+        assert_eq!(lines[18], "Minute 18, move down.");
+
+        let mut pos = board.start_pos;
+        for line in &lines[1..] {
+            let d = match line.split_once(", ").unwrap().1 {
+                "move up." => Dir::Up,
+                "move right." => Dir::Right,
+                "move down." => Dir::Down,
+                "move left." => Dir::Left,
+                _ => continue,
+            };
+            pos = board.move_player(pos, d).unwrap();
+        }
+        assert_eq!(pos, board.end_pos);
+    }
+
+    #[test]
+    fn test_frame() {
+        let board = Board::read(EXAMPLE).unwrap();
+        // From the puzzle description.
+        let minute1 = Frame { board: &board, round: 1, expedition: Some(Point::new(1, 1)) };
+        assert_eq!(minute1.to_string(), "\
+#.######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+");
+        let start = Frame { board: &board, round: 0, expedition: None };
+        assert_eq!(start.to_string().trim_end(), EXAMPLE);
+        assert_eq!(draw(&minute1, Viewport::UNLIMITED, false), minute1.to_string());
+        let row = draw(&minute1, Viewport::UNLIMITED, true).lines().nth(1).unwrap().to_string();
+        assert_eq!(row, "\x1b[90m#\x1b[32mE\x1b[36m>\x1b[34m3\x1b[0m.\x1b[36m<\x1b[0m.\x1b[90m#\x1b[0m");
+    }
+}
@@ -0,0 +1,605 @@
+use std::io::BufRead;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+use aoclib::trace;
+use crate::error::AocError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseSnafuError {
+    // A character that isn't a SNAFU digit, at a 1-based line and column of the input.
+    BadChar { line: usize, column: usize, c: char },
+    // A number too big for an i64, from from_snafu.
+    Overflow,
+}
+
+impl fmt::Display for ParseSnafuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSnafuError::BadChar { line, column, c } => {
+                write!(f, "line {line}, column {column}: unexpected character {c:?}")
+            },
+            ParseSnafuError::Overflow => write!(f, "{SnafuOverflowError}"),
+        }
+    }
+}
+
+impl Error for ParseSnafuError {}
+
+impl From<ParseSnafuError> for AocError {
+    fn from(e: ParseSnafuError) -> Self {
+        AocError::Parse(e.to_string())
+    }
+}
+
+fn from_snafu_digit(c: char) -> Option<i64> {
+    match c {
+        '2' => Some(2),
+        '1' => Some(1),
+        '0' => Some(0),
+        '-' => Some(-1),
+        '=' => Some(-2),
+        _ => None,
+    }
+}
+
+fn to_snafu_digit(digit: i64) -> char {
+    match digit {
+        2 => '2',
+        1 => '1',
+        0 => '0',
+        -1 => '-',
+        -2 => '=',
+        _ => panic!("unexpected digit: {digit}"),
+    }
+}
+
+pub fn from_snafu(s: &str) -> Result<i64, ParseSnafuError> {
+    checked_value(&parse_digits(s)?).ok_or(ParseSnafuError::Overflow)
+}
+
+// Zero is "0". Negative numbers start with a negative digit, like -3 is "-2", since SNAFU doesn't
+// need a sign.
+pub fn to_snafu(n: i64) -> String {
+    let mut snafu: String = String::new();
+    let mut n = n;
+
+    // Find the largest place value needed. The most that can be written with `place_value` as the
+    // top digit is 2 * place_value + place_value / 2 either way from zero, which is rearranged to
+    // avoid overflowing near i64::MAX and i64::MIN.
+    let magnitude = n.unsigned_abs();
+    let mut place_value: i64 = 1;
+    while magnitude.saturating_sub(place_value as u64 / 2 + 1) / 2 >= place_value as u64 {
+        place_value *= 5;
+    }
+
+    while place_value > 0 {
+        let orig_n = n;
+        let mut digit = 0;
+        if n > 0 {
+            // `place_value / 2` is the max value representable by subsequent digits.
+            while n > place_value / 2 {
+                n -= place_value;
+                digit += 1;
+            }
+        } else {
+            while n < -place_value / 2 {
+                n += place_value;
+                digit -= 1;
+            }
+        }
+        trace!("orig_n={orig_n} n={n} pv={place_value} digit={digit}");
+        snafu.push(to_snafu_digit(digit));
+        place_value /= 5;
+    }
+    snafu
+}
+
+// Parse a SNAFU number into digit values from -2 to 2, least significant first, so numbers can be
+// added without converting them to a fixed-size integer. Errors are reported on line 1, since `s`
+// is a single number.
+pub fn parse_digits(s: &str) -> Result<Vec<i8>, ParseSnafuError> {
+    let mut digits = s.chars()
+        .enumerate()
+        .map(|(i, c)| from_snafu_digit(c).map(|d| d as i8).ok_or(ParseSnafuError::BadChar { line: 1, column: i + 1, c }))
+        .collect::<Result<Vec<i8>, _>>()?;
+    digits.reverse();
+    Ok(digits)
+}
+
+pub fn format_digits(digits: &[i8]) -> String {
+    let s: String = digits.iter()
+        .rev()
+        .skip_while(|&&d| d == 0)
+        .map(|&d| to_snafu_digit(d as i64))
+        .collect();
+    if s.is_empty() { "0".to_string() } else { s }
+}
+
+// Add `n` to `sum` a digit at a time. Each column adds up to between -5 and 5 counting the carry,
+// which is brought back into range by carrying 1 or borrowing 1 from the next column.
+pub fn add_digits(sum: &mut Vec<i8>, n: &[i8]) {
+    if sum.len() < n.len() {
+        sum.resize(n.len(), 0);
+    }
+    let mut carry = 0;
+    for (i, d) in sum.iter_mut().enumerate() {
+        if carry == 0 && i >= n.len() {
+            break;
+        }
+        let mut column = *d + n.get(i).copied().unwrap_or(0) + carry;
+        carry = 0;
+        if column > 2 {
+            column -= 5;
+            carry = 1;
+        } else if column < -2 {
+            column += 5;
+            carry = -1;
+        }
+        *d = column;
+    }
+    if carry != 0 {
+        sum.push(carry);
+    }
+}
+
+// The value of a SNAFU number given as digits, or None if it doesn't fit in an i64.
+pub fn checked_value(digits: &[i8]) -> Option<i64> {
+    // Accumulate in an i128 so numbers near i64::MIN and i64::MAX don't overflow before the last
+    // digit brings them back into range.
+    let value = digits.iter()
+        .rev()
+        .try_fold(0i128, |value, &d| value.checked_mul(5)?.checked_add(d as i128))?;
+    i64::try_from(value).ok()
+}
+
+// A SNAFU number of any size.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Snafu {
+    // Least significant first, without any leading zeros, so zero has no digits.
+    digits: Vec<i8>,
+}
+
+impl Snafu {
+    fn from_digits(mut digits: Vec<i8>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Snafu { digits }
+    }
+
+    pub fn digits(&self) -> &[i8] {
+        &self.digits
+    }
+}
+
+impl std::str::FromStr for Snafu {
+    type Err = ParseSnafuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Snafu::from_digits(parse_digits(s)?))
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_digits(&self.digits))
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(n: i64) -> Self {
+        let digits = parse_digits(&to_snafu(n)).expect("to_snafu should only write SNAFU digits");
+        Snafu::from_digits(digits)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnafuOverflowError;
+
+impl fmt::Display for SnafuOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SNAFU number too big for an i64")
+    }
+}
+
+impl Error for SnafuOverflowError {}
+
+impl From<SnafuOverflowError> for AocError {
+    fn from(e: SnafuOverflowError) -> Self {
+        AocError::Parse(e.to_string())
+    }
+}
+
+impl TryFrom<&Snafu> for i64 {
+    type Error = SnafuOverflowError;
+
+    fn try_from(n: &Snafu) -> Result<Self, Self::Error> {
+        checked_value(&n.digits).ok_or(SnafuOverflowError)
+    }
+}
+
+impl TryFrom<Snafu> for i64 {
+    type Error = SnafuOverflowError;
+
+    fn try_from(n: Snafu) -> Result<Self, Self::Error> {
+        i64::try_from(&n)
+    }
+}
+
+impl AddAssign<&Snafu> for Snafu {
+    fn add_assign(&mut self, other: &Snafu) {
+        add_digits(&mut self.digits, &other.digits);
+        // Cancelling digits can leave zeros at the top.
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+}
+
+impl AddAssign for Snafu {
+    fn add_assign(&mut self, other: Snafu) {
+        *self += &other;
+    }
+}
+
+impl Add<&Snafu> for Snafu {
+    type Output = Snafu;
+
+    fn add(mut self, other: &Snafu) -> Snafu {
+        self += other;
+        self
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: Snafu) -> Snafu {
+        self + &other
+    }
+}
+
+impl std::iter::Sum for Snafu {
+    fn sum<I: Iterator<Item=Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), |sum, n| sum + n)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Base {
+    Decimal,
+    Snafu,
+}
+
+impl Base {
+    // Guess which base `s` is written in. Decimal integers, with an optional leading minus sign,
+    // are taken to be decimal even if they'd also be valid SNAFU, like "12" or "-10".
+    pub fn detect(s: &str) -> Base {
+        let unsigned = s.strip_prefix('-').unwrap_or(s);
+        if !unsigned.is_empty() && unsigned.chars().all(|c| c.is_ascii_digit()) {
+            Base::Decimal
+        } else {
+            Base::Snafu
+        }
+    }
+}
+
+impl std::str::FromStr for Base {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(Base::Decimal),
+            "snafu" => Ok(Base::Snafu),
+            _ => Err(format!("unknown base: {s}")),
+        }
+    }
+}
+
+// Convert `s` from `from`, or the base it looks like it's in, to the other base.
+pub fn convert(s: &str, from: Option<Base>) -> Result<String, AocError> {
+    let s = s.trim();
+    match from.unwrap_or_else(|| Base::detect(s)) {
+        Base::Decimal => Ok(to_snafu(s.parse()?)),
+        Base::Snafu => Ok(i64::try_from(s.parse::<Snafu>()?)?.to_string()),
+    }
+}
+
+// Sum the SNAFU numbers on each line, without any limit on how big they get. The input is read a
+// buffer at a time and parsed as it goes, so only the longest line and the sum are held in memory.
+pub fn sum_stream(mut r: impl BufRead) -> Result<Vec<i8>, AocError> {
+    let mut sum: Vec<i8> = Vec::new();
+    // The digits of the current line, most significant first.
+    let mut digits: Vec<i8> = Vec::new();
+    let (mut line, mut column) = (1, 0);
+    let mut add_line = |digits: &mut Vec<i8>| {
+        digits.reverse();
+        add_digits(&mut sum, digits);
+        digits.clear();
+    };
+    loop {
+        let buf = r.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for &b in buf {
+            match b {
+                b'\n' => {
+                    add_line(&mut digits);
+                    line += 1;
+                    column = 0;
+                },
+                b'\r' => (),
+                _ => {
+                    column += 1;
+                    // Report bytes that aren't ASCII as the replacement character, rather than
+                    // decoding UTF-8 across buffers.
+                    let c = if b.is_ascii() { b as char } else { char::REPLACEMENT_CHARACTER };
+                    let d = from_snafu_digit(c).ok_or(ParseSnafuError::BadChar { line, column, c })?;
+                    digits.push(d as i8);
+                },
+            }
+        }
+        let len = buf.len();
+        r.consume(len);
+    }
+    add_line(&mut digits);
+    Ok(sum)
+}
+
+pub fn part1(r: impl BufRead) -> Result<String, AocError> {
+    Ok(format_digits(&sum_stream(r)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+    use std::io;
+
+    const EXAMPLE: &str = "\
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122";
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(EXAMPLE.as_bytes()).unwrap(), "2=-1=0");
+    }
+
+    #[test]
+    fn test_part1_huge() {
+        // Far more than fits in an i64: 2000 numbers of forty 2s, which is (5^40 - 1) / 2 each.
+        let input = vec!["2".repeat(40); 2000].join("\n");
+        let mut expected = parse_digits(&"2".repeat(40)).unwrap();
+        for _ in 0..1999 {
+            add_digits(&mut expected, &parse_digits(&"2".repeat(40)).unwrap());
+        }
+        assert_eq!(part1(input.as_bytes()).unwrap(), format_digits(&expected));
+        assert!(format_digits(&expected).len() > 40);
+    }
+
+    #[test]
+    fn test_add_digits() {
+        // Every SNAFU number up to three digits long, including the negative ones.
+        let mut numbers = vec![String::new()];
+        for _ in 0..3 {
+            numbers = numbers.iter()
+                .flat_map(|n| "=-012".chars().map(move |c| format!("{n}{c}")))
+                .chain(numbers.iter().cloned())
+                .collect();
+        }
+        numbers.retain(|n| !n.is_empty());
+        for a in &numbers {
+            for b in &numbers {
+                let mut sum = parse_digits(a).unwrap();
+                add_digits(&mut sum, &parse_digits(b).unwrap());
+                let expected = from_snafu(a).unwrap() + from_snafu(b).unwrap();
+                assert_eq!(from_snafu(&format_digits(&sum)).unwrap(), expected, "{a} + {b}");
+            }
+        }
+        assert_eq!(format_digits(&[]), "0");
+        assert_eq!(format_digits(&[2, 0, 0]), "2");
+        assert_eq!(format_digits(&parse_digits("2=-1=0").unwrap()), "2=-1=0");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = part1("1=\n12x1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, AocError::Parse(_)));
+        assert_eq!(err.to_string(), "line 2, column 3: unexpected character 'x'");
+        assert_eq!(from_snafu("3"), Err(ParseSnafuError::BadChar { line: 1, column: 1, c: '3' }));
+        assert_eq!(part1("1=\r\n12\r\n".as_bytes()).unwrap(), "20");
+    }
+
+    #[test]
+    fn test_from_snafu() {
+        assert_eq!(from_snafu("1").unwrap(), 1);
+        assert_eq!(from_snafu("2").unwrap(), 2);
+        assert_eq!(from_snafu("1=").unwrap(), 3);
+        assert_eq!(from_snafu("1-").unwrap(), 4);
+        assert_eq!(from_snafu("10").unwrap(), 5);
+        assert_eq!(from_snafu("11").unwrap(), 6);
+        assert_eq!(from_snafu("12").unwrap(), 7);
+        assert_eq!(from_snafu("2=").unwrap(), 8);
+        assert_eq!(from_snafu("2-").unwrap(), 9);
+        assert_eq!(from_snafu("20").unwrap(), 10);
+        assert_eq!(from_snafu("1=0").unwrap(), 15);
+        assert_eq!(from_snafu("1-0").unwrap(), 20);
+        assert_eq!(from_snafu("1=11-2").unwrap(), 2022);
+        assert_eq!(from_snafu("1-0---0").unwrap(), 12345);
+        assert_eq!(from_snafu("1121-1110-1=0").unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_to_snafu() {
+        assert_eq!(to_snafu(0), "0");
+        assert_eq!(to_snafu(1), "1");
+        assert_eq!(to_snafu(2), "2");
+        assert_eq!(to_snafu(3), "1=");
+        assert_eq!(to_snafu(4), "1-");
+        assert_eq!(to_snafu(5), "10");
+        assert_eq!(to_snafu(6), "11");
+        assert_eq!(to_snafu(7), "12");
+        assert_eq!(to_snafu(8), "2=");
+        assert_eq!(to_snafu(9), "2-");
+        assert_eq!(to_snafu(10), "20");
+        assert_eq!(to_snafu(12), "22");
+        assert_eq!(to_snafu(13), "1==");
+        assert_eq!(to_snafu(15), "1=0");
+        assert_eq!(to_snafu(20), "1-0");
+        assert_eq!(to_snafu(2022), "1=11-2");
+        assert_eq!(to_snafu(12345), "1-0---0");
+        assert_eq!(to_snafu(314159265), "1121-1110-1=0");
+        assert_eq!(to_snafu(-1), "-");
+        assert_eq!(to_snafu(-2), "=");
+        assert_eq!(to_snafu(-3), "-2");
+        assert_eq!(to_snafu(-2022), "-2--1=");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut boundaries = vec![1, 2, 3, i64::MAX - 1, i64::MAX];
+        for k in 1..=27 {
+            let pv = 5i64.pow(k);
+            // The largest number with k digits, and the place values themselves.
+            boundaries.extend([(pv - 1) / 2, (pv - 1) / 2 + 1, pv - 1, pv, pv + 1]);
+        }
+        let mut rng = Rng::new(1);
+        // Random numbers of every magnitude and sign.
+        let random_numbers = (0..10_000).map(|i| (rng.next_u64() as i64) >> (i % 64));
+        boundaries.extend(boundaries.clone().iter().map(|n| -n));
+        boundaries.extend([0, i64::MIN, i64::MIN + 1]);
+        for n in boundaries.into_iter().chain(random_numbers) {
+            let snafu = to_snafu(n);
+            assert_eq!(from_snafu(&snafu), Ok(n), "{n} -> {snafu}");
+            assert_eq!(snafu.starts_with('0'), n == 0, "{n} -> {snafu}");
+            assert_eq!(snafu.starts_with(['-', '=']), n < 0, "{n} -> {snafu}");
+        }
+        assert_eq!(to_snafu(i64::MAX), "1110--=-02=100==0-0=11=11212");
+        // Longer numbers don't fit, unless they start with zeros.
+        assert_eq!(from_snafu("1110--=-02=100==0-0=11=11220"), Err(ParseSnafuError::Overflow));
+        assert_eq!(from_snafu(&"1".repeat(30)), Err(ParseSnafuError::Overflow));
+        assert_eq!(from_snafu(&"=".repeat(30)), Err(ParseSnafuError::Overflow));
+        assert_eq!(from_snafu(&format!("{}1", "0".repeat(40))), Ok(1));
+        assert_eq!(ParseSnafuError::Overflow.to_string(), "SNAFU number too big for an i64");
+        assert_eq!(to_snafu(i64::MIN), "---011210=2-00220102--2--==2");
+    }
+
+    #[test]
+    fn test_parse_random() {
+        // Random strings of up to 27 digits, which always fit in an i64, with the occasional
+        // character that isn't a digit.
+        let alphabet: Vec<char> = "=-012=-012=-012=-012x ".chars().collect();
+        let mut rng = Rng::new(1);
+        for _ in 0..10_000 {
+            let len = rng.below(28);
+            let s: String = (0..len).map(|_| *rng.choose(&alphabet)).collect();
+            match s.chars().position(|c| !"=-012".contains(c)) {
+                Some(i) => {
+                    let c = s.chars().nth(i).unwrap();
+                    assert_eq!(from_snafu(&s), Err(ParseSnafuError::BadChar { line: 1, column: i + 1, c }));
+                },
+                None => {
+                    let n = from_snafu(&s).unwrap();
+                    assert_eq!(from_snafu(&format_digits(&parse_digits(&s).unwrap())), Ok(n));
+                    assert_eq!(to_snafu(n), format_digits(&parse_digits(&s).unwrap()), "{s}");
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert("2022", None).unwrap(), "1=11-2");
+        assert_eq!(convert("-3", None).unwrap(), "-2");
+        assert_eq!(convert(" 1=11-2\n", None).unwrap(), "2022");
+        assert_eq!(convert("=", None).unwrap(), "-2");
+        assert_eq!(convert("12", None).unwrap(), "22");
+        assert_eq!(convert("12", Some(Base::Snafu)).unwrap(), "7");
+        assert!(convert("1=x", None).is_err());
+        assert!(convert("1=", Some(Base::Decimal)).is_err());
+        assert!(convert("99999999999999999999", None).is_err());
+        assert!(convert(&"2".repeat(28), None).is_err());
+        assert_eq!(convert(&to_snafu(i64::MIN), None).unwrap(), i64::MIN.to_string());
+        assert_eq!(checked_value(&parse_digits(&to_snafu(i64::MAX)).unwrap()), Some(i64::MAX));
+        assert_eq!(checked_value(&[]), Some(0));
+    }
+
+    // Reads `line` over and over, `n` times, without holding more than one copy in memory.
+    struct Repeat {
+        line: &'static [u8],
+        n: usize,
+        pos: usize,
+    }
+
+    impl io::Read for Repeat {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.n == 0 {
+                return Ok(0);
+            }
+            let len = buf.len().min(self.line.len() - self.pos);
+            buf[..len].copy_from_slice(&self.line[self.pos..(self.pos + len)]);
+            self.pos += len;
+            if self.pos == self.line.len() {
+                self.pos = 0;
+                self.n -= 1;
+            }
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn test_sum_stream() {
+        // Tiny buffers split numbers across reads.
+        let r = io::BufReader::with_capacity(3, EXAMPLE.as_bytes());
+        assert_eq!(format_digits(&sum_stream(r).unwrap()), "2=-1=0");
+        let r = io::BufReader::with_capacity(3, "1=\r\n12x\n".as_bytes());
+        let err = sum_stream(r).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 3: unexpected character 'x'");
+        let err = sum_stream("1\n\u{e9}".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 1: unexpected character '\u{fffd}'");
+        assert_eq!(format_digits(&sum_stream("".as_bytes()).unwrap()), "0");
+
+        let n = 300_000;
+        let r = io::BufReader::new(Repeat { line: b"2=-1=0\n", n, pos: 0 });
+        let expected = to_snafu(from_snafu("2=-1=0").unwrap() * n as i64);
+        assert_eq!(format_digits(&sum_stream(r).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_snafu() {
+        let numbers: Vec<Snafu> = EXAMPLE.lines().map(|l| l.parse().unwrap()).collect();
+        let sum: Snafu = numbers.iter().cloned().sum();
+        assert_eq!(sum.to_string(), "2=-1=0");
+        assert_eq!(i64::try_from(&sum), Ok(4890));
+        assert_eq!(Snafu::from(4890), sum);
+        assert_eq!(numbers[0].clone() + &numbers[1], Snafu::from(1747 + 906));
+
+        assert_eq!("0012".parse::<Snafu>().unwrap(), Snafu::from(7));
+        assert_eq!(Snafu::from(0).to_string(), "0");
+        assert_eq!(Snafu::from(0).digits(), &[]);
+        // Adding a number and its negation leaves no digits behind.
+        let mut n = Snafu::from(-2022);
+        n += Snafu::from(2022);
+        assert_eq!(n, Snafu::default());
+        assert_eq!("1x".parse::<Snafu>(), Err(ParseSnafuError::BadChar { line: 1, column: 2, c: 'x' }));
+
+        let max = Snafu::from(i64::MAX);
+        assert_eq!(i64::try_from(&max), Ok(i64::MAX));
+        assert_eq!(i64::try_from(max + Snafu::from(1)), Err(SnafuOverflowError));
+        assert_eq!(i64::try_from(Snafu::from(i64::MIN)), Ok(i64::MIN));
+    }
+}
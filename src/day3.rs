@@ -0,0 +1,352 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use aoclib::iter::Chunks;
+use aoclib::parallel;
+
+use crate::error::AocError;
+
+pub const LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// Which characters are items, and their priorities.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Priorities {
+    // Indexed by ASCII character, with 0 for ones that aren't items, since every priority is
+    // at least 1.
+    ascii: [u32; 128],
+    other: HashMap<char, u32>,
+}
+
+impl Default for Priorities {
+    fn default() -> Self {
+        Priorities::from_alphabet(LETTERS).unwrap()
+    }
+}
+
+impl Priorities {
+    // Items in order of priority, from 1 up, like LETTERS.
+    pub fn from_alphabet(alphabet: &str) -> Result<Priorities, String> {
+        let mut priorities = Priorities { ascii: [0; 128], other: HashMap::new() };
+        for (c, priority) in alphabet.chars().zip(1..) {
+            if c == '\n' || c == '\r' {
+                return Err("line endings can't be items".to_string());
+            }
+            if priorities.get(c).is_ok() {
+                return Err(format!("repeated item: {c:?}"));
+            }
+            match priorities.ascii.get_mut(c as usize) {
+                Some(p) => *p = priority,
+                None => _ = priorities.other.insert(c, priority),
+            }
+        }
+        if priorities.ascii.iter().all(|&p| p == 0) && priorities.other.is_empty() {
+            return Err("the alphabet is empty".to_string());
+        }
+        Ok(priorities)
+    }
+
+    pub fn get(&self, c: char) -> Result<u32, String> {
+        let priority = match self.ascii.get(c as usize) {
+            Some(&p) => p,
+            None => self.other.get(&c).copied().unwrap_or(0),
+        };
+        match priority {
+            0 => Err(format!("unexpected item: {c:?}")),
+            p => Ok(p),
+        }
+    }
+
+    fn check_items(&self, line: &str) -> Result<(), String> {
+        line.chars().try_for_each(|c| self.get(c).map(|_| ()))
+    }
+}
+
+// Split a rucksack in half, by items rather than bytes, so that reports can show bad lines.
+fn compartments(line: &str) -> (&str, &str) {
+    let mid = line.char_indices().nth(line.chars().count()/2).map_or(line.len(), |(i, _)| i);
+    line.split_at(mid)
+}
+
+fn rucksack_priority(priorities: &Priorities, line: &str) -> Result<u32, String> {
+    priorities.check_items(line)?;
+    let n = line.chars().count();
+    if !n.is_multiple_of(2) {
+        return Err(format!("odd number of items, {n}, so the compartments can't be the same size"));
+    }
+    let item = duplicate(line).ok_or("no item is in both compartments")?;
+    priorities.get(item)
+}
+
+// An item that's in both of a rucksack's compartments, if there is one.
+fn duplicate(line: &str) -> Option<char> {
+    let (l, r) = compartments(line);
+    let l_set: HashSet<char> = HashSet::from_iter(l.chars());
+    let r_set: HashSet<char> = HashSet::from_iter(r.chars());
+    let mut intersection = l_set.intersection(&r_set);
+    intersection.next().copied()
+}
+
+pub fn part1<T>(priorities: &Priorities, lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    sum_rucksacks(priorities, lines, 1)
+}
+
+pub fn part2<T>(priorities: &Priorities, lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    Ok(badges(priorities, lines)?.iter().map(|b| b.priority).sum())
+}
+
+// Like part1, but with the rucksacks split between threads.
+pub fn part1_parallel<T: AsRef<str> + Sync>(priorities: &Priorities, lines: &[T], threads: usize) -> Result<u32, AocError> {
+    let sums = parallel::map_chunks(lines, 1, threads, |i, chunk| sum_rucksacks(priorities, chunk.iter(), i + 1));
+    // The chunks are in order, so the first error is the one for the earliest line.
+    sums.into_iter().sum()
+}
+
+// Like part2, but with the groups split between threads.
+pub fn part2_parallel<T: AsRef<str> + Sync>(priorities: &Priorities, lines: &[T], threads: usize) -> Result<u32, AocError> {
+    let sums = parallel::map_chunks(lines, 3, threads, |i, chunk| {
+        Ok::<u32, AocError>(read_badges(priorities, chunk.iter(), i + 1)?.iter().map(|b| b.priority).sum())
+    });
+    sums.into_iter().sum()
+}
+
+// Sum the priorities of the rucksacks on lines numbered from `line_no`.
+fn sum_rucksacks<T>(priorities: &Priorities, lines: T, line_no: usize) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut sum = 0;
+    for (line_no, line) in (line_no..).zip(lines) {
+        let line = line.as_ref();
+        sum += rucksack_priority(priorities, line).map_err(|e| AocError::Parse(format!("line {line_no}: {e}: {line:?}")))?;
+    }
+    Ok(sum)
+}
+
+// A rucksack's duplicated item, for reports. Unlike part1, bad rucksacks are included rather than
+// being errors, so they can be looked at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    // The 1-based line the rucksack's on.
+    pub line_no: usize,
+    pub left: String,
+    pub right: String,
+    // Rucksacks without a duplicated item, or with one that isn't an item, have a priority of 0.
+    pub item: Option<char>,
+    pub priority: u32,
+}
+
+pub fn duplicates<T>(priorities: &Priorities, lines: T) -> Vec<Duplicate>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    lines.enumerate().map(|(i, line)| {
+        let line = line.as_ref();
+        let (left, right) = compartments(line);
+        let item = duplicate(line);
+        Duplicate {
+            line_no: i + 1,
+            left: left.to_string(),
+            right: right.to_string(),
+            item,
+            priority: item.map_or(0, |c| priorities.get(c).unwrap_or(0)),
+        }
+    }).collect()
+}
+
+// The item a group of three elves all carry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Badge {
+    // The 1-based line of the group's first rucksack.
+    pub line_no: usize,
+    pub item: char,
+    pub priority: u32,
+}
+
+pub fn badges<T>(priorities: &Priorities, lines: T) -> Result<Vec<Badge>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    read_badges(priorities, lines, 1)
+}
+
+// The badges of the groups on lines numbered from `first_line`.
+fn read_badges<T>(priorities: &Priorities, lines: T, first_line: usize) -> Result<Vec<Badge>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut badges = Vec::new();
+    for (i, chunk) in Chunks::new(lines, 3).enumerate() {
+        let line_no = first_line + 3*i;
+        let chunk = chunk.map_err(|e| {
+            AocError::Parse(format!("line {line_no}: groups should have 3 rucksacks, but the last has {}", e.items.len()))
+        })?;
+        for (j, line) in chunk.iter().enumerate() {
+            let line = line.as_ref();
+            priorities.check_items(line).map_err(|e| AocError::Parse(format!("line {}: {e}: {line:?}", line_no + j)))?;
+        }
+        let item = find_common_item(chunk.iter())
+            .map_err(|e| AocError::Parse(format!("lines {}-{}: {e}", line_no, line_no + 2)))?;
+        badges.push(Badge { line_no, item, priority: priorities.get(item)? });
+    }
+    Ok(badges)
+}
+
+pub fn write_duplicates(mut w: impl Write, duplicates: &[Duplicate]) -> io::Result<()> {
+    writeln!(w, "{:>5} {:<4} {:>8} compartments", "line", "item", "priority")?;
+    for d in duplicates {
+        let item = d.item.map_or("-".to_string(), |c| c.to_string());
+        writeln!(w, "{:>5} {:<4} {:>8} {} {}", d.line_no, item, d.priority, d.left, d.right)?;
+    }
+    Ok(())
+}
+
+pub fn write_badges(mut w: impl Write, badges: &[Badge]) -> io::Result<()> {
+    writeln!(w, "{:>5} {:>11} {:<5} {:>8}", "group", "lines", "badge", "priority")?;
+    for (i, b) in badges.iter().enumerate() {
+        let lines = format!("{}-{}", b.line_no, b.line_no + 2);
+        writeln!(w, "{:>5} {:>11} {:<5} {:>8}", i + 1, lines, b.item, b.priority)?;
+    }
+    Ok(())
+}
+
+fn find_common_item<T>(lines: T) -> Result<char, String>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut set: HashSet<char> = HashSet::new();
+    for line in lines {
+        let line_set = HashSet::from_iter(line.as_ref().chars());
+        match set.len() {
+            0 => set = line_set,
+            _ => set = set.intersection(&line_set).cloned().collect(),
+        }
+    }
+    if set.len() != 1 {
+        return Err(format!("expected exactly one item in every rucksack, found {}", set.len()));
+    }
+    Ok(set.into_iter().next().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines() -> Vec<String> {
+        let input = "\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw".to_owned();
+        input.lines().map(|l| l.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_part1() {
+        let sum = part1(&Priorities::default(), lines().iter()).unwrap();
+        assert_eq!(sum, 157);
+    }
+
+    #[test]
+    fn test_part2() {
+        let sum = part2(&Priorities::default(), lines().iter()).unwrap();
+        assert_eq!(sum, 70);
+    }
+
+    #[test]
+    fn test_parallel() {
+        // Enough groups that every thread gets some.
+        let lines: Vec<String> = (0..50).flat_map(|_| lines()).collect();
+        for threads in [1, 2, 5, 16] {
+            assert_eq!(part1_parallel(&Priorities::default(), &lines, threads).unwrap(), 157 * 50, "threads={threads}");
+            assert_eq!(part2_parallel(&Priorities::default(), &lines, threads).unwrap(), 70 * 50, "threads={threads}");
+        }
+
+        // Errors have the line they'd have without threads, and the earliest one wins.
+        let mut bad = lines.clone();
+        bad[100] = "abc".to_string();
+        bad[250] = "ab1b".to_string();
+        for threads in [1, 4] {
+            assert_eq!(part1_parallel(&Priorities::default(), &bad, threads).unwrap_err().to_string(), part1(&Priorities::default(), bad.iter()).unwrap_err().to_string());
+            assert_eq!(part2_parallel(&Priorities::default(), &bad, threads).unwrap_err().to_string(), part2(&Priorities::default(), bad.iter()).unwrap_err().to_string());
+        }
+        assert!(part1(&Priorities::default(), bad.iter()).unwrap_err().to_string().starts_with("line 101: "));
+        assert!(part2_parallel(&Priorities::default(), &lines[..299], 4).unwrap_err().to_string().starts_with("line 298: "));
+    }
+
+    #[test]
+    fn test_priorities() {
+        let default = Priorities::default();
+        assert_eq!((default.get('a'), default.get('Z')), (Ok(1), Ok(52)));
+        assert_eq!(default.get('0'), Err("unexpected item: '0'".to_string()));
+
+        let digits = Priorities::from_alphabet("0123456789").unwrap();
+        assert_eq!(part1(&digits, ["1231", "9189"].iter()).unwrap(), 2 + 10);
+        assert_eq!(part2(&digits, ["1052", "3064", "0009"].iter()).unwrap(), 1);
+        assert!(part1(&digits, ["abca"].iter()).is_err());
+
+        // Anything but line endings can be an item, and compartments are split by items, not bytes.
+        let greek = Priorities::from_alphabet("αβγδ €").unwrap();
+        assert_eq!(part1(&greek, ["αβ€β", "γ δ "].iter()).unwrap(), 2 + 5);
+        assert!(part1(&greek, ["αβ€"].iter()).unwrap_err().to_string().contains("odd number of items, 3"));
+
+        assert_eq!(Priorities::from_alphabet("abca"), Err("repeated item: 'a'".to_string()));
+        assert_eq!(Priorities::from_alphabet(""), Err("the alphabet is empty".to_string()));
+        assert!(Priorities::from_alphabet("a\nb").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err1 = |lines: &[&str]| part1(&Priorities::default(), lines.iter()).unwrap_err().to_string();
+        let err2 = |lines: &[&str]| part2(&Priorities::default(), lines.iter()).unwrap_err().to_string();
+        assert_eq!(err1(&["abca", "ab1b"]), "line 2: unexpected item: '1': \"ab1b\"");
+        assert_eq!(err1(&["abcab"]), "line 1: odd number of items, 5, so the compartments can't be the same size: \"abcab\"");
+        assert_eq!(err1(&["abcd"]), "line 1: no item is in both compartments: \"abcd\"");
+        assert_eq!(err1(&["aébé"]), "line 1: unexpected item: 'é': \"aébé\"");
+        assert_eq!(err2(&["ab", "ac", "ad", "ab"]), "line 4: groups should have 3 rucksacks, but the last has 1");
+        assert_eq!(err2(&["ab", "ab", "ab"]), "lines 1-3: expected exactly one item in every rucksack, found 2");
+        assert_eq!(err2(&["ab", "a b", "ab"]), "line 2: unexpected item: ' ': \"a b\"");
+    }
+
+    #[test]
+    fn test_report() {
+        let mut input = lines();
+        input.insert(1, "abcd".to_string());
+        let dups = duplicates(&Priorities::default(), input.iter());
+        let items: Vec<(usize, Option<char>, u32)> = dups.iter().map(|d| (d.line_no, d.item, d.priority)).collect();
+        assert_eq!(items[..3], [(1, Some('p'), 16), (2, None, 0), (3, Some('L'), 38)]);
+        assert_eq!((dups[0].left.as_str(), dups[0].right.as_str()), ("vJrwpWtwJgWr", "hcsFMMfFFhFp"));
+
+        let mut w = Vec::new();
+        write_duplicates(&mut w, &dups[..2]).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), concat!(
+            " line item priority compartments\n",
+            "    1 p          16 vJrwpWtwJgWr hcsFMMfFFhFp\n",
+            "    2 -           0 ab cd\n",
+        ));
+
+        let badges = badges(&Priorities::default(), lines().iter()).unwrap();
+        assert_eq!(badges[1], Badge { line_no: 4, item: 'Z', priority: 52 });
+        let mut w = Vec::new();
+        write_badges(&mut w, &badges).unwrap();
+        assert_eq!(String::from_utf8(w).unwrap(), concat!(
+            "group       lines badge priority\n",
+            "    1         1-3 r           18\n",
+            "    2         4-6 Z           52\n",
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+use std::io::{self, Write};
+
+use aoclib::intervals::{DisjointIntervals, Interval};
+
+use crate::error::AocError;
+
+// An elf's inclusive section assignment, eg 2-4.
+fn parse_sections(s: &str) -> Result<Interval<i64>, String> {
+    match s.split('-').collect::<Vec<&str>>()[..] {
+        [start, end] => {
+            let start = start.parse::<i64>().map_err(|e| format!("parse range {s:?}: {e}"))?;
+            let end = end.parse::<i64>().map_err(|e| format!("parse range {s:?}: {e}"))?;
+            if start > end {
+                return Err(format!("parse range {s:?}: start is after end"));
+            }
+            Ok(Interval::new(start, end))
+        },
+        _ => Err(format!("parse range {s:?}: expected START-END")),
+    }
+}
+
+// A line's assignments, one per elf in the team. There's usually a pair, but there can be more.
+fn line_to_ranges(line: &str) -> Result<Vec<Interval<i64>>, String> {
+    let ranges = line.split(',').map(parse_sections).collect::<Result<Vec<_>, _>>()?;
+    if ranges.len() < 2 {
+        return Err("expected at least two ranges on line".to_owned());
+    }
+    Ok(ranges)
+}
+
+// Each line's assignments, in order.
+fn read_teams<T>(lines: T) -> Result<Vec<Vec<Interval<i64>>>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    lines.enumerate()
+        .map(|(i, l)| {
+            let line = l.as_ref();
+            line_to_ranges(line).map_err(|e| AocError::Parse(format!("line {}: {e}: {line:?}", i + 1)))
+        })
+        .collect()
+}
+
+// Two elves' assignments from a team, with the 1-based line they're on and their 1-based places
+// on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pair {
+    pub line_no: usize,
+    pub elves: (usize, usize),
+    pub a: Interval<i64>,
+    pub b: Interval<i64>,
+}
+
+// Every pair of elves on each line: a team of n has n*(n-1)/2 of them.
+fn pairs<T>(lines: T) -> Result<Vec<Pair>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut pairs = Vec::new();
+    for (i, ranges) in read_teams(lines)?.iter().enumerate() {
+        for (j, &a) in ranges.iter().enumerate() {
+            for (k, &b) in ranges.iter().enumerate().skip(j + 1) {
+                pairs.push(Pair { line_no: i + 1, elves: (j + 1, k + 1), a, b });
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+impl Pair {
+    // Whether one assignment contains the other, like part1 counts.
+    pub fn contained(&self) -> bool {
+        self.a.covers(&self.b) || self.b.covers(&self.a)
+    }
+
+    // Whether the assignments overlap at all, like part2 counts.
+    pub fn overlapping(&self) -> bool {
+        self.a.intersection(&self.b).is_some()
+    }
+}
+
+// The pairs that part1 or part2 count, to check which lines they are.
+pub fn report<T>(lines: T, part: u32) -> Result<Vec<Pair>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut pairs = pairs(lines)?;
+    pairs.retain(|p| if part == 1 { p.contained() } else { p.overlapping() });
+    Ok(pairs)
+}
+
+pub fn write_report(mut w: impl Write, pairs: &[Pair]) -> io::Result<()> {
+    writeln!(w, "{:>5} {:>5} {:>11} {:>11}", "line", "elves", "first", "second")?;
+    for p in pairs {
+        let elves = format!("{},{}", p.elves.0, p.elves.1);
+        let a = format!("{}-{}", p.a.start, p.a.end);
+        let b = format!("{}-{}", p.b.start, p.b.end);
+        writeln!(w, "{:>5} {:>5} {:>11} {:>11}", p.line_no, elves, a, b)?;
+    }
+    Ok(())
+}
+
+pub fn part1<T>(lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    Ok(report(lines, 1)?.len() as u32)
+}
+
+pub fn part2<T>(lines: T) -> Result<u32, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    Ok(report(lines, 2)?.len() as u32)
+}
+
+// The total number of sections that both elves in a pair are assigned, over all the pairs.
+pub fn part3<T>(lines: T) -> Result<i64, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    Ok(pairs(lines)?.iter().map(|p| p.a.intersection(&p.b).map_or(0, |r| r.len())).sum())
+}
+
+// How all the assignments together cover the sections.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coverage {
+    // The number of sections assigned to anyone.
+    pub covered: i64,
+    // The longest run of unassigned sections between assigned ones.
+    pub largest_gap: Option<Interval<i64>>,
+    // The lowest section assigned to the most elves, and how many that is.
+    pub busiest: Option<(i64, usize)>,
+}
+
+pub fn coverage<T>(lines: T) -> Result<Coverage, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let ranges: Vec<Interval<i64>> = read_teams(lines)?.into_iter().flatten().collect();
+    let sections: DisjointIntervals<i64> = ranges.iter().map(|r| r.half_open()).collect();
+    let largest_gap = match (sections.iter().next(), sections.iter().next_back()) {
+        (Some(first), Some(last)) => sections.gaps(first.start..last.end)
+            .into_iter()
+            .map(|g| Interval::new(g.start, g.end - 1))
+            .fold(None, |best: Option<Interval<i64>>, g| match best {
+                Some(b) if b.len() >= g.len() => Some(b),
+                _ => Some(g),
+            }),
+        _ => None,
+    };
+
+    // Sweep over the ranges' ends, keeping count of the elves on the current section. Where a
+    // range starts and another ends, the end goes first.
+    let mut events: Vec<(i64, i64)> = ranges.iter().flat_map(|r| [(r.start, 1), (r.end + 1, -1)]).collect();
+    events.sort();
+    let mut busiest: Option<(i64, usize)> = None;
+    let mut elves = 0;
+    for (section, delta) in events {
+        elves += delta;
+        if busiest.is_none_or(|(_, n)| elves as usize > n) {
+            busiest = Some((section, elves as usize));
+        }
+    }
+
+    Ok(Coverage { covered: sections.covered_len(), largest_gap, busiest })
+}
+
+pub fn write_coverage(mut w: impl Write, coverage: &Coverage) -> io::Result<()> {
+    writeln!(w, "covered {}", coverage.covered)?;
+    match coverage.largest_gap {
+        Some(g) => writeln!(w, "largest gap {}-{} ({} sections)", g.start, g.end, g.len())?,
+        None => writeln!(w, "largest gap none")?,
+    }
+    match coverage.busiest {
+        Some((section, elves)) => writeln!(w, "most overlapped {section} ({elves} elves)"),
+        None => writeln!(w, "most overlapped none"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn lines() -> Vec<String> {
+        let input = "\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+        input.lines().map(&str::to_string).collect()
+    }
+
+    #[test]
+    fn test_part1() {
+        let sum = part1(lines().iter()).unwrap();
+        assert_eq!(sum, 2);
+    }
+
+    #[test]
+    fn test_part2() {
+        let sum = part2(lines().iter()).unwrap();
+        assert_eq!(sum, 4);
+    }
+
+    #[test]
+    fn test_part3() {
+        // 5-7,7-9 share 7, 2-8,3-7 share 3-7, 6-6,4-6 share 6, and 2-6,4-8 share 4-6.
+        assert_eq!(part3(lines().iter()).unwrap(), 1 + 5 + 1 + 3);
+    }
+
+    #[test]
+    fn test_report() {
+        let line_nos = |part| -> Vec<usize> { report(lines().iter(), part).unwrap().iter().map(|p| p.line_no).collect() };
+        assert_eq!(line_nos(1), [4, 5]);
+        assert_eq!(line_nos(2), [3, 4, 5, 6]);
+
+        let mut out = Vec::new();
+        write_report(&mut out, &report(lines().iter(), 1).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), concat!(
+            " line elves       first      second\n",
+            "    4   1,2         2-8         3-7\n",
+            "    5   1,2         6-6         4-6\n",
+        ));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let want = Coverage { covered: 8, largest_gap: None, busiest: Some((6, 8)) };
+        assert_eq!(coverage(lines().iter()).unwrap(), want);
+
+        let input = ["1-2,10-12", "4-5,4-4", "8-9,8-8"];
+        let want = Coverage { covered: 9, largest_gap: Some(Interval::new(6, 7)), busiest: Some((4, 2)) };
+        assert_eq!(coverage(input.iter()).unwrap(), want);
+
+        let mut out = Vec::new();
+        write_coverage(&mut out, &want).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "covered 9\nlargest gap 6-7 (2 sections)\nmost overlapped 4 (2 elves)\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |input: &[&str]| part1(input.iter()).unwrap_err().to_string();
+        assert_eq!(err(&["2-4,6-8", "2-x,4-5"]),
+            "line 2: parse range \"2-x\": invalid digit found in string: \"2-x,4-5\"");
+        assert_eq!(err(&["2-4"]), "line 1: expected at least two ranges on line: \"2-4\"");
+        assert_eq!(err(&["2-4,6-8", "2-4,6-8", "2,6-8"]),
+            "line 3: parse range \"2\": expected START-END: \"2,6-8\"");
+        assert_eq!(err(&["2-1,3-4"]), "line 1: parse range \"2-1\": start is after end: \"2-1,3-4\"");
+        assert!(coverage(["2-1,3-4"].iter()).is_err());
+        assert!(coverage(["1-2,3-4", ""].iter()).is_err());
+    }
+
+    #[test]
+    fn test_teams() {
+        // 2-8 contains 3-4 and 6-8, which don't touch, and 1-9 contains 1-1 and 5-5.
+        let input = ["2-8,3-4,6-8", "1-1,5-5,1-9"];
+        assert_eq!(part1(input.iter()).unwrap(), 2 + 2);
+        assert_eq!(part2(input.iter()).unwrap(), 2 + 2);
+        assert_eq!(part3(input.iter()).unwrap(), 2 + 3 + 1 + 1);
+        let elves: Vec<(usize, usize)> = report(input.iter(), 1).unwrap().iter().map(|p| p.elves).collect();
+        assert_eq!(elves, [(1, 2), (1, 3), (1, 3), (2, 3)]);
+    }
+}
@@ -0,0 +1,812 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::str::FromStr;
+
+use aoclib::render::{Glyph, NoRender, Scene, Sink};
+use aoclib::scan::Scanner;
+
+use crate::error::AocError;
+
+// The crates in each stack, from the bottom up.
+pub type Stacks = Vec<Vec<char>>;
+
+// Move n crates from stack src to stack dst, which are indexes into the stacks, in the order of
+// their labels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub n: usize,
+    pub src: usize,
+    pub dst: usize,
+}
+
+impl Move {
+    // The move as it's written in the input, with the stacks' labels.
+    pub fn labelled(self, labels: &[String]) -> LabelledMove<'_> {
+        LabelledMove(self, labels)
+    }
+}
+
+pub struct LabelledMove<'a>(Move, &'a [String]);
+
+impl fmt::Display for LabelledMove<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let LabelledMove(mv, labels) = self;
+        write!(f, "move {} from {} to {}", mv.n, labels[mv.src], labels[mv.dst])
+    }
+}
+
+// A model of crane, which decides what order the crates in a move end up in.
+pub trait Crane {
+    fn apply(&self, stacks: &mut Stacks, mv: Move);
+}
+
+// Moves crates one at a time, so they end up in reverse order.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn apply(&self, stacks: &mut Stacks, mv: Move) {
+        move_lifo(stacks, mv);
+    }
+}
+
+// Moves all the crates at once, so they keep their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn apply(&self, stacks: &mut Stacks, mv: Move) {
+        move_fifo(stacks, mv);
+    }
+}
+
+// Moves up to a number of crates at once, keeping the order of each lift but reversing the order
+// of the lifts. A limit of 1 is a CrateMover 9000.
+pub struct LimitedCrane(pub usize);
+
+impl Crane for LimitedCrane {
+    fn apply(&self, stacks: &mut Stacks, mv: Move) {
+        let limit = self.0.max(1);
+        let mut left = mv.n;
+        while left > 0 {
+            let n = left.min(limit);
+            move_fifo(stacks, Move { n, ..mv });
+            left -= n;
+        }
+    }
+}
+
+// A crane by its model number, like 9000, or limit-K for a LimitedCrane that lifts K at a time.
+pub fn parse_crane(s: &str) -> Result<Box<dyn Crane>, String> {
+    match s {
+        "9000" => Ok(Box::new(CrateMover9000)),
+        "9001" => Ok(Box::new(CrateMover9001)),
+        _ => {
+            let limit = s.strip_prefix("limit-").and_then(|k| k.parse().ok()).filter(|&k| k > 0);
+            match limit {
+                Some(k) => Ok(Box::new(LimitedCrane(k))),
+                None => Err(format!("unknown crane: {s}")),
+            }
+        },
+    }
+}
+
+pub fn part1<T>(lines: T) -> Result<String, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    run(lines, &CrateMover9000)
+}
+
+pub fn part2<T>(lines: T) -> Result<String, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    run(lines, &CrateMover9001)
+}
+
+// The crates on top of each stack once the crane's made all the moves.
+pub fn run<T>(lines: T, crane: &dyn Crane) -> Result<String, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    render(lines, crane, 1, &mut NoRender)
+}
+
+// Like run, but send the stacks to `sink` at the start, after every `every` moves, and at the end.
+pub fn render<T>(lines: T, crane: &dyn Crane, every: usize, sink: &mut dyn Sink) -> Result<String, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    Ok(tops(simulate(lines, crane, every, sink)?.stacks()))
+}
+
+// Like render, but return the replay with all the moves made, for every crate in the stacks and
+// their labels, not just the crates on top.
+pub fn simulate<'a, T>(lines: T, crane: &'a dyn Crane, every: usize, sink: &mut dyn Sink) -> Result<Replay<'a>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut replay = Replay::new(lines, crane)?;
+    sink.frame(format_args!("Start"), &replay.view())?;
+    replay.seek(replay.len(), every, sink)?;
+    Ok(replay)
+}
+
+// Like simulate, but stop after move `to`, or if it's negative, that many moves before the end.
+// Those are reached by making all the moves and then undoing the last ones, so the frames sent to
+// `sink` go forward and then back.
+pub fn replay_to<'a, T>(lines: T, crane: &'a dyn Crane, to: i64, every: usize, sink: &mut dyn Sink) -> Result<Replay<'a>, AocError>
+where
+    T: Iterator,
+    T::Item: AsRef<str>,
+{
+    let mut replay = Replay::new(lines, crane)?;
+    sink.frame(format_args!("Start"), &replay.view())?;
+    if to < 0 {
+        replay.seek(replay.len(), every, sink)?;
+    }
+    let to = if to < 0 { replay.len().saturating_sub(to.unsigned_abs() as usize) } else { to as usize };
+    replay.seek(to, every, sink)?;
+    Ok(replay)
+}
+
+// The moves, and the stacks after some of them have been made. Each move that's made is logged
+// with the crates it took, so that it can be undone whichever crane made it, and the moves can be
+// stepped through back and forth.
+pub struct Replay<'a> {
+    crane: &'a dyn Crane,
+    stacks: Stacks,
+    // Each stack's label, like "1" or "A", which the moves use to say which stack they mean.
+    labels: Vec<String>,
+    // The moves and the lines they're on.
+    moves: Vec<(usize, Move)>,
+    // The crates each move made so far took off its source stack, from the bottom up.
+    log: Vec<Vec<char>>,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new<T>(lines: T, crane: &'a dyn Crane) -> Result<Self, AocError>
+    where
+        T: Iterator,
+        T::Item: AsRef<str>,
+    {
+        let mut lines = lines.enumerate().map(|(i, l)| (i + 1, l));
+        let (labels, stacks) = parse_stacks(&mut lines)?;
+        let moves = moves(lines, &labels).collect::<Result<_, _>>()?;
+        Ok(Replay { crane, stacks, labels, moves, log: Vec::new() })
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn view(&self) -> StacksView<'_> {
+        StacksView { stacks: &self.stacks, labels: &self.labels }
+    }
+
+    // The number of moves made so far.
+    pub fn position(&self) -> usize {
+        self.log.len()
+    }
+
+    // The number of moves there are.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // Make the next move and return it, or None if they've all been made.
+    pub fn step(&mut self) -> Result<Option<Move>, AocError> {
+        let Some(&(line_no, mv)) = self.moves.get(self.log.len()) else {
+            return Ok(None);
+        };
+        check_move(&self.stacks, &self.labels, mv)
+            .map_err(|e| AocError::Parse(format!("line {line_no}: {e}: \"{}\"", mv.labelled(&self.labels))))?;
+        let src = &self.stacks[mv.src];
+        self.log.push(src[src.len() - mv.n..].to_vec());
+        self.crane.apply(&mut self.stacks, mv);
+        Ok(Some(mv))
+    }
+
+    // Take back the last move made and return it, or None if there isn't one.
+    pub fn undo(&mut self) -> Option<Move> {
+        let taken = self.log.pop()?;
+        let (_, mv) = self.moves[self.log.len()];
+        let dst = &mut self.stacks[mv.dst];
+        dst.truncate(dst.len() - mv.n);
+        self.stacks[mv.src].extend(taken);
+        Some(mv)
+    }
+
+    // Step forward or back until `n` moves have been made, or all of them if there are fewer, and
+    // send the stacks to `sink` after every `every` steps and at the end.
+    pub fn seek(&mut self, n: usize, every: usize, sink: &mut dyn Sink) -> Result<(), AocError> {
+        let (n, every) = (n.min(self.len()), every.max(1));
+        let mut nsteps = 0;
+        while self.position() != n {
+            let title = if self.position() < n {
+                let mv = self.step()?.expect("there's a next move");
+                format!("Move {}: {}", self.position(), mv.labelled(&self.labels))
+            } else {
+                let mv = self.undo().expect("there's a move to undo");
+                format!("Undo move {}: {}", self.position() + 1, mv.labelled(&self.labels))
+            };
+            nsteps += 1;
+            if nsteps % every == 0 {
+                sink.frame(format_args!("{title}"), &self.view())?;
+            }
+        }
+        if nsteps % every != 0 {
+            sink.frame(format_args!("Move {n}: done"), &self.view())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn tops(stacks: &Stacks) -> String {
+    stacks.iter().map(|s| s.last().unwrap_or(&' ')).collect()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown report format: {s}")),
+        }
+    }
+}
+
+// Each stack's label and crates, from the bottom up. In JSON, stacks are also numbered from 1 in
+// the order of their labels.
+pub fn write_stacks(mut w: impl Write, format: ReportFormat, labels: &[String], stacks: &Stacks) -> io::Result<()> {
+    match format {
+        ReportFormat::Text => {
+            writeln!(w, "stack crates")?;
+            for (label, stack) in labels.iter().zip(stacks) {
+                writeln!(w, "{:>5} {}", label, stack.iter().collect::<String>())?;
+            }
+        },
+        ReportFormat::Json => {
+            writeln!(w, "[")?;
+            for (i, (label, stack)) in labels.iter().zip(stacks).enumerate() {
+                let sep = if i + 1 < stacks.len() { "," } else { "" };
+                let crates = stack.iter().collect::<String>();
+                writeln!(w, "  {{\"stack\": {}, \"label\": {label:?}, \"crates\": {crates:?}}}{sep}", i + 1)?;
+            }
+            writeln!(w, "]")?;
+        },
+    }
+    Ok(())
+}
+
+// Draws the stacks like the top of the input, with their labels underneath.
+struct StacksView<'a> {
+    stacks: &'a Stacks,
+    labels: &'a [String],
+}
+
+impl Scene for StacksView<'_> {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        let height = self.stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        (0..(4 * self.stacks.len() as i64 - 1).max(0), 0..height as i64 + 1)
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        let (_, ys) = self.extent();
+        let (stack, col) = (x as usize / 4, x as usize % 4);
+        if y == ys.end - 1 {
+            // Labels are centered under their crates, as far as they fit.
+            return self.labels[stack].chars().nth(col.wrapping_sub(1)).unwrap_or(' ').into();
+        }
+        let level = (ys.end - 2 - y) as usize;
+        match (self.stacks[stack].get(level), col) {
+            (Some(_), 0) => '['.into(),
+            (Some(&c), 1) => c.into(),
+            (Some(_), 2) => ']'.into(),
+            _ => ' '.into(),
+        }
+    }
+}
+
+// The stacks' labels and crates, from the diagram at the top of the input. The labels can be
+// numbers, like in the puzzle, or letters or any other words, as long as there aren't two the
+// same. The lines are numbered, so that errors can say where they are.
+fn parse_stacks<I, S>(lines: &mut I) -> Result<(Vec<String>, Stacks), AocError>
+where
+    I: Iterator<Item = (usize, S)>,
+    S: AsRef<str>,
+{
+    // The rows of crates come before the line labelling the stacks, which says how many there are
+    // and which column each is in, so they're kept until it's found.
+    let mut rows: Vec<(usize, String)> = Vec::new();
+    let mut labels = None;
+    for (line_no, line) in lines.by_ref() {
+        let line = line.as_ref();
+        if line.trim().starts_with('[') {
+            rows.push((line_no, line.to_owned()));
+        } else {
+            labels = Some((line_no, label_columns(line)));
+            break;
+        }
+    }
+    let labels = match labels {
+        Some((line_no, labels)) if !labels.is_empty() => {
+            for (i, (_, label)) in labels.iter().enumerate() {
+                if labels[..i].iter().any(|(_, l)| l == label) {
+                    return Err(AocError::Parse(format!("line {line_no}: there are two stacks labelled {label}")));
+                }
+            }
+            labels
+        },
+        Some((line_no, _)) => {
+            return Err(AocError::Parse(format!("line {line_no}: expected the stack labels, like \" 1   2   3\"")));
+        },
+        None => return Err(AocError::Parse("missing the line labelling the stacks".to_string())),
+    };
+
+    // Read the crates from the top down, so errors are in order, then stack them from the bottom up.
+    let mut crates: Vec<(usize, char)> = Vec::new();
+    for (line_no, row) in &rows {
+        let err = |col: usize, msg: &str| AocError::Parse(format!("line {line_no}, col {}: {msg}: {row:?}", col + 1));
+        let chars: Vec<char> = row.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i..] {
+                [' ', ..] => i += 1,
+                ['[', c, ']', ..] if c.is_ascii_alphabetic() => {
+                    // Its label is somewhere under the crate's brackets.
+                    let stack = labels.iter()
+                        .position(|(l, _)| l.start <= i + 2 && i < l.end)
+                        .ok_or_else(|| err(i, "crate isn't over a stack label"))?;
+                    crates.push((stack, c));
+                    i += 3;
+                },
+                _ => return Err(err(i, "expected a crate, like [A]")),
+            }
+        }
+    }
+    let mut stacks: Stacks = vec![Vec::new(); labels.len()];
+    for (stack, c) in crates.into_iter().rev() {
+        stacks[stack].push(c);
+    }
+    Ok((labels.into_iter().map(|(_, label)| label).collect(), stacks))
+}
+
+// Each stack label and the columns it takes up, like 1..2 for the " 1 " in " 1   2 ". A label can
+// be more than one character, like "10" or "east".
+fn label_columns(line: &str) -> Vec<(Range<usize>, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut labels: Vec<(Range<usize>, String)> = Vec::new();
+    let mut start = None;
+    for (i, c) in chars.iter().chain([&' ']).enumerate() {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                labels.push((s..i, chars[s..i].iter().collect()));
+                start = None;
+            },
+            _ => (),
+        }
+    }
+    labels
+}
+
+// The moves after the diagram, with their line numbers. Blank lines are skipped.
+fn moves<'a, I, S>(lines: I, labels: &'a [String]) -> impl Iterator<Item = Result<(usize, Move), AocError>> + 'a
+where
+    I: Iterator<Item = (usize, S)> + 'a,
+    S: AsRef<str>,
+{
+    lines.filter(|(_, l)| !l.as_ref().trim().is_empty()).map(|(line_no, line)| {
+        let line = line.as_ref();
+        let mv = parse_move(line, labels).map_err(|e| AocError::Parse(format!("line {line_no}: {e}: {line:?}")))?;
+        Ok((line_no, mv))
+    })
+}
+
+// eg: move 1 from 2 to 1, where the stacks are given by their labels.
+fn parse_move(line: &str, labels: &[String]) -> Result<Move, String> {
+    let mut s = Scanner::new(line.trim_end());
+    s.expect("move ")?;
+    let n = s.int()?;
+    s.expect(" from ")?;
+    let src = parse_label(&mut s, labels)?;
+    s.expect(" to ")?;
+    let dst = parse_label(&mut s, labels)?;
+    s.end()?;
+    Ok(Move { n, src, dst })
+}
+
+// The index of the stack with the label that's next.
+fn parse_label(s: &mut Scanner, labels: &[String]) -> Result<usize, String> {
+    let pos = s.pos();
+    let label = s.take_while(|c| !c.is_whitespace());
+    if label.is_empty() {
+        return Err(s.error(format_args!("expected a stack label, got {:?}", s.rest())));
+    }
+    labels.iter().position(|l| l == label).ok_or_else(|| format!("col {}: there's no stack {label}", pos + 1))
+}
+
+// Whether there are enough crates to move, so the cranes don't have to check.
+fn check_move(stacks: &Stacks, labels: &[String], mv: Move) -> Result<(), String> {
+    let have = stacks[mv.src].len();
+    if have < mv.n {
+        return Err(format!("stack {} only has {have} crates", labels[mv.src]));
+    }
+    Ok(())
+}
+
+// The moves take the crates off in one go rather than popping them one at a time, which is much
+// faster when moves are big. See bench_moves.
+fn move_lifo(stacks: &mut Stacks, mv: Move) {
+    let src = &mut stacks[mv.src];
+    let mut crates = src.split_off(src.len() - mv.n);
+    crates.reverse();
+    stacks[mv.dst].extend(crates);
+}
+
+fn move_fifo(stacks: &mut Stacks, mv: Move) {
+    let src = &mut stacks[mv.src];
+    let crates = src.split_off(src.len() - mv.n);
+    stacks[mv.dst].extend(crates);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fmt::Debug;
+
+    fn lines() -> Vec<String> {
+        // `input` starts with an extra empty line which we remove below.
+        let input = "
+    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+        input.trim_start_matches('\n').lines().map(|l| l.to_owned()).collect()
+    }
+
+    // The moves as they were first written, a crate at a time, to check the bulk ones against.
+    fn move_lifo_each(stacks: &mut Stacks, mv: Move) {
+        for _ in 0..mv.n {
+            let item = stacks[mv.src].pop().unwrap();
+            stacks[mv.dst].push(item);
+        }
+    }
+
+    fn move_fifo_each(stacks: &mut Stacks, mv: Move) {
+        let mut scratch: Vec<char> = Vec::new();
+        for _ in 0..mv.n {
+            let item = stacks[mv.src].pop().unwrap();
+            scratch.push(item);
+        }
+        while let Some(item) = scratch.pop() {
+            stacks[mv.dst].push(item);
+        }
+    }
+
+    type MoveFn = fn(&mut Stacks, Move);
+
+    // The stacks at the start of a generated input and after each way of making its moves.
+    fn make_moves(size: usize, seed: u64, move_fns: &[MoveFn]) -> Vec<(Stacks, std::time::Duration)> {
+        let mut input = Vec::new();
+        crate::gen::generate(5, size, seed, &mut input).unwrap();
+        let input = String::from_utf8(input).unwrap();
+        let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l));
+        let (labels, start) = parse_stacks(&mut lines).unwrap();
+        let moves: Vec<Move> = moves(lines, &labels).map(|mv| mv.unwrap().1).collect();
+        move_fns.iter().map(|move_fn| {
+            let mut stacks = start.clone();
+            let t = std::time::Instant::now();
+            for &mv in &moves {
+                move_fn(&mut stacks, mv);
+            }
+            (stacks, t.elapsed())
+        }).collect()
+    }
+
+    #[test]
+    fn test_bulk_moves() {
+        for seed in 1..=5 {
+            let got = make_moves(200, seed, &[move_lifo, move_lifo_each, move_fifo, move_fifo_each]);
+            assert_eq!(got[0].0, got[1].0, "seed={seed}");
+            assert_eq!(got[2].0, got[3].0, "seed={seed}");
+        }
+    }
+
+    // Run with `cargo test --release --lib day5 -- --ignored --nocapture`.
+    #[test] #[ignore]
+    fn bench_moves() {
+        let names = ["lifo", "lifo a crate at a time", "fifo", "fifo a crate at a time"];
+        let got = make_moves(20000, 1, &[move_lifo, move_lifo_each, move_fifo, move_fifo_each]);
+        for (name, (_, elapsed)) in names.iter().zip(got) {
+            println!("{name}: {elapsed:?}");
+        }
+    }
+
+    fn numbered<T>(lines: &[T]) -> impl Iterator<Item = (usize, &T)> {
+        lines.iter().enumerate().map(|(i, l)| (i + 1, l))
+    }
+
+    fn parse_moves<I, S>(lines: I, labels: &[String], stacks: &mut Stacks, crane: &dyn Crane)
+    where
+        I: Iterator<Item = (usize, S)>,
+        S: AsRef<str>,
+    {
+        for mv in moves(lines, labels) {
+            crane.apply(stacks, mv.unwrap().1);
+        }
+    }
+
+    fn assert_slices_eq<T>(a: &[T], b: &[T])
+    where
+        T: Eq + Debug,
+    {
+        let (mut it_a, mut it_b) = (a.iter(), b.iter());
+        loop {
+            let (i, j) = (it_a.next(), it_b.next());
+            if let (None, None) = (i, j) {
+                return;
+            }
+            if i != j {
+                dbg!(a);
+                dbg!(b);
+                if let (Some(i), Some(j)) = (i, j) {
+                    assert_eq!(i, j);
+                } else {
+                    assert_eq!(i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_stacks() {
+        let (labels, stacks) = parse_stacks(&mut numbered(&lines())).unwrap();
+        assert_eq!(labels, ["1", "2", "3"]);
+        assert_slices_eq(&stacks[0], &['Z', 'N']);
+        assert_slices_eq(&stacks[1], &['M', 'C', 'D']);
+        assert_slices_eq(&stacks[2], &['P']);
+    }
+
+    #[test]
+    fn test_parse_stacks_many() {
+        // Eleven stacks, with two-digit labels lined up with their crates.
+        let input = [
+            "                                        [K]",
+            "[A]                                 [J] [L]",
+            " 1   2   3   4   5   6   7   8   9  10  11",
+            "",
+            "move 1 from 10 to 2",
+            "move 2 from 11 to 1",
+        ];
+        let mut lines = numbered(&input);
+        let (labels, mut stacks) = parse_stacks(&mut lines).unwrap();
+        assert_eq!(stacks.len(), 11);
+        assert_slices_eq(&stacks[9], &['J']);
+        assert_slices_eq(&stacks[10], &['L', 'K']);
+        parse_moves(&mut lines, &labels, &mut stacks, &CrateMover9000);
+        assert_slices_eq(&stacks[0], &['A', 'K', 'L']);
+        assert_slices_eq(&stacks[1], &['J']);
+        assert_eq!(part2(input.iter()).unwrap(), "KJ         ");
+    }
+
+    #[test]
+    fn test_parse_stacks_labelled() {
+        // The example with lettered stacks, and then with words, some wider than their crates.
+        let input = [
+            "    [D]",
+            "[N] [C]",
+            "[Z] [M] [P]",
+            " A   B   C",
+            "",
+            "move 1 from B to A",
+            "move 3 from A to C",
+            "move 2 from B to A",
+            "move 1 from A to B",
+        ];
+        let replay = simulate(input.iter(), &CrateMover9000, 1, &mut NoRender).unwrap();
+        assert_eq!(replay.labels(), ["A", "B", "C"]);
+        assert_eq!(tops(replay.stacks()), "CMZ");
+        assert_eq!(part2(input.iter()).unwrap(), "MCD");
+
+        let input = [
+            "[N]      [C]",
+            "[Z] [M]  [P]",
+            "dock ship yard",
+            "move 1 from yard to ship",
+            "move 2 from dock to yard",
+        ];
+        let replay = simulate(input.iter(), &CrateMover9001, 1, &mut NoRender).unwrap();
+        assert_eq!(replay.labels(), ["dock", "ship", "yard"]);
+        assert_eq!(replay.stacks(), &[vec![], vec!['M', 'C'], vec!['P', 'Z', 'N']]);
+    }
+
+    #[test]
+    fn test_cranes() {
+        let stacks = || vec![vec!['A', 'B', 'C', 'D', 'E'], vec![]];
+        let mv = Move { n: 5, src: 0, dst: 1 };
+        for (name, want) in [("9000", "EDCBA"), ("9001", "ABCDE"), ("limit-1", "EDCBA"), ("limit-2", "DEBCA"), ("limit-9", "ABCDE")] {
+            let mut stacks = stacks();
+            parse_crane(name).unwrap().apply(&mut stacks, mv);
+            assert_eq!(stacks[1].iter().collect::<String>(), want, "{name}");
+            assert!(stacks[0].is_empty());
+        }
+        assert!(parse_crane("9002").is_err());
+        assert!(parse_crane("limit-0").is_err());
+        assert_eq!(run(lines().iter(), &LimitedCrane(2)).unwrap(), "MCZ");
+    }
+
+    // Keeps the frames drawn, each after its title.
+    struct Frames(Vec<String>);
+
+    impl Frames {
+        fn titles(&self) -> Vec<&str> {
+            self.0.iter().map(|f| f.lines().next().unwrap()).collect()
+        }
+    }
+
+    impl Sink for Frames {
+        fn frame(&mut self, title: fmt::Arguments, scene: &dyn Scene) -> io::Result<()> {
+            let picture = aoclib::render::draw(scene, aoclib::render::Viewport::UNLIMITED, false);
+            self.0.push(format!("{title}\n{picture}"));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut frames = Frames(Vec::new());
+        assert_eq!(render(lines().iter(), &CrateMover9000, 3, &mut frames).unwrap(), "CMZ");
+        assert_eq!(frames.titles(), ["Start", "Move 3: move 2 from 2 to 1", "Move 4: done"]);
+        assert_eq!(frames.0[0], concat!(
+            "Start\n",
+            "    [D]    \n",
+            "[N] [C]    \n",
+            "[Z] [M] [P]\n",
+            " 1   2   3 \n",
+        ));
+        assert_eq!(frames.0[2], concat!(
+            "Move 4: done\n",
+            "        [Z]\n",
+            "        [N]\n",
+            "        [D]\n",
+            "[C] [M] [P]\n",
+            " 1   2   3 \n",
+        ));
+    }
+
+    #[test]
+    fn test_write_stacks() {
+        let replay = simulate(lines().iter(), &CrateMover9001, 1, &mut NoRender).unwrap();
+        assert_eq!(replay.stacks(), &[vec!['M'], vec!['C'], vec!['P', 'Z', 'N', 'D']]);
+        let write = |format, labels: &[String]| {
+            let mut out = Vec::new();
+            write_stacks(&mut out, format, labels, replay.stacks()).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(write(ReportFormat::Text, replay.labels()), "stack crates\n    1 M\n    2 C\n    3 PZND\n");
+        assert_eq!(write(ReportFormat::Json, replay.labels()), concat!(
+            "[\n",
+            "  {\"stack\": 1, \"label\": \"1\", \"crates\": \"M\"},\n",
+            "  {\"stack\": 2, \"label\": \"2\", \"crates\": \"C\"},\n",
+            "  {\"stack\": 3, \"label\": \"3\", \"crates\": \"PZND\"}\n",
+            "]\n",
+        ));
+        let letters = ["A", "B", "C"].map(String::from);
+        assert_eq!(write(ReportFormat::Text, &letters), "stack crates\n    A M\n    B C\n    C PZND\n");
+        assert_eq!("json".parse(), Ok(ReportFormat::Json));
+        assert!("csv".parse::<ReportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |edit: &dyn Fn(&mut Vec<String>)| {
+            let mut input = lines();
+            edit(&mut input);
+            part1(input.iter()).unwrap_err().to_string()
+        };
+        assert_eq!(err(&|l| l[1] = "[N] (C)".into()), "line 2, col 5: expected a crate, like [A]: \"[N] (C)\"");
+        assert_eq!(err(&|l| l[2].push_str(" [Q]")), "line 3, col 13: crate isn't over a stack label: \"[Z] [M] [P] [Q]\"");
+        assert_eq!(err(&|l| l[3] = "".into()), "line 4: expected the stack labels, like \" 1   2   3\"");
+        assert_eq!(err(&|l| l.truncate(3)), "missing the line labelling the stacks");
+        assert_eq!(err(&|l| l[6] = "move 3 from 1 to".into()), "line 7: col 14: expected \" to \", got \" to\": \"move 3 from 1 to\"");
+        assert_eq!(err(&|l| l[6] = "move x from 1 to 3".into()),
+            "line 7: col 6: expected an integer, got \"x from 1 to 3\": \"move x from 1 to 3\"");
+        assert_eq!(err(&|l| l[6] = "move 1 from 0 to 3".into()), "line 7: col 13: there's no stack 0: \"move 1 from 0 to 3\"");
+        assert_eq!(err(&|l| l[6] = "move 1 from 1 to 4".into()), "line 7: col 18: there's no stack 4: \"move 1 from 1 to 4\"");
+        assert_eq!(err(&|l| l[6] = "move 1 from  to 3".into()),
+            "line 7: col 13: expected a stack label, got \" to 3\": \"move 1 from  to 3\"");
+        assert_eq!(err(&|l| l[3] = " 1   2   1".into()), "line 4: there are two stacks labelled 1");
+        assert_eq!(err(&|l| l[6] = "move 4 from 1 to 3".into()), "line 7: stack 1 only has 3 crates: \"move 4 from 1 to 3\"");
+        assert_eq!(err(&|l| l.push("stop".into())), "line 10: col 1: expected \"move \", got \"stop\": \"stop\"");
+        // Blank lines among the moves are fine.
+        assert_eq!(part1(lines().iter().chain([&String::new()])).unwrap(), "CMZ");
+    }
+
+    #[test]
+    fn test_replay() {
+        let stacks = |crane: &dyn Crane, to| replay_to(lines().iter(), crane, to, 1, &mut NoRender).unwrap().stacks().clone();
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001, &LimitedCrane(2)] {
+            // Undoing moves gets back to the same stacks as stopping before them.
+            let forward: Vec<Stacks> = (0..=4).map(|to| stacks(crane, to)).collect();
+            let back: Vec<Stacks> = (-4..0).map(|to| stacks(crane, to)).collect();
+            assert_eq!(forward[..4], back);
+            assert_eq!(forward[0], parse_stacks(&mut numbered(&lines())).unwrap().1);
+            assert_eq!(stacks(crane, 9), forward[4]);
+            assert_eq!(stacks(crane, -9), forward[0]);
+        }
+
+        let lines = lines();
+        let mut replay = Replay::new(lines.iter(), &CrateMover9000).unwrap();
+        assert_eq!((replay.position(), replay.len()), (0, 4));
+        assert_eq!(replay.undo(), None);
+        assert_eq!(replay.step().unwrap(), Some(Move { n: 1, src: 1, dst: 0 }));
+        assert_eq!(replay.stacks(), &[vec!['Z', 'N', 'D'], vec!['M', 'C'], vec!['P']]);
+        assert_eq!(replay.undo(), Some(Move { n: 1, src: 1, dst: 0 }));
+        assert_eq!(replay.stacks()[1], ['M', 'C', 'D']);
+
+        let mut frames = Frames(Vec::new());
+        replay.seek(2, 1, &mut frames).unwrap();
+        replay.seek(0, 2, &mut frames).unwrap();
+        assert_eq!(frames.titles(), ["Move 1: move 1 from 2 to 1", "Move 2: move 3 from 1 to 3", "Undo move 1: move 1 from 2 to 1"]);
+    }
+
+    #[test]
+    fn test_label_columns() {
+        let label = |cols: Range<usize>, label: &str| (cols, label.to_string());
+        assert_eq!(label_columns(" 1   2   3 "), [label(1..2, "1"), label(5..6, "2"), label(9..10, "3")]);
+        assert_eq!(label_columns(" 9  10  11"), [label(1..2, "9"), label(4..6, "10"), label(8..10, "11")]);
+        assert_eq!(label_columns("dock ship"), [label(0..4, "dock"), label(5..9, "ship")]);
+        assert!(label_columns("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_moves_lifo() {
+        let lines = lines();
+        let mut lines = numbered(&lines);
+        let (labels, mut stacks) = parse_stacks(&mut lines).unwrap();
+        parse_moves(&mut lines, &labels, &mut stacks, &CrateMover9000);
+        assert_slices_eq(&stacks[0], &['C']);
+        assert_slices_eq(&stacks[1], &['M']);
+        assert_slices_eq(&stacks[2], &['P', 'D', 'N', 'Z']);
+    }
+
+    #[test]
+    fn test_parse_moves_fifo() {
+        let lines = lines();
+        let mut lines = numbered(&lines);
+        let (labels, mut stacks) = parse_stacks(&mut lines).unwrap();
+        parse_moves(&mut lines, &labels, &mut stacks, &CrateMover9001);
+        assert_slices_eq(&stacks[0], &['M']);
+        assert_slices_eq(&stacks[1], &['C']);
+        assert_slices_eq(&stacks[2], &['P', 'Z', 'N', 'D']);
+    }
+}
@@ -0,0 +1,312 @@
+use std::io::{self, BufRead, Write};
+
+use aoclib::simd;
+
+// Part 1's start-of-packet markers are 4 different characters, and part 2's start-of-message
+// markers are 14.
+pub const PACKET_MARKER_LEN: usize = 4;
+pub const MESSAGE_MARKER_LEN: usize = 14;
+
+// The number of characters read by the end of the first `len` in a row that are all different,
+// or None if there aren't any. `len` should be positive.
+pub fn find_marker(s: &str, len: usize) -> Option<usize> {
+    // With the simd feature, short markers are faster to find with vectors. See bench_find_marker.
+    if simd::enabled() && len <= simd::MAX_VECTOR_WINDOW {
+        return simd::find_distinct_window(s.as_bytes(), len);
+    }
+    MarkerFinder::new(len).feed(s.as_bytes()).map(|at| at as usize)
+}
+
+// Like find_marker, but reading the datastream a buffer at a time, so it doesn't have to fit in
+// memory, or even have a newline. It ends at the first newline if there is one. Only as much is
+// read as it takes to find the marker.
+pub fn find_marker_in(r: impl BufRead, len: usize) -> io::Result<Option<u64>> {
+    let mut first = None;
+    markers_in(r, len, |at| {
+        first = Some(at);
+        false
+    })?;
+    Ok(first)
+}
+
+// Read the datastream like find_marker_in, calling `f` with the end of every marker, not just the
+// first, for as long as it returns true. Markers can overlap, so a run of more than `len`
+// different characters has a marker ending at each of them after the first `len`.
+pub fn markers_in(mut r: impl BufRead, len: usize, mut f: impl FnMut(u64) -> bool) -> io::Result<()> {
+    let mut finder = MarkerFinder::new(len);
+    loop {
+        let buf = r.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+        let newline = buf.iter().position(|&b| b == b'\n');
+        let mut rest = &buf[..newline.unwrap_or(buf.len())];
+        loop {
+            let before = finder.pos;
+            let Some(at) = finder.feed(rest) else {
+                break;
+            };
+            if !f(at) {
+                return Ok(());
+            }
+            rest = &rest[(at - before) as usize..];
+        }
+        if newline.is_some() {
+            return Ok(());
+        }
+        let n = buf.len();
+        r.consume(n);
+    }
+}
+
+// How many markers there are and how far apart, for seeing what a datastream is like.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MarkerStats {
+    pub count: u64,
+    pub first: Option<u64>,
+    pub last: Option<u64>,
+    // The fewest and most characters from the end of one marker to the end of the next.
+    pub shortest_gap: Option<u64>,
+    pub longest_gap: Option<u64>,
+}
+
+impl MarkerStats {
+    pub fn add(&mut self, at: u64) {
+        if let Some(last) = self.last {
+            let gap = at - last;
+            self.shortest_gap = Some(self.shortest_gap.map_or(gap, |g| g.min(gap)));
+            self.longest_gap = Some(self.longest_gap.map_or(gap, |g| g.max(gap)));
+        }
+        self.count += 1;
+        self.first = self.first.or(Some(at));
+        self.last = Some(at);
+    }
+
+    // The average gap between markers, if there are at least two.
+    pub fn mean_gap(&self) -> Option<f64> {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) if self.count > 1 => Some((last - first) as f64 / (self.count - 1) as f64),
+            _ => None,
+        }
+    }
+}
+
+pub fn write_marker_stats(mut w: impl Write, stats: &MarkerStats) -> io::Result<()> {
+    let show = |v: Option<u64>| v.map_or("none".to_string(), |v| v.to_string());
+    writeln!(w, "markers {}", stats.count)?;
+    writeln!(w, "first {}", show(stats.first))?;
+    writeln!(w, "last {}", show(stats.last))?;
+    writeln!(w, "shortest gap {}", show(stats.shortest_gap))?;
+    writeln!(w, "longest gap {}", show(stats.longest_gap))?;
+    match stats.mean_gap() {
+        Some(mean) => writeln!(w, "mean gap {mean:.2}"),
+        None => writeln!(w, "mean gap none"),
+    }
+}
+
+// Looks for a marker in a datastream that's fed to it a piece at a time.
+//
+// The window slides along a byte at a time, keeping a count of each byte in it and of how many
+// bytes it has more than one of, so each step is the same few operations however long the window
+// is. See bench_find_marker.
+pub struct MarkerFinder {
+    len: usize,
+    counts: [u32; 256],
+    dups: usize,
+    // The last `len` bytes, as a ring, with the oldest at `next`, which is where the next byte goes.
+    window: Vec<u8>,
+    next: usize,
+    // The number of bytes fed so far.
+    pos: u64,
+}
+
+impl MarkerFinder {
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "markers should be at least one character long");
+        MarkerFinder { len, counts: [0; 256], dups: 0, window: vec![0; len], next: 0, pos: 0 }
+    }
+
+    // Slide the window over `bytes`, stopping at the end of the first marker in them. Returns the
+    // number of bytes fed by then, over all the calls, or None if there's no marker yet.
+    pub fn feed(&mut self, bytes: &[u8]) -> Option<u64> {
+        for &b in bytes {
+            if self.pos >= self.len as u64 {
+                let old = self.window[self.next] as usize;
+                self.dups -= (self.counts[old] == 2) as usize;
+                self.counts[old] -= 1;
+            }
+            self.window[self.next] = b;
+            self.counts[b as usize] += 1;
+            self.dups += (self.counts[b as usize] == 2) as usize;
+            self.next += 1;
+            if self.next == self.len {
+                self.next = 0;
+            }
+            self.pos += 1;
+            if self.dups == 0 && self.pos >= self.len as u64 {
+                return Some(self.pos);
+            }
+        }
+        None
+    }
+}
+
+pub fn part1(s: &str) -> Option<usize> {
+    find_marker(s, PACKET_MARKER_LEN)
+}
+
+pub fn part2(s: &str) -> Option<usize> {
+    find_marker(s, MESSAGE_MARKER_LEN)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::rng::Rng;
+    use std::time::Instant;
+
+    // Checking each window on its own, which is how find_marker used to work.
+    fn find_marker_windows(s: &str, len: usize) -> Option<usize> {
+        s.as_bytes().windows(len).position(simd::all_distinct).map(|i| i + len)
+    }
+
+    const EXAMPLES: [&str; 5] = [
+        "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+        "bvwbjplbgvbhsrlpgdmjqwftvncz",
+        "nppdvjthqldpwncqszvftbrmjlhg",
+        "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+        "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+    ];
+
+    #[test]
+    fn test_part1() {
+        let got: Vec<_> = EXAMPLES.iter().map(|s| part1(s)).collect();
+        assert_eq!(got, [Some(7), Some(5), Some(6), Some(10), Some(11)]);
+    }
+
+    #[test]
+    fn test_part2() {
+        let got: Vec<_> = EXAMPLES.iter().map(|s| part2(s)).collect();
+        assert_eq!(got, [Some(19), Some(23), Some(23), Some(29), Some(26)]);
+    }
+
+    #[test]
+    fn test_find_marker() {
+        assert_eq!(find_marker(EXAMPLES[0], 1), Some(1));
+        assert_eq!(find_marker(EXAMPLES[0], 2), Some(2));
+        assert_eq!(find_marker(EXAMPLES[0], 3), Some(3));
+        // Longer than a SIMD register.
+        assert_eq!(find_marker("aabcdefghijklmnopqrstuvwxyz", 20), Some(21));
+        assert_eq!(find_marker("abcabc", 4), None);
+        assert_eq!(find_marker("abc", 4), None);
+    }
+
+    #[test]
+    fn test_find_marker_in() {
+        // A buffer of 3 bytes, so the windows straddle the reads.
+        let find = |s: &str, len| find_marker_in(io::BufReader::with_capacity(3, s.as_bytes()), len).unwrap();
+        for s in EXAMPLES {
+            assert_eq!(find(s, 4), part1(s).map(|at| at as u64));
+            assert_eq!(find(s, 14), part2(s).map(|at| at as u64));
+        }
+        assert_eq!(find("aabcd", 4), Some(5));
+        // The datastream ends at the newline.
+        assert_eq!(find("aabc\nd", 4), None);
+        assert_eq!(find("aabcd\n", 4), Some(5));
+        assert_eq!(find("", 4), None);
+
+        let mut finder = MarkerFinder::new(3);
+        assert_eq!(finder.feed(b"aab"), None);
+        assert_eq!(finder.feed(b"b"), None);
+        assert_eq!(finder.feed(b"cde"), Some(6));
+    }
+
+    #[test]
+    fn test_markers_in() {
+        let markers = |s: &str, len| {
+            let mut all = Vec::new();
+            markers_in(io::BufReader::with_capacity(3, s.as_bytes()), len, |at| {
+                all.push(at);
+                true
+            }).unwrap();
+            all
+        };
+        assert_eq!(markers("abcdd", 2), [2, 3, 4]);
+        assert_eq!(markers("aabcdefaa\nbc", 4), [5, 6, 7, 8]);
+        assert_eq!(markers("aaaa", 2), []);
+        // Every window of distinct characters, checked one at a time.
+        for s in EXAMPLES {
+            let want: Vec<u64> = (4..=s.len()).filter(|&end| find_marker(&s[end - 4..end], 4).is_some()).map(|end| end as u64).collect();
+            assert_eq!(markers(s, 4), want, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_marker_stats() {
+        let mut stats = MarkerStats::default();
+        assert_eq!(stats.mean_gap(), None);
+        for at in [6, 7, 8, 20] {
+            stats.add(at);
+        }
+        assert_eq!(stats, MarkerStats { count: 4, first: Some(6), last: Some(20), shortest_gap: Some(1), longest_gap: Some(12) });
+        assert_eq!(stats.mean_gap(), Some(14.0 / 3.0));
+        let mut out = Vec::new();
+        write_marker_stats(&mut out, &stats).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "markers 4\nfirst 6\nlast 20\nshortest gap 1\nlongest gap 12\nmean gap 4.67\n");
+        let mut out = Vec::new();
+        write_marker_stats(&mut out, &MarkerStats::default()).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("markers 0\nfirst none\n"));
+    }
+
+    type FindFn = fn(&str, usize) -> Option<usize>;
+
+    fn find_marker_counts(s: &str, len: usize) -> Option<usize> {
+        MarkerFinder::new(len).feed(s.as_bytes()).map(|at| at as usize)
+    }
+
+    fn find_marker_simd(s: &str, len: usize) -> Option<usize> {
+        simd::find_distinct_window(s.as_bytes(), len)
+    }
+
+    // The vector search is only a real test with the simd feature.
+    #[test]
+    fn test_find_marker_random() {
+        let mut rng = Rng::new(6);
+        for _ in 0..2000 {
+            let letters = 1 + rng.below(26) as u8;
+            let s: String = (0..rng.below(60)).map(|_| (b'a' + rng.below(letters as usize) as u8) as char).collect();
+            let len = 1 + rng.below(20);
+            let want = find_marker_windows(&s, len);
+            assert_eq!(find_marker_counts(&s, len), want, "{s:?} len={len}");
+            assert_eq!(find_marker_simd(&s, len), want, "{s:?} len={len}");
+            assert_eq!(find_marker(&s, len), want, "{s:?} len={len}");
+        }
+        for seed in 1..=5 {
+            let mut input = Vec::new();
+            crate::gen::generate(6, 5000, seed, &mut input).unwrap();
+            let input = String::from_utf8(input).unwrap();
+            for len in [4, 8, 13, 14, 16] {
+                assert_eq!(find_marker_simd(&input, len), find_marker_counts(&input, len), "seed={seed} len={len}");
+            }
+        }
+    }
+
+    // Run with `cargo test --release --lib --features simd day6 -- --ignored --nocapture`, or without
+    // the feature to see how the scalar fallback does.
+    #[test] #[ignore]
+    fn bench_find_marker() {
+        let mut input = Vec::new();
+        crate::gen::generate(6, 10_000_000, 1, &mut input).unwrap();
+        let input = String::from_utf8(input).unwrap();
+        for len in [PACKET_MARKER_LEN, MESSAGE_MARKER_LEN] {
+            let finders: [(&str, FindFn); 3] =
+                [("counts", find_marker_counts), ("windows", find_marker_windows), ("simd", find_marker_simd)];
+            for (name, find) in finders {
+                let t = Instant::now();
+                let marker = find(&input, len);
+                println!("len {len:>2} {name:>7}: {marker:?} in {:?}", t.elapsed());
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::io::BufRead;
+use std::cmp;
+
+use aoclib::grid::Grid;
+use aoclib::simd;
+use crate::error::AocError;
+
+struct Coords {
+    start: (usize, usize),
+    end: (usize, usize),
+    done: bool,
+}
+
+impl Coords {
+    // start and end are inclusive.
+    pub fn new(start: (usize, usize), end: (usize, usize)) -> Self {
+        Coords { start, end, done: false}
+    }
+
+    // Return a new start that's one step closer to `end`.
+    fn forward(start: usize, end: usize) -> usize {
+        use cmp::Ordering::{Equal, Less, Greater};
+        match start.cmp(&end) {
+            Equal => start,
+            Less => start.saturating_add(1),
+            Greater => start.saturating_sub(1),
+        }
+    }
+}
+
+impl Iterator for Coords {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.done {
+            return None;
+        }
+        let this = self.start;
+        self.start = (
+            Self::forward(self.start.0, self.end.0),
+            Self::forward(self.start.1, self.end.1),
+        );
+        if this == self.start {
+            self.done = true;
+        }
+        Some(this)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl ExactSizeIterator for Coords {
+    fn len(&self) -> usize {
+        if self.done {
+            0
+        } else {
+            let max_delta = cmp::max(
+                self.end.0.abs_diff(self.start.0),
+                self.end.1.abs_diff(self.start.1));
+            max_delta + 1
+        }
+    }
+}
+
+// Read the grid of tree heights, one digit per tree.
+fn read_heights<U: BufRead>(r: U) -> Result<Grid<u8>, String> {
+    let mut rows: Vec<Vec<u8>> = Vec::new();
+    for (line_num, line) in r.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let row = line.chars().enumerate()
+            .map(|(col, height)| {
+                height.to_digit(10)
+                    .map(|h| h as u8)
+                    .ok_or(format!("parse height at {},{})", line_num, col))
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        rows.push(row);
+    }
+    if rows.is_empty() {
+        return Err("no trees".to_string());
+    }
+    Grid::from_rows(rows).map_err(|e| format!("wrong number of fields: {}", e))
+}
+
+// Coords are (row, col), while the grid is indexed by (x, y).
+fn height(height_map: &Grid<u8>, (row, col): (usize, usize)) -> u8 {
+    height_map[(col, row)]
+}
+
+// 1 for each tree that can be seen from outside the grid. Looking in from the top and bottom is a
+// scan down or up the rows, and looking in from the sides is the same on the transposed grid.
+fn visibility(height_map: &Grid<u8>) -> Grid<u8> {
+    let (width, height) = (height_map.width(), height_map.height());
+    let heights: Vec<u8> = height_map.iter().copied().collect();
+    let mut vis = vec![0; width * height];
+    simd::mark_visible(&heights, width, 0..height, &mut vis);
+    simd::mark_visible(&heights, width, (0..height).rev(), &mut vis);
+
+    let transposed: Vec<u8> = (0..width).flat_map(|x| height_map.col(x).copied()).collect();
+    let mut transposed_vis = vec![0; width * height];
+    simd::mark_visible(&transposed, height, 0..width, &mut transposed_vis);
+    simd::mark_visible(&transposed, height, (0..width).rev(), &mut transposed_vis);
+
+    let mut vis_map = Grid::new(width, height, 0);
+    for ((x, y), _) in height_map.cells() {
+        vis_map[(x, y)] = vis[y * width + x] | transposed_vis[x * height + y];
+    }
+    vis_map
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<usize, AocError> {
+    let height_map = read_heights(r)?;
+    Ok(visible_tree_count(&height_map))
+}
+
+fn visible_tree_count(height_map: &Grid<u8>) -> usize {
+    let vis_map = visibility(height_map);
+    vis_map.iter().map(|v| *v as usize).sum()
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<usize, AocError> {
+    let height_map = read_heights(r)?;
+    Ok(highest_scenic_score(height_map))
+}
+
+fn highest_scenic_score(height_map: Grid<u8>) -> usize {
+    // Edges have a scenic score of 0, so skip them by starting at one and ending one space before
+    // the end of each row or col.
+    let mut max_score = 0;
+    for row in 1..(height_map.height() - 1) {
+        for col in 1..(height_map.width() - 1) {
+            let score = scenic_score(&height_map, (row, col));
+            max_score = cmp::max(max_score, score);
+        }
+    }
+    max_score
+}
+
+fn scenic_score(height_map: &Grid<u8>, tree: (usize, usize)) -> usize {
+    let (row, col) = tree;
+    if row == 0 || col == 0 || row == height_map.height() - 1 || col == height_map.width() - 1 {
+        return 0;
+    }
+    let dsts = [
+        (0, col),
+        (height_map.height() - 1, col),
+        (row, 0),
+        (row, height_map.width() - 1),
+    ];
+    let a = height(height_map, tree);
+    let mut score = 1;
+    for dst in dsts.iter() {
+        let mut dist = 0;
+        for (row, col) in Coords::new(tree, *dst).skip(1) {
+            dist += 1;
+            let b = height(height_map, (row, col));
+            if b >= a {
+                break;
+            }
+        }
+        if dist == 0 {
+            return 0;
+        }
+        score *= dist;
+    }
+    score
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+30373
+25512
+65332
+33549
+35390";
+
+    #[test]
+    fn from_lines() {
+        let ar = read_heights(EXAMPLE.as_bytes()).unwrap();
+        for (row, line) in EXAMPLE.lines().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                let height: u8 = c.to_digit(10).unwrap() as u8;
+                assert_eq!(height, ar[(col, row)], "mismatch at row={} col={}", row, col);
+            }
+        }
+    }
+
+    #[test]
+    fn visibility_count() {
+        let height_map = read_heights(EXAMPLE.as_bytes()).unwrap();
+        let count = visible_tree_count(&height_map);
+        assert_eq!(count, 21);
+    }
+
+    #[test]
+    fn coords_forward() {
+        let mut coords = Coords::new((0, 3), (2, 3));
+        assert_eq!(coords.next(), Some((0usize, 3usize)));
+        assert_eq!(coords.next(), Some((1usize, 3usize)));
+        assert_eq!(coords.next(), Some((2usize, 3usize)));
+        assert_eq!(coords.next(), None);
+    }
+
+    #[test]
+    fn coords_backward() {
+        let mut coords = Coords::new((3, 3), (3, 0));
+        assert_eq!(coords.next(), Some((3usize, 3usize)));
+        assert_eq!(coords.next(), Some((3usize, 2usize)));
+        assert_eq!(coords.next(), Some((3usize, 1usize)));
+        assert_eq!(coords.next(), Some((3usize, 0usize)));
+        assert_eq!(coords.next(), None);
+    }
+
+    #[test]
+    fn coords_len() {
+        let coords = Coords::new((3, 3), (3, 0));
+        assert_eq!(coords.len(), 4);
+
+    }
+
+    #[test]
+    fn test_scenic_score() {
+        let height_map = read_heights(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(highest_scenic_score(height_map), 8);
+    }
+}
@@ -0,0 +1,181 @@
+use std::cmp;
+use std::io::BufRead;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use aoclib::geom::{Dir, Point2};
+use aoclib::render::{Glyph, NoRender, Scene, Sink};
+use crate::error::AocError;
+
+type Pos = Point2<i32>;
+
+fn go(p: Pos, dir: Dir) -> Pos {
+    let (dx, dy) = dir.delta();
+    Pos::new(p.x + dx as i32, p.y + dy as i32)
+}
+
+fn follow(p: Pos, o: Pos) -> Pos {
+    if p.chebyshev(o) > 1 {
+        // Move straight or diagonally toward `o`, reducing Chebyshev distance by 1.
+        Pos::new(one_closer(p.x, o.x), one_closer(p.y, o.y))
+    } else {
+        p
+    }
+}
+
+fn one_closer(src: i32, tgt: i32) -> i32 {
+    use cmp::Ordering::{Equal, Less, Greater};
+    match src.cmp(&tgt) {
+        Equal => src,
+        Less => src + 1,
+        Greater => src - 1,
+    }
+}
+
+// The rope and where its tail has been, drawn like the puzzle description does, with the visited
+// tiles under the rope as #'s.
+struct Rope {
+    knots: Vec<Pos>,
+    visited: HashSet<Pos>,
+    // The columns and rows the head has been in, which the rest of the rope and so the visited
+    // tiles are always within.
+    xs: Range<i64>,
+    ys: Range<i64>,
+}
+
+impl Rope {
+    fn new(nknots: usize) -> Self {
+        let start = Pos::new(0, 0);
+        Rope {
+            knots: vec![start; nknots],
+            visited: HashSet::from([start]),
+            xs: 0..1,
+            ys: 0..1,
+        }
+    }
+
+    fn step(&mut self, dir: Dir) {
+        self.knots[0] = go(self.knots[0], dir);
+        for i in 1..self.knots.len() {
+            self.knots[i] = follow(self.knots[i], self.knots[i-1]);
+        }
+        self.visited.insert(self.knots[self.knots.len() - 1]);
+
+        let head = self.knots[0];
+        let (x, y) = (head.x as i64, head.y as i64);
+        self.xs = self.xs.start.min(x)..self.xs.end.max(x + 1);
+        self.ys = self.ys.start.min(y)..self.ys.end.max(y + 1);
+    }
+}
+
+impl Scene for Rope {
+    fn extent(&self) -> (Range<i64>, Range<i64>) {
+        (self.xs.clone(), self.ys.clone())
+    }
+
+    fn glyph(&self, x: i64, y: i64) -> Glyph {
+        let p = Pos::new(x as i32, y as i32);
+        match self.knots.iter().position(|&k| k == p) {
+            Some(0) => 'H',
+            Some(_) if self.knots.len() == 2 => 'T',
+            Some(i) => char::from_digit(i as u32, 36).unwrap_or('*'),
+            None if p == Pos::new(0, 0) => 's',
+            None if self.visited.contains(&p) => '#',
+            None => '.',
+        }.into()
+    }
+
+    fn focus(&self) -> Option<(i64, i64)> {
+        Some((self.knots[0].x as i64, self.knots[0].y as i64))
+    }
+}
+
+// Move a rope of `nknots` knots as the motions in `r` say, sending it to `sink` after each step,
+// and return the number of positions its tail visited.
+fn simulate<T: BufRead>(r: T, nknots: usize, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    let mut rope = Rope::new(nknots);
+    for line in r.lines() {
+        let line = line?;
+        if let [dir, count] = line.split_whitespace().collect::<Vec<&str>>()[..] {
+            let dir: Dir = dir.parse()?;
+            let count: u32 = count.parse::<u32>().map_err(|e| e.to_string())?;
+            for i in 0..count {
+                rope.step(dir);
+                sink.frame(format_args!("== {line} ({}/{count}) ==", i + 1), &rope)?;
+            }
+        } else {
+            return Err(AocError::Parse(format!("unexpected line: {}", line)));
+        }
+    }
+    Ok(rope.visited.len())
+}
+
+pub fn part1<T: BufRead>(r: T) -> Result<usize, AocError> {
+    part1_render(r, &mut NoRender)
+}
+
+pub fn part1_render<T: BufRead>(r: T, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    simulate(r, 2, sink)
+}
+
+pub fn part2<T: BufRead>(r: T) -> Result<usize, AocError> {
+    part2_render(r, &mut NoRender)
+}
+
+pub fn part2_render<T: BufRead>(r: T, sink: &mut dyn Sink) -> Result<usize, AocError> {
+    simulate(r, 10, sink)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aoclib::render::{draw, Viewport};
+
+    const EXAMPLE_PART1: &str = "\
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2";
+
+    const EXAMPLE_PART2: &str = "\
+R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20";
+
+    #[test]
+    fn test_follow() {
+        let head = Pos::new(2, 1);
+        assert_eq!(follow(Pos::new(0, 0), head), Pos::new(1, 1));
+        assert_eq!(follow(Pos::new(1, 1), head), Pos::new(1, 1));
+    }
+
+    #[test]
+    fn test_part1() {
+        let count = part1(EXAMPLE_PART1.as_bytes()).unwrap();
+        assert_eq!(count, 13);
+    }
+
+    #[test]
+    fn test_rope() {
+        let mut rope = Rope::new(2);
+        for dir in [Dir::Right, Dir::Right, Dir::Up, Dir::Right] {
+            rope.step(dir);
+        }
+        assert_eq!(draw(&rope, Viewport::UNLIMITED, false), "..TH\ns#..\n");
+    }
+
+    #[test]
+    fn test_part2() {
+        let count = part2(EXAMPLE_PART2.as_bytes()).unwrap();
+        assert_eq!(count, 36);
+    }
+}
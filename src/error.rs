@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+
+// The error type for every day's solver, so the runner can treat them all the same.
+#[derive(Debug)]
+pub enum AocError {
+    // The input isn't what the puzzle describes.
+    Parse(String),
+    Io(io::Error),
+    // The input is well-formed, but the puzzle can't be solved for it, eg there's no route.
+    NoSolution(String),
+    // A command line option is malformed, or doesn't make sense for the input.
+    BadArgs(String),
+    // The runner has no solver for this part.
+    NoSolver { day: u32, part: u32 },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Parse(msg) | AocError::NoSolution(msg) | AocError::BadArgs(msg) => {
+                write!(f, "{msg}")
+            },
+            AocError::Io(e) => write!(f, "{e}"),
+            AocError::NoSolver { day, part } => write!(f, "day{day} part{part} has no solver"),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AocError {
+    fn from(e: io::Error) -> Self {
+        AocError::Io(e)
+    }
+}
+
+// Most of the parsers report problems as strings, so `?` treats them as parse errors.
+impl From<String> for AocError {
+    fn from(msg: String) -> Self {
+        AocError::Parse(msg)
+    }
+}
+
+impl From<&str> for AocError {
+    fn from(msg: &str) -> Self {
+        AocError::Parse(msg.to_string())
+    }
+}
+
+impl From<ParseIntError> for AocError {
+    fn from(e: ParseIntError) -> Self {
+        AocError::Parse(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from() {
+        assert!(matches!(AocError::from("bad line"), AocError::Parse(msg) if msg == "bad line"));
+        assert!(matches!("x".parse::<u32>().map_err(AocError::from), Err(AocError::Parse(_))));
+        let e = AocError::from(io::Error::other("closed"));
+        assert_eq!(e.to_string(), "closed");
+        assert!(e.source().is_some());
+        assert_eq!(AocError::NoSolver { day: 17, part: 2 }.to_string(), "day17 part2 has no solver");
+    }
+}
@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// Downloaded inputs are cached here, relative to the working directory.
+pub const INPUT_DIR: &str = "input";
+
+const USER_AGENT: &str = "github.com/torbiak/advent_of_code_2022";
+
+pub fn input_path(dir: impl AsRef<Path>, day: u32) -> PathBuf {
+    dir.as_ref().join(format!("{day}.dat"))
+}
+
+// Find the adventofcode.com session token, from $AOC_SESSION or else the first line of
+// $XDG_CONFIG_HOME/aoc/session (~/.config/aoc/session by default).
+pub fn session() -> Result<String, Box<dyn Error>> {
+    if let Ok(token) = std::env::var("AOC_SESSION") {
+        return parse_session(&token);
+    }
+    let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let home = std::env::var_os("HOME").ok_or("neither AOC_SESSION nor HOME is set")?;
+            PathBuf::from(home).join(".config")
+        },
+    };
+    let path = config_dir.join("aoc").join("session");
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("AOC_SESSION isn't set and can't read {}: {e}", path.display()))?;
+    parse_session(&contents)
+}
+
+// Accept the token either bare or as it appears in a cookie header.
+fn parse_session(s: &str) -> Result<String, Box<dyn Error>> {
+    let token = s.lines().next().unwrap_or("").trim();
+    let token = token.strip_prefix("session=").unwrap_or(token);
+    if token.is_empty() {
+        return Err("empty session token".into());
+    }
+    if !token.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err("session token should be alphanumeric".into());
+    }
+    Ok(token.to_string())
+}
+
+// Download a day's input with curl. The cookie is passed on stdin so the token doesn't show up in
+// the process list.
+pub fn download(day: u32, session: &str) -> Result<String, Box<dyn Error>> {
+    if !(1..=25).contains(&day) {
+        return Err(format!("no such day: {day}").into());
+    }
+    let url = format!("https://adventofcode.com/2022/day/{day}/input");
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--user-agent", USER_AGENT, "--header", "@-", &url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("run curl: {e}"))?;
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(stdin, "Cookie: session={session}")?;
+    drop(stdin);
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!("download {url}: curl {}", output.status).into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+// Return the cached input for a day, downloading it first if it's not in `dir` yet.
+pub fn cached_input(dir: impl AsRef<Path>, day: u32) -> Result<String, Box<dyn Error>> {
+    let path = input_path(&dir, day);
+    if path.exists() {
+        return Ok(fs::read_to_string(path)?);
+    }
+    let input = download(day, &session()?)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, &input).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_session() {
+        assert_eq!(parse_session("53616c74\n").unwrap(), "53616c74");
+        assert_eq!(parse_session("session=53616c74").unwrap(), "53616c74");
+        assert!(parse_session("\n").is_err());
+        assert!(parse_session("abc; rm -rf").is_err());
+    }
+
+    #[test]
+    fn test_cached_input() {
+        assert_eq!(input_path("input", 7), Path::new("input/7.dat"));
+        let dir = std::env::temp_dir().join(format!("aoc-fetch-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(input_path(&dir, 3), "vJrwpWtwJgWrhcsFMMfFFhFp\n").unwrap();
+        assert_eq!(cached_input(&dir, 3).unwrap(), "vJrwpWtwJgWrhcsFMMfFFhFp\n");
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(download(26, "x").is_err());
+    }
+}
@@ -0,0 +1,253 @@
+// Random but valid puzzle inputs, much bigger than the official ones if asked, for benchmarking
+// the solvers. The same day, size and seed always give the same input.
+
+use std::io::Write;
+
+use aoclib::rng::Rng;
+
+use crate::error::AocError;
+
+// The days with a generator, and the size of the official input in the generator's terms, which
+// is used when no size is given:
+//
+//     1: elves
+//     3: groups of three rucksacks
+//     5: moves of the crane
+//     6: characters in the datastream, but at least 14
+//     9: moves of the rope's head
+//     16: valves
+//     23: rows and columns of the grove
+//     24: columns inside the valley's walls, with a fifth as many rows, but at least 4
+pub const DEFAULT_SIZES: &[(u32, usize)] = &[(1, 250), (3, 100), (5, 500), (6, 4096), (9, 2000), (16, 60), (23, 70), (24, 120)];
+
+pub fn default_size(day: u32) -> Option<usize> {
+    DEFAULT_SIZES.iter().find(|&&(d, _)| d == day).map(|&(_, size)| size)
+}
+
+pub fn generate(day: u32, size: usize, seed: u64, w: impl Write) -> Result<(), AocError> {
+    let mut rng = Rng::new(seed);
+    match day {
+        1 => elves(&mut rng, size, w),
+        3 => rucksacks(&mut rng, size, w),
+        5 => crates(&mut rng, size, w),
+        6 => datastream(&mut rng, size, w),
+        9 => rope_moves(&mut rng, size, w),
+        16 => volcano(&mut rng, size, w),
+        23 => grove(&mut rng, size, w),
+        24 => valley(&mut rng, size, w),
+        _ => {
+            let days: Vec<String> = DEFAULT_SIZES.iter().map(|(d, _)| d.to_string()).collect();
+            Err(AocError::BadArgs(format!("no generator for day {day}, only for days {}", days.join(", "))))
+        },
+    }
+}
+
+fn elves(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    for i in 0..n {
+        if i > 0 {
+            writeln!(w)?;
+        }
+        for _ in 0..=rng.below(15) {
+            writeln!(w, "{}", rng.range(1000..60_000))?;
+        }
+    }
+    Ok(())
+}
+
+// Each group's badge is the only item all three of its elves carry, since the other letters are
+// dealt out between them, and each rucksack has one item in both compartments, since the rest of
+// the elf's letters are dealt out between the compartments.
+fn rucksacks(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    let mut letters: Vec<u8> = (b'a'..=b'z').chain(b'A'..=b'Z').collect();
+    for _ in 0..n {
+        shuffle(rng, &mut letters);
+        let (badge, rest) = letters.split_first().unwrap();
+        for pool in rest.chunks(rest.len() / 3) {
+            let (dup, pool) = pool.split_first().unwrap();
+            let (left_pool, right_pool) = pool.split_at(pool.len() / 2);
+            let len = rng.range(4..16) as usize;
+            let mut left: Vec<u8> = vec![*dup, *badge];
+            left.extend((2..len).map(|_| *rng.choose(left_pool)));
+            let mut right: Vec<u8> = vec![*dup];
+            right.extend((1..len).map(|_| *rng.choose(right_pool)));
+            shuffle(rng, &mut left);
+            shuffle(rng, &mut right);
+            left.extend(right);
+            w.write_all(&left)?;
+            writeln!(w)?;
+        }
+    }
+    Ok(())
+}
+
+fn shuffle<T>(rng: &mut Rng, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i + 1));
+    }
+}
+
+// Nine stacks, and moves that only ever take crates that are there. With more moves the stacks
+// start taller, so the moves are bigger too.
+fn crates(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    let most = (n / 50).max(8) as i64;
+    let mut stacks: Vec<Vec<char>> = (0..9)
+        .map(|_| (0..rng.range(1..most + 1)).map(|_| (b'A' + rng.below(26) as u8) as char).collect())
+        .collect();
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks.iter()
+            .map(|s| s.get(level).map_or("   ".to_string(), |c| format!("[{c}]")))
+            .collect();
+        writeln!(w, "{}", row.join(" ").trim_end())?;
+    }
+    let labels: Vec<String> = (1..=stacks.len()).map(|i| format!(" {i} ")).collect();
+    writeln!(w, "{}", labels.join(" "))?;
+    writeln!(w)?;
+    for _ in 0..n {
+        let src = loop {
+            let src = rng.below(stacks.len());
+            if !stacks[src].is_empty() {
+                break src;
+            }
+        };
+        let dst = (src + 1 + rng.below(stacks.len() - 1)) % stacks.len();
+        let count = rng.range(1..stacks[src].len() as i64 + 1) as usize;
+        // Which crates go where doesn't matter for keeping the moves possible, just how many.
+        let at = stacks[src].len() - count;
+        let moved = stacks[src].split_off(at);
+        stacks[dst].extend(moved);
+        writeln!(w, "move {count} from {} to {}", src + 1, dst + 1)?;
+    }
+    Ok(())
+}
+
+// Letters from just the first half of the alphabet, so there can't be a start-of-message marker
+// until one is put in somewhere in the second half of the stream. Start-of-packet markers turn up
+// early anyway.
+fn datastream(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    let n = n.max(14);
+    let mut stream: Vec<u8> = (0..n).map(|_| b'a' + rng.below(13) as u8).collect();
+    let mut letters: Vec<u8> = (b'a'..=b'z').collect();
+    shuffle(rng, &mut letters);
+    let at = rng.range((n / 2).min(n - 14) as i64..(n - 14) as i64 + 1) as usize;
+    stream[at..at + 14].copy_from_slice(&letters[..14]);
+    w.write_all(&stream)?;
+    writeln!(w)?;
+    Ok(())
+}
+
+fn rope_moves(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    for _ in 0..n {
+        writeln!(w, "{} {}", rng.choose(&['U', 'D', 'L', 'R']), rng.range(1..20))?;
+    }
+    Ok(())
+}
+
+// Valves are named AA, AB, ... and the tunnels go both ways, like in the real input. A random
+// spanning tree keeps every valve reachable from AA, and some extra tunnels make loops.
+fn volcano(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    // day16 numbers the valves with a u8.
+    if !(1..=256).contains(&n) {
+        return Err(AocError::BadArgs(format!("day16 needs 1 to 256 valves, not {n}")));
+    }
+    let name = |i: usize| format!("{}{}", (b'A' + (i / 26) as u8) as char, (b'A' + (i % 26) as u8) as char);
+    let mut tunnels: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    };
+    for i in 1..n {
+        connect(i, rng.below(i));
+    }
+    for _ in 0..n / 2 {
+        connect(rng.below(n), rng.below(n));
+    }
+    for (i, dsts) in tunnels.iter().enumerate() {
+        // The start valve never has any flow, and neither do most of the others.
+        let flow = if i > 0 && rng.one_in(4) { rng.range(1..26) } else { 0 };
+        let dsts: Vec<String> = dsts.iter().map(|&d| name(d)).collect();
+        let lead = if dsts.len() == 1 { "tunnel leads to valve" } else { "tunnels lead to valves" };
+        writeln!(w, "Valve {} has flow rate={flow}; {lead} {}", name(i), dsts.join(", "))?;
+    }
+    Ok(())
+}
+
+// About half the tiles have an elf, like in the real input.
+fn grove(rng: &mut Rng, n: usize, mut w: impl Write) -> Result<(), AocError> {
+    for _ in 0..n {
+        let row: String = (0..n).map(|_| if rng.one_in(2) { '#' } else { '.' }).collect();
+        writeln!(w, "{row}")?;
+    }
+    Ok(())
+}
+
+// The openings are in the top left and bottom right corners, like in the real input. Blizzards in
+// their columns can't go up or down, since they'd leave the valley. A third of the tiles have a
+// blizzard, fewer than in the real input, so that small valleys are rarely impassable.
+fn valley(rng: &mut Rng, width: usize, mut w: impl Write) -> Result<(), AocError> {
+    let width = width.max(1);
+    let height = (width / 5).max(4);
+    writeln!(w, "#.{}", "#".repeat(width))?;
+    for _ in 0..height {
+        let row: String = (0..width)
+            .map(|x| {
+                let dirs: &[char] = if x == 0 || x == width - 1 { &['<', '>'] } else { &['^', '>', 'v', '<'] };
+                if rng.one_in(3) { *rng.choose(dirs) } else { '.' }
+            })
+            .collect();
+        writeln!(w, "#{row}#")?;
+    }
+    writeln!(w, "{}.#", "#".repeat(width))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner::solve;
+
+    fn gen(day: u32, size: usize, seed: u64) -> String {
+        let mut buf = Vec::new();
+        generate(day, size, seed, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for &(day, _) in DEFAULT_SIZES {
+            for seed in 1..=3 {
+                let input = gen(day, 12, seed);
+                for part in 1..=2 {
+                    if let Err(e) = solve(day, part, &input) {
+                        panic!("day{day} part{part} seed={seed}: {e}\n{input}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        assert_eq!(gen(9, 100, 7), gen(9, 100, 7));
+        assert_ne!(gen(9, 100, 7), gen(9, 100, 8));
+        assert_eq!(gen(9, 100, 7).lines().count(), 100);
+        assert_eq!(gen(1, 10, 1).split("\n\n").count(), 10);
+        assert_eq!(gen(3, 10, 1).lines().count(), 30);
+        assert_eq!(gen(5, 10, 1).lines().filter(|l| l.starts_with("move")).count(), 10);
+        assert_eq!(gen(6, 100, 1).trim_end().len(), 100);
+        assert_eq!(gen(6, 1, 1).trim_end().len(), 14);
+        assert_eq!(gen(16, 256, 1).lines().last().unwrap().get(..8), Some("Valve JV"));
+        assert_eq!(gen(24, 10, 1).lines().count(), 6);
+        assert_eq!(gen(24, 100, 1).lines().count(), 22);
+        assert_eq!(default_size(16), Some(60));
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        assert!(matches!(generate(2, 10, 1, Vec::new()), Err(AocError::BadArgs(_))));
+        assert!(matches!(generate(16, 257, 1, Vec::new()), Err(AocError::BadArgs(_))));
+        assert!(matches!(generate(16, 0, 1, Vec::new()), Err(AocError::BadArgs(_))));
+    }
+}
@@ -0,0 +1,412 @@
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant};
+
+use aoclib::memstats::MemStats;
+
+use crate::*;
+use crate::error::AocError;
+
+pub type Answer = Result<Solution, AocError>;
+
+// An answer, as that day's own binary would print it, along with how many states were searched
+// and how much memory they took for days that keep track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub answer: String,
+    pub nstates: Option<usize>,
+    pub mem: Option<MemStats>,
+}
+
+impl Solution {
+    pub fn new(answer: impl fmt::Display) -> Solution {
+        Solution { answer: answer.to_string(), nstates: None, mem: None }
+    }
+
+    pub fn with_nstates(answer: impl fmt::Display, nstates: usize) -> Solution {
+        Solution { nstates: Some(nstates), ..Solution::new(answer) }
+    }
+
+    pub fn with_mem(self, mem: MemStats) -> Solution {
+        Solution { mem: Some(mem), ..self }
+    }
+
+    // One line of JSON describing the run.
+    pub fn json(&self, day: u32, part: u32, timing: &Timing) -> String {
+        let mut s = format!("{{\"day\":{day},\"part\":{part},\"answer\":");
+        write_json_string(&mut s, &self.answer);
+        write!(s, ",\"elapsed_ms\":{:.3}", millis(timing.total)).unwrap();
+        match self.nstates {
+            Some(n) => write!(s, ",\"nstates\":{n}").unwrap(),
+            None => s.push_str(",\"nstates\":null"),
+        }
+        match self.mem {
+            Some(m) => write!(
+                s,
+                ",\"mem\":{{\"arena_len\":{},\"peak_queue\":{},\"peak_bytes\":{}}}",
+                m.arena_len, m.peak_queue, m.peak_bytes,
+            ).unwrap(),
+            None => s.push_str(",\"mem\":null"),
+        }
+        s.push_str(",\"phases_ms\":{");
+        for (i, (name, elapsed)) in timing.phases.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(s, "{sep}\"{name}\":{:.3}", millis(*elapsed)).unwrap();
+        }
+        s.push_str("}}");
+        s
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+thread_local! {
+    // Phases recorded by `phase`, while `solve_timed` is running on this thread.
+    static PHASES: RefCell<Option<Vec<(&'static str, Duration)>>> = const { RefCell::new(None) };
+}
+
+// Run and time a phase of a solver, such as parsing the input, so solve_timed can report it
+// separately. Outside of solve_timed this just calls f.
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    // Don't read the clock unless it's needed, since wasm32 doesn't have one.
+    if PHASES.with(|phases| phases.borrow().is_none()) {
+        return f();
+    }
+    let start = Instant::now();
+    let v = f();
+    let elapsed = start.elapsed();
+    PHASES.with(|phases| {
+        if let Some(phases) = phases.borrow_mut().as_mut() {
+            phases.push((name, elapsed));
+        }
+    });
+    v
+}
+
+#[derive(Debug, Clone)]
+pub struct Timing {
+    pub total: Duration,
+    // Time not spent in any phase is put in a final "solve" phase.
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timing {
+    fn new(total: Duration, mut phases: Vec<(&'static str, Duration)>) -> Timing {
+        let timed: Duration = phases.iter().map(|&(_, d)| d).sum();
+        phases.push(("solve", total.saturating_sub(timed)));
+        Timing { total, phases }
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, elapsed) in &self.phases {
+            write!(f, "{name}={:.3}ms ", millis(*elapsed))?;
+        }
+        write!(f, "total={:.3}ms", millis(self.total))
+    }
+}
+
+fn write_json_string(s: &mut String, v: &str) {
+    s.push('"');
+    for c in v.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(s, "\\u{:04x}", c as u32).unwrap(),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+}
+
+// Lets a solver part evaluate to either a plain answer or a Solution with stats.
+pub trait IntoSolution {
+    fn into_solution(self) -> Solution;
+}
+
+impl<T: fmt::Display> IntoSolution for T {
+    fn into_solution(self) -> Solution {
+        Solution::new(self)
+    }
+}
+
+impl IntoSolution for Solution {
+    fn into_solution(self) -> Solution {
+        self
+    }
+}
+
+// A day's puzzle, taking the whole input.
+pub trait Solver: Sync {
+    fn day(&self) -> u32;
+
+    // Parts that were solved some other way, or that don't exist, like day25 part2, have no
+    // solver.
+    fn part1(&self, _input: &str) -> Answer {
+        Err(AocError::NoSolver { day: self.day(), part: 1 })
+    }
+
+    fn part2(&self, _input: &str) -> Answer {
+        Err(AocError::NoSolver { day: self.day(), part: 2 })
+    }
+
+    fn solve(&self, part: u32, input: &str) -> Answer {
+        match part {
+            1 => self.part1(input),
+            2 => self.part2(input),
+            _ => Err(AocError::BadArgs(format!("no such part: {part}"))),
+        }
+    }
+}
+
+// Define a unit struct implementing Solver for each day and list them all in SOLVERS. A part left
+// out falls back to the trait's default, which fails.
+macro_rules! solvers {
+    ($(
+        $name:ident = $day:literal {
+            $(part1($i1:ident) => $p1:expr,)?
+            $(part2($i2:ident) => $p2:expr,)?
+        }
+    )*) => {
+        $(
+            struct $name;
+
+            impl Solver for $name {
+                fn day(&self) -> u32 {
+                    $day
+                }
+
+                $(fn part1(&self, $i1: &str) -> Answer {
+                    Ok($p1.into_solution())
+                })?
+
+                $(fn part2(&self, $i2: &str) -> Answer {
+                    Ok($p2.into_solution())
+                })?
+            }
+        )*
+
+        // Every day's solver, in order.
+        pub static SOLVERS: &[&dyn Solver] = &[$(&$name),*];
+    };
+}
+
+solvers! {
+    Day1 = 1 {
+        part1(input) => day1::part1(input.as_bytes()),
+        part2(input) => day1::part2(input.as_bytes()),
+    }
+    Day2 = 2 {
+        part1(input) => day2::part1(&day2::Rules::default(), day2::Format::Letters, input.lines()),
+        part2(input) => day2::part2(&day2::Rules::default(), day2::Format::Letters, input.lines()),
+    }
+    Day3 = 3 {
+        part1(input) => day3::part1(&day3::Priorities::default(), input.lines())?,
+        part2(input) => day3::part2(&day3::Priorities::default(), input.lines())?,
+    }
+    Day4 = 4 {
+        part1(input) => day4::part1(input.lines())?,
+        part2(input) => day4::part2(input.lines())?,
+    }
+    Day5 = 5 {
+        part1(input) => day5::part1(input.lines())?,
+        part2(input) => day5::part2(input.lines())?,
+    }
+    Day6 = 6 {
+        part1(input) => day6::part1(first_line(input)).ok_or_else(no_marker)?,
+        part2(input) => day6::part2(first_line(input)).ok_or_else(no_marker)?,
+    }
+    Day7 = 7 {
+        part1(input) => day7::part1(input.lines())?,
+        part2(input) => day7::part2(input.lines())?,
+    }
+    Day8 = 8 {
+        part1(input) => day8::part1(input.as_bytes())?,
+        part2(input) => day8::part2(input.as_bytes())?,
+    }
+    Day9 = 9 {
+        part1(input) => day9::part1(input.as_bytes())?,
+        part2(input) => day9::part2(input.as_bytes())?,
+    }
+    Day10 = 10 {
+        part1(input) => day10::part1(input.as_bytes()),
+        part2(input) => day10::part2(input.as_bytes()),
+    }
+    Day11 = 11 {
+        part1(input) => day11::part1(input.as_bytes())?,
+        part2(input) => day11::part2(input.as_bytes())?,
+    }
+    Day12 = 12 {
+        part1(input) => day12::part1(input.as_bytes())?,
+        part2(input) => day12::part2(input.as_bytes())?,
+    }
+    Day13 = 13 {
+        part1(input) => day13::part1(input.as_bytes())?,
+        part2(input) => day13::part2(input.as_bytes())?,
+    }
+    Day14 = 14 {
+        part1(input) => day14::part1(input.as_bytes())?,
+        part2(input) => day14::part2_fast(input.as_bytes())?,
+    }
+    Day15 = 15 {
+        part1(input) => day15::part1(input.as_bytes(), 2000000)?,
+        part2(input) => day15::part2(input.as_bytes(), 4000000, 4000000)?,
+    }
+    Day16 = 16 {
+        part1(input) => with_stats(day16a::part1_stats(input.as_bytes())?),
+        part2(input) => with_stats(day16b::part2_stats(input.as_bytes())?),
+    }
+    Day17 = 17 {
+        part1(input) => day17::height(input.trim(), 2022),
+    }
+    Day18 = 18 {
+        part1(input) => day18::part1(input.as_bytes())?,
+        part2(input) => day18::part2(input.as_bytes())?,
+    }
+    Day19 = 19 {
+        part1(input) => with_stats(day19::part1_stats(input.as_bytes())?),
+        part2(input) => with_stats(day19::part2_stats(input.as_bytes())?),
+    }
+    Day20 = 20 {
+        part1(input) => day20::part1(input.as_bytes())?,
+        part2(input) => day20::part2(input.as_bytes())?,
+    }
+    Day21 = 21 {
+        part1(input) => day21::part1(input.as_bytes())?,
+        part2(input) => day21::part2(input.as_bytes())?,
+    }
+    Day22 = 22 {
+        part1(input) => day22::part1(input.as_bytes(), day22::Start::default())?,
+        part2(input) => {
+            day22::part2(input.as_bytes(), day22::CubeTopology::part2(), day22::Start::default())?
+        },
+    }
+    Day23 = 23 {
+        part1(input) => {
+            let mut board = phase("parse", || {
+                day23::Backend::Sparse.read(input.as_bytes(), &day23::Rules::default())
+            })?;
+            day23::part1(board.as_mut(), day23::PART1_ROUNDS, |_, _| {})
+        },
+        part2(input) => {
+            let mut board = phase("parse", || {
+                day23::Backend::Sparse.read(input.as_bytes(), &day23::Rules::default())
+            })?;
+            day23::part2(board.as_mut(), |_, _| {})?
+        },
+    }
+    Day24 = 24 {
+        part1(input) => day24(input, 1)?,
+        part2(input) => day24(input, 3)?,
+    }
+    Day25 = 25 {
+        part1(input) => day25::part1(input.as_bytes())?,
+    }
+}
+
+pub fn solver(day: u32) -> Option<&'static dyn Solver> {
+    SOLVERS.iter().copied().find(|s| s.day() == day)
+}
+
+// Solve one part of a day's puzzle for the given input.
+pub fn solve(day: u32, part: u32, input: &str) -> Answer {
+    solver(day).ok_or_else(|| AocError::BadArgs(format!("no such day: {day}")))?.solve(part, input)
+}
+
+// Solve a puzzle and time it, including any phases the solver marked.
+pub fn solve_timed(day: u32, part: u32, input: &str) -> Result<(Solution, Timing), AocError> {
+    PHASES.with(|phases| *phases.borrow_mut() = Some(Vec::new()));
+    let start = Instant::now();
+    let solution = solve(day, part, input);
+    let total = start.elapsed();
+    let phases = PHASES.with(|phases| phases.borrow_mut().take()).unwrap_or_default();
+    Ok((solution?, Timing::new(total, phases)))
+}
+
+fn with_stats<T: fmt::Display>((answer, nstates, mem): (T, usize, MemStats)) -> Solution {
+    Solution::with_nstates(answer, nstates).with_mem(mem)
+}
+
+fn day24(input: &str, trips: usize) -> Answer {
+    let board = phase("parse", || day24::Board::read(input))?;
+    let route = day24::travel(&board, &day24::trips(&board, trips), &day24::Strategy::default())?;
+    Ok(Solution::with_nstates(route.rounds(), route.stats.nstates))
+}
+
+fn no_marker() -> AocError {
+    AocError::NoSolution("no marker found".to_string())
+}
+
+fn first_line(input: &str) -> &str {
+    input.lines().next().unwrap_or("")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+        assert_eq!(solve(1, 1, input).unwrap(), Solution::new(24000));
+        assert_eq!(solve(1, 2, input).unwrap(), Solution::new(45000));
+        assert_eq!(solve(6, 1, "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n").unwrap().answer, "7");
+        assert_eq!(solve(25, 1, "1=-0-2\n12111\n").unwrap().answer, "1-111=");
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        assert!(solve(0, 1, "").is_err());
+        assert!(solve(26, 1, "").is_err());
+        assert!(solve(1, 3, "").is_err());
+        assert!(solve(25, 2, "").is_err());
+        assert!(matches!(solve(17, 2, ""), Err(AocError::NoSolver { day: 17, part: 2 })));
+        assert!(matches!(solve(24, 1, ""), Err(AocError::Parse(_))));
+        assert!(matches!(solve(1, 3, ""), Err(AocError::BadArgs(_))));
+    }
+
+    #[test]
+    fn test_json() {
+        let timing = Timing::new(Duration::from_micros(1500), vec![]);
+        assert_eq!(
+            Solution::new("ab\"c\n").json(10, 2, &timing),
+            r#"{"day":10,"part":2,"answer":"ab\"c\n","elapsed_ms":1.500,"nstates":null,"mem":null,"phases_ms":{"solve":1.500}}"#,
+        );
+        let timing = Timing::new(Duration::from_millis(3), vec![("parse", Duration::from_millis(1))]);
+        assert_eq!(
+            Solution::with_nstates(54, 7).json(24, 2, &timing),
+            r#"{"day":24,"part":2,"answer":"54","elapsed_ms":3.000,"nstates":7,"mem":null,"phases_ms":{"parse":1.000,"solve":2.000}}"#,
+        );
+        let mem = MemStats { arena_len: 3, peak_queue: 2, peak_bytes: 100 };
+        assert_eq!(
+            Solution::with_nstates(1651, 9).with_mem(mem).json(16, 1, &timing),
+            r#"{"day":16,"part":1,"answer":"1651","elapsed_ms":3.000,"nstates":9,"mem":{"arena_len":3,"peak_queue":2,"peak_bytes":100},"phases_ms":{"parse":1.000,"solve":2.000}}"#,
+        );
+        let board = "#.######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#\n";
+        assert!(solve(24, 2, board).unwrap().nstates.is_some());
+    }
+
+    #[test]
+    fn test_solve_timed() {
+        let board = "#.######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#\n";
+        let (solution, timing) = solve_timed(24, 1, board).unwrap();
+        assert_eq!(solution.answer, "18");
+        let names: Vec<&str> = timing.phases.iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, ["parse", "solve"]);
+        assert!(timing.phases.iter().map(|&(_, d)| d).sum::<Duration>() <= timing.total);
+
+        // Phases aren't recorded outside of solve_timed.
+        assert_eq!(phase("parse", || 1), 1);
+        PHASES.with(|phases| assert!(phases.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_solvers() {
+        let days: Vec<u32> = SOLVERS.iter().map(|s| s.day()).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,70 @@
+// A plain interface to the solvers for running them in a web page, with the input pasted in
+// instead of read from a file. See www/index.html.
+//
+// wasm-bindgen isn't used, so the exports only pass numbers: the page copies the input into memory
+// it gets from `alloc`, calls `solve` with a pointer to it, and reads the answer back from the
+// returned buffer, which starts with its length as a little-endian u32.
+//
+// Build with:
+//
+//     cargo rustc --lib --release --target wasm32-unknown-unknown --crate-type cdylib
+//     cp target/wasm32-unknown-unknown/release/advent_of_code_2022.wasm www/
+
+use crate::runner;
+
+// Solve a puzzle, returning the answer or a message starting with "error: ".
+pub fn solve(day: u32, part: u32, input: &str) -> String {
+    match runner::solve(day, part, input) {
+        Ok(solution) => solution.answer,
+        Err(e) => format!("error: {e}"),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod exports {
+    use std::mem;
+
+    #[no_mangle]
+    pub extern "C" fn alloc(len: usize) -> *mut u8 {
+        let mut buf: Vec<u8> = Vec::with_capacity(len);
+        let ptr = buf.as_mut_ptr();
+        mem::forget(buf);
+        ptr
+    }
+
+    // Free a buffer from `alloc`, or one returned by `solve` along with its length prefix.
+    #[no_mangle]
+    pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+        drop(Vec::from_raw_parts(ptr, 0, len));
+    }
+
+    // Takes ownership of the input buffer, which must hold `len` bytes from `alloc`.
+    #[no_mangle]
+    pub unsafe extern "C" fn solve(day: u32, part: u32, input: *mut u8, len: usize) -> *mut u8 {
+        let input = Vec::from_raw_parts(input, len, len);
+        let answer = match String::from_utf8(input) {
+            Ok(input) => super::solve(day, part, &input),
+            Err(_) => "error: input isn't UTF-8".to_string(),
+        };
+        let mut buf = Vec::with_capacity(4 + answer.len());
+        buf.extend_from_slice(&(answer.len() as u32).to_le_bytes());
+        buf.extend_from_slice(answer.as_bytes());
+        // dealloc needs the capacity to match, so the page can free exactly 4 + length bytes.
+        let mut buf = buf.into_boxed_slice();
+        let ptr = buf.as_mut_ptr();
+        mem::forget(buf);
+        ptr
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(solve(25, 1, "1=-0-2\n12111\n"), "1-111=");
+        assert_eq!(solve(25, 2, ""), "error: day25 part2 has no solver");
+        assert!(solve(30, 1, "").starts_with("error: "));
+    }
+}
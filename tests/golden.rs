@@ -0,0 +1,63 @@
+// Check that every solver still gets the recorded answer for the real inputs, so refactors of the
+// shared code can't quietly change any of them. Answers are in answers.toml and inputs in
+// input/DAY.dat; parts missing either are skipped.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use advent_of_code_2022::answers::{Answers, Verdict, ANSWERS_FILE};
+use advent_of_code_2022::fetch::input_path;
+use advent_of_code_2022::runner::SOLVERS;
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn test_golden() {
+    let answers = Answers::read(root().join(ANSWERS_FILE)).unwrap();
+    let jobs: Vec<(usize, u32)> = (0..SOLVERS.len()).flat_map(|i| [(i, 1), (i, 2)]).collect();
+    let next_job = AtomicUsize::new(0);
+    let skipped = Mutex::new(Vec::new());
+    let failures = Mutex::new(Vec::new());
+    let nthreads = thread::available_parallelism().map_or(1, |n| n.get());
+    thread::scope(|scope| {
+        for _ in 0..nthreads {
+            scope.spawn(|| {
+                while let Some(&(i, part)) = jobs.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+                    let solver = SOLVERS[i];
+                    let day = solver.day();
+                    let (Some(_), Ok(input)) = (
+                        answers.get(day, part),
+                        fs::read_to_string(input_path(root().join("input"), day)),
+                    ) else {
+                        skipped.lock().unwrap().push(format!("day{day} part{part}"));
+                        continue;
+                    };
+                    let failure = match solver.solve(part, &input) {
+                        Ok(got) => match answers.check(day, part, &got.answer) {
+                            Verdict::Wrong { want } => format!("got {:?}, want {want:?}", got.answer),
+                            Verdict::Right | Verdict::Unknown => continue,
+                        },
+                        Err(e) => format!("error: {e}"),
+                    };
+                    failures.lock().unwrap().push(format!("day{day} part{part}: {failure}"));
+                }
+            });
+        }
+    });
+
+    let mut skipped = skipped.into_inner().unwrap();
+    let mut failures = failures.into_inner().unwrap();
+    skipped.sort();
+    failures.sort();
+    let npassed = jobs.len() - skipped.len() - failures.len();
+    eprintln!("golden: {npassed} passed, {} failed, {} skipped", failures.len(), skipped.len());
+    if !skipped.is_empty() {
+        eprintln!("skipped: {}", skipped.join(", "));
+    }
+    assert!(failures.is_empty(), "wrong answers:\n{}", failures.join("\n"));
+}